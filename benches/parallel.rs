@@ -0,0 +1,98 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Compare sequential and parallel hex encoding and checksums of large payloads across thread counts.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rand::{rngs::OsRng, RngCore};
+use tari_utilities::{
+    checksum::crc32,
+    hex::from_hex,
+    parallel::{par_crc32, par_from_hex, par_to_hex},
+};
+
+const SIZES: [usize; 2] = [1 << 20, 16 << 20];
+const THREADS: [usize; 4] = [1, 2, 4, 8];
+
+fn encode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("par_to_hex");
+    for size in SIZES {
+        let mut bytes = vec![0u8; size];
+        OsRng.fill_bytes(&mut bytes);
+        group.throughput(Throughput::Bytes(size as u64));
+        for threads in THREADS {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            group.bench_with_input(
+                BenchmarkId::new(format!("{} threads", threads), size),
+                &bytes,
+                |b, bytes| b.iter(|| pool.install(|| par_to_hex(bytes).unwrap())),
+            );
+        }
+    }
+    group.finish();
+}
+
+fn decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("par_from_hex");
+    for size in SIZES {
+        let mut bytes = vec![0u8; size];
+        OsRng.fill_bytes(&mut bytes);
+        let hex = par_to_hex(&bytes).unwrap();
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("sequential", size), &hex, |b, hex| {
+            b.iter(|| from_hex(hex).unwrap())
+        });
+        for threads in THREADS {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            group.bench_with_input(
+                BenchmarkId::new(format!("{} threads", threads), size),
+                &hex,
+                |b, hex| b.iter(|| pool.install(|| par_from_hex(hex).unwrap())),
+            );
+        }
+    }
+    group.finish();
+}
+
+fn checksum(c: &mut Criterion) {
+    let mut group = c.benchmark_group("par_crc32");
+    for size in SIZES {
+        let mut bytes = vec![0u8; size];
+        OsRng.fill_bytes(&mut bytes);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new("sequential", size), &bytes, |b, bytes| {
+            b.iter(|| crc32(bytes))
+        });
+        for threads in THREADS {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            group.bench_with_input(
+                BenchmarkId::new(format!("{} threads", threads), size),
+                &bytes,
+                |b, bytes| b.iter(|| pool.install(|| par_crc32(bytes))),
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, encode, decode, checksum);
+criterion_main!(benches);
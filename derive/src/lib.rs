@@ -0,0 +1,361 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Derive macros for `tari_utilities`. Use them through the `derive` feature of `tari_utilities`, which re-exports
+//! them, rather than depending on this crate directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input,
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Data,
+    DeriveInput,
+    Error,
+    Expr,
+    Fields,
+    Meta,
+    Token,
+    Type,
+};
+
+/// Derive the byte newtype trait suite for a tuple struct wrapping a `Vec<u8>`, with a maximum length.
+///
+/// The maximum is set with `#[byte_newtype(max_len = ...)]` and defaults to
+/// `tari_utilities::byte_newtype::DEFAULT_MAX_LEN`. Every constructor, parser and deserializer rejects longer data.
+/// See `tari_utilities::byte_newtype` for the generated items.
+#[proc_macro_derive(ByteNewtype, attributes(byte_newtype))]
+pub fn derive_byte_newtype(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "ByteNewtype does not support generics",
+        ));
+    }
+    check_fields(input)?;
+    let max_len = max_len(input)?;
+
+    Ok(quote! {
+        impl #name {
+            /// The maximum length in bytes
+            pub const MAX_LEN: usize = #max_len;
+
+            /// Wrap the given bytes, failing if they are longer than [Self::MAX_LEN]
+            #[allow(dead_code)]
+            pub fn try_new(bytes: ::tari_utilities::byte_newtype::__private::Vec<u8>) -> Result<Self, ::tari_utilities::ByteArrayError> {
+                if bytes.len() > Self::MAX_LEN {
+                    return Err(
+                        ::tari_utilities::ByteArrayError::incorrect_length(Self::MAX_LEN, bytes.len())
+                            .with_context(stringify!(#name)),
+                    );
+                }
+                Ok(Self(bytes))
+            }
+
+            /// Return the wrapped bytes
+            #[allow(dead_code)]
+            pub fn into_inner(self) -> ::tari_utilities::byte_newtype::__private::Vec<u8> {
+                self.0
+            }
+        }
+
+        impl ::tari_utilities::ByteArray for #name {
+            fn from_canonical_bytes(bytes: &[u8]) -> Result<Self, ::tari_utilities::ByteArrayError> {
+                if bytes.len() > Self::MAX_LEN {
+                    return Err(
+                        ::tari_utilities::ByteArrayError::incorrect_length(Self::MAX_LEN, bytes.len())
+                            .with_context(stringify!(#name)),
+                    );
+                }
+                Ok(Self(bytes.to_vec()))
+            }
+
+            fn as_bytes(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl ::core::convert::TryFrom<::tari_utilities::byte_newtype::__private::Vec<u8>> for #name {
+            type Error = ::tari_utilities::ByteArrayError;
+
+            fn try_from(bytes: ::tari_utilities::byte_newtype::__private::Vec<u8>) -> Result<Self, Self::Error> {
+                Self::try_new(bytes)
+            }
+        }
+
+        impl ::core::convert::AsRef<[u8]> for #name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl ::core::fmt::Display for #name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::tari_utilities::hex::fmt_hex(f, &self.0)
+            }
+        }
+
+        impl ::core::fmt::Debug for #name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::tari_utilities::byte_newtype::fmt_debug(f, stringify!(#name), &self.0)
+            }
+        }
+
+        impl ::core::str::FromStr for #name {
+            type Err = ::tari_utilities::hex::HexError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                <Self as ::tari_utilities::hex::Hex>::from_hex(s)
+            }
+        }
+
+        ::tari_utilities::__byte_newtype_serde!(#name);
+        ::tari_utilities::__byte_newtype_borsh_vec!(#name);
+    })
+}
+
+/// Derive `tari_utilities::hex::Hex`, `Display` and `FromStr` for a tuple struct wrapping a `[u8; N]` or `Vec<u8>`.
+///
+/// The type is displayed and parsed as lowercase hex. A `[u8; N]` newtype only parses exactly `N` bytes. Do not use
+/// this on a type that also implements `ByteArray`, which already provides `Hex`.
+#[proc_macro_derive(Hex)]
+pub fn derive_hex(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_hex(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand_hex(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(input.generics.span(), "Hex does not support generics"));
+    }
+    let ty = byte_field_type(input, "Hex")?;
+
+    Ok(quote! {
+        impl ::tari_utilities::hex::Hex for #name {
+            fn from_hex(hex: &str) -> Result<Self, ::tari_utilities::hex::HexError> {
+                <#ty as ::tari_utilities::hex::Hex>::from_hex(hex).map(Self)
+            }
+
+            fn to_hex(&self) -> ::tari_utilities::byte_newtype::__private::String {
+                ::tari_utilities::hex::Hex::to_hex(&self.0)
+            }
+        }
+
+        impl ::core::fmt::Display for #name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::tari_utilities::hex::fmt_hex(f, &self.0)
+            }
+        }
+
+        impl ::core::str::FromStr for #name {
+            type Err = ::tari_utilities::hex::HexError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                <Self as ::tari_utilities::hex::Hex>::from_hex(s)
+            }
+        }
+    })
+}
+
+/// Derive `tari_utilities::ByteArray`, `TryFrom<&[u8]>` and `AsRef<[u8]>` for a tuple struct wrapping a `[u8; N]` or
+/// `Vec<u8>`.
+///
+/// A `[u8; N]` newtype only accepts exactly `N` bytes, and length errors name the newtype. The type also gets
+/// `tari_utilities::hex::Hex` through its blanket implementation for byte arrays.
+#[proc_macro_derive(ByteArray)]
+pub fn derive_byte_array(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_byte_array(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_byte_array(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(input.generics.span(), "ByteArray does not support generics"));
+    }
+    let ty = byte_field_type(input, "ByteArray")?;
+
+    Ok(quote! {
+        impl ::tari_utilities::ByteArray for #name {
+            fn from_canonical_bytes(bytes: &[u8]) -> Result<Self, ::tari_utilities::ByteArrayError> {
+                <#ty as ::tari_utilities::ByteArray>::from_canonical_bytes(bytes)
+                    .map(Self)
+                    .map_err(|err| err.with_context(stringify!(#name)))
+            }
+
+            fn as_bytes(&self) -> &[u8] {
+                ::tari_utilities::ByteArray::as_bytes(&self.0)
+            }
+        }
+
+        impl ::core::convert::TryFrom<&[u8]> for #name {
+            type Error = ::tari_utilities::ByteArrayError;
+
+            fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+                <Self as ::tari_utilities::ByteArray>::from_canonical_bytes(bytes)
+            }
+        }
+
+        impl ::core::convert::AsRef<[u8]> for #name {
+            fn as_ref(&self) -> &[u8] {
+                ::tari_utilities::ByteArray::as_bytes(&self.0)
+            }
+        }
+    })
+}
+
+/// Derive `tari_utilities::ByteArrayRef` for a `#[repr(transparent)]` tuple struct wrapping a `[u8; N]`, so that it
+/// can be borrowed from a byte slice without copying. The type must also implement `tari_utilities::ByteArray`,
+/// usually with `#[derive(ByteArray)]`.
+///
+/// The generated code casts the reference to the array, which is sound because of the `repr(transparent)` layout. It
+/// allows `unsafe_code` locally, so it also compiles in crates that deny it.
+#[proc_macro_derive(ByteArrayRef)]
+pub fn derive_byte_array_ref(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_byte_array_ref(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_byte_array_ref(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let name = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new(
+            input.generics.span(),
+            "ByteArrayRef does not support generics",
+        ));
+    }
+    let ty = byte_field_type(input, "ByteArrayRef")?;
+    if !matches!(ty, Type::Array(_)) {
+        return Err(Error::new(ty.span(), "ByteArrayRef requires a `[u8; N]` field"));
+    }
+    if !is_repr_transparent(input)? {
+        return Err(Error::new(
+            name.span(),
+            "ByteArrayRef requires the struct to be `#[repr(transparent)]`",
+        ));
+    }
+
+    Ok(quote! {
+        impl ::tari_utilities::ByteArrayRef for #name {
+            #[allow(unsafe_code)]
+            fn try_from_bytes_ref(bytes: &[u8]) -> Result<&Self, ::tari_utilities::ByteArrayError> {
+                let array = <#ty as ::tari_utilities::ByteArrayRef>::try_from_bytes_ref(bytes)
+                    .map_err(|err| err.with_context(stringify!(#name)))?;
+                // SAFETY: the type is `repr(transparent)` over the array, so a reference to one is a valid reference
+                // to the other
+                Ok(unsafe { &*(array as *const #ty as *const Self) })
+            }
+        }
+    })
+}
+
+/// Return whether the input has a `#[repr(transparent)]` attribute.
+fn is_repr_transparent(input: &DeriveInput) -> Result<bool, Error> {
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        let reprs = attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)?;
+        if reprs.iter().any(|repr| repr.path().is_ident("transparent")) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Return the field type of a tuple struct with a single `[u8; N]` or `Vec<u8>` field, for the derive `derive`.
+fn byte_field_type<'a>(input: &'a DeriveInput, derive: &str) -> Result<&'a Type, Error> {
+    let error = || {
+        Error::new(
+            input.ident.span(),
+            format!(
+                "{} can only be derived for `struct Name([u8; N])` or `struct Name(Vec<u8>)`",
+                derive
+            ),
+        )
+    };
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => return Err(error()),
+    };
+    let field = match fields {
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0],
+        _ => return Err(error()),
+    };
+    match &field.ty {
+        ty @ Type::Array(_) => Ok(ty),
+        ty @ Type::Path(path) if path.path.segments.last().is_some_and(|s| s.ident == "Vec") => Ok(ty),
+        ty => Err(Error::new(
+            ty.span(),
+            format!("{} requires a `[u8; N]` or `Vec<u8>` field", derive),
+        )),
+    }
+}
+
+/// Check that the input is a tuple struct with a single `Vec<u8>` field.
+fn check_fields(input: &DeriveInput) -> Result<(), Error> {
+    let error = || {
+        Error::new(
+            input.ident.span(),
+            "ByteNewtype can only be derived for `struct Name(Vec<u8>)`",
+        )
+    };
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => return Err(error()),
+    };
+    let field = match fields {
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0],
+        _ => return Err(error()),
+    };
+    match &field.ty {
+        Type::Path(path) if path.path.segments.last().is_some_and(|s| s.ident == "Vec") => Ok(()),
+        ty => Err(Error::new(ty.span(), "ByteNewtype requires a `Vec<u8>` field")),
+    }
+}
+
+/// Read `max_len` from the `byte_newtype` attribute, or fall back to the default.
+fn max_len(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let mut max_len = None;
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("byte_newtype")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("max_len") {
+                let value: Expr = meta.value()?.parse()?;
+                max_len = Some(quote!(#value));
+                Ok(())
+            } else {
+                Err(meta.error("unknown byte_newtype attribute, expected `max_len`"))
+            }
+        })?;
+    }
+    Ok(max_len.unwrap_or_else(|| quote!(::tari_utilities::byte_newtype::DEFAULT_MAX_LEN)))
+}
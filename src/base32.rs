@@ -0,0 +1,512 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//! Base32 encoding and decoding from [RFC 4648](https://www.rfc-editor.org/rfc/rfc4648), with the standard and
+//! extended hex alphabets, padded or unpadded, and with the human-oriented
+//! [z-base-32](https://philzimmermann.com/docs/human-oriented-base-32-encoding.txt) and
+//! [Crockford](https://www.crockford.com/base32.html) alphabets.
+//!
+//! Encoding produces uppercase symbols for the RFC 4648 and Crockford alphabets and lowercase symbols for z-base-32,
+//! as their specifications do, and decoding accepts either case. Padded variants require the
+//! `=` padding to complete the last group of 8 symbols, and unpadded variants reject it. The unused bits of the last
+//! symbol must be zero, so every byte string has exactly one encoding in each variant.
+//!
+//! ```edition2018
+//! # use tari_utilities::base32::{Base32, Base32Variant};
+//! let secret = b"12345678901234567890".to_vec();
+//! assert_eq!(secret.to_base32(), "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+//! assert_eq!(
+//!     Vec::from_base32("gezdgnbvgy3tqojqgezdgnbvgy3tqojq").unwrap(),
+//!     secret
+//! );
+//! assert_eq!(
+//!     b"f".to_vec()
+//!         .to_base32_variant(Base32Variant::ExtendedHexNoPad),
+//!     "CO"
+//! );
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use snafu::prelude::*;
+
+use crate::ByteArray;
+
+/// The number of bytes in a group, which encodes to [GROUP_SYMBOLS] symbols.
+const GROUP_BYTES: usize = 5;
+/// The number of symbols a full group encodes to.
+const GROUP_SYMBOLS: usize = 8;
+/// The number of symbols each partial group size encodes to.
+const ENCODED_GROUP_SIZES: [usize; GROUP_BYTES + 1] = [0, 2, 4, 5, 7, 8];
+const STANDARD_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+const EXTENDED_HEX_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHIJKLMNOPQRSTUV";
+const Z_BASE_32_ALPHABET: &[u8; 32] = b"ybndrfg8ejkmcpqxot1uwisza345h769";
+const CROCKFORD_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// Trait for encoding/decoding to base32.
+pub trait Base32 {
+    /// Convert from a standard, padded base32 string.
+    fn from_base32(b32: &str) -> Result<Self, Base32Error>
+    where Self: Sized {
+        Self::from_base32_variant(b32, Base32Variant::Standard)
+    }
+
+    /// Convert from a base32 string in the given variant.
+    fn from_base32_variant(b32: &str, variant: Base32Variant) -> Result<Self, Base32Error>
+    where Self: Sized;
+
+    /// Convert to a standard, padded base32 string.
+    fn to_base32(&self) -> String {
+        self.to_base32_variant(Base32Variant::Standard)
+    }
+
+    /// Convert to a base32 string in the given variant.
+    fn to_base32_variant(&self, variant: Base32Variant) -> String;
+}
+
+/// The alphabet and padding of a base32 encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base32Variant {
+    /// The standard alphabet, `A-Z` and `2-7`, padded with `=`.
+    Standard,
+    /// The standard alphabet without padding, as used by TOTP secrets and onion addresses.
+    StandardNoPad,
+    /// The extended hex alphabet, `0-9` and `A-V`, padded with `=`. It preserves the sort order of the bytes.
+    ExtendedHex,
+    /// The extended hex alphabet without padding.
+    ExtendedHexNoPad,
+    /// The z-base-32 alphabet, which is never padded. It avoids symbols that are easily confused and puts the
+    /// easiest to read and write where they occur most, for identifiers that people handle.
+    ZBase32,
+    /// Douglas Crockford's alphabet, `0-9` and `A-Z` without `I`, `L`, `O` and `U`, which is never padded. Decoding
+    /// also reads `I` and `L` as `1` and `O` as `0`. Like the extended hex alphabet, it preserves the sort order of
+    /// the bytes. ULIDs use it.
+    Crockford,
+}
+
+impl Base32Variant {
+    pub(crate) const fn alphabet(self) -> &'static [u8; 32] {
+        match self {
+            Base32Variant::Standard | Base32Variant::StandardNoPad => STANDARD_ALPHABET,
+            Base32Variant::ExtendedHex | Base32Variant::ExtendedHexNoPad => EXTENDED_HEX_ALPHABET,
+            Base32Variant::ZBase32 => Z_BASE_32_ALPHABET,
+            Base32Variant::Crockford => CROCKFORD_ALPHABET,
+        }
+    }
+
+    const fn is_padded(self) -> bool {
+        matches!(self, Base32Variant::Standard | Base32Variant::ExtendedHex)
+    }
+
+    /// Return the value of a symbol in either case, or `None` if it is not in the alphabet.
+    pub(crate) const fn decode_symbol(self, symbol: u8) -> Option<u8> {
+        match self {
+            Base32Variant::Standard | Base32Variant::StandardNoPad => match symbol {
+                b'A'..=b'Z' => Some(symbol.wrapping_sub(b'A')),
+                b'a'..=b'z' => Some(symbol.wrapping_sub(b'a')),
+                b'2'..=b'7' => Some(symbol.wrapping_sub(b'2').wrapping_add(26)),
+                _ => None,
+            },
+            Base32Variant::ExtendedHex | Base32Variant::ExtendedHexNoPad => match symbol {
+                b'0'..=b'9' => Some(symbol.wrapping_sub(b'0')),
+                b'A'..=b'V' => Some(symbol.wrapping_sub(b'A').wrapping_add(10)),
+                b'a'..=b'v' => Some(symbol.wrapping_sub(b'a').wrapping_add(10)),
+                _ => None,
+            },
+            Base32Variant::ZBase32 => {
+                let symbol = symbol.to_ascii_lowercase();
+                let mut i = 0;
+                while i < Z_BASE_32_ALPHABET.len() {
+                    // The index is bounded by the loop condition
+                    #[allow(clippy::indexing_slicing)]
+                    if Z_BASE_32_ALPHABET[i] == symbol {
+                        return Some(i as u8);
+                    }
+                    i = i.saturating_add(1);
+                }
+                None
+            },
+            Base32Variant::Crockford => match symbol.to_ascii_uppercase() {
+                c @ b'0'..=b'9' => Some(c.wrapping_sub(b'0')),
+                b'O' => Some(0),
+                b'I' | b'L' => Some(1),
+                c @ b'A'..=b'H' => Some(c.wrapping_sub(b'A').wrapping_add(10)),
+                c @ (b'J' | b'K') => Some(c.wrapping_sub(b'J').wrapping_add(18)),
+                c @ (b'M' | b'N') => Some(c.wrapping_sub(b'M').wrapping_add(20)),
+                c @ b'P'..=b'T' => Some(c.wrapping_sub(b'P').wrapping_add(22)),
+                c @ b'V'..=b'Z' => Some(c.wrapping_sub(b'V').wrapping_add(27)),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Errors for base32 decoding.
+#[derive(Debug, Snafu, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum Base32Error {
+    #[snafu(display("Only base32 characters are permitted, found {character:?} at position {position}"))]
+    InvalidCharacter { position: usize, character: char },
+    #[snafu(display("{length} symbols is not a valid length for base32"))]
+    InvalidLength { length: usize },
+    #[snafu(display("The base32 padding is missing or incorrect"))]
+    InvalidPadding {},
+    #[snafu(display("The unused bits of the last base32 symbol are not zero"))]
+    NonZeroTrailingBits {},
+    #[snafu(display("Byte array error: `{reason}'"))]
+    ByteArrayError { reason: String },
+}
+
+impl<T: ByteArray> Base32 for T {
+    fn from_base32_variant(b32: &str, variant: Base32Variant) -> Result<Self, Base32Error> {
+        let bytes = from_base32(b32, variant)?;
+        Self::from_canonical_bytes(&bytes).map_err(|e| Base32Error::ByteArrayError { reason: e.to_string() })
+    }
+
+    fn to_base32_variant(&self, variant: Base32Variant) -> String {
+        to_base32(self.as_bytes(), variant)
+    }
+}
+
+/// The number of symbols, including any padding, that `len` bytes encode to.
+pub const fn encoded_len(len: usize, variant: Base32Variant) -> usize {
+    let full = (len / GROUP_BYTES).saturating_mul(GROUP_SYMBOLS);
+    let partial = match len % GROUP_BYTES {
+        0 => 0,
+        _ if variant.is_padded() => GROUP_SYMBOLS,
+        1 => 2,
+        2 => 4,
+        3 => 5,
+        _ => 7,
+    };
+    full.saturating_add(partial)
+}
+
+/// Encode the bytes as base32 in the given variant.
+pub fn to_base32(bytes: &[u8], variant: Base32Variant) -> String {
+    let mut b32 = String::with_capacity(encoded_len(bytes.len(), variant));
+    // Writing to a string cannot fail
+    let _result = fmt_base32(&mut b32, bytes, variant);
+    b32
+}
+
+/// Write the bytes as base32 to a formatter or any other [fmt::Write], without allocating. The output is identical to
+/// [to_base32].
+pub fn fmt_base32<W: fmt::Write + ?Sized>(w: &mut W, bytes: &[u8], variant: Base32Variant) -> fmt::Result {
+    fmt_base32_case(w, bytes, variant, false)
+}
+
+/// Write the bytes as base32 with lowercase symbols, as used by onion addresses. Decoding accepts either case.
+pub fn fmt_base32_lower<W: fmt::Write + ?Sized>(w: &mut W, bytes: &[u8], variant: Base32Variant) -> fmt::Result {
+    fmt_base32_case(w, bytes, variant, true)
+}
+
+fn fmt_base32_case<W: fmt::Write + ?Sized>(
+    w: &mut W,
+    bytes: &[u8],
+    variant: Base32Variant,
+    lowercase: bool,
+) -> fmt::Result {
+    let alphabet = variant.alphabet();
+    for group in bytes.chunks(GROUP_BYTES) {
+        // Each group is a 40-bit big-endian integer, zero-filled on the right
+        let value = (0..GROUP_BYTES).fold(0u64, |value, i| {
+            (value << 8) | u64::from(group.get(i).copied().unwrap_or_default())
+        });
+        let symbols = ENCODED_GROUP_SIZES.get(group.len()).copied().unwrap_or_default();
+        let mut buffer = [b'='; GROUP_SYMBOLS];
+        for (i, c) in buffer.iter_mut().take(symbols).enumerate() {
+            let shift = 35usize.saturating_sub(i.saturating_mul(5));
+            let symbol = alphabet
+                .get((value >> shift) as usize & 0x1f)
+                .copied()
+                .unwrap_or_default();
+            *c = if lowercase { symbol.to_ascii_lowercase() } else { symbol };
+        }
+        let len = if variant.is_padded() { GROUP_SYMBOLS } else { symbols };
+        let encoded = buffer.get(..len).unwrap_or_default();
+        w.write_str(core::str::from_utf8(encoded).map_err(|_| fmt::Error)?)?;
+    }
+    Ok(())
+}
+
+/// Displays bytes as base32 without allocating, for use in log statements and error messages.
+///
+/// ```edition2018
+/// # use tari_utilities::base32::{Base32Display, Base32Variant};
+/// let secret = [0xde, 0xad, 0xbe, 0xef];
+/// assert_eq!(
+///     format!(
+///         "Secret {}",
+///         Base32Display(&secret, Base32Variant::StandardNoPad)
+///     ),
+///     "Secret 32W353Y"
+/// );
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Base32Display<'a>(pub &'a [u8], pub Base32Variant);
+
+impl fmt::Display for Base32Display<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_base32(f, self.0, self.1)
+    }
+}
+
+impl fmt::Debug for Base32Display<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_base32(f, self.0, self.1)
+    }
+}
+
+/// Decode a base32 string in the given variant into bytes.
+///
+/// # Errors
+/// Returns [Base32Error::InvalidCharacter] for a symbol that is not in the alphabet, [Base32Error::InvalidLength] if
+/// the number of symbols cannot encode whole bytes, [Base32Error::InvalidPadding] if the padding is wrong for the
+/// variant, and [Base32Error::NonZeroTrailingBits] if the encoding is not canonical.
+pub fn from_base32(b32: &str, variant: Base32Variant) -> Result<Vec<u8>, Base32Error> {
+    let symbols = unpadded(b32.as_bytes(), variant)?;
+    ensure!(
+        symbols.len().is_multiple_of(GROUP_SYMBOLS) || decoded_group_len(symbols.len() % GROUP_SYMBOLS) != 0,
+        InvalidLengthSnafu { length: symbols.len() }
+    );
+    let mut bytes = Vec::with_capacity(symbols.len().saturating_mul(5) / 8);
+    for (index, group) in symbols.chunks(GROUP_SYMBOLS).enumerate() {
+        let start = index.saturating_mul(GROUP_SYMBOLS);
+        let mut value = 0u64;
+        for (i, symbol) in group.iter().enumerate() {
+            let digit = variant.decode_symbol(*symbol).context(InvalidCharacterSnafu {
+                position: start.saturating_add(i),
+                character: char::from(*symbol),
+            })?;
+            value = (value << 5) | u64::from(digit);
+        }
+        let len = decoded_group_len(group.len());
+        // The bits beyond the last whole byte must be zero
+        let trailing = group.len().saturating_mul(5).saturating_sub(len.saturating_mul(8));
+        ensure!(value.trailing_zeros() as usize >= trailing, NonZeroTrailingBitsSnafu);
+        let value = value >> trailing;
+        bytes.extend((0..len).rev().map(|i| (value >> i.saturating_mul(8)) as u8));
+    }
+    Ok(bytes)
+}
+
+/// The number of bytes a group of symbols decodes to, or zero if no number of bytes encodes to that many symbols.
+fn decoded_group_len(symbols: usize) -> usize {
+    ENCODED_GROUP_SIZES
+        .iter()
+        .position(|size| *size == symbols)
+        .unwrap_or_default()
+}
+
+/// Check the padding of the input for the variant and return the symbols without it.
+fn unpadded(b32: &[u8], variant: Base32Variant) -> Result<&[u8], Base32Error> {
+    let len = b32.iter().rposition(|c| *c != b'=').map_or(0, |i| i.saturating_add(1));
+    let (symbols, padding) = b32.split_at(len);
+    if variant.is_padded() {
+        ensure!(b32.len().is_multiple_of(GROUP_SYMBOLS), InvalidPaddingSnafu);
+        ensure!(padding.len() < GROUP_SYMBOLS, InvalidPaddingSnafu);
+    } else {
+        ensure!(padding.is_empty(), InvalidPaddingSnafu);
+    }
+    if let Some(position) = symbols.iter().position(|c| *c == b'=') {
+        return InvalidCharacterSnafu {
+            position,
+            character: '=',
+        }
+        .fail();
+    }
+    Ok(symbols)
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+
+    use super::*;
+
+    const VARIANTS: [Base32Variant; 6] = [
+        Base32Variant::Standard,
+        Base32Variant::StandardNoPad,
+        Base32Variant::ExtendedHex,
+        Base32Variant::ExtendedHexNoPad,
+        Base32Variant::ZBase32,
+        Base32Variant::Crockford,
+    ];
+
+    #[test]
+    fn rfc_vectors() {
+        let vectors = [
+            ("", "", ""),
+            ("f", "MY======", "CO======"),
+            ("fo", "MZXQ====", "CPNG===="),
+            ("foo", "MZXW6===", "CPNMU==="),
+            ("foob", "MZXW6YQ=", "CPNMUOG="),
+            ("fooba", "MZXW6YTB", "CPNMUOJ1"),
+            ("foobar", "MZXW6YTBOI======", "CPNMUOJ1E8======"),
+        ];
+        for (plain, standard, hex) in vectors {
+            let bytes = plain.as_bytes();
+            assert_eq!(to_base32(bytes, Base32Variant::Standard), standard);
+            assert_eq!(to_base32(bytes, Base32Variant::ExtendedHex), hex);
+            assert_eq!(
+                to_base32(bytes, Base32Variant::StandardNoPad),
+                standard.trim_end_matches('=')
+            );
+            assert_eq!(from_base32(standard, Base32Variant::Standard).unwrap(), bytes);
+            assert_eq!(from_base32(hex, Base32Variant::ExtendedHex).unwrap(), bytes);
+            assert_eq!(
+                from_base32(
+                    &hex.trim_end_matches('=').to_lowercase(),
+                    Base32Variant::ExtendedHexNoPad
+                )
+                .unwrap(),
+                bytes
+            );
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        for len in 0..32 {
+            let bytes = (0..len).map(|i| (i * 37 + 11) as u8).collect::<Vec<_>>();
+            for variant in VARIANTS {
+                let encoded = bytes.to_base32_variant(variant);
+                assert_eq!(encoded.len(), encoded_len(len, variant));
+                assert_eq!(Vec::from_base32_variant(&encoded, variant).unwrap(), bytes);
+                let mut lower = String::new();
+                fmt_base32_lower(&mut lower, &bytes, variant).unwrap();
+                assert_eq!(lower, encoded.to_lowercase());
+                assert_eq!(Base32Display(&bytes, variant).to_string(), encoded);
+            }
+        }
+    }
+
+    #[test]
+    fn z_base_32() {
+        let vectors = [("", ""), ("f", "ca"), ("hello", "pb1sa5dx"), ("foobar", "c3zs6aubqe")];
+        for (plain, encoded) in vectors {
+            assert_eq!(to_base32(plain.as_bytes(), Base32Variant::ZBase32), encoded);
+            assert_eq!(from_base32(encoded, Base32Variant::ZBase32).unwrap(), plain.as_bytes());
+            assert_eq!(
+                from_base32(&encoded.to_uppercase(), Base32Variant::ZBase32).unwrap(),
+                plain.as_bytes()
+            );
+        }
+        assert_eq!(
+            from_base32("pb1sa5dl", Base32Variant::ZBase32),
+            Err(Base32Error::InvalidCharacter {
+                position: 7,
+                character: 'l'
+            })
+        );
+        assert_eq!(
+            from_base32("ca======", Base32Variant::ZBase32),
+            Err(Base32Error::InvalidPadding {})
+        );
+        assert_eq!(
+            from_base32("cb", Base32Variant::ZBase32),
+            Err(Base32Error::NonZeroTrailingBits {})
+        );
+    }
+
+    #[test]
+    fn crockford() {
+        let vectors = [("", ""), ("f", "CR"), ("foobar", "CSQPYRK1E8")];
+        for (plain, encoded) in vectors {
+            assert_eq!(to_base32(plain.as_bytes(), Base32Variant::Crockford), encoded);
+            assert_eq!(
+                from_base32(encoded, Base32Variant::Crockford).unwrap(),
+                plain.as_bytes()
+            );
+            assert_eq!(
+                from_base32(&encoded.to_lowercase(), Base32Variant::Crockford).unwrap(),
+                plain.as_bytes()
+            );
+        }
+        for (i, &c) in CROCKFORD_ALPHABET.iter().enumerate() {
+            assert_eq!(Base32Variant::Crockford.decode_symbol(c), Some(i as u8));
+            assert_eq!(
+                Base32Variant::Crockford.decode_symbol(c.to_ascii_lowercase()),
+                Some(i as u8)
+            );
+        }
+        assert_eq!(Base32Variant::Crockford.decode_symbol(b'o'), Some(0));
+        assert_eq!(Base32Variant::Crockford.decode_symbol(b'I'), Some(1));
+        assert_eq!(Base32Variant::Crockford.decode_symbol(b'l'), Some(1));
+        assert_eq!(Base32Variant::Crockford.decode_symbol(b'U'), None);
+        assert_eq!(Base32Variant::Crockford.decode_symbol(b'-'), None);
+        assert_eq!(
+            from_base32("CR==", Base32Variant::Crockford),
+            Err(Base32Error::InvalidPadding {})
+        );
+    }
+
+    #[test]
+    fn invalid_input() {
+        use Base32Variant::*;
+        assert_eq!(
+            from_base32("MZXW1===", Standard),
+            Err(Base32Error::InvalidCharacter {
+                position: 4,
+                character: '1'
+            })
+        );
+        assert_eq!(
+            from_base32("MZXW6YTBW", StandardNoPad),
+            Err(Base32Error::InvalidLength { length: 9 })
+        );
+        assert_eq!(from_base32("MZXW6", Standard), Err(Base32Error::InvalidPadding {}));
+        assert_eq!(
+            from_base32("MZXW6===", StandardNoPad),
+            Err(Base32Error::InvalidPadding {})
+        );
+        assert_eq!(from_base32("========", Standard), Err(Base32Error::InvalidPadding {}));
+        assert_eq!(
+            from_base32("MY=A====", Standard),
+            Err(Base32Error::InvalidCharacter {
+                position: 2,
+                character: '='
+            })
+        );
+        assert_eq!(
+            from_base32("MZ", StandardNoPad),
+            Err(Base32Error::NonZeroTrailingBits {})
+        );
+        assert_eq!(
+            from_base32("CPNMUOJ1", Standard).unwrap_err(),
+            Base32Error::InvalidCharacter {
+                position: 7,
+                character: '1'
+            }
+        );
+        assert!(matches!(
+            <[u8; 4]>::from_base32("MZXW6==="),
+            Err(Base32Error::ByteArrayError { .. })
+        ));
+        assert_eq!(<[u8; 3]>::from_base32("MZXW6===").unwrap(), *b"foo");
+        assert_eq!(Vec::from_base32("").unwrap(), vec![0u8; 0]);
+    }
+}
@@ -0,0 +1,410 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//! Base64 encoding and decoding from [RFC 4648](https://www.rfc-editor.org/rfc/rfc4648), with the standard and URL-safe
+//! alphabets, padded or unpadded.
+//!
+//! This works under `no_std` with `alloc`, and [encode_into] and [decode_into] work on caller buffers without
+//! allocating at all. Padded variants require the `=` padding to complete the last group of 4 symbols, and unpadded
+//! variants reject it. The unused bits of the last symbol must be zero, so every byte string has exactly one encoding
+//! in each variant. The [Base64](crate::encoding::Base64) trait, with the `std` feature, is built on this module.
+//!
+//! ```edition2018
+//! # use tari_utilities::base64::{decode_into, encode_into, from_base64, to_base64, Base64Variant};
+//! assert_eq!(to_base64(b"foobar", Base64Variant::Standard), "Zm9vYmFy");
+//! assert_eq!(from_base64("-_8", Base64Variant::UrlSafeNoPad).unwrap(), [0xfb, 0xff]);
+//!
+//! let mut encoded = [0u8; 8];
+//! let len = encode_into(b"fooba", &mut encoded, Base64Variant::Standard).unwrap();
+//! assert_eq!(&encoded[..len], b"Zm9vYmE=");
+//! let mut decoded = [0u8; 5];
+//! assert_eq!(decode_into(&encoded[..len], &mut decoded, Base64Variant::Standard), Ok(5));
+//! assert_eq!(&decoded, b"fooba");
+//! ```
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use snafu::prelude::*;
+
+/// The number of bytes in a group, which encodes to [GROUP_SYMBOLS] symbols.
+const GROUP_BYTES: usize = 3;
+/// The number of symbols a full group encodes to.
+const GROUP_SYMBOLS: usize = 4;
+const STANDARD_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_SAFE_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// The alphabet and padding of a base64 encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base64Variant {
+    /// The standard alphabet, with `+` and `/`, padded with `=`.
+    Standard,
+    /// The standard alphabet without padding.
+    StandardNoPad,
+    /// The URL and filename safe alphabet, with `-` and `_`, padded with `=`.
+    UrlSafe,
+    /// The URL and filename safe alphabet without padding.
+    UrlSafeNoPad,
+}
+
+impl Base64Variant {
+    pub(crate) const fn alphabet(self) -> &'static [u8; 64] {
+        match self {
+            Base64Variant::Standard | Base64Variant::StandardNoPad => STANDARD_ALPHABET,
+            Base64Variant::UrlSafe | Base64Variant::UrlSafeNoPad => URL_SAFE_ALPHABET,
+        }
+    }
+
+    const fn is_padded(self) -> bool {
+        matches!(self, Base64Variant::Standard | Base64Variant::UrlSafe)
+    }
+
+    /// Return the value of a symbol, or `None` if it is not in the alphabet.
+    const fn decode_symbol(self, symbol: u8) -> Option<u8> {
+        match symbol {
+            b'A'..=b'Z' => Some(symbol.wrapping_sub(b'A')),
+            b'a'..=b'z' => Some(symbol.wrapping_sub(b'a').wrapping_add(26)),
+            b'0'..=b'9' => Some(symbol.wrapping_sub(b'0').wrapping_add(52)),
+            b'+' if matches!(self, Base64Variant::Standard | Base64Variant::StandardNoPad) => Some(62),
+            b'/' if matches!(self, Base64Variant::Standard | Base64Variant::StandardNoPad) => Some(63),
+            b'-' if matches!(self, Base64Variant::UrlSafe | Base64Variant::UrlSafeNoPad) => Some(62),
+            b'_' if matches!(self, Base64Variant::UrlSafe | Base64Variant::UrlSafeNoPad) => Some(63),
+            _ => None,
+        }
+    }
+}
+
+/// Errors for base64 encoding and decoding.
+#[derive(Debug, Clone, Snafu, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum Base64CodecError {
+    #[snafu(display("Only base64 characters are permitted, found {character:?} at position {position}"))]
+    InvalidCharacter { position: usize, character: char },
+    #[snafu(display("{length} symbols is not a valid length for base64"))]
+    InvalidLength { length: usize },
+    #[snafu(display("The base64 padding is missing or incorrect"))]
+    InvalidPadding {},
+    #[snafu(display("The unused bits of the last base64 symbol are not zero"))]
+    NonZeroTrailingBits {},
+    #[snafu(display("The output buffer is too small; {needed} bytes are needed"))]
+    BufferTooSmall { needed: usize },
+}
+
+/// The number of symbols, including any padding, that `len` bytes encode to.
+pub const fn encoded_len(len: usize, variant: Base64Variant) -> usize {
+    let full = (len / GROUP_BYTES).saturating_mul(GROUP_SYMBOLS);
+    let partial = match len % GROUP_BYTES {
+        0 => 0,
+        _ if variant.is_padded() => GROUP_SYMBOLS,
+        1 => 2,
+        _ => 3,
+    };
+    full.saturating_add(partial)
+}
+
+/// Encode the bytes as base64 in the given variant. With the `simd` feature, this uses the fastest implementation
+/// supported by the CPU.
+pub fn to_base64(bytes: &[u8], variant: Base64Variant) -> String {
+    #[cfg(feature = "simd")]
+    return crate::simd::to_base64(bytes, variant);
+    #[cfg(not(feature = "simd"))]
+    {
+        let mut b64 = String::with_capacity(encoded_len(bytes.len(), variant));
+        // Writing to a string cannot fail
+        let _result = fmt_base64(&mut b64, bytes, variant);
+        b64
+    }
+}
+
+/// Write the bytes as base64 to a formatter or any other [fmt::Write], without allocating. The output is identical to
+/// [to_base64].
+pub fn fmt_base64<W: fmt::Write + ?Sized>(w: &mut W, bytes: &[u8], variant: Base64Variant) -> fmt::Result {
+    for group in bytes.chunks(GROUP_BYTES) {
+        let (buffer, len) = encode_group(group, variant);
+        let encoded = buffer.get(..len).unwrap_or_default();
+        w.write_str(core::str::from_utf8(encoded).map_err(|_| fmt::Error)?)?;
+    }
+    Ok(())
+}
+
+/// Encode bytes as base64 into the start of `out`, without allocating. Returns the number of symbols written, which is
+/// [encoded_len] of the input length.
+///
+/// # Errors
+/// Returns [Base64CodecError::BufferTooSmall] if `out` cannot hold all the symbols, in which case nothing is written.
+pub fn encode_into(bytes: &[u8], out: &mut [u8], variant: Base64Variant) -> Result<usize, Base64CodecError> {
+    let needed = encoded_len(bytes.len(), variant);
+    ensure!(needed <= out.len(), BufferTooSmallSnafu { needed });
+    let mut out = out.iter_mut();
+    for group in bytes.chunks(GROUP_BYTES) {
+        let (buffer, len) = encode_group(group, variant);
+        // The symbols come first, so that `out` is not advanced past the end of the group
+        for (symbol, c) in buffer.iter().take(len).zip(out.by_ref()) {
+            *c = *symbol;
+        }
+    }
+    Ok(needed)
+}
+
+/// Encode up to three bytes, returning the symbols and how many of them to write.
+fn encode_group(group: &[u8], variant: Base64Variant) -> ([u8; GROUP_SYMBOLS], usize) {
+    let alphabet = variant.alphabet();
+    let value = (0..GROUP_BYTES).fold(0u32, |value, i| {
+        (value << 8) | u32::from(group.get(i).copied().unwrap_or_default())
+    });
+    let symbols = group.len().saturating_add(1);
+    let mut buffer = [b'='; GROUP_SYMBOLS];
+    for (i, c) in buffer.iter_mut().take(symbols).enumerate() {
+        let shift = 18usize.saturating_sub(i.saturating_mul(6));
+        *c = alphabet
+            .get((value >> shift) as usize & 0x3f)
+            .copied()
+            .unwrap_or_default();
+    }
+    let len = if variant.is_padded() { GROUP_SYMBOLS } else { symbols };
+    (buffer, len)
+}
+
+/// Displays bytes as base64 without allocating, for use in log statements and error messages.
+///
+/// ```edition2018
+/// # use tari_utilities::base64::{Base64Display, Base64Variant};
+/// let token = [0xfb, 0xff, 0x01];
+/// assert_eq!(
+///     format!("Token {}", Base64Display(&token, Base64Variant::UrlSafe)),
+///     "Token -_8B"
+/// );
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Base64Display<'a>(pub &'a [u8], pub Base64Variant);
+
+impl fmt::Display for Base64Display<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_base64(f, self.0, self.1)
+    }
+}
+
+impl fmt::Debug for Base64Display<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_base64(f, self.0, self.1)
+    }
+}
+
+/// Decode a base64 string in the given variant into bytes. With the `simd` feature, this uses the fastest
+/// implementation supported by the CPU.
+///
+/// # Errors
+/// Returns [Base64CodecError::InvalidCharacter] for a symbol that is not in the alphabet,
+/// [Base64CodecError::InvalidLength] if the number of symbols cannot encode whole bytes,
+/// [Base64CodecError::InvalidPadding] if the padding is wrong for the variant, and
+/// [Base64CodecError::NonZeroTrailingBits] if the encoding is not canonical.
+pub fn from_base64(b64: &str, variant: Base64Variant) -> Result<Vec<u8>, Base64CodecError> {
+    #[cfg(feature = "simd")]
+    return crate::simd::from_base64(b64, variant);
+    #[cfg(not(feature = "simd"))]
+    {
+        let symbols = unpadded(b64.as_bytes(), variant)?;
+        let mut bytes = alloc::vec![0; decoded_len(symbols.len())];
+        decode_symbols(symbols, &mut bytes, variant)?;
+        Ok(bytes)
+    }
+}
+
+/// Decode base64 into the start of `out`, without allocating. Returns the number of bytes written.
+///
+/// # Errors
+/// Returns the same errors as [from_base64], and [Base64CodecError::BufferTooSmall] if `out` cannot hold the bytes.
+/// The contents of `out` are unspecified after an error.
+pub fn decode_into<T: AsRef<[u8]>>(b64: T, out: &mut [u8], variant: Base64Variant) -> Result<usize, Base64CodecError> {
+    let symbols = unpadded(b64.as_ref(), variant)?;
+    let needed = decoded_len(symbols.len());
+    ensure!(needed <= out.len(), BufferTooSmallSnafu { needed });
+    decode_symbols(symbols, out, variant)?;
+    Ok(needed)
+}
+
+/// The number of bytes encoded by a valid number of symbols, without padding.
+const fn decoded_len(symbols: usize) -> usize {
+    (symbols / GROUP_SYMBOLS)
+        .saturating_mul(GROUP_BYTES)
+        .saturating_add((symbols % GROUP_SYMBOLS).saturating_sub(1))
+}
+
+/// Check the padding and length of the input for the variant and return the symbols without padding.
+fn unpadded(b64: &[u8], variant: Base64Variant) -> Result<&[u8], Base64CodecError> {
+    let len = b64.iter().rposition(|c| *c != b'=').map_or(0, |i| i.saturating_add(1));
+    let (symbols, padding) = b64.split_at(len);
+    if variant.is_padded() {
+        ensure!(
+            b64.len().is_multiple_of(GROUP_SYMBOLS) && padding.len() < GROUP_SYMBOLS.saturating_sub(1),
+            InvalidPaddingSnafu
+        );
+    } else {
+        ensure!(padding.is_empty(), InvalidPaddingSnafu);
+    }
+    if let Some(position) = symbols.iter().position(|c| *c == b'=') {
+        return InvalidCharacterSnafu {
+            position,
+            character: '=',
+        }
+        .fail();
+    }
+    ensure!(symbols.len() % GROUP_SYMBOLS != 1, InvalidLengthSnafu {
+        length: b64.len()
+    });
+    Ok(symbols)
+}
+
+/// Decode symbols of a valid length into the start of `out`, which must be large enough.
+fn decode_symbols(symbols: &[u8], out: &mut [u8], variant: Base64Variant) -> Result<(), Base64CodecError> {
+    let mut out = out.iter_mut();
+    for (index, group) in symbols.chunks(GROUP_SYMBOLS).enumerate() {
+        let start = index.saturating_mul(GROUP_SYMBOLS);
+        let mut value = 0u32;
+        for (i, symbol) in group.iter().enumerate() {
+            let digit = variant.decode_symbol(*symbol).context(InvalidCharacterSnafu {
+                position: start.saturating_add(i),
+                character: char::from(*symbol),
+            })?;
+            value = (value << 6) | u32::from(digit);
+        }
+        let len = group.len().saturating_sub(1);
+        // The bits beyond the last whole byte must be zero
+        let trailing = group.len().saturating_mul(6).saturating_sub(len.saturating_mul(8));
+        ensure!(value.trailing_zeros() as usize >= trailing, NonZeroTrailingBitsSnafu);
+        let value = value >> trailing;
+        for (i, byte) in (0..len).rev().zip(out.by_ref()) {
+            *byte = (value >> i.saturating_mul(8)) as u8;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{string::ToString, vec};
+
+    use super::*;
+
+    const VARIANTS: [Base64Variant; 4] = [
+        Base64Variant::Standard,
+        Base64Variant::StandardNoPad,
+        Base64Variant::UrlSafe,
+        Base64Variant::UrlSafeNoPad,
+    ];
+
+    #[test]
+    fn rfc_vectors() {
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (plain, encoded) in vectors {
+            let bytes = plain.as_bytes();
+            assert_eq!(to_base64(bytes, Base64Variant::Standard), encoded);
+            assert_eq!(
+                to_base64(bytes, Base64Variant::UrlSafeNoPad),
+                encoded.trim_end_matches('=')
+            );
+            assert_eq!(from_base64(encoded, Base64Variant::UrlSafe).unwrap(), bytes);
+            assert_eq!(
+                from_base64(encoded.trim_end_matches('='), Base64Variant::StandardNoPad).unwrap(),
+                bytes
+            );
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        for len in 0..32 {
+            let bytes = (0..len).map(|i| (i * 97 + 251) as u8).collect::<Vec<_>>();
+            for variant in VARIANTS {
+                let encoded = to_base64(&bytes, variant);
+                assert_eq!(encoded.len(), encoded_len(len, variant));
+                assert_eq!(Base64Display(&bytes, variant).to_string(), encoded);
+                assert_eq!(from_base64(&encoded, variant).unwrap(), bytes);
+
+                let mut out = vec![0; encoded.len()];
+                assert_eq!(encode_into(&bytes, &mut out, variant), Ok(encoded.len()));
+                assert_eq!(out, encoded.as_bytes());
+                let mut decoded = vec![0; len];
+                assert_eq!(decode_into(&out, &mut decoded, variant), Ok(len));
+                assert_eq!(decoded, bytes);
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_input() {
+        use Base64Variant::*;
+        assert_eq!(
+            from_base64("Zm9v-A==", Standard),
+            Err(Base64CodecError::InvalidCharacter {
+                position: 4,
+                character: '-'
+            })
+        );
+        assert_eq!(
+            from_base64("Zm9v+A==", UrlSafe),
+            Err(Base64CodecError::InvalidCharacter {
+                position: 4,
+                character: '+'
+            })
+        );
+        assert_eq!(
+            from_base64("Zm9vY", StandardNoPad),
+            Err(Base64CodecError::InvalidLength { length: 5 })
+        );
+        assert_eq!(from_base64("Zm8", Standard), Err(Base64CodecError::InvalidPadding {}));
+        assert_eq!(
+            from_base64("Zm8=", StandardNoPad),
+            Err(Base64CodecError::InvalidPadding {})
+        );
+        assert_eq!(from_base64("Z===", Standard), Err(Base64CodecError::InvalidPadding {}));
+        assert_eq!(
+            from_base64("Zg=v", Standard),
+            Err(Base64CodecError::InvalidCharacter {
+                position: 2,
+                character: '='
+            })
+        );
+        assert_eq!(
+            from_base64("Zh==", Standard),
+            Err(Base64CodecError::NonZeroTrailingBits {})
+        );
+        assert_eq!(from_base64("", Standard).unwrap(), vec![0u8; 0]);
+
+        let mut small = [0u8; 3];
+        assert_eq!(
+            encode_into(b"fo", &mut small, Standard),
+            Err(Base64CodecError::BufferTooSmall { needed: 4 })
+        );
+        assert_eq!(
+            decode_into("Zm9vYg==", &mut small, Standard),
+            Err(Base64CodecError::BufferTooSmall { needed: 4 })
+        );
+    }
+}
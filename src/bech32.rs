@@ -0,0 +1,466 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//! Bech32 ([BIP-173](https://github.com/bitcoin/bips/blob/master/bip-0173.mediawiki)) and Bech32m
+//! ([BIP-350](https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki)) encoding of bytes with a
+//! human-readable part (HRP).
+//!
+//! A Bech32 string is the HRP, the separator `1`, the data as 5-bit symbols and a six symbol checksum, which detects
+//! any error affecting up to four symbols. Strings are encoded in lowercase and decoded in either case, but not a mix
+//! of both, and are at most [MAX_LENGTH] characters long. Bytes are packed into 5-bit symbols with zero padding, which
+//! must be zero and shorter than a symbol when decoding.
+//!
+//! ```edition2018
+//! # use tari_utilities::bech32::{decode, encode, Bech32Variant};
+//! let address = encode("tari", &[0xde, 0xad, 0xbe, 0xef], Bech32Variant::Bech32m).unwrap();
+//! assert_eq!(address, "tari1m6kmamc34ssl3");
+//! let decoded = decode(&address).unwrap();
+//! assert_eq!(decoded.hrp, "tari");
+//! assert_eq!(decoded.data, [0xde, 0xad, 0xbe, 0xef]);
+//! assert_eq!(decoded.variant, Bech32Variant::Bech32m);
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, iter};
+
+use snafu::prelude::*;
+
+use crate::ByteArray;
+
+/// The longest valid Bech32 string, in characters.
+pub const MAX_LENGTH: usize = 90;
+/// The longest valid human-readable part, in characters.
+pub const MAX_HRP_LENGTH: usize = 83;
+/// The number of symbols in the checksum.
+const CHECKSUM_LEN: usize = 6;
+const SEPARATOR: char = '1';
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [0x3b6a_57b2, 0x2650_8e6d, 0x1ea1_19fa, 0x3d42_33dd, 0x2a14_62b3];
+
+/// Trait for encoding/decoding to Bech32 with a human-readable part.
+pub trait Bech32 {
+    /// Convert from a Bech32 string, which must have the given human-readable part and variant.
+    fn from_bech32(s: &str, hrp: &str, variant: Bech32Variant) -> Result<Self, Bech32Error>
+    where Self: Sized;
+
+    /// Convert to a Bech32 string with the given human-readable part.
+    ///
+    /// # Errors
+    /// Returns an error if the human-readable part is invalid or the result would be longer than [MAX_LENGTH].
+    fn to_bech32(&self, hrp: &str, variant: Bech32Variant) -> Result<String, Bech32Error>;
+}
+
+/// The checksum variant of a Bech32 string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bech32Variant {
+    /// The original checksum from BIP-173, used by segwit version 0 addresses.
+    Bech32,
+    /// The checksum from BIP-350, which fixes the insertion weakness of Bech32 and is recommended for new formats.
+    Bech32m,
+}
+
+impl Bech32Variant {
+    const fn constant(self) -> u32 {
+        match self {
+            Bech32Variant::Bech32 => 1,
+            Bech32Variant::Bech32m => 0x2bc8_30a3,
+        }
+    }
+
+    fn from_constant(constant: u32) -> Option<Self> {
+        [Bech32Variant::Bech32, Bech32Variant::Bech32m]
+            .iter()
+            .copied()
+            .find(|variant| variant.constant() == constant)
+    }
+}
+
+/// A decoded Bech32 string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bech32Decoded {
+    /// The human-readable part, in lowercase
+    pub hrp: String,
+    /// The data bytes
+    pub data: Vec<u8>,
+    /// The checksum variant
+    pub variant: Bech32Variant,
+}
+
+/// Errors for Bech32 encoding and decoding.
+#[derive(Debug, Snafu, PartialEq, Eq)]
+#[snafu(context(suffix(Bech32Snafu)))]
+#[allow(missing_docs)]
+pub enum Bech32Error {
+    #[snafu(display("The human-readable part must be 1 to {MAX_HRP_LENGTH} ASCII characters from `!' to `~'"))]
+    InvalidHrp {},
+    #[snafu(display("Bech32 strings of {length} characters are not valid"))]
+    InvalidLength { length: usize },
+    #[snafu(display("Bech32 strings must not mix upper and lower case"))]
+    MixedCase {},
+    #[snafu(display("The Bech32 string has no separator"))]
+    MissingSeparator {},
+    #[snafu(display("Only Bech32 characters are permitted, found {character:?} at position {position}"))]
+    InvalidCharacter { position: usize, character: char },
+    #[snafu(display("The Bech32 checksum is invalid"))]
+    InvalidChecksum {},
+    #[snafu(display("The padding of the Bech32 data is invalid"))]
+    InvalidPadding {},
+    #[snafu(display("Expected the human-readable part `{expected}'"))]
+    HrpMismatch { expected: String },
+    #[snafu(display("Expected a {expected:?} checksum"))]
+    VariantMismatch { expected: Bech32Variant },
+    #[snafu(display("Byte array error: `{reason}'"))]
+    ByteArrayError { reason: String },
+}
+
+impl<T: ByteArray> Bech32 for T {
+    fn from_bech32(s: &str, hrp: &str, variant: Bech32Variant) -> Result<Self, Bech32Error> {
+        let decoded = decode(s)?;
+        ensure!(decoded.hrp.eq_ignore_ascii_case(hrp), HrpMismatchBech32Snafu {
+            expected: hrp
+        });
+        ensure!(decoded.variant == variant, VariantMismatchBech32Snafu {
+            expected: variant
+        });
+        Self::from_canonical_bytes(&decoded.data).map_err(|e| Bech32Error::ByteArrayError { reason: e.to_string() })
+    }
+
+    fn to_bech32(&self, hrp: &str, variant: Bech32Variant) -> Result<String, Bech32Error> {
+        encode(hrp, self.as_bytes(), variant)
+    }
+}
+
+/// The length of the Bech32 string for an HRP of `hrp_len` characters and `data_len` bytes.
+pub const fn encoded_len(hrp_len: usize, data_len: usize) -> usize {
+    let symbols = data_len.saturating_mul(8).div_ceil(5);
+    hrp_len
+        .saturating_add(1)
+        .saturating_add(symbols)
+        .saturating_add(CHECKSUM_LEN)
+}
+
+/// Encode the data with the human-readable part, which is converted to lowercase.
+///
+/// # Errors
+/// Returns [Bech32Error::InvalidHrp] if the human-readable part is invalid, and [Bech32Error::InvalidLength] if the
+/// result would be longer than [MAX_LENGTH].
+pub fn encode(hrp: &str, data: &[u8], variant: Bech32Variant) -> Result<String, Bech32Error> {
+    check_hrp(hrp)?;
+    let length = encoded_len(hrp.len(), data.len());
+    ensure!(length <= MAX_LENGTH, InvalidLengthBech32Snafu { length });
+    let mut encoded = String::with_capacity(length);
+    write_bech32(&mut encoded, hrp, data, variant).map_err(|_| Bech32Error::InvalidHrp {})?;
+    Ok(encoded)
+}
+
+/// Write the data as Bech32 to a formatter or any other [fmt::Write], without allocating. The output is identical to
+/// [encode], and an invalid human-readable part or overlong result is reported as [fmt::Error].
+pub fn fmt_bech32<W: fmt::Write + ?Sized>(w: &mut W, hrp: &str, data: &[u8], variant: Bech32Variant) -> fmt::Result {
+    check_hrp(hrp).map_err(|_| fmt::Error)?;
+    if encoded_len(hrp.len(), data.len()) > MAX_LENGTH {
+        return Err(fmt::Error);
+    }
+    write_bech32(w, hrp, data, variant)
+}
+
+fn write_bech32<W: fmt::Write + ?Sized>(w: &mut W, hrp: &str, data: &[u8], variant: Bech32Variant) -> fmt::Result {
+    let hrp = hrp.as_bytes().iter().map(u8::to_ascii_lowercase);
+    for c in hrp.clone() {
+        w.write_char(char::from(c))?;
+    }
+    w.write_char(SEPARATOR)?;
+    let residue = polymod(
+        hrp_expand(hrp)
+            .chain(to_symbols(data))
+            .chain(iter::repeat_n(0, CHECKSUM_LEN)),
+    ) ^ variant.constant();
+    let checksum = (0..CHECKSUM_LEN)
+        .rev()
+        .map(|i| (residue >> i.saturating_mul(5)) as u8 & 0x1f);
+    for symbol in to_symbols(data).chain(checksum) {
+        w.write_char(char::from(
+            CHARSET.get(usize::from(symbol)).copied().unwrap_or_default(),
+        ))?;
+    }
+    Ok(())
+}
+
+/// Displays data as Bech32 without allocating. An invalid human-readable part or overlong result fails to format.
+///
+/// ```edition2018
+/// # use tari_utilities::bech32::{Bech32Display, Bech32Variant};
+/// let address = Bech32Display::new("tari", &[0xde, 0xad, 0xbe, 0xef], Bech32Variant::Bech32m);
+/// assert_eq!(format!("Send to {}", address), "Send to tari1m6kmamc34ssl3");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Bech32Display<'a> {
+    hrp: &'a str,
+    data: &'a [u8],
+    variant: Bech32Variant,
+}
+
+impl<'a> Bech32Display<'a> {
+    /// Display the data with the human-readable part.
+    pub fn new(hrp: &'a str, data: &'a [u8], variant: Bech32Variant) -> Self {
+        Self { hrp, data, variant }
+    }
+}
+
+impl fmt::Display for Bech32Display<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_bech32(f, self.hrp, self.data, self.variant)
+    }
+}
+
+impl fmt::Debug for Bech32Display<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_bech32(f, self.hrp, self.data, self.variant)
+    }
+}
+
+/// Decode a Bech32 or Bech32m string, detecting the variant from the checksum.
+///
+/// # Errors
+/// Returns an error if the string is too long, mixes case, has an invalid human-readable part or character, has a
+/// checksum that is invalid for both variants, or its data padding is not zero.
+pub fn decode(s: &str) -> Result<Bech32Decoded, Bech32Error> {
+    ensure!(s.len() <= MAX_LENGTH, InvalidLengthBech32Snafu { length: s.len() });
+    let has_lower = s.bytes().any(|c| c.is_ascii_lowercase());
+    let has_upper = s.bytes().any(|c| c.is_ascii_uppercase());
+    ensure!(!(has_lower && has_upper), MixedCaseBech32Snafu);
+
+    let separator = s.rfind(SEPARATOR).context(MissingSeparatorBech32Snafu)?;
+    let (hrp, data) = s.split_at(separator);
+    let data = data.get(1..).unwrap_or_default();
+    check_hrp(hrp)?;
+    ensure!(data.len() >= CHECKSUM_LEN, InvalidLengthBech32Snafu { length: s.len() });
+
+    let mut symbols = Vec::with_capacity(data.len());
+    for (i, c) in data.bytes().enumerate() {
+        let symbol = CHARSET
+            .iter()
+            .position(|symbol| *symbol == c.to_ascii_lowercase())
+            .context(InvalidCharacterBech32Snafu {
+                position: separator.saturating_add(1).saturating_add(i),
+                character: char::from(c),
+            })?;
+        symbols.push(symbol as u8);
+    }
+    let hrp = hrp.to_ascii_lowercase();
+    let residue = polymod(hrp_expand(hrp.bytes()).chain(symbols.iter().copied()));
+    let variant = Bech32Variant::from_constant(residue).context(InvalidChecksumBech32Snafu)?;
+
+    let payload = symbols
+        .get(..symbols.len().saturating_sub(CHECKSUM_LEN))
+        .unwrap_or_default();
+    let data = from_symbols(payload)?;
+    Ok(Bech32Decoded { hrp, data, variant })
+}
+
+fn check_hrp(hrp: &str) -> Result<(), Bech32Error> {
+    ensure!(
+        (1..=MAX_HRP_LENGTH).contains(&hrp.len()) && hrp.bytes().all(|c| (b'!'..=b'~').contains(&c)),
+        InvalidHrpBech32Snafu
+    );
+    Ok(())
+}
+
+/// The BCH checksum over GF(32) from BIP-173.
+fn polymod<I: Iterator<Item = u8>>(values: I) -> u32 {
+    values.fold(1u32, |checksum, value| {
+        let top = checksum >> 25;
+        let checksum = ((checksum & 0x01ff_ffff) << 5) ^ u32::from(value);
+        GENERATOR
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| (top >> i) & 1 == 1)
+            .fold(checksum, |checksum, (_, generator)| checksum ^ generator)
+    })
+}
+
+/// The high bits of each HRP character, a zero, then the low bits of each character.
+fn hrp_expand<I: Iterator<Item = u8> + Clone>(hrp: I) -> impl Iterator<Item = u8> {
+    hrp.clone()
+        .map(|c| c >> 5)
+        .chain(iter::once(0))
+        .chain(hrp.map(|c| c & 0x1f))
+}
+
+/// Split bytes into 5-bit symbols, padding the last symbol with zero bits.
+fn to_symbols(data: &[u8]) -> impl Iterator<Item = u8> + '_ {
+    let symbols = data.len().saturating_mul(8).div_ceil(5);
+    (0..symbols).map(move |i| {
+        let bit = i.saturating_mul(5);
+        let byte = bit / 8;
+        let window = u16::from_be_bytes([
+            data.get(byte).copied().unwrap_or_default(),
+            data.get(byte.saturating_add(1)).copied().unwrap_or_default(),
+        ]);
+        (window >> 11usize.saturating_sub(bit % 8)) as u8 & 0x1f
+    })
+}
+
+/// Join 5-bit symbols into bytes. The leftover bits must be fewer than five and zero.
+fn from_symbols(symbols: &[u8]) -> Result<Vec<u8>, Bech32Error> {
+    let mut data = Vec::with_capacity(symbols.len().saturating_mul(5) / 8);
+    let mut buffer = 0u32;
+    let mut bits = 0u32;
+    for symbol in symbols {
+        buffer = ((buffer << 5) | u32::from(*symbol)) & 0xfff;
+        bits = bits.saturating_add(5);
+        if bits >= 8 {
+            bits = bits.saturating_sub(8);
+            data.push((buffer >> bits) as u8);
+        }
+    }
+    ensure!(
+        bits < 5 && buffer & (1u32 << bits).wrapping_sub(1) == 0,
+        InvalidPaddingBech32Snafu
+    );
+    Ok(data)
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::format;
+
+    use super::*;
+
+    /// Decode a string and check that it re-encodes to the same string in lowercase.
+    fn round_trip(s: &str) -> Bech32Variant {
+        let decoded = decode(s).unwrap();
+        assert_eq!(
+            encode(&decoded.hrp, &decoded.data, decoded.variant).unwrap(),
+            s.to_ascii_lowercase()
+        );
+        decoded.variant
+    }
+
+    /// Encode raw symbols with a Bech32m checksum, to build strings with invalid padding.
+    fn encode_symbols(hrp: &str, symbols: &[u8]) -> String {
+        let residue = polymod(
+            hrp_expand(hrp.bytes())
+                .chain(symbols.iter().copied())
+                .chain(iter::repeat_n(0, CHECKSUM_LEN)),
+        ) ^ Bech32Variant::Bech32m.constant();
+        let checksum = (0..CHECKSUM_LEN).rev().map(|i| (residue >> (i * 5)) as u8 & 0x1f);
+        let data = symbols
+            .iter()
+            .copied()
+            .chain(checksum)
+            .map(|symbol| char::from(CHARSET[usize::from(symbol)]));
+        format!("{}1{}", hrp, data.collect::<String>())
+    }
+
+    #[test]
+    fn valid_checksums() {
+        // Test vectors from BIP-173 and BIP-350 that carry whole bytes
+        for s in [
+            "A12UEL5L",
+            "a12uel5l",
+            "abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw",
+            "split1checkupstagehandshakeupstreamerranterredcaperred2y9e3w",
+            "?1ezyfcl",
+        ] {
+            assert_eq!(round_trip(s), Bech32Variant::Bech32);
+        }
+        for s in [
+            "A1LQFN3A",
+            "a1lqfn3a",
+            "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx",
+            "split1checkupstagehandshakeupstreamerranterredcaperredlc445v",
+            "?1v759aa",
+        ] {
+            assert_eq!(round_trip(s), Bech32Variant::Bech32m);
+        }
+    }
+
+    #[test]
+    fn invalid_strings() {
+        assert_eq!(decode("pzry9x0s0muk"), Err(Bech32Error::MissingSeparator {}));
+        assert_eq!(decode("1pzry9x0s0muk"), Err(Bech32Error::InvalidHrp {}));
+        assert_eq!(
+            decode("x1b4n0q5v"),
+            Err(Bech32Error::InvalidCharacter {
+                position: 2,
+                character: 'b'
+            })
+        );
+        assert_eq!(decode("li1dgmt3"), Err(Bech32Error::InvalidLength { length: 8 }));
+        assert_eq!(decode("A1G7SGD8"), Err(Bech32Error::InvalidChecksum {}));
+        assert_eq!(decode("10a06t8"), Err(Bech32Error::InvalidHrp {}));
+        assert_eq!(decode("1qzzfhee"), Err(Bech32Error::InvalidHrp {}));
+        assert_eq!(decode("\u{7f}1axkwrx"), Err(Bech32Error::InvalidHrp {}));
+        assert_eq!(decode("a1Lqfn3a"), Err(Bech32Error::MixedCase {}));
+        assert!(matches!(
+            decode("an84characterslonghumanreadablepartthatcontainsthetheexcludedcharactersbioandnumber11d6pts4"),
+            Err(Bech32Error::InvalidLength { length: 91 })
+        ));
+        // A lone symbol is five bits of padding, and a non-zero final bit is non-zero padding
+        assert_eq!(decode(&encode_symbols("a", &[0])), Err(Bech32Error::InvalidPadding {}));
+        assert_eq!(
+            decode(&encode_symbols("a", &[0, 1])),
+            Err(Bech32Error::InvalidPadding {})
+        );
+        assert_eq!(decode(&encode_symbols("a", &[0, 0])).unwrap().data, [0]);
+    }
+
+    #[test]
+    fn trait_and_display() {
+        let key = [7u8; 32];
+        let address = key.to_bech32("tari", Bech32Variant::Bech32m).unwrap();
+        assert_eq!(address.len(), encoded_len(4, 32));
+        assert_eq!(
+            Bech32Display::new("tari", &key, Bech32Variant::Bech32m).to_string(),
+            address
+        );
+        assert_eq!(
+            <[u8; 32]>::from_bech32(&address.to_ascii_uppercase(), "tari", Bech32Variant::Bech32m).unwrap(),
+            key
+        );
+        assert_eq!(
+            <[u8; 32]>::from_bech32(&address, "tbri", Bech32Variant::Bech32m),
+            Err(Bech32Error::HrpMismatch {
+                expected: "tbri".to_string()
+            })
+        );
+        assert_eq!(
+            <[u8; 32]>::from_bech32(&address, "tari", Bech32Variant::Bech32),
+            Err(Bech32Error::VariantMismatch {
+                expected: Bech32Variant::Bech32
+            })
+        );
+        assert!(matches!(
+            <[u8; 31]>::from_bech32(&address, "tari", Bech32Variant::Bech32m),
+            Err(Bech32Error::ByteArrayError { .. })
+        ));
+        assert_eq!(
+            encode("tari", &[0; 64], Bech32Variant::Bech32),
+            Err(Bech32Error::InvalidLength { length: 114 })
+        );
+        assert_eq!(encode("", &key, Bech32Variant::Bech32), Err(Bech32Error::InvalidHrp {}));
+        let mut out = String::new();
+        assert!(fmt_bech32(&mut out, "ta ri", &key, Bech32Variant::Bech32).is_err());
+    }
+}
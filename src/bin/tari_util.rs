@@ -0,0 +1,322 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! `tari_util`: the crate's conversions from the shell, for debugging payloads on servers.
+//!
+//! Input is taken from the command line argument, or from standard input if it is omitted. Decoded bytes are written
+//! to standard output as they are, so pipe them into `tari_util hexdump` to inspect them.
+
+use std::{
+    convert::TryFrom,
+    io::{self, Read, Write},
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use tari_utilities::{
+    base32::{from_base32, to_base32, Base32Variant},
+    checksum::{crc16_ccitt, crc32, xxhash64},
+    encoding::Base58,
+    epoch_time::EpochTime,
+    hex::{from_hex, to_hex, HexDump},
+};
+
+#[derive(Parser)]
+#[clap(author, version, about, long_about = None)]
+#[clap(propagate_version = true)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Hex encode or decode
+    Hex {
+        #[clap(subcommand)]
+        direction: Direction,
+    },
+    /// Monero-style base58 encode or decode
+    Base58 {
+        #[clap(subcommand)]
+        direction: Direction,
+    },
+    /// RFC 4648 base32 encode or decode
+    Base32 {
+        /// Omit the `=` padding when encoding, and expect none when decoding
+        #[clap(long)]
+        no_pad: bool,
+        #[clap(subcommand)]
+        direction: Direction,
+    },
+    /// Compute a checksum of the input
+    Checksum {
+        /// The checksum algorithm
+        #[clap(value_enum)]
+        algorithm: Algorithm,
+        /// The seed for xxhash64
+        #[clap(long, default_value_t = 0)]
+        seed: u64,
+        /// The input, or standard input if omitted
+        input: Option<String>,
+    },
+    /// Convert between Unix timestamps and UTC dates (`YYYY-MM-DDTHH:MM:SSZ`). Shows the current time if omitted.
+    Timestamp {
+        /// A Unix timestamp in seconds, or a UTC date
+        value: Option<String>,
+    },
+    /// Show the input as offsets, hex bytes and printable ASCII
+    Hexdump {
+        /// The input, or standard input if omitted
+        input: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum Direction {
+    /// Encode the input
+    Encode {
+        /// The input, or standard input if omitted
+        input: Option<String>,
+    },
+    /// Decode the input
+    Decode {
+        /// The input, or standard input if omitted
+        input: Option<String>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Algorithm {
+    Crc32,
+    Crc16,
+    Xxhash64,
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn run(cli: Cli) -> Result<(), String> {
+    let output = match cli.command {
+        Command::Hex {
+            direction: Direction::Encode { input },
+        } => line(to_hex(&read_input(input)?)),
+        Command::Hex {
+            direction: Direction::Decode { input },
+        } => from_hex(&read_text(input)?).map_err(|e| e.to_string())?,
+        Command::Base58 {
+            direction: Direction::Encode { input },
+        } => line(read_input(input)?.to_base58()),
+        Command::Base58 {
+            direction: Direction::Decode { input },
+        } => Vec::<u8>::from_base58(&read_text(input)?).map_err(|e| e.to_string())?,
+        Command::Base32 { no_pad, direction } => {
+            let variant = if no_pad {
+                Base32Variant::StandardNoPad
+            } else {
+                Base32Variant::Standard
+            };
+            match direction {
+                Direction::Encode { input } => line(to_base32(&read_input(input)?, variant)),
+                Direction::Decode { input } => from_base32(&read_text(input)?, variant).map_err(|e| e.to_string())?,
+            }
+        },
+        Command::Checksum { algorithm, seed, input } => {
+            let data = read_input(input)?;
+            line(match algorithm {
+                Algorithm::Crc32 => format!("{:08x}", crc32(&data)),
+                Algorithm::Crc16 => format!("{:04x}", crc16_ccitt(&data)),
+                Algorithm::Xxhash64 => format!("{:016x}", xxhash64(&data, seed)),
+            })
+        },
+        Command::Timestamp { value } => line(convert_timestamp(value.as_deref())?),
+        Command::Hexdump { input } => hexdump(&read_input(input)?).into_bytes(),
+    };
+    io::stdout().write_all(&output).map_err(|e| e.to_string())
+}
+
+fn line(mut text: String) -> Vec<u8> {
+    text.push('\n');
+    text.into_bytes()
+}
+
+fn read_input(input: Option<String>) -> Result<Vec<u8>, String> {
+    match input {
+        Some(input) => Ok(input.into_bytes()),
+        None => {
+            let mut data = Vec::new();
+            io::stdin().read_to_end(&mut data).map_err(|e| e.to_string())?;
+            Ok(data)
+        },
+    }
+}
+
+fn read_text(input: Option<String>) -> Result<String, String> {
+    let data = read_input(input)?;
+    String::from_utf8(data)
+        .map(|text| text.trim().to_string())
+        .map_err(|_| "the input is not UTF-8".to_string())
+}
+
+fn convert_timestamp(value: Option<&str>) -> Result<String, String> {
+    match value {
+        None => {
+            let now = EpochTime::now().as_u64();
+            Ok(format!("{} {}", now, format_utc(now)))
+        },
+        Some(value) if value.bytes().all(|b| b.is_ascii_digit()) => {
+            let secs = value.parse::<u64>().map_err(|e| e.to_string())?;
+            Ok(format_utc(EpochTime::from(secs).as_u64()))
+        },
+        Some(value) => parse_utc(value).map(|secs| secs.to_string()).ok_or_else(|| {
+            format!(
+                "`{}` is not a timestamp or a date of the form YYYY-MM-DDTHH:MM:SSZ",
+                value
+            )
+        }),
+    }
+}
+
+/// Return the days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Return the proleptic Gregorian date of the days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn format_utc(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let secs_of_day = secs % 86_400;
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60
+    )
+}
+
+fn parse_utc(value: &str) -> Option<u64> {
+    let bytes = value.as_bytes();
+    if bytes.len() != 20 ||
+        [(4, b'-'), (7, b'-'), (10, b'T'), (13, b':'), (16, b':'), (19, b'Z')]
+            .iter()
+            .any(|&(i, c)| bytes[i] != c)
+    {
+        return None;
+    }
+    let field = |range: std::ops::Range<usize>| -> Option<i64> {
+        let digits = value.get(range)?;
+        if digits.bytes().all(|b| b.is_ascii_digit()) {
+            digits.parse().ok()
+        } else {
+            None
+        }
+    };
+    let (year, month, day) = (field(0..4)?, field(5..7)?, field(8..10)?);
+    let (hour, minute, second) = (field(11..13)?, field(14..16)?, field(17..19)?);
+    if year < 1970 || !(1..=12).contains(&month) || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+    let days = days_from_civil(year, month, day);
+    // Reject days past the end of the month
+    if day < 1 || civil_from_days(days) != (year, month, day) {
+        return None;
+    }
+    u64::try_from(days * 86_400 + hour * 3600 + minute * 60 + second).ok()
+}
+
+fn hexdump(data: &[u8]) -> String {
+    HexDump::new(data).to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(format_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_utc(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_utc(1_700_000_000), "2023-11-14T22:13:20Z");
+        assert_eq!(parse_utc("2023-11-14T22:13:20Z"), Some(1_700_000_000));
+        assert_eq!(parse_utc("2000-02-29T00:00:00Z"), Some(951_782_400));
+        for secs in [0, 59, 86_399, 86_400, 4_102_444_800, 253_402_300_799] {
+            assert_eq!(parse_utc(&format_utc(secs)), Some(secs));
+        }
+        for bad in [
+            "2023-02-29T00:00:00Z",
+            "2023-13-01T00:00:00Z",
+            "2023-01-01T24:00:00Z",
+            "1969-12-31T23:59:59Z",
+            "2023-01-01 00:00:00Z",
+            "2023-01-01T00:00:00",
+            "+023-01-01T00:00:00Z",
+        ] {
+            assert_eq!(parse_utc(bad), None, "{}", bad);
+        }
+        assert_eq!(convert_timestamp(Some("0")).unwrap(), "1970-01-01T00:00:00Z");
+        assert_eq!(convert_timestamp(Some("1970-01-01T00:01:00Z")).unwrap(), "60");
+        assert!(convert_timestamp(Some("yesterday")).is_err());
+    }
+
+    #[test]
+    fn hexdumps() {
+        assert_eq!(hexdump(b""), "");
+        let expected = [
+            "00000000: 48 65 6c 6c 6f 2c 20 77 6f 72 6c 64 21 0a 00 ff  Hello, world!...\n",
+            "00000010: 6d 6f 72 65                                      more\n",
+        ];
+        assert_eq!(hexdump(b"Hello, world!\n\x00\xffmore"), expected.concat());
+    }
+}
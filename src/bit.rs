@@ -19,50 +19,38 @@
 // SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
-#![allow(clippy::needless_range_loop)]
 //! Functions for conversion between integer and bit array.
 
 use alloc::vec::Vec;
-use core::mem;
+
+/// The mask of each bit in a byte, least significant first.
+const BIT_MASKS: [u8; 8] = [1, 2, 4, 8, 16, 32, 64, 128];
 
 /// Converts a single input byte to 8 bits (little-endian).
 pub fn byte_to_bits(value: u8) -> [bool; 8] {
-    let mut bits = [false; 8];
-    for i in 0..8 {
-        bits[i] = value & (1 << i) != 0;
-    }
-    bits
+    BIT_MASKS.map(|mask| value & mask != 0)
 }
 
 /// Converts a vector of input bytes to a vector of bits
 pub fn bytes_to_bits(bytes: &[u8]) -> Vec<bool> {
-    let mut bits: Vec<bool> = vec![false; bytes.len() * 8];
-    for i in 0..bytes.len() {
-        let bit_index = i * 8;
-        bits[bit_index..(bit_index + 8)].copy_from_slice(&byte_to_bits(bytes[i]));
-    }
-    bits
+    bytes.iter().flat_map(|byte| byte_to_bits(*byte)).collect()
 }
 
 /// Converts a vector of input bits (little-endian) to its integer representation
 /// Returns None if the length of `bits` is greater than the number of bits in a `usize`, which would cause an attempt
 /// to shift left with overflow
 pub fn checked_bits_to_uint(bits: &[bool]) -> Option<usize> {
-    const PTR_SIZE_BITS: usize = mem::size_of::<usize>() * 8;
-
-    if bits.len() > PTR_SIZE_BITS {
+    if bits.len() > usize::BITS as usize {
         None
     } else {
-        let mut value: usize = 0;
-        for i in 0..bits.len() {
-            value |= usize::from(bits[i]) << i;
-        }
-        Some(value)
+        Some(bits.iter().rev().fold(0, |value, bit| (value << 1) | usize::from(*bit)))
     }
 }
 
 #[cfg(test)]
 mod test {
+    use core::mem;
+
     use super::*;
 
     #[test]
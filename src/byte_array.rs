@@ -21,12 +21,12 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 //! A trait that offers representation of data types as a byte array or hex string.
-
 use alloc::{string::String, vec::Vec};
+use core::convert::TryFrom;
 
 use snafu::prelude::*;
 
-use crate::hex::{from_hex, to_hex, Hex, HexError};
+use crate::hex::{encode_bytes, from_hex, Hex, HexError};
 
 /// Errors for [ByteArray] trait.
 #[derive(Debug, Snafu, PartialEq, Eq)]
@@ -38,8 +38,43 @@ pub enum ByteArrayError {
         reason: String,
     },
     /// The lenght doesn't fit to the array.
-    #[snafu(display("The input data was the incorrect length to perform the desired conversion"))]
-    IncorrectLength {},
+    #[snafu(display(
+        "The input data was the incorrect length to perform the desired conversion: expected {expected} bytes for {}, \
+         got {actual}",
+        context.unwrap_or("the target type")
+    ))]
+    IncorrectLength {
+        /// The expected length in bytes, or the maximum length for types with a variable length
+        expected: usize,
+        /// The length of the input in bytes
+        actual: usize,
+        /// The name of the type being converted to, if known
+        context: Option<&'static str>,
+    },
+}
+
+impl ByteArrayError {
+    /// An [IncorrectLength](ByteArrayError::IncorrectLength) error without a type name.
+    pub const fn incorrect_length(expected: usize, actual: usize) -> Self {
+        ByteArrayError::IncorrectLength {
+            expected,
+            actual,
+            context: None,
+        }
+    }
+
+    /// Set the name of the type being converted to, for errors that carry one.
+    #[must_use]
+    pub fn with_context(self, name: &'static str) -> Self {
+        match self {
+            ByteArrayError::IncorrectLength { expected, actual, .. } => ByteArrayError::IncorrectLength {
+                expected,
+                actual,
+                context: Some(name),
+            },
+            err => err,
+        }
+    }
 }
 
 /// Trait the allows converting to/from [array][[u8]]/[vec][[u8]].
@@ -67,6 +102,19 @@ pub trait ByteArray: Sized {
 
     /// Return the type as a byte array.
     fn as_bytes(&self) -> &[u8];
+
+    /// Return the type as a [Bytes](bytes::Bytes) buffer.
+    #[cfg(feature = "bytes")]
+    fn to_bytes_buf(&self) -> bytes::Bytes {
+        bytes::Bytes::copy_from_slice(self.as_bytes())
+    }
+
+    /// Try and convert the given [Bytes](bytes::Bytes) buffer to the implemented type, with the same checks as
+    /// [ByteArray::from_canonical_bytes]. `Vec<u8>` and `Bytes` take the buffer over without copying where possible.
+    #[cfg(feature = "bytes")]
+    fn from_bytes_buf(buf: bytes::Bytes) -> Result<Self, ByteArrayError> {
+        Self::from_canonical_bytes(&buf)
+    }
 }
 
 impl ByteArray for Vec<u8> {
@@ -85,12 +133,57 @@ impl ByteArray for Vec<u8> {
     fn as_bytes(&self) -> &[u8] {
         Vec::as_slice(self)
     }
+
+    #[cfg(feature = "bytes")]
+    fn to_bytes_buf(&self) -> bytes::Bytes {
+        bytes::Bytes::from(self.clone())
+    }
+
+    #[cfg(feature = "bytes")]
+    fn from_bytes_buf(buf: bytes::Bytes) -> Result<Self, ByteArrayError> {
+        Ok(Vec::from(buf))
+    }
 }
 
+#[cfg(feature = "bytes")]
+impl ByteArray for bytes::Bytes {
+    fn from_canonical_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+        Ok(bytes::Bytes::copy_from_slice(bytes))
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn to_bytes_buf(&self) -> bytes::Bytes {
+        self.clone()
+    }
+
+    fn from_bytes_buf(buf: bytes::Bytes) -> Result<Self, ByteArrayError> {
+        Ok(buf)
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl ByteArray for bytes::BytesMut {
+    fn from_canonical_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+        Ok(bytes::BytesMut::from(bytes))
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        self
+    }
+
+    fn from_bytes_buf(buf: bytes::Bytes) -> Result<Self, ByteArrayError> {
+        Ok(bytes::BytesMut::from(buf))
+    }
+}
+
+/// Arrays of every length are byte arrays, and only accept input of exactly their length.
 impl<const I: usize> ByteArray for [u8; I] {
     fn from_canonical_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
         if bytes.len() != I {
-            return Err(ByteArrayError::IncorrectLength {});
+            return Err(ByteArrayError::incorrect_length(I, bytes.len()).with_context(core::any::type_name::<Self>()));
         }
         let mut a = [0u8; I];
         a.copy_from_slice(bytes);
@@ -102,6 +195,32 @@ impl<const I: usize> ByteArray for [u8; I] {
     }
 }
 
+/// A fixed-size [ByteArray] that can be borrowed straight out of a byte slice, so that parsing keys and hashes out of a
+/// network buffer does not copy them.
+///
+/// This is implemented for `[u8; N]`, by [define_byte_newtype](crate::define_byte_newtype), and by
+/// `#[derive(ByteArrayRef)]` for `#[repr(transparent)]` newtypes around `[u8; N]`. It is only suitable for types where
+/// every input of the right length is valid, as there is no opportunity to check or normalise the bytes.
+///
+/// ```edition2018
+/// # use tari_utilities::ByteArrayRef;
+/// let buffer = [7u8; 40];
+/// let key: &[u8; 32] = <[u8; 32]>::try_from_bytes_ref(&buffer[8..]).unwrap();
+/// assert_eq!(key, &[7; 32]);
+/// assert!(<[u8; 32]>::try_from_bytes_ref(&buffer).is_err());
+/// ```
+pub trait ByteArrayRef: ByteArray {
+    /// Borrow the bytes as the implemented type, with the same length check as [ByteArray::from_canonical_bytes].
+    fn try_from_bytes_ref(bytes: &[u8]) -> Result<&Self, ByteArrayError>;
+}
+
+impl<const I: usize> ByteArrayRef for [u8; I] {
+    fn try_from_bytes_ref(bytes: &[u8]) -> Result<&Self, ByteArrayError> {
+        <&Self>::try_from(bytes)
+            .map_err(|_| ByteArrayError::incorrect_length(I, bytes.len()).with_context(core::any::type_name::<Self>()))
+    }
+}
+
 impl<T: ByteArray> Hex for T {
     fn from_hex(hex: &str) -> Result<Self, HexError> {
         let v = from_hex(hex)?;
@@ -109,12 +228,14 @@ impl<T: ByteArray> Hex for T {
     }
 
     fn to_hex(&self) -> String {
-        to_hex(self.as_bytes())
+        encode_bytes(self.as_bytes())
     }
 }
 
 #[cfg(test)]
 mod test {
+    use alloc::string::ToString;
+
     use super::*;
 
     #[test]
@@ -154,10 +275,150 @@ mod test {
         assert_eq!(v.to_hex(), "deadbeef");
     }
 
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derived() {
+        use core::convert::TryFrom;
+
+        use crate::ByteArray;
+
+        #[derive(Debug, PartialEq, Eq, ByteArray)]
+        struct NodeId([u8; 4]);
+
+        #[derive(Debug, PartialEq, Eq, ByteArray)]
+        struct Payload(Vec<u8>);
+
+        let id = NodeId::from_canonical_bytes(&[1, 2, 3, 4]).unwrap();
+        assert_eq!(id, NodeId([1, 2, 3, 4]));
+        assert_eq!(id.as_bytes(), [1, 2, 3, 4]);
+        assert_eq!(id.as_ref(), [1, 2, 3, 4]);
+        assert_eq!(NodeId::try_from(&[1u8, 2, 3, 4][..]).unwrap(), id);
+        assert_eq!(id.to_hex(), "01020304");
+        assert_eq!(NodeId::from_hex("01020304").unwrap(), id);
+        assert_eq!(
+            NodeId::from_canonical_bytes(&[1, 2]).unwrap_err(),
+            ByteArrayError::IncorrectLength {
+                expected: 4,
+                actual: 2,
+                context: Some("NodeId")
+            }
+        );
+
+        let payload = Payload::try_from(&[9u8; 10][..]).unwrap();
+        assert_eq!(payload.to_vec(), vec![9; 10]);
+        assert_eq!(Payload::from_canonical_bytes(&[]).unwrap(), Payload(Vec::new()));
+    }
+
+    #[test]
+    fn borrowed_array() {
+        let buffer = [1u8, 2, 3, 4, 5];
+        let array = <[u8; 4]>::try_from_bytes_ref(&buffer[1..]).unwrap();
+        assert_eq!(array, &[2, 3, 4, 5]);
+        assert!(core::ptr::eq(array.as_ptr(), buffer[1..].as_ptr()));
+        assert_eq!(
+            <[u8; 4]>::try_from_bytes_ref(&buffer).unwrap_err(),
+            ByteArrayError::IncorrectLength {
+                expected: 4,
+                actual: 5,
+                context: Some("[u8; 4]")
+            }
+        );
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derived_ref() {
+        use crate::{ByteArray, ByteArrayRef};
+
+        #[derive(Debug, PartialEq, Eq, ByteArray, ByteArrayRef)]
+        #[repr(transparent)]
+        struct PublicKey([u8; 4]);
+
+        let buffer = [0u8, 1, 2, 3, 4];
+        let key = PublicKey::try_from_bytes_ref(&buffer[1..]).unwrap();
+        assert_eq!(key, &PublicKey([1, 2, 3, 4]));
+        assert!(core::ptr::eq(key.as_bytes().as_ptr(), buffer[1..].as_ptr()));
+        assert_eq!(
+            PublicKey::try_from_bytes_ref(&buffer).unwrap_err(),
+            ByteArrayError::IncorrectLength {
+                expected: 4,
+                actual: 5,
+                context: Some("PublicKey")
+            }
+        );
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn bytes_interop() {
+        use bytes::{Bytes, BytesMut};
+
+        let buf = Bytes::from(vec![1u8, 2, 3, 4]);
+        let array = <[u8; 4]>::from_bytes_buf(buf.clone()).unwrap();
+        assert_eq!(array, [1, 2, 3, 4]);
+        assert_eq!(array.to_bytes_buf(), buf);
+        assert!(matches!(
+            <[u8; 3]>::from_bytes_buf(buf.clone()),
+            Err(ByteArrayError::IncorrectLength {
+                expected: 3,
+                actual: 4,
+                ..
+            })
+        ));
+
+        // A uniquely owned buffer is taken over by a vector without copying
+        let buf = Bytes::from(vec![5u8; 64]);
+        let ptr = buf.as_ptr();
+        let v = Vec::from_bytes_buf(buf).unwrap();
+        assert_eq!(v.as_ptr(), ptr);
+        assert_eq!(v.to_bytes_buf(), Bytes::from(vec![5u8; 64]));
+
+        let buf = Bytes::from_static(b"abc");
+        assert_eq!(Bytes::from_bytes_buf(buf.clone()).unwrap().as_ptr(), buf.as_ptr());
+        assert_eq!(Bytes::from_canonical_bytes(b"abc").unwrap(), buf);
+        assert_eq!(
+            BytesMut::from_bytes_buf(buf.clone()).unwrap(),
+            BytesMut::from(&b"abc"[..])
+        );
+        assert_eq!(BytesMut::from_canonical_bytes(b"abc").unwrap().to_hex(), "616263");
+        assert_eq!(Bytes::from_hex("616263").unwrap(), buf);
+    }
+
     #[test]
     fn test_error_handling() {
         let err = <[u8; 32]>::from_canonical_bytes(&[1, 2, 3, 4]).unwrap_err();
-        assert_eq!(err, ByteArrayError::IncorrectLength {});
+        assert_eq!(err, ByteArrayError::IncorrectLength {
+            expected: 32,
+            actual: 4,
+            context: Some("[u8; 32]")
+        });
+        assert_eq!(
+            err.to_string(),
+            "The input data was the incorrect length to perform the desired conversion: expected 32 bytes for [u8; \
+             32], got 4"
+        );
+        assert_eq!(
+            ByteArrayError::incorrect_length(2, 3).to_string(),
+            "The input data was the incorrect length to perform the desired conversion: expected 2 bytes for the \
+             target type, got 3"
+        );
+        assert_eq!(
+            ByteArrayError::incorrect_length(2, 3).with_context("Id"),
+            ByteArrayError::IncorrectLength {
+                expected: 2,
+                actual: 3,
+                context: Some("Id")
+            }
+        );
+
+        let err = <[u8; 4]>::from_canonical_bytes(&[1, 2, 3, 4, 5]).unwrap_err();
+        assert!(matches!(err, ByteArrayError::IncorrectLength {
+            expected: 4,
+            actual: 5,
+            ..
+        }));
+        assert_eq!(<[u8; 0]>::from_canonical_bytes(&[]).unwrap(), [0u8; 0]);
+        assert!(<[u8; 0]>::from_canonical_bytes(&[0]).is_err());
 
         let err = <[u8; 32]>::from_hex("abcd").unwrap_err();
         assert!(matches!(err, HexError::HexConversionError {}));
@@ -0,0 +1,285 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A checked sequential reader over a byte slice, for hand-written binary decoders.
+//!
+//! Every read either consumes exactly the bytes it needs or fails without moving the cursor, and errors report the
+//! position at which decoding failed.
+//!
+//! ```edition2018
+//! # use tari_utilities::byte_cursor::ByteCursor;
+//! let mut cursor = ByteCursor::new(&[1, 0x34, 0x12, 0xac, 0x02, b'h', b'i']);
+//! assert_eq!(cursor.read_u8().unwrap(), 1);
+//! assert_eq!(cursor.read_u16_le().unwrap(), 0x1234);
+//! let len = cursor.read_varint().unwrap();
+//! assert_eq!(len, 300);
+//! assert!(cursor.read_bytes(len as usize).is_err());
+//! assert_eq!(cursor.read_bytes(2).unwrap(), b"hi");
+//! cursor.finish().unwrap();
+//! ```
+
+use snafu::prelude::*;
+
+use crate::varint::{self, VarintError};
+
+/// Errors for [ByteCursor].
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum ByteCursorError {
+    /// A read needed more bytes than remain.
+    #[snafu(display("Needed {needed} bytes at position {position} but only {remaining} remain"))]
+    UnexpectedEnd {
+        /// The position of the read
+        position: usize,
+        /// The number of bytes needed
+        needed: usize,
+        /// The number of bytes remaining
+        remaining: usize,
+    },
+    /// A varint is not minimally encoded or does not fit in a `u64`.
+    #[snafu(display("Invalid varint at position {position}"))]
+    InvalidVarint {
+        /// The position of the varint
+        position: usize,
+    },
+    /// Bytes remain after decoding is finished.
+    #[snafu(display("{remaining} unexpected trailing bytes at position {position}"))]
+    TrailingBytes {
+        /// The position of the first trailing byte
+        position: usize,
+        /// The number of trailing bytes
+        remaining: usize,
+    },
+}
+
+/// A checked sequential reader over a byte slice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ByteCursor<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+macro_rules! read_int {
+    ($($(#[$doc:meta])* $name:ident: $ty:ty = $from:ident;)*) => {
+        $(
+            $(#[$doc])*
+            pub fn $name(&mut self) -> Result<$ty, ByteCursorError> {
+                self.read_array().map(<$ty>::$from)
+            }
+        )*
+    };
+}
+
+impl<'a> ByteCursor<'a> {
+    read_int! {
+        /// Read a little-endian `u16`.
+        read_u16_le: u16 = from_le_bytes;
+        /// Read a big-endian `u16`.
+        read_u16_be: u16 = from_be_bytes;
+        /// Read a little-endian `u32`.
+        read_u32_le: u32 = from_le_bytes;
+        /// Read a big-endian `u32`.
+        read_u32_be: u32 = from_be_bytes;
+        /// Read a little-endian `u64`.
+        read_u64_le: u64 = from_le_bytes;
+        /// Read a big-endian `u64`.
+        read_u64_be: u64 = from_be_bytes;
+    }
+
+    /// Create a cursor at the start of `data`.
+    pub const fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    /// Return the number of bytes read so far.
+    pub const fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Return the unread bytes.
+    pub fn remaining_bytes(&self) -> &'a [u8] {
+        self.data.get(self.position..).unwrap_or_default()
+    }
+
+    /// Return the number of unread bytes.
+    pub fn remaining(&self) -> usize {
+        self.remaining_bytes().len()
+    }
+
+    /// Return `true` if every byte has been read.
+    pub fn is_empty(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Read the next `n` bytes.
+    ///
+    /// # Errors
+    /// Returns [ByteCursorError::UnexpectedEnd] if fewer than `n` bytes remain.
+    pub fn read_bytes(&mut self, n: usize) -> Result<&'a [u8], ByteCursorError> {
+        let remaining = self.remaining_bytes();
+        let bytes = remaining.get(..n).context(UnexpectedEndSnafu {
+            position: self.position,
+            needed: n,
+            remaining: remaining.len(),
+        })?;
+        self.position = self.position.saturating_add(n);
+        Ok(bytes)
+    }
+
+    /// Skip the next `n` bytes.
+    ///
+    /// # Errors
+    /// Returns [ByteCursorError::UnexpectedEnd] if fewer than `n` bytes remain.
+    pub fn skip(&mut self, n: usize) -> Result<(), ByteCursorError> {
+        self.read_bytes(n).map(|_| ())
+    }
+
+    /// Read the next `N` bytes into an array.
+    ///
+    /// # Errors
+    /// Returns [ByteCursorError::UnexpectedEnd] if fewer than `N` bytes remain.
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], ByteCursorError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.read_bytes(N)?);
+        Ok(array)
+    }
+
+    /// Read a byte.
+    ///
+    /// # Errors
+    /// Returns [ByteCursorError::UnexpectedEnd] if no bytes remain.
+    pub fn read_u8(&mut self) -> Result<u8, ByteCursorError> {
+        self.read_array::<1>().map(|[byte]| byte)
+    }
+
+    /// Read a minimally encoded unsigned LEB128 varint, the same encoding as the [framing](crate::framing) length
+    /// prefix. The [varint] module decodes other integer types.
+    ///
+    /// # Errors
+    /// Returns [ByteCursorError::InvalidVarint] if the varint is not minimally encoded or overflows a `u64`, and
+    /// [ByteCursorError::UnexpectedEnd] if the input ends within it.
+    pub fn read_varint(&mut self) -> Result<u64, ByteCursorError> {
+        let remaining = self.remaining_bytes();
+        match varint::decode::<u64>(remaining) {
+            Ok((value, len)) => {
+                self.position = self.position.saturating_add(len);
+                Ok(value)
+            },
+            Err(VarintError::Truncated {}) => UnexpectedEndSnafu {
+                position: self.position,
+                needed: remaining.len().saturating_add(1),
+                remaining: remaining.len(),
+            }
+            .fail(),
+            Err(_) => InvalidVarintSnafu {
+                position: self.position,
+            }
+            .fail(),
+        }
+    }
+
+    /// Check that every byte has been read.
+    ///
+    /// # Errors
+    /// Returns [ByteCursorError::TrailingBytes] if any bytes remain.
+    pub fn finish(self) -> Result<(), ByteCursorError> {
+        ensure!(self.is_empty(), TrailingBytesSnafu {
+            position: self.position,
+            remaining: self.remaining(),
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn integers() {
+        let data = [1u8, 2, 3, 4, 5, 6, 7, 8];
+        let mut cursor = ByteCursor::new(&data);
+        assert_eq!(cursor.read_u16_be().unwrap(), 0x0102);
+        assert_eq!(cursor.read_u16_le().unwrap(), 0x0403);
+        assert_eq!(cursor.read_u32_be().unwrap(), 0x05060708);
+        assert!(cursor.is_empty());
+
+        let mut cursor = ByteCursor::new(&data);
+        assert_eq!(cursor.read_u32_le().unwrap(), 0x04030201);
+        cursor.skip(4).unwrap();
+        cursor.finish().unwrap();
+        assert_eq!(ByteCursor::new(&data).read_u64_le().unwrap(), 0x0807060504030201);
+        assert_eq!(ByteCursor::new(&data).read_u64_be().unwrap(), 0x0102030405060708);
+        assert_eq!(ByteCursor::new(&data).read_array::<3>().unwrap(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn failed_reads_do_not_advance() {
+        let mut cursor = ByteCursor::new(&[1, 2, 3]);
+        cursor.read_u8().unwrap();
+        assert_eq!(
+            cursor.read_u32_le(),
+            Err(ByteCursorError::UnexpectedEnd {
+                position: 1,
+                needed: 4,
+                remaining: 2
+            })
+        );
+        assert_eq!(cursor.position(), 1);
+        assert_eq!(cursor.remaining_bytes(), &[2, 3]);
+        assert!(cursor.skip(3).is_err());
+        assert_eq!(cursor.read_bytes(2).unwrap(), &[2, 3]);
+        assert!(cursor.read_u8().is_err());
+        assert_eq!(cursor.read_bytes(0).unwrap(), &[] as &[u8]);
+    }
+
+    #[test]
+    fn varints() {
+        let mut cursor = ByteCursor::new(&[0x00, 0x7f, 0xac, 0x02, 0x80, 0x00, 0x80]);
+        assert_eq!(cursor.read_varint().unwrap(), 0);
+        assert_eq!(cursor.read_varint().unwrap(), 127);
+        assert_eq!(cursor.read_varint().unwrap(), 300);
+        assert_eq!(
+            cursor.read_varint(),
+            Err(ByteCursorError::InvalidVarint { position: 4 })
+        );
+        cursor.skip(2).unwrap();
+        assert!(matches!(
+            cursor.read_varint(),
+            Err(ByteCursorError::UnexpectedEnd { position: 6, .. })
+        ));
+        assert_eq!(cursor.position(), 6);
+    }
+
+    #[test]
+    fn finishing() {
+        let mut cursor = ByteCursor::new(&[1, 2, 3]);
+        cursor.read_u8().unwrap();
+        assert_eq!(
+            cursor.finish(),
+            Err(ByteCursorError::TrailingBytes {
+                position: 1,
+                remaining: 2
+            })
+        );
+        ByteCursor::new(&[]).finish().unwrap();
+    }
+}
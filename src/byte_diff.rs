@@ -0,0 +1,198 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Finding and showing the first difference between two byte strings.
+//!
+//! [diff_bytes] compares two byte slices and returns a [ByteDiff], whose [Display](fmt::Display) output gives the
+//! lengths, the offset of the first difference and a hex window around it with the differing byte bracketed. The
+//! [assert_bytes_eq](crate::assert_bytes_eq) macro panics with this report, which is far easier to read than the
+//! `Debug` output of two long byte vectors.
+//!
+//! ```edition2018
+//! # use tari_utilities::byte_diff::diff_bytes;
+//! let diff = diff_bytes(b"consensus", b"consensos");
+//! assert_eq!(diff.first_difference(), Some(7));
+//! assert_eq!(
+//!     diff.to_string(),
+//!     "byte slices differ at offset 7 (both 9 bytes)\n left @ 0: 63 6f 6e 73 65 6e 73 [75] \
+//!      73\nright @ 0: 63 6f 6e 73 65 6e 73 [6f] 73"
+//! );
+//! ```
+
+use core::fmt;
+
+/// The number of bytes shown on each side of the first difference.
+pub const DEFAULT_CONTEXT: usize = 8;
+
+/// Compare two byte slices.
+pub fn diff_bytes<'a>(left: &'a [u8], right: &'a [u8]) -> ByteDiff<'a> {
+    let first_difference = left
+        .iter()
+        .zip(right)
+        .position(|(l, r)| l != r)
+        .or_else(|| (left.len() != right.len()).then(|| left.len().min(right.len())));
+    ByteDiff {
+        left,
+        right,
+        first_difference,
+        context: DEFAULT_CONTEXT,
+    }
+}
+
+/// The result of comparing two byte slices with [diff_bytes].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteDiff<'a> {
+    left: &'a [u8],
+    right: &'a [u8],
+    first_difference: Option<usize>,
+    context: usize,
+}
+
+impl ByteDiff<'_> {
+    /// Return `true` if the slices are equal.
+    pub fn is_equal(&self) -> bool {
+        self.first_difference.is_none()
+    }
+
+    /// Return `true` if the slices have different lengths.
+    pub fn lengths_differ(&self) -> bool {
+        self.left.len() != self.right.len()
+    }
+
+    /// The offset of the first differing byte. If one slice is a prefix of the other, this is the length of the
+    /// shorter one.
+    pub fn first_difference(&self) -> Option<usize> {
+        self.first_difference
+    }
+
+    /// Show `context` bytes on each side of the first difference, instead of [DEFAULT_CONTEXT].
+    #[must_use]
+    pub fn with_context(mut self, context: usize) -> Self {
+        self.context = context;
+        self
+    }
+
+    fn fmt_window(&self, f: &mut fmt::Formatter<'_>, label: &str, bytes: &[u8], offset: usize) -> fmt::Result {
+        let start = offset.saturating_sub(self.context);
+        let end = offset.saturating_add(self.context).saturating_add(1).min(bytes.len());
+        write!(f, "{:>5} @ {}:", label, start)?;
+        for (i, byte) in bytes.iter().enumerate().take(end).skip(start) {
+            if i == offset {
+                write!(f, " [{:02x}]", byte)?;
+            } else {
+                write!(f, " {:02x}", byte)?;
+            }
+        }
+        if offset >= bytes.len() {
+            f.write_str(" [end]")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ByteDiff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let offset = match self.first_difference {
+            None => return write!(f, "byte slices are equal ({} bytes)", self.left.len()),
+            Some(offset) => offset,
+        };
+        write!(f, "byte slices differ at offset {} ", offset)?;
+        if self.lengths_differ() {
+            writeln!(f, "(left {} bytes, right {} bytes)", self.left.len(), self.right.len())?;
+        } else {
+            writeln!(f, "(both {} bytes)", self.left.len())?;
+        }
+        self.fmt_window(f, "left", self.left, offset)?;
+        writeln!(f)?;
+        self.fmt_window(f, "right", self.right, offset)
+    }
+}
+
+/// Assert that two byte strings are equal, panicking with a [ByteDiff](crate::byte_diff::ByteDiff) report if they are
+/// not. Both arguments must implement `AsRef<[u8]>`.
+///
+/// ```edition2018,should_panic
+/// # use tari_utilities::assert_bytes_eq;
+/// assert_bytes_eq!(vec![1u8, 2, 3], [1u8, 2, 4]);
+/// ```
+#[macro_export]
+macro_rules! assert_bytes_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                let diff = $crate::byte_diff::diff_bytes(
+                    ::core::convert::AsRef::<[u8]>::as_ref(left),
+                    ::core::convert::AsRef::<[u8]>::as_ref(right),
+                );
+                if !diff.is_equal() {
+                    panic!("assertion failed: byte strings are not equal\n{}", diff);
+                }
+            },
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{string::ToString, vec};
+
+    use super::*;
+
+    #[test]
+    fn differences() {
+        let equal = diff_bytes(b"abc", b"abc");
+        assert!(equal.is_equal());
+        assert_eq!(equal.to_string(), "byte slices are equal (3 bytes)");
+
+        let left = (0u8..32).collect::<alloc::vec::Vec<_>>();
+        let mut right = left.clone();
+        right[20] = 0xff;
+        let diff = diff_bytes(&left, &right).with_context(2);
+        assert_eq!(diff.first_difference(), Some(20));
+        assert!(!diff.lengths_differ());
+        assert_eq!(
+            diff.to_string(),
+            "byte slices differ at offset 20 (both 32 bytes)\n left @ 18: 12 13 [14] 15 16\nright @ 18: 12 13 [ff] 15 \
+             16"
+        );
+    }
+
+    #[test]
+    fn length_differences() {
+        let diff = diff_bytes(b"abcd", b"ab").with_context(1);
+        assert_eq!(diff.first_difference(), Some(2));
+        assert!(diff.lengths_differ());
+        assert_eq!(
+            diff.to_string(),
+            "byte slices differ at offset 2 (left 4 bytes, right 2 bytes)\n left @ 1: 62 [63] 64\nright @ 1: 62 [end]"
+        );
+        assert_eq!(diff_bytes(b"", b"a").first_difference(), Some(0));
+    }
+
+    #[test]
+    fn assertion_macro() {
+        assert_bytes_eq!(vec![1u8, 2], [1u8, 2]);
+        let result = std::panic::catch_unwind(|| assert_bytes_eq!(b"ab", b"ac"));
+        let message = result.unwrap_err().downcast::<alloc::string::String>().unwrap();
+        assert!(message.contains("differ at offset 1"));
+    }
+}
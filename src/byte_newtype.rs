@@ -0,0 +1,435 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! The [define_byte_newtype](crate::define_byte_newtype) macro, for fixed-size byte identifiers such as transaction
+//! IDs and hashes, and with the `derive` feature the `ByteNewtype` derive for variable-length wrappers such as
+//! scripts and proofs.
+//!
+//! ```edition2018
+//! # use tari_utilities::{define_byte_newtype, hex::Hex, ByteArray};
+//! define_byte_newtype!(
+//!     /// The ID of a transaction
+//!     TxId,
+//!     32
+//! );
+//!
+//! let id: TxId = "0101010101010101010101010101010101010101010101010101010101010101".parse().unwrap();
+//! assert_eq!(id, TxId::new([1; 32]));
+//! assert_eq!(id.to_hex(), id.to_string());
+//! assert_eq!(format!("{:?}", id), "TxId(01010101..01010101)");
+//! assert_eq!(TxId::from_canonical_bytes(id.as_bytes()).unwrap(), id);
+//! ```
+//!
+//! `#[derive(ByteNewtype)]` applies to a tuple struct around a `Vec<u8>` and generates the same trait suite, except
+//! for `Copy`, `Default` and the comparison traits, which can be derived as usual. It also generates a `MAX_LEN`
+//! constant, set with `#[byte_newtype(max_len = ...)]` and defaulting to [DEFAULT_MAX_LEN], a `try_new` constructor,
+//! `TryFrom<Vec<u8>>` and `into_inner`. Every constructor, parser and deserializer rejects data longer than `MAX_LEN`,
+//! and Borsh checks the length prefix before allocating.
+//!
+//! ```edition2018,ignore
+//! use tari_utilities::ByteNewtype;
+//!
+//! #[derive(Clone, PartialEq, Eq, ByteNewtype)]
+//! #[byte_newtype(max_len = 4096)]
+//! pub struct Script(Vec<u8>);
+//!
+//! assert!(Script::try_new(vec![0; 4097]).is_err());
+//! ```
+
+/// Re-exports used by the macro, so that callers do not need these crates as direct dependencies.
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::{string::String, vec::Vec};
+
+    #[cfg(feature = "borsh")]
+    pub use ::borsh;
+    #[cfg(feature = "serde")]
+    pub use ::serde;
+}
+
+/// The default maximum length of a `ByteNewtype`, in bytes.
+pub const DEFAULT_MAX_LEN: usize = 262_144;
+
+/// The number of bytes shown at each end of the [Debug](core::fmt::Debug) output of a byte newtype.
+pub const DEBUG_BYTES: usize = 4;
+
+/// Write the abbreviated [Debug](core::fmt::Debug) form of a byte newtype, such as `TxId(01020304..1d1e1f20)`. Only
+/// the first and last [DEBUG_BYTES] bytes are shown, so long identifiers do not flood logs.
+#[doc(hidden)]
+pub fn fmt_debug(f: &mut core::fmt::Formatter<'_>, name: &str, bytes: &[u8]) -> core::fmt::Result {
+    write!(f, "{}(", name)?;
+    let head = bytes.get(..DEBUG_BYTES);
+    let tail = bytes.get(bytes.len().saturating_sub(DEBUG_BYTES)..);
+    match (head, tail) {
+        (Some(head), Some(tail)) if bytes.len() > DEBUG_BYTES.saturating_mul(2) => {
+            crate::hex::fmt_hex(f, head)?;
+            f.write_str("..")?;
+            crate::hex::fmt_hex(f, tail)?;
+        },
+        _ => crate::hex::fmt_hex(f, bytes)?,
+    }
+    f.write_str(")")
+}
+
+/// Define a public newtype around `[u8; N]` with the usual trait suite:
+/// - [ByteArray](crate::ByteArray) and therefore [Hex](crate::hex::Hex), plus `AsRef<[u8]>` and conversions to and from
+///   the array
+/// - [ByteArrayRef](crate::ByteArrayRef), as the type is `repr(transparent)`, to borrow it from a byte slice
+/// - [Display](core::fmt::Display) as lowercase hex and [FromStr](core::str::FromStr) from hex
+/// - an abbreviated [Debug](core::fmt::Debug) that shows only the ends of the bytes
+/// - `Clone`, `Copy`, `Default`, `PartialEq`, `Eq`, `PartialOrd`, `Ord` and `Hash`
+/// - serde as hex in human-readable formats and bytes otherwise, with the `serde` feature of this crate
+/// - Borsh as the raw bytes, with the `borsh` feature of this crate
+///
+/// Doc comments and other attributes before the name are applied to the type.
+#[macro_export]
+macro_rules! define_byte_newtype {
+    ($(#[$meta:meta])* $name:ident, $len:expr $(,)?) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[repr(transparent)]
+        pub struct $name([u8; $len]);
+
+        impl $name {
+            /// The length in bytes
+            pub const LEN: usize = $len;
+
+            /// Wrap the given bytes
+            pub const fn new(bytes: [u8; $len]) -> Self {
+                Self(bytes)
+            }
+
+            /// All zero bytes, which is also the default
+            #[allow(dead_code)]
+            pub const fn zeroed() -> Self {
+                Self([0u8; $len])
+            }
+
+            /// Return the bytes as an array
+            #[allow(dead_code)]
+            pub const fn as_array(&self) -> &[u8; $len] {
+                &self.0
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::zeroed()
+            }
+        }
+
+        impl $crate::ByteArray for $name {
+            fn from_canonical_bytes(bytes: &[u8]) -> Result<Self, $crate::ByteArrayError> {
+                <[u8; $len] as $crate::ByteArray>::from_canonical_bytes(bytes)
+                    .map(Self)
+                    .map_err(|err| err.with_context(stringify!($name)))
+            }
+
+            fn as_bytes(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl $crate::ByteArrayRef for $name {
+            #[allow(unsafe_code)]
+            fn try_from_bytes_ref(bytes: &[u8]) -> Result<&Self, $crate::ByteArrayError> {
+                let array = <[u8; $len] as $crate::ByteArrayRef>::try_from_bytes_ref(bytes)
+                    .map_err(|err| err.with_context(stringify!($name)))?;
+                // SAFETY: the type is `repr(transparent)` over the array, so a reference to one is a valid reference
+                // to the other
+                Ok(unsafe { &*(array as *const [u8; $len] as *const Self) })
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl From<[u8; $len]> for $name {
+            fn from(bytes: [u8; $len]) -> Self {
+                Self(bytes)
+            }
+        }
+
+        impl From<$name> for [u8; $len] {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl ::core::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                $crate::hex::fmt_hex(f, &self.0)
+            }
+        }
+
+        impl ::core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                $crate::byte_newtype::fmt_debug(f, stringify!($name), &self.0)
+            }
+        }
+
+        impl ::core::str::FromStr for $name {
+            type Err = $crate::hex::HexError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                <Self as $crate::hex::Hex>::from_hex(s)
+            }
+        }
+
+        $crate::__byte_newtype_serde!($name);
+        $crate::__byte_newtype_borsh!($name, $len);
+    };
+}
+
+// The optional impls are chosen by the features of this crate, which a `cfg` inside the exported macro would instead
+// read from the calling crate.
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __byte_newtype_serde {
+    ($name:ident) => {
+        impl $crate::byte_newtype::__private::serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where S: $crate::byte_newtype::__private::serde::Serializer {
+                $crate::serde::hex::serialize(self, serializer)
+            }
+        }
+
+        impl<'de> $crate::byte_newtype::__private::serde::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where D: $crate::byte_newtype::__private::serde::Deserializer<'de> {
+                $crate::serde::hex::deserialize(deserializer)
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __byte_newtype_serde {
+    ($name:ident) => {};
+}
+
+#[cfg(feature = "borsh")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __byte_newtype_borsh {
+    ($name:ident, $len:expr) => {
+        impl $crate::byte_newtype::__private::borsh::BorshSerialize for $name {
+            fn serialize<W: $crate::byte_newtype::__private::borsh::maybestd::io::Write>(
+                &self,
+                writer: &mut W,
+            ) -> $crate::byte_newtype::__private::borsh::maybestd::io::Result<()> {
+                writer.write_all(&self.0)
+            }
+        }
+
+        impl $crate::byte_newtype::__private::borsh::BorshDeserialize for $name {
+            fn deserialize_reader<R: $crate::byte_newtype::__private::borsh::maybestd::io::Read>(
+                reader: &mut R,
+            ) -> $crate::byte_newtype::__private::borsh::maybestd::io::Result<Self> {
+                let mut bytes = [0u8; $len];
+                reader.read_exact(&mut bytes)?;
+                Ok(Self(bytes))
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "borsh"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __byte_newtype_borsh {
+    ($name:ident, $len:expr) => {};
+}
+
+/// Borsh for a `ByteNewtype`, in the same format as `Vec<u8>`: a `u32` length followed by the bytes.
+#[cfg(feature = "borsh")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __byte_newtype_borsh_vec {
+    ($name:ident) => {
+        impl $crate::byte_newtype::__private::borsh::BorshSerialize for $name {
+            fn serialize<W: $crate::byte_newtype::__private::borsh::maybestd::io::Write>(
+                &self,
+                writer: &mut W,
+            ) -> $crate::byte_newtype::__private::borsh::maybestd::io::Result<()> {
+                $crate::byte_newtype::__private::borsh::BorshSerialize::serialize(&self.0, writer)
+            }
+        }
+
+        impl $crate::byte_newtype::__private::borsh::BorshDeserialize for $name {
+            fn deserialize_reader<R: $crate::byte_newtype::__private::borsh::maybestd::io::Read>(
+                reader: &mut R,
+            ) -> $crate::byte_newtype::__private::borsh::maybestd::io::Result<Self> {
+                use $crate::byte_newtype::__private::borsh::maybestd::io::{Error, ErrorKind};
+
+                let len =
+                    <u32 as $crate::byte_newtype::__private::borsh::BorshDeserialize>::deserialize_reader(reader)?;
+                let len = <usize as ::core::convert::TryFrom<u32>>::try_from(len)
+                    .ok()
+                    .filter(|len| *len <= Self::MAX_LEN)
+                    .ok_or_else(|| Error::new(ErrorKind::InvalidData, "byte newtype is too long"))?;
+                let mut bytes = $crate::byte_newtype::__private::Vec::new();
+                bytes.resize(len, 0u8);
+                reader.read_exact(&mut bytes)?;
+                Ok(Self(bytes))
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "borsh"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __byte_newtype_borsh_vec {
+    ($name:ident) => {};
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{format, string::ToString};
+
+    use crate::{hex::Hex, ByteArray};
+
+    define_byte_newtype!(
+        /// A test identifier
+        Id,
+        4
+    );
+    define_byte_newtype!(LongId, 64);
+
+    #[test]
+    fn trait_suite() {
+        let id = Id::new([0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(id.to_string(), "deadbeef");
+        assert_eq!("DEADBEEF".parse::<Id>().unwrap(), id);
+        assert!("deadbe".parse::<Id>().is_err());
+        assert_eq!(format!("{:?}", id), "Id(deadbeef)");
+        assert_eq!(Id::from_hex(&id.to_hex()).unwrap(), id);
+        assert_eq!(
+            Id::from_canonical_bytes(&[1, 2, 3]).unwrap_err(),
+            crate::ByteArrayError::IncorrectLength {
+                expected: 4,
+                actual: 3,
+                context: Some("Id")
+            }
+        );
+        assert_eq!(<[u8; 4]>::from(id), [0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(Id::default().as_array(), &[0; 4]);
+        const GENESIS: Id = Id::new(crate::hex!("deadbeef"));
+        const EMPTY: Id = Id::zeroed();
+        assert_eq!(GENESIS, id);
+        assert_eq!(EMPTY, Id::default());
+        assert!(Id::new([0, 0, 0, 1]) < Id::new([0, 0, 1, 0]));
+        assert_eq!(Id::LEN, 4);
+
+        let buffer = [0xff, 0xde, 0xad, 0xbe, 0xef];
+        let borrowed = <Id as crate::ByteArrayRef>::try_from_bytes_ref(&buffer[1..]).unwrap();
+        assert_eq!(borrowed, &id);
+        assert!(<Id as crate::ByteArrayRef>::try_from_bytes_ref(&buffer).is_err());
+
+        let long = LongId::from([7; 64]);
+        assert_eq!(format!("{:?}", long), "LongId(07070707..07070707)");
+        assert_eq!(long.as_ref().len(), 64);
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn serialization() {
+        let id = Id::new([1, 2, 3, 4]);
+        assert_eq!(serde_json::to_string(&id).unwrap(), "\"01020304\"");
+        assert_eq!(serde_json::from_str::<Id>("\"01020304\"").unwrap(), id);
+        let binary = bincode::serialize(&id).unwrap();
+        assert_eq!(bincode::deserialize::<Id>(&binary).unwrap(), id);
+        assert!(serde_json::from_str::<Id>("\"010203\"").is_err());
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derived() {
+        use alloc::{vec, vec::Vec};
+        use core::convert::TryFrom;
+
+        use crate::ByteNewtype;
+
+        #[derive(Clone, PartialEq, Eq, ByteNewtype)]
+        #[byte_newtype(max_len = 4)]
+        struct Script(Vec<u8>);
+
+        #[derive(ByteNewtype)]
+        struct Proof(Vec<u8>);
+
+        assert_eq!(Script::MAX_LEN, 4);
+        assert_eq!(Proof::MAX_LEN, super::DEFAULT_MAX_LEN);
+        let script = Script::try_new(vec![0xab, 0xcd]).unwrap();
+        assert_eq!(script.to_string(), "abcd");
+        assert_eq!(format!("{:?}", script), "Script(abcd)");
+        assert_eq!("abcd".parse::<Script>().unwrap(), script);
+        assert!("0102030405".parse::<Script>().is_err());
+        assert_eq!(
+            Script::try_from(vec![0; 5]).unwrap_err(),
+            crate::ByteArrayError::IncorrectLength {
+                expected: 4,
+                actual: 5,
+                context: Some("Script")
+            }
+        );
+        assert!(Script::from_canonical_bytes(&[0; 5]).is_err());
+        assert_eq!(
+            Script::from_canonical_bytes(&[]).unwrap().into_inner(),
+            Vec::<u8>::new()
+        );
+
+        #[cfg(feature = "serialize")]
+        {
+            assert_eq!(serde_json::to_string(&script).unwrap(), "\"abcd\"");
+            assert_eq!(serde_json::from_str::<Script>("\"abcd\"").unwrap(), script);
+            assert!(serde_json::from_str::<Script>("\"0102030405\"").is_err());
+        }
+        #[cfg(feature = "borsh")]
+        {
+            use borsh::{BorshDeserialize, BorshSerialize};
+
+            let bytes = script.try_to_vec().unwrap();
+            assert_eq!(bytes, [2, 0, 0, 0, 0xab, 0xcd]);
+            assert_eq!(Script::try_from_slice(&bytes).unwrap(), script);
+            assert!(Script::try_from_slice(&[5, 0, 0, 0, 1, 2, 3, 4, 5]).is_err());
+            assert!(Script::try_from_slice(&[255, 255, 255, 255]).is_err());
+        }
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn borsh() {
+        use borsh::{BorshDeserialize, BorshSerialize};
+
+        let id = Id::new([1, 2, 3, 4]);
+        let bytes = id.try_to_vec().unwrap();
+        assert_eq!(bytes, [1, 2, 3, 4]);
+        assert_eq!(Id::try_from_slice(&bytes).unwrap(), id);
+    }
+}
@@ -0,0 +1,188 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Precise, validated edits of serialized byte buffers.
+//!
+//! A [BytePatch] overwrites the bytes at an offset with replacement bytes of the same length, so the buffer keeps its
+//! size and every other byte keeps its position. [apply_patches] checks that every patch is within the buffer and
+//! that no two patches overlap before changing anything, which makes it suitable for corrupting specific fields of
+//! canonical blobs in tests and for migration tools.
+//!
+//! ```edition2018
+//! # use tari_utilities::byte_patch::{apply_patches, BytePatch};
+//! let patched = apply_patches(b"version 1, flags 00", &[
+//!     BytePatch::new(8, b"2".to_vec()),
+//!     BytePatch::new(17, b"ff".to_vec()),
+//! ])
+//! .unwrap();
+//! assert_eq!(patched, b"version 2, flags ff");
+//! ```
+
+use alloc::vec::Vec;
+
+use snafu::prelude::*;
+
+/// Errors for applying patches.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum BytePatchError {
+    /// A patch extends past the end of the buffer.
+    #[snafu(display("Patch {index} at offset {offset} with {len} bytes exceeds the buffer of {buffer_len} bytes"))]
+    OutOfBounds {
+        /// The index of the patch in the list
+        index: usize,
+        /// The offset of the patch
+        offset: usize,
+        /// The number of replacement bytes
+        len: usize,
+        /// The length of the buffer
+        buffer_len: usize,
+    },
+    /// Two patches change some of the same bytes.
+    #[snafu(display("Patches {first} and {second} overlap"))]
+    Overlap {
+        /// The index of the earlier patch in the buffer
+        first: usize,
+        /// The index of the later patch in the buffer
+        second: usize,
+    },
+}
+
+/// Replacement bytes for the bytes at an offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BytePatch {
+    /// The offset of the first replaced byte
+    pub offset: usize,
+    /// The new bytes
+    pub bytes: Vec<u8>,
+}
+
+impl BytePatch {
+    /// Create a patch that writes `bytes` at `offset`.
+    pub fn new(offset: usize, bytes: Vec<u8>) -> Self {
+        Self { offset, bytes }
+    }
+
+    /// The offset just past the last replaced byte, or `None` if it overflows.
+    pub fn end(&self) -> Option<usize> {
+        self.offset.checked_add(self.bytes.len())
+    }
+}
+
+/// Check that all patches fit in a buffer of `buffer_len` bytes and that none overlap.
+///
+/// # Errors
+/// Returns the first problem found: a patch out of bounds, or otherwise two overlapping patches.
+pub fn validate_patches(buffer_len: usize, patches: &[BytePatch]) -> Result<(), BytePatchError> {
+    for (index, patch) in patches.iter().enumerate() {
+        ensure!(patch.end().is_some_and(|end| end <= buffer_len), OutOfBoundsSnafu {
+            index,
+            offset: patch.offset,
+            len: patch.bytes.len(),
+            buffer_len
+        });
+    }
+    // Empty patches change nothing and cannot overlap anything
+    let mut order = (0..patches.len())
+        .filter(|i| patches.get(*i).is_some_and(|p| !p.bytes.is_empty()))
+        .collect::<Vec<_>>();
+    order.sort_by_key(|i| patches.get(*i).map(|p| p.offset));
+    for pair in order.windows(2) {
+        if let [first, second] = *pair {
+            let first_end = patches.get(first).and_then(BytePatch::end);
+            let second_start = patches.get(second).map(|p| p.offset);
+            ensure!(first_end <= second_start, OverlapSnafu { first, second });
+        }
+    }
+    Ok(())
+}
+
+/// Apply patches to a buffer in place. Nothing is changed unless all the patches are valid.
+///
+/// # Errors
+/// Returns an error if a patch is out of bounds or two patches overlap.
+pub fn apply_patches_in_place(buffer: &mut [u8], patches: &[BytePatch]) -> Result<(), BytePatchError> {
+    validate_patches(buffer.len(), patches)?;
+    for patch in patches {
+        if let Some(target) = patch.end().and_then(|end| buffer.get_mut(patch.offset..end)) {
+            target.copy_from_slice(&patch.bytes);
+        }
+    }
+    Ok(())
+}
+
+/// Return a copy of `buffer` with the patches applied.
+///
+/// # Errors
+/// Returns an error if a patch is out of bounds or two patches overlap.
+pub fn apply_patches(buffer: &[u8], patches: &[BytePatch]) -> Result<Vec<u8>, BytePatchError> {
+    let mut patched = buffer.to_vec();
+    apply_patches_in_place(&mut patched, patches)?;
+    Ok(patched)
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn applying() {
+        let patches = [
+            BytePatch::new(3, vec![9, 9]),
+            BytePatch::new(0, vec![7]),
+            BytePatch::new(5, vec![]),
+        ];
+        assert_eq!(apply_patches(&[0; 5], &patches).unwrap(), [7, 0, 0, 9, 9]);
+        assert_eq!(apply_patches(&[1, 2], &[]).unwrap(), [1, 2]);
+        // Adjacent patches do not overlap
+        let adjacent = [BytePatch::new(1, vec![5]), BytePatch::new(0, vec![4])];
+        assert_eq!(apply_patches(&[0, 0], &adjacent).unwrap(), [4, 5]);
+    }
+
+    #[test]
+    fn validation() {
+        let mut buffer = [1u8, 2, 3, 4];
+        assert_eq!(
+            apply_patches_in_place(&mut buffer, &[
+                BytePatch::new(0, vec![0]),
+                BytePatch::new(3, vec![0, 0])
+            ]),
+            Err(BytePatchError::OutOfBounds {
+                index: 1,
+                offset: 3,
+                len: 2,
+                buffer_len: 4
+            })
+        );
+        assert_eq!(buffer, [1, 2, 3, 4]);
+        assert_eq!(
+            apply_patches(&buffer, &[BytePatch::new(2, vec![0, 0]), BytePatch::new(0, vec![0; 3])]),
+            Err(BytePatchError::Overlap { first: 1, second: 0 })
+        );
+        assert!(matches!(
+            apply_patches(&buffer, &[BytePatch::new(usize::MAX, vec![0])]),
+            Err(BytePatchError::OutOfBounds { .. })
+        ));
+        assert_eq!(BytePatch::new(usize::MAX, vec![0]).end(), None);
+    }
+}
@@ -0,0 +1,234 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Parsing and formatting of byte sizes such as `10MiB` or `1.5 GB`, for configuration values.
+//!
+//! Binary suffixes (`KiB`, `MiB`, ...) are powers of 1024 and decimal suffixes (`kB` or `KB`, `MB`, ...) are powers
+//! of 1000. Suffixes are case-sensitive, and ambiguous forms such as `K`, `m` or `Mb` are rejected rather than
+//! guessed at. A number may have a fractional part as long as the result is a whole number of bytes.
+//!
+//! ```edition2018
+//! # use tari_utilities::byte_size::{format_byte_size, parse_byte_size, UnitSystem};
+//! assert_eq!(parse_byte_size("10MiB").unwrap(), 10 * 1024 * 1024);
+//! assert_eq!(parse_byte_size("1.5 kB").unwrap(), 1500);
+//! assert!(parse_byte_size("10M").is_err());
+//! assert_eq!(format_byte_size(1536, UnitSystem::Binary), "1.5 KiB");
+//! assert_eq!(format_byte_size(1536, UnitSystem::Decimal), "1.53 kB");
+//! ```
+
+use alloc::{format, string::String};
+use core::convert::TryFrom;
+
+use snafu::prelude::*;
+
+/// Errors for parsing byte sizes.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum ByteSizeError {
+    /// The number is missing or malformed.
+    #[snafu(display("Invalid number in byte size"))]
+    InvalidNumber {},
+    /// The suffix is not a known unit.
+    #[snafu(display("Unknown byte size suffix `{suffix}`"))]
+    UnknownSuffix {
+        /// The suffix
+        suffix: String,
+    },
+    /// The size does not fit in a `u64`.
+    #[snafu(display("Byte size is too large"))]
+    Overflow {},
+    /// The size is not a whole number of bytes.
+    #[snafu(display("Byte size is not a whole number of bytes"))]
+    FractionalBytes {},
+}
+
+/// Whether sizes are formatted in powers of 1024 or 1000.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitSystem {
+    /// Powers of 1024: `KiB`, `MiB`, `GiB`, `TiB`, `PiB` and `EiB`
+    Binary,
+    /// Powers of 1000: `kB`, `MB`, `GB`, `TB`, `PB` and `EB`
+    Decimal,
+}
+
+const BINARY_UNITS: [(&str, u64); 7] = [
+    ("B", 1),
+    ("KiB", 1 << 10),
+    ("MiB", 1 << 20),
+    ("GiB", 1 << 30),
+    ("TiB", 1 << 40),
+    ("PiB", 1 << 50),
+    ("EiB", 1 << 60),
+];
+
+const DECIMAL_UNITS: [(&str, u64); 7] = [
+    ("B", 1),
+    ("kB", 1_000),
+    ("MB", 1_000_000),
+    ("GB", 1_000_000_000),
+    ("TB", 1_000_000_000_000),
+    ("PB", 1_000_000_000_000_000),
+    ("EB", 1_000_000_000_000_000_000),
+];
+
+fn multiplier(suffix: &str) -> Option<u64> {
+    match suffix {
+        "" => Some(1),
+        "KB" => Some(1_000),
+        _ => BINARY_UNITS
+            .iter()
+            .chain(DECIMAL_UNITS.iter())
+            .find(|(name, _)| *name == suffix)
+            .map(|(_, multiplier)| *multiplier),
+    }
+}
+
+/// Parse a byte size: a number, optionally followed by spaces and a unit suffix. A bare number is a count of bytes.
+///
+/// # Errors
+/// Returns an error if the number is malformed, the suffix is unknown, or the size does not fit in a `u64` or is not
+/// a whole number of bytes.
+pub fn parse_byte_size(s: &str) -> Result<u64, ByteSizeError> {
+    let s = s.trim();
+    let split = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let (number, suffix) = s.split_at(split);
+    let suffix = suffix.trim_start_matches(' ');
+    let multiplier = multiplier(suffix).context(UnknownSuffixSnafu { suffix })?;
+
+    let (whole, fraction) = number.split_once('.').unwrap_or((number, "0"));
+    let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    ensure!(is_digits(whole) && is_digits(fraction), InvalidNumberSnafu);
+    let whole = whole
+        .parse::<u64>()
+        .ok()
+        .and_then(|whole| whole.checked_mul(multiplier))
+        .context(OverflowSnafu)?;
+
+    // A multiplier of at most 2^60 can only cancel a denominator of up to 10^18, so longer fractions cannot be whole
+    let fraction = fraction.trim_end_matches('0');
+    ensure!(fraction.len() <= 18, FractionalBytesSnafu);
+    let denominator = 10u128.pow(fraction.len() as u32);
+    let numerator = fraction
+        .parse::<u128>()
+        .unwrap_or_default()
+        .saturating_mul(u128::from(multiplier));
+    ensure!(numerator.checked_rem(denominator) == Some(0), FractionalBytesSnafu);
+    let fraction = numerator.checked_div(denominator).unwrap_or_default();
+    u64::try_from(fraction)
+        .ok()
+        .and_then(|fraction| whole.checked_add(fraction))
+        .context(OverflowSnafu)
+}
+
+/// Format a byte size in the largest unit that it is at least one of, with up to two decimal places, rounded down.
+pub fn format_byte_size(bytes: u64, units: UnitSystem) -> String {
+    let table = match units {
+        UnitSystem::Binary => &BINARY_UNITS,
+        UnitSystem::Decimal => &DECIMAL_UNITS,
+    };
+    let (name, unit) = table
+        .iter()
+        .rev()
+        .find(|(_, unit)| bytes >= *unit)
+        .copied()
+        .unwrap_or(("B", 1));
+    let hundredths = u128::from(bytes)
+        .saturating_mul(100)
+        .checked_div(u128::from(unit))
+        .unwrap_or_default();
+    let whole = hundredths.checked_div(100).unwrap_or_default();
+    match hundredths.checked_rem(100).unwrap_or_default() {
+        0 => format!("{} {}", whole, name),
+        fraction => {
+            let fraction = format!("{:02}", fraction);
+            format!("{}.{} {}", whole, fraction.trim_end_matches('0'), name)
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        assert_eq!(parse_byte_size("0"), Ok(0));
+        assert_eq!(parse_byte_size("512"), Ok(512));
+        assert_eq!(parse_byte_size("512B"), Ok(512));
+        assert_eq!(parse_byte_size(" 10MiB "), Ok(10 << 20));
+        assert_eq!(parse_byte_size("10 MiB"), Ok(10 << 20));
+        assert_eq!(parse_byte_size("2kB"), Ok(2000));
+        assert_eq!(parse_byte_size("2KB"), Ok(2000));
+        assert_eq!(parse_byte_size("3GB"), Ok(3_000_000_000));
+        assert_eq!(parse_byte_size("1.5KiB"), Ok(1536));
+        assert_eq!(parse_byte_size("0.25 MB"), Ok(250_000));
+        assert_eq!(parse_byte_size("1.000KiB"), Ok(1024));
+        assert_eq!(parse_byte_size("16EiB"), Err(ByteSizeError::Overflow {}));
+        assert_eq!(
+            parse_byte_size("15.999999999999999999EiB"),
+            Err(ByteSizeError::FractionalBytes {})
+        );
+        assert_eq!(parse_byte_size("18446744073709551615"), Ok(u64::MAX));
+        assert_eq!(parse_byte_size("18446744073709551616"), Err(ByteSizeError::Overflow {}));
+        assert_eq!(parse_byte_size("0.000000000000000001EB"), Ok(1));
+    }
+
+    #[test]
+    fn strictness() {
+        for suffix in ["K", "k", "M", "Mb", "mb", "KIB", "kiB", "Ki", "bytes", "B B"] {
+            assert_eq!(
+                parse_byte_size(&format!("1{}", suffix)),
+                Err(ByteSizeError::UnknownSuffix {
+                    suffix: suffix.to_string()
+                }),
+                "{}",
+                suffix
+            );
+        }
+        for number in ["", "MiB", ".5MiB", "5.MiB", "1.2.3", "-1", "+1"] {
+            assert!(parse_byte_size(number).is_err(), "{}", number);
+        }
+        assert_eq!(parse_byte_size("1.5B"), Err(ByteSizeError::FractionalBytes {}));
+        assert_eq!(parse_byte_size("0.1KiB"), Err(ByteSizeError::FractionalBytes {}));
+        assert_eq!(
+            parse_byte_size("0.0000000000000000001EB"),
+            Err(ByteSizeError::FractionalBytes {})
+        );
+    }
+
+    #[test]
+    fn formatting() {
+        assert_eq!(format_byte_size(0, UnitSystem::Binary), "0 B");
+        assert_eq!(format_byte_size(1023, UnitSystem::Binary), "1023 B");
+        assert_eq!(format_byte_size(1024, UnitSystem::Binary), "1 KiB");
+        assert_eq!(format_byte_size(10 << 20, UnitSystem::Binary), "10 MiB");
+        assert_eq!(format_byte_size(1_278_000_000, UnitSystem::Binary), "1.19 GiB");
+        assert_eq!(format_byte_size(u64::MAX, UnitSystem::Binary), "15.99 EiB");
+        assert_eq!(format_byte_size(999, UnitSystem::Decimal), "999 B");
+        assert_eq!(format_byte_size(1_100_000, UnitSystem::Decimal), "1.1 MB");
+        assert_eq!(format_byte_size(u64::MAX, UnitSystem::Decimal), "18.44 EB");
+        for bytes in [1, 1536, 10 << 20, 1 << 60] {
+            assert_eq!(parse_byte_size(&format_byte_size(bytes, UnitSystem::Binary)), Ok(bytes));
+        }
+    }
+}
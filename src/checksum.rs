@@ -0,0 +1,647 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Checksums for detecting accidental corruption of data.
+//!
+//! This module provides CRC32 (IEEE), CRC16-CCITT and xxHash64. Each is available as a streaming type implementing
+//! [Checksum], for data that arrives in pieces, and as a one-shot function. None of these are cryptographic hashes and
+//! they must not be used to detect deliberate tampering.
+//!
+//! [Checksummed] appends a checksum to the bytes of any [ByteArray] type, so that typos are caught when its hex or
+//! base58 form is decoded.
+//!
+//! ```edition2018
+//! # use tari_utilities::checksum::{crc32, Checksum, Crc32};
+//! let mut crc = Crc32::default();
+//! crc.update(b"1234");
+//! crc.update(b"56789");
+//! assert_eq!(crc.finalize(), 0xcbf4_3926);
+//! assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+//! ```
+
+use alloc::{string::ToString, vec::Vec};
+use core::{fmt, marker::PhantomData, mem::size_of};
+
+use crate::{
+    byte_array::{ByteArray, ByteArrayError},
+    dammsum::ByteDammSum,
+};
+
+/// A checksum that can be computed incrementally.
+pub trait Checksum: Default {
+    /// The type of the final checksum value.
+    type Output;
+
+    /// Add the data to the checksum.
+    fn update(&mut self, data: &[u8]);
+
+    /// Return the checksum of all the data added so far.
+    fn finalize(self) -> Self::Output;
+
+    /// Compute the checksum of the data in one step.
+    fn checksum(data: &[u8]) -> Self::Output {
+        let mut checksum = Self::default();
+        checksum.update(data);
+        checksum.finalize()
+    }
+}
+
+//-------------------------------------------         CRC32          ---------------------------------------------//
+
+/// The reflected CRC32 (IEEE 802.3) polynomial.
+const CRC32_POLY: u32 = 0xedb8_8320;
+const CRC32_TABLE: [u32; 256] = crc32_table();
+
+// Only evaluated at compile time, where an out of range index would fail the build
+#[allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ CRC32_POLY
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// The CRC32 checksum used by Ethernet, zlib and PNG (reflected polynomial `0xedb88320`, initial value and final XOR
+/// `0xffffffff`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc32 {
+    state: u32,
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self { state: u32::MAX }
+    }
+}
+
+impl Checksum for Crc32 {
+    type Output = u32;
+
+    fn update(&mut self, data: &[u8]) {
+        #[cfg(feature = "simd")]
+        {
+            self.state = crate::simd::crc32_update(self.state, data);
+        }
+        #[cfg(not(feature = "simd"))]
+        {
+            self.state = crc32_update(self.state, data);
+        }
+    }
+
+    fn finalize(self) -> u32 {
+        !self.state
+    }
+}
+
+/// Feed `data` through the CRC32 register `state` a byte at a time, without the initial and final inversions.
+pub(crate) fn crc32_update(state: u32, data: &[u8]) -> u32 {
+    data.iter().fold(state, |crc, byte| {
+        table_entry(&CRC32_TABLE, crc.to_le_bytes()[0] ^ byte) ^ (crc >> 8)
+    })
+}
+
+/// Compute the CRC32 checksum of the data.
+pub fn crc32(data: &[u8]) -> u32 {
+    Crc32::checksum(data)
+}
+
+/// Combine the CRC32 checksums of two consecutive pieces of data, `crc_a` of the first and `crc_b` of the second,
+/// into the checksum of their concatenation. `len_b` is the length of the second piece.
+///
+/// This allows the checksum of a large payload to be computed from the checksums of its parts in parallel.
+pub fn crc32_combine(crc_a: u32, crc_b: u32, len_b: u64) -> u32 {
+    // The operator for a single zero bit, followed by the operators for two and four zero bits
+    let mut odd = [0u32; 32];
+    odd[0] = CRC32_POLY;
+    let mut row = 1u32;
+    for entry in odd.iter_mut().skip(1) {
+        *entry = row;
+        row <<= 1;
+    }
+    let mut even = gf2_matrix_square(&odd);
+    odd = gf2_matrix_square(&even);
+
+    // Apply `len_b` zero bytes to `crc_a` by repeated squaring of the operator
+    let mut crc = crc_a;
+    let mut len = len_b;
+    while len != 0 {
+        even = gf2_matrix_square(&odd);
+        if len & 1 == 1 {
+            crc = gf2_matrix_times(&even, crc);
+        }
+        len >>= 1;
+        if len == 0 {
+            break;
+        }
+        odd = gf2_matrix_square(&even);
+        if len & 1 == 1 {
+            crc = gf2_matrix_times(&odd, crc);
+        }
+        len >>= 1;
+    }
+    crc ^ crc_b
+}
+
+fn gf2_matrix_times(matrix: &[u32; 32], vector: u32) -> u32 {
+    matrix
+        .iter()
+        .fold((0, vector), |(sum, vector), row| {
+            (if vector & 1 == 1 { sum ^ row } else { sum }, vector >> 1)
+        })
+        .0
+}
+
+fn gf2_matrix_square(matrix: &[u32; 32]) -> [u32; 32] {
+    let mut square = [0u32; 32];
+    for (entry, row) in square.iter_mut().zip(matrix.iter()) {
+        *entry = gf2_matrix_times(matrix, *row);
+    }
+    square
+}
+
+//-------------------------------------------      CRC16-CCITT       ---------------------------------------------//
+
+/// The CRC16-CCITT polynomial.
+const CRC16_POLY: u16 = 0x1021;
+const CRC16_TABLE: [u16; 256] = crc16_table();
+
+// Only evaluated at compile time, where an out of range index would fail the build
+#[allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+const fn crc16_table() -> [u16; 256] {
+    let mut table = [0u16; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000 == 0 {
+                crc << 1
+            } else {
+                (crc << 1) ^ CRC16_POLY
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// The CRC16-CCITT checksum, in the common "CCITT-FALSE" form (polynomial `0x1021`, initial value `0xffff`, no
+/// reflection and no final XOR).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crc16Ccitt {
+    state: u16,
+}
+
+impl Default for Crc16Ccitt {
+    fn default() -> Self {
+        Self { state: u16::MAX }
+    }
+}
+
+impl Checksum for Crc16Ccitt {
+    type Output = u16;
+
+    fn update(&mut self, data: &[u8]) {
+        self.state = data.iter().fold(self.state, |crc, byte| {
+            table_entry(&CRC16_TABLE, crc.to_be_bytes()[0] ^ byte) ^ (crc << 8)
+        });
+    }
+
+    fn finalize(self) -> u16 {
+        self.state
+    }
+}
+
+/// Compute the CRC16-CCITT checksum of the data.
+pub fn crc16_ccitt(data: &[u8]) -> u16 {
+    Crc16Ccitt::checksum(data)
+}
+
+/// Look up a CRC table entry.
+// A `u8` index is always within a 256-entry table
+#[allow(clippy::indexing_slicing)]
+fn table_entry<T: Copy>(table: &[T; 256], index: u8) -> T {
+    table[usize::from(index)]
+}
+
+//-------------------------------------------        xxHash64        ---------------------------------------------//
+
+const PRIME64_1: u64 = 0x9e37_79b1_85eb_ca87;
+const PRIME64_2: u64 = 0xc2b2_ae3d_27d4_eb4f;
+const PRIME64_3: u64 = 0x1656_67b1_9e37_79f9;
+const PRIME64_4: u64 = 0x85eb_ca77_c2b2_ae63;
+const PRIME64_5: u64 = 0x27d4_eb2f_1656_67c5;
+
+/// The number of bytes consumed by each round of the four accumulators.
+const STRIPE_LEN: usize = 32;
+
+/// The xxHash64 hash, a fast non-cryptographic hash suitable for detecting corruption of large payloads.
+#[derive(Debug, Clone)]
+pub struct XxHash64 {
+    seed: u64,
+    accumulators: [u64; 4],
+    buffer: [u8; STRIPE_LEN],
+    buffered: usize,
+    total_len: u64,
+}
+
+impl XxHash64 {
+    /// Create a new hash with the given seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            accumulators: [
+                seed.wrapping_add(PRIME64_1).wrapping_add(PRIME64_2),
+                seed.wrapping_add(PRIME64_2),
+                seed,
+                seed.wrapping_sub(PRIME64_1),
+            ],
+            buffer: [0u8; STRIPE_LEN],
+            buffered: 0,
+            total_len: 0,
+        }
+    }
+
+    fn process_stripe(&mut self, stripe: &[u8]) {
+        for (accumulator, lane) in self.accumulators.iter_mut().zip(stripe.chunks_exact(8)) {
+            *accumulator = xxh64_round(*accumulator, read_u64(lane));
+        }
+    }
+}
+
+impl Default for XxHash64 {
+    fn default() -> Self {
+        Self::with_seed(0)
+    }
+}
+
+impl Checksum for XxHash64 {
+    type Output = u64;
+
+    fn update(&mut self, data: &[u8]) {
+        let mut data = data;
+        self.total_len = self.total_len.wrapping_add(data.len() as u64);
+
+        // Complete a partially filled stripe first
+        if self.buffered > 0 {
+            let (head, rest) = data.split_at(STRIPE_LEN.saturating_sub(self.buffered).min(data.len()));
+            let end = self.buffered.saturating_add(head.len());
+            if let Some(space) = self.buffer.get_mut(self.buffered..end) {
+                space.copy_from_slice(head);
+            }
+            self.buffered = end;
+            data = rest;
+            if self.buffered < STRIPE_LEN {
+                return;
+            }
+            let buffer = self.buffer;
+            self.process_stripe(&buffer);
+            self.buffered = 0;
+        }
+
+        let mut stripes = data.chunks_exact(STRIPE_LEN);
+        for stripe in &mut stripes {
+            self.process_stripe(stripe);
+        }
+        let remainder = stripes.remainder();
+        if let Some(space) = self.buffer.get_mut(..remainder.len()) {
+            space.copy_from_slice(remainder);
+        }
+        self.buffered = remainder.len();
+    }
+
+    fn finalize(self) -> u64 {
+        let mut hash = if self.total_len >= STRIPE_LEN as u64 {
+            let [a, b, c, d] = self.accumulators;
+            let hash = a
+                .rotate_left(1)
+                .wrapping_add(b.rotate_left(7))
+                .wrapping_add(c.rotate_left(12))
+                .wrapping_add(d.rotate_left(18));
+            self.accumulators.iter().fold(hash, |hash, accumulator| {
+                (hash ^ xxh64_round(0, *accumulator))
+                    .wrapping_mul(PRIME64_1)
+                    .wrapping_add(PRIME64_4)
+            })
+        } else {
+            self.seed.wrapping_add(PRIME64_5)
+        };
+        hash = hash.wrapping_add(self.total_len);
+
+        let tail = self.buffer.get(..self.buffered).unwrap_or_default();
+        let mut words = tail.chunks_exact(8);
+        for word in &mut words {
+            hash ^= xxh64_round(0, read_u64(word));
+            hash = hash.rotate_left(27).wrapping_mul(PRIME64_1).wrapping_add(PRIME64_4);
+        }
+        let mut halves = words.remainder().chunks_exact(4);
+        for half in &mut halves {
+            hash ^= read_u64(half).wrapping_mul(PRIME64_1);
+            hash = hash.rotate_left(23).wrapping_mul(PRIME64_2).wrapping_add(PRIME64_3);
+        }
+        for byte in halves.remainder() {
+            hash ^= u64::from(*byte).wrapping_mul(PRIME64_5);
+            hash = hash.rotate_left(11).wrapping_mul(PRIME64_1);
+        }
+
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(PRIME64_2);
+        hash ^= hash >> 29;
+        hash = hash.wrapping_mul(PRIME64_3);
+        hash ^ (hash >> 32)
+    }
+}
+
+/// Compute the xxHash64 hash of the data with the given seed.
+pub fn xxhash64(data: &[u8], seed: u64) -> u64 {
+    let mut hash = XxHash64::with_seed(seed);
+    hash.update(data);
+    hash.finalize()
+}
+
+fn xxh64_round(accumulator: u64, input: u64) -> u64 {
+    accumulator
+        .wrapping_add(input.wrapping_mul(PRIME64_2))
+        .rotate_left(31)
+        .wrapping_mul(PRIME64_1)
+}
+
+/// Read up to 8 bytes as a little-endian integer.
+fn read_u64(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .rev()
+        .fold(0, |value, byte| (value << 8) | u64::from(*byte))
+}
+
+//-------------------------------------------      Checksummed       ---------------------------------------------//
+
+/// A [ByteArray] value whose byte form has a checksum appended, giving address-like types typo detection when they are
+/// rendered as hex or base58.
+///
+/// The checksum defaults to a single [ByteDammSum] byte, which detects every single-byte error and every transposition
+/// of adjacent bytes. Any [Checksum] with an integer output can be used instead, and is appended in big-endian order.
+///
+/// ```edition2018
+/// # use tari_utilities::{checksum::Checksummed, hex::Hex};
+/// let address = Checksummed::new(vec![0xde_u8, 0xad, 0xbe, 0xef]);
+/// let hex = address.to_hex();
+/// assert_eq!(hex.len(), 10);
+/// assert_eq!(Checksummed::<Vec<u8>>::from_hex(&hex).unwrap(), address);
+/// assert!(Checksummed::<Vec<u8>>::from_hex("deadbfef00").is_err());
+/// ```
+pub struct Checksummed<T, C = ByteDammSum> {
+    value: T,
+    bytes: Vec<u8>,
+    checksum: PhantomData<fn() -> C>,
+}
+
+impl<T: ByteArray, C> Checksummed<T, C>
+where
+    C: Checksum,
+    C::Output: Into<u64>,
+{
+    /// The number of bytes the checksum adds to the byte form.
+    pub const CHECKSUM_LEN: usize = size_of::<C::Output>();
+
+    /// Wrap the value, computing its checksum.
+    pub fn new(value: T) -> Self {
+        let mut bytes = value.to_vec();
+        let checksum = Self::checksum_bytes(&bytes);
+        bytes.extend_from_slice(&checksum);
+        Self {
+            value,
+            bytes,
+            checksum: PhantomData,
+        }
+    }
+
+    /// Return the checksum of the data as big-endian bytes.
+    fn checksum_bytes(data: &[u8]) -> Vec<u8> {
+        let checksum = C::checksum(data).into().to_be_bytes();
+        let start = checksum.len().saturating_sub(Self::CHECKSUM_LEN);
+        checksum.get(start..).unwrap_or_default().to_vec()
+    }
+}
+
+impl<T, C> Checksummed<T, C> {
+    /// Return a reference to the wrapped value.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// Return the wrapped value, discarding the checksum.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: ByteArray, C> From<T> for Checksummed<T, C>
+where
+    C: Checksum,
+    C::Output: Into<u64>,
+{
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: ByteArray, C> ByteArray for Checksummed<T, C>
+where
+    C: Checksum,
+    C::Output: Into<u64>,
+{
+    /// Verify and strip the checksum, then convert the remaining bytes to the wrapped type.
+    fn from_canonical_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+        let split = bytes
+            .len()
+            .checked_sub(Self::CHECKSUM_LEN)
+            .ok_or_else(|| ByteArrayError::ConversionError {
+                reason: "The input is too short to contain a checksum".to_string(),
+            })?;
+        let (data, checksum) = bytes.split_at(split);
+        if Self::checksum_bytes(data) != checksum {
+            return Err(ByteArrayError::ConversionError {
+                reason: "The checksum is invalid".to_string(),
+            });
+        }
+        Ok(Self {
+            value: T::from_canonical_bytes(data)?,
+            bytes: bytes.to_vec(),
+            checksum: PhantomData,
+        })
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+// Implemented by hand so that the checksum type needs none of these traits
+impl<T: Clone, C> Clone for Checksummed<T, C> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            bytes: self.bytes.clone(),
+            checksum: PhantomData,
+        }
+    }
+}
+
+impl<T: PartialEq, C> PartialEq for Checksummed<T, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq, C> Eq for Checksummed<T, C> {}
+
+impl<T: fmt::Debug, C> fmt::Debug for Checksummed<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Checksummed").field(&self.value).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    const SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+    fn long_input() -> Vec<u8> {
+        (0..=255u8).cycle().take(768).collect()
+    }
+
+    /// Check that the streaming checksum gives the same result however the data is split.
+    fn check_streaming<C>(data: &[u8], expected: C::Output)
+    where
+        C: Checksum,
+        C::Output: PartialEq + core::fmt::Debug,
+    {
+        assert_eq!(C::checksum(data), expected);
+        for split in [1, 3, 7, 8, 31, 32, 33, 100] {
+            let mut checksum = C::default();
+            for piece in data.chunks(split) {
+                checksum.update(piece);
+            }
+            checksum.update(&[]);
+            assert_eq!(checksum.finalize(), expected);
+        }
+    }
+
+    #[test]
+    fn crc32_vectors() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"a"), 0xe8b7_be43);
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        check_streaming::<Crc32>(b"Nobody inspects the spammish repetition", 0xad42_70ed);
+        check_streaming::<Crc32>(&long_input(), 0xb0c0_df2a);
+    }
+
+    #[test]
+    fn crc32_combination() {
+        let data = long_input();
+        for split in [0, 1, 100, 767, 768] {
+            let (a, b) = data.split_at(split);
+            assert_eq!(crc32_combine(crc32(a), crc32(b), b.len() as u64), crc32(&data));
+        }
+    }
+
+    #[test]
+    fn crc16_vectors() {
+        assert_eq!(crc16_ccitt(b""), 0xffff);
+        assert_eq!(crc16_ccitt(b"a"), 0x9d77);
+        assert_eq!(crc16_ccitt(b"123456789"), 0x29b1);
+        check_streaming::<Crc16Ccitt>(b"Nobody inspects the spammish repetition", 0x2dcb);
+        check_streaming::<Crc16Ccitt>(&long_input(), 0xd51a);
+    }
+
+    #[test]
+    fn xxhash64_vectors() {
+        assert_eq!(xxhash64(b"", 0), 0xef46_db37_51d8_e999);
+        assert_eq!(xxhash64(b"a", 0), 0xd24e_c4f1_a98c_6e5b);
+        assert_eq!(xxhash64(b"abc", 0), 0x44bc_2cf5_ad77_0999);
+        assert_eq!(xxhash64(b"123456789", 0), 0x8cb8_41db_40e6_ae83);
+        assert_eq!(xxhash64(b"", SEED), 0xc434_9fc9_3c01_0000);
+        assert_eq!(xxhash64(b"abc", SEED), 0x2ed0_f59d_6b43_ac8b);
+        assert_eq!(xxhash64(&long_input(), SEED), 0x919e_1c78_9d52_2e91);
+        check_streaming::<XxHash64>(b"Nobody inspects the spammish repetition", 0xfbce_a83c_8a37_8bf1);
+        check_streaming::<XxHash64>(&long_input(), 0x8e03_c838_c596_036f);
+    }
+
+    #[test]
+    fn checksummed() {
+        let address = Checksummed::<[u8; 4]>::new([0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(address.as_bytes().len(), 5);
+        assert_eq!(address.value(), &[0xde, 0xad, 0xbe, 0xef]);
+
+        let decoded = Checksummed::<[u8; 4]>::from_canonical_bytes(address.as_bytes()).unwrap();
+        assert_eq!(decoded, address);
+        assert_eq!(decoded.into_inner(), [0xde, 0xad, 0xbe, 0xef]);
+
+        // Every single-byte error and adjacent transposition is detected
+        let bytes = address.to_vec();
+        for i in 0..bytes.len() {
+            let mut typo = bytes.clone();
+            typo[i] ^= 0x01;
+            assert!(Checksummed::<[u8; 4]>::from_canonical_bytes(&typo).is_err());
+            if i + 1 < bytes.len() {
+                let mut swapped = bytes.clone();
+                swapped.swap(i, i + 1);
+                assert!(Checksummed::<[u8; 4]>::from_canonical_bytes(&swapped).is_err());
+            }
+        }
+        assert!(Checksummed::<Vec<u8>>::from_canonical_bytes(&[]).is_err());
+        assert!(Checksummed::<[u8; 3]>::from_canonical_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn checksummed_with_crc32() {
+        let value = b"123456789".to_vec();
+        let checksummed = Checksummed::<_, Crc32>::new(value.clone());
+        assert_eq!(Checksummed::<Vec<u8>, Crc32>::CHECKSUM_LEN, 4);
+        assert_eq!(&checksummed.as_bytes()[9..], &[0xcb, 0xf4, 0x39, 0x26]);
+        assert_eq!(
+            Checksummed::<Vec<u8>, Crc32>::from_canonical_bytes(checksummed.as_bytes())
+                .unwrap()
+                .into_inner(),
+            value
+        );
+    }
+}
@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-//! Function which tries to convert a series of `T`s to `U`s.
+//! Functions for converting a series of `T`s to `U`s and for changing the byte order of arrays.
 
 use alloc::vec::Vec;
 use core::convert::TryInto;
@@ -39,6 +39,20 @@ where
     Ok(result)
 }
 
+/// Reverse the byte order of an array, e.g. to convert a little-endian value to big-endian. This is a `const fn` so it
+/// can be used to define constants.
+// The index is below `N`, and a `const fn` cannot use the checked slice methods
+#[allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+pub const fn reverse_bytes<const N: usize>(bytes: [u8; N]) -> [u8; N] {
+    let mut result = [0u8; N];
+    let mut i = 0;
+    while i < N {
+        result[i] = bytes[N - 1 - i];
+        i += 1;
+    }
+    result
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -51,6 +65,14 @@ mod test {
 
     #[test]
     fn convert_all_failed() {
-        assert!(try_convert_all::<_, u32, _>(vec![std::i64::MAX, 2, 3, 4, 5]).is_err());
+        assert!(try_convert_all::<_, u32, _>(vec![i64::MAX, 2, 3, 4, 5]).is_err());
+    }
+
+    #[test]
+    fn reverse_byte_order() {
+        const BE: [u8; 4] = reverse_bytes(0x0102_0304u32.to_le_bytes());
+        assert_eq!(BE, 0x0102_0304u32.to_be_bytes());
+        assert_eq!(reverse_bytes([1u8]), [1]);
+        assert_eq!(reverse_bytes([0u8; 0]), [0u8; 0]);
     }
 }
@@ -0,0 +1,249 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A counter that never repeats a value, even across restarts.
+//!
+//! [MonotonicCounter] persists a high-water mark to a small file: every value below the mark may already have been
+//! handed out, so after a restart counting resumes from the mark. To avoid a disk write for every value, the counter
+//! reserves values in batches and only persists the end of each batch. A crash loses at most the unused rest of a
+//! batch, which is skipped rather than repeated.
+//!
+//! The file is replaced atomically by writing a temporary file, syncing it and renaming it over the old one. It holds
+//! the mark as 8 little-endian bytes followed by their CRC-32, so a damaged file is detected instead of silently
+//! restarting from zero.
+//!
+//! ```edition2018,no_run
+//! # use tari_utilities::counter::MonotonicCounter;
+//! let mut nonces = MonotonicCounter::open("nonce.counter", 1_000)?;
+//! let nonce = nonces.next_value()?;
+//! let sequence_numbers = nonces.reserve(16)?;
+//! assert!(sequence_numbers.start > nonce);
+//! # Ok::<(), tari_utilities::counter::CounterError>(())
+//! ```
+
+use alloc::string::{String, ToString};
+use core::{convert::TryInto, ops::Range};
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use snafu::prelude::*;
+
+use crate::checksum::crc32;
+
+/// Errors for persisting a counter.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum CounterError {
+    /// The counter file could not be read or written.
+    #[snafu(display("Counter file I/O failed: {reason}"))]
+    Io {
+        /// The underlying I/O error
+        reason: String,
+    },
+    /// The counter file exists but does not hold a valid high-water mark.
+    #[snafu(display("Counter file is corrupt"))]
+    Corrupt {},
+    /// The counter has reached `u64::MAX`.
+    #[snafu(display("Counter is exhausted"))]
+    Exhausted {},
+}
+
+impl From<io::Error> for CounterError {
+    fn from(err: io::Error) -> Self {
+        CounterError::Io {
+            reason: err.to_string(),
+        }
+    }
+}
+
+/// The length of the counter file.
+const FILE_LEN: usize = 12;
+
+/// A persistent counter whose values strictly increase across restarts.
+#[derive(Debug)]
+pub struct MonotonicCounter {
+    path: PathBuf,
+    next: u64,
+    high_water_mark: u64,
+    batch_size: u64,
+}
+
+impl MonotonicCounter {
+    /// Open the counter stored at `path`, creating it at zero if the file does not exist. Values are reserved
+    /// `batch_size` at a time; a batch size of zero is treated as one, which persists every value.
+    ///
+    /// # Errors
+    /// Returns [CounterError::Corrupt] if the file is damaged, or [CounterError::Io] if it cannot be read.
+    pub fn open<P: AsRef<Path>>(path: P, batch_size: u64) -> Result<Self, CounterError> {
+        let path = path.as_ref().to_path_buf();
+        let high_water_mark = match fs::read(&path) {
+            Ok(contents) => decode(&contents)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path,
+            next: high_water_mark,
+            high_water_mark,
+            batch_size: batch_size.max(1),
+        })
+    }
+
+    /// The value the next call to [next_value](Self::next_value) will return.
+    pub fn peek(&self) -> u64 {
+        self.next
+    }
+
+    /// The persisted high-water mark. Values from [peek](Self::peek) up to this mark are handed out without a disk
+    /// write.
+    pub fn high_water_mark(&self) -> u64 {
+        self.high_water_mark
+    }
+
+    /// The path of the counter file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Return the next value.
+    ///
+    /// # Errors
+    /// Returns [CounterError::Exhausted] once `u64::MAX` is reached, or [CounterError::Io] if a new batch cannot be
+    /// persisted. A failed call hands out nothing.
+    pub fn next_value(&mut self) -> Result<u64, CounterError> {
+        self.reserve(1).map(|range| range.start)
+    }
+
+    /// Reserve `count` consecutive values and return them as a range. The values are persisted before they are
+    /// returned, so they are never handed out again.
+    ///
+    /// # Errors
+    /// Returns [CounterError::Exhausted] if fewer than `count` values are left, or [CounterError::Io] if the
+    /// reservation cannot be persisted. A failed call hands out nothing.
+    pub fn reserve(&mut self, count: u64) -> Result<Range<u64>, CounterError> {
+        let end = self.next.checked_add(count).context(ExhaustedSnafu)?;
+        if end > self.high_water_mark {
+            let mark = end.saturating_add(self.batch_size).saturating_sub(1).max(end);
+            self.persist(mark)?;
+        }
+        let start = self.next;
+        self.next = end;
+        Ok(start..end)
+    }
+
+    /// Atomically replace the counter file with a new high-water mark.
+    fn persist(&mut self, mark: u64) -> Result<(), CounterError> {
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+        {
+            let mut file = File::create(&tmp)?;
+            file.write_all(&encode(mark))?;
+            file.sync_all()?;
+        }
+        fs::rename(&tmp, &self.path)?;
+        // Make the rename itself durable. Directories cannot be opened for syncing on every platform.
+        #[cfg(unix)]
+        if let Some(dir) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            File::open(dir)?.sync_all()?;
+        }
+        self.high_water_mark = mark;
+        Ok(())
+    }
+}
+
+fn encode(mark: u64) -> [u8; FILE_LEN] {
+    let mut contents = [0u8; FILE_LEN];
+    let (value, checksum) = contents.split_at_mut(8);
+    value.copy_from_slice(&mark.to_le_bytes());
+    checksum.copy_from_slice(&crc32(value).to_le_bytes());
+    contents
+}
+
+fn decode(contents: &[u8]) -> Result<u64, CounterError> {
+    ensure!(contents.len() == FILE_LEN, CorruptSnafu);
+    let (value, checksum) = contents.split_at(8);
+    ensure!(crc32(value).to_le_bytes() == checksum, CorruptSnafu);
+    let value = value.try_into().map_err(|_| CounterError::Corrupt {})?;
+    Ok(u64::from_le_bytes(value))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(alloc::format!("tari_counter_{}_{}", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn resumes_after_restart() {
+        let path = temp_path("restart");
+        let mut counter = MonotonicCounter::open(&path, 10).unwrap();
+        assert_eq!(counter.next_value(), Ok(0));
+        assert_eq!(counter.next_value(), Ok(1));
+        assert_eq!(counter.high_water_mark(), 10);
+        assert_eq!(counter.reserve(5), Ok(2..7));
+        assert_eq!(counter.reserve(5), Ok(7..12));
+        assert_eq!(counter.high_water_mark(), 21);
+        drop(counter);
+
+        // Simulate a crash: the unused rest of the batch is skipped
+        let mut counter = MonotonicCounter::open(&path, 10).unwrap();
+        assert_eq!(counter.peek(), 21);
+        assert_eq!(counter.next_value(), Ok(21));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn unbatched() {
+        let path = temp_path("unbatched");
+        let mut counter = MonotonicCounter::open(&path, 0).unwrap();
+        assert_eq!(counter.next_value(), Ok(0));
+        assert_eq!(counter.high_water_mark(), 1);
+        assert_eq!(counter.reserve(0), Ok(1..1));
+        assert_eq!(MonotonicCounter::open(&path, 0).unwrap().peek(), 1);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn exhaustion_and_corruption() {
+        let path = temp_path("exhausted");
+        fs::write(&path, encode(u64::MAX - 1)).unwrap();
+        let mut counter = MonotonicCounter::open(&path, 100).unwrap();
+        assert_eq!(counter.next_value(), Ok(u64::MAX - 1));
+        assert_eq!(counter.reserve(2), Err(CounterError::Exhausted {}));
+        assert_eq!(counter.peek(), u64::MAX);
+
+        let mut damaged = encode(5);
+        damaged[0] ^= 1;
+        fs::write(&path, damaged).unwrap();
+        assert_eq!(MonotonicCounter::open(&path, 1).unwrap_err(), CounterError::Corrupt {});
+        fs::write(&path, [0u8; 3]).unwrap();
+        assert_eq!(MonotonicCounter::open(&path, 1).unwrap_err(), CounterError::Corrupt {});
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,328 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Constant-time operations for handling secret data.
+//!
+//! Every operation in the crate whose timing must not depend on secret values lives in this module, and the secret
+//! types ([SafeArray](crate::safe_array::SafeArray), [Hidden](crate::hidden::Hidden) and
+//! [SafePassword](crate::password::SafePassword)) use it for their comparisons. The building blocks come from the
+//! [subtle](https://docs.rs/subtle) crate, whose [Choice] type carries results without converting them to a `bool`
+//! until the caller decides to.
+//!
+//! # Leakage model
+//! Unless stated otherwise, the functions here may leak:
+//! - the lengths of their inputs and outputs, and
+//! - for the decoders, whether the whole input was valid, but not the position or value of an invalid character.
+//!
+//! They do not branch on, or index memory by, the contents of their inputs. This is best effort: like `subtle`, it
+//! relies on optimization barriers rather than guarantees from the compiler, so the generated code for a new target
+//! or compiler should be checked if it matters.
+
+use alloc::{string::String, vec::Vec};
+
+use snafu::prelude::*;
+pub use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+/// Errors from the constant-time decoders.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum DecodeError {
+    /// The input length is not valid for the encoding.
+    #[snafu(display("The input length is invalid for the encoding"))]
+    InvalidLength {},
+    /// The input contains a character that is not part of the encoding, or non-zero padding bits.
+    #[snafu(display("The input contains an invalid character"))]
+    InvalidCharacter {},
+}
+
+/// Compare two slices in constant time. Slices of different lengths are never equal.
+///
+/// Leaks: the lengths of the slices.
+pub fn eq<T: ConstantTimeEq>(a: &[T], b: &[T]) -> Choice {
+    a.ct_eq(b)
+}
+
+/// Return `a` if `choice` is false and `b` if it is true, in constant time.
+///
+/// Leaks: nothing.
+pub fn select<T: ConditionallySelectable>(a: &T, b: &T, choice: Choice) -> T {
+    T::conditional_select(a, b, choice)
+}
+
+/// Return a copy of `a` if `choice` is false and of `b` if it is true, in constant time, or `None` if the slices have
+/// different lengths.
+///
+/// Leaks: the lengths of the slices.
+pub fn select_bytes(a: &[u8], b: &[u8], choice: Choice) -> Option<Vec<u8>> {
+    if a.len() != b.len() {
+        return None;
+    }
+    Some(
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| u8::conditional_select(a, b, choice))
+            .collect(),
+    )
+}
+
+/// Return whether every byte of the slice is zero, in constant time. An empty slice is zero.
+///
+/// Leaks: the length of the slice.
+pub fn is_zero(bytes: &[u8]) -> Choice {
+    bytes.iter().fold(0u8, |acc, byte| acc | byte).ct_eq(&0)
+}
+
+/// Encode bytes as lowercase hex in constant time.
+///
+/// Leaks: the length of the input.
+pub fn hex_encode(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len().saturating_mul(2));
+    for byte in bytes {
+        hex.push(char::from(hex_digit(byte >> 4)));
+        hex.push(char::from(hex_digit(byte & 0x0f)));
+    }
+    hex
+}
+
+/// Decode a hex string into bytes in constant time. Both lowercase and uppercase digits are accepted, but unlike
+/// [from_hex](crate::hex::from_hex), surrounding whitespace and a `0x` prefix are not, as stripping them would
+/// branch on the input.
+///
+/// Leaks: the length of the input and whether it was valid.
+pub fn hex_decode(hex: &str) -> Result<Vec<u8>, DecodeError> {
+    let digits = hex.as_bytes();
+    ensure!(digits.len().is_multiple_of(2), InvalidLengthSnafu);
+    let mut valid = 0xffu8;
+    let mut bytes = Vec::with_capacity(digits.len() / 2);
+    for pair in digits.chunks_exact(2) {
+        let byte = pair.iter().fold(0u8, |byte, digit| {
+            let (value, digit_valid) = hex_digit_value(*digit);
+            valid &= digit_valid;
+            (byte << 4) | value
+        });
+        bytes.push(byte);
+    }
+    finish(bytes, valid)
+}
+
+/// Decode standard base64 (RFC 4648, with `+`, `/` and `=` padding) into bytes in constant time. The input must be
+/// padded to a multiple of four characters and the unused bits of the last character must be zero.
+///
+/// Leaks: the length of the input, the amount of padding (and so the length of the output), and whether it was valid.
+pub fn base64_decode(b64: &str) -> Result<Vec<u8>, DecodeError> {
+    let chars = b64.as_bytes();
+    ensure!(chars.len().is_multiple_of(4), InvalidLengthSnafu);
+    // The padding is at the end and only determines the output length, which is public
+    let padding = chars.iter().rev().take(2).take_while(|c| **c == b'=').count();
+    let mut valid = 0xffu8;
+    let mut bytes = Vec::with_capacity((chars.len() / 4).saturating_mul(3));
+    let mut quads = chars.chunks_exact(4).peekable();
+    while let Some(quad) = quads.next() {
+        let is_last = quads.peek().is_none();
+        let data_len = if is_last { 4usize.saturating_sub(padding) } else { 4 };
+        let mut group = 0u32;
+        for (i, c) in quad.iter().enumerate() {
+            let (value, char_valid) = if i < data_len { base64_value(*c) } else { (0, 0xff) };
+            valid &= char_valid;
+            group = (group << 6) | u32::from(value);
+        }
+        let [_, b0, b1, b2] = group.to_be_bytes();
+        match data_len {
+            4 => bytes.extend_from_slice(&[b0, b1, b2]),
+            3 => {
+                bytes.extend_from_slice(&[b0, b1]);
+                valid &= is_zero(&[b2]).unwrap_u8().wrapping_neg();
+            },
+            _ => {
+                bytes.push(b0);
+                valid &= is_zero(&[b1, b2]).unwrap_u8().wrapping_neg();
+            },
+        }
+    }
+    finish(bytes, valid)
+}
+
+/// Return the decoded bytes if every character was valid, clearing them otherwise.
+fn finish(mut bytes: Vec<u8>, valid: u8) -> Result<Vec<u8>, DecodeError> {
+    if bool::from(valid.ct_eq(&0xff)) {
+        Ok(bytes)
+    } else {
+        #[cfg(feature = "zeroize")]
+        bytes.zeroize();
+        #[cfg(not(feature = "zeroize"))]
+        bytes.iter_mut().for_each(|byte| *byte = 0);
+        InvalidCharacterSnafu.fail()
+    }
+}
+
+/// Return `0xff` if `lo <= c <= hi` and `0` otherwise, without branching on `c`.
+fn range_mask(c: u8, lo: u8, hi: u8) -> u8 {
+    let c = i16::from(c);
+    // Each difference is negative, so has its sign bit set, exactly when its bound is satisfied
+    let above_lo = i16::from(lo).wrapping_sub(1).wrapping_sub(c);
+    let below_hi = c.wrapping_sub(i16::from(hi)).wrapping_sub(1);
+    ((above_lo & below_hi) >> 8) as u8
+}
+
+/// Return the lowercase hex digit of a nibble, without branching on it.
+fn hex_digit(nibble: u8) -> u8 {
+    // `9 - nibble` is negative, so has its sign bit set, exactly when the nibble is a letter
+    let letter = ((9u8.wrapping_sub(nibble) as i8) >> 7) as u8;
+    nibble.wrapping_add(b'0').wrapping_add(letter & (b'a' - b'0' - 10))
+}
+
+/// Return the value of a hex digit and `0xff` if it is valid, or `0` and `0` if it is not.
+fn hex_digit_value(c: u8) -> (u8, u8) {
+    let digit = range_mask(c, b'0', b'9');
+    let lower = range_mask(c, b'a', b'f');
+    let upper = range_mask(c, b'A', b'F');
+    let value = (digit & c.wrapping_sub(b'0')) |
+        (lower & c.wrapping_sub(b'a').wrapping_add(10)) |
+        (upper & c.wrapping_sub(b'A').wrapping_add(10));
+    (value, digit | lower | upper)
+}
+
+/// Return the value of a base64 character and `0xff` if it is valid, or `0` and `0` if it is not.
+fn base64_value(c: u8) -> (u8, u8) {
+    let upper = range_mask(c, b'A', b'Z');
+    let lower = range_mask(c, b'a', b'z');
+    let digit = range_mask(c, b'0', b'9');
+    let plus = range_mask(c, b'+', b'+');
+    let slash = range_mask(c, b'/', b'/');
+    let value = (upper & c.wrapping_sub(b'A')) |
+        (lower & c.wrapping_sub(b'a').wrapping_add(26)) |
+        (digit & c.wrapping_sub(b'0').wrapping_add(52)) |
+        (plus & 62) |
+        (slash & 63);
+    (value, upper | lower | digit | plus | slash)
+}
+
+/// Black-box tests, which use only the public API and compare it against the variable-time implementations.
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+
+    use rand::{rngs::OsRng, Rng, RngCore};
+
+    use super::*;
+    use crate::hex::{from_hex, to_hex};
+
+    #[test]
+    fn equality() {
+        assert!(bool::from(eq::<u8>(&[], &[])));
+        assert!(bool::from(eq(&[1u8, 2, 3], &[1, 2, 3])));
+        assert!(!bool::from(eq(&[1u8, 2, 3], &[1, 2, 4])));
+        assert!(!bool::from(eq(&[1u8, 2, 3], &[1, 2])));
+        assert!(bool::from(eq(&[1u32, 2], &[1, 2])));
+        for _ in 0..100 {
+            let mut a = vec![0u8; OsRng.gen_range(1..64)];
+            OsRng.fill_bytes(&mut a);
+            let mut b = a.clone();
+            assert!(bool::from(eq(&a, &b)));
+            let i = OsRng.gen_range(0..b.len());
+            b[i] ^= 1 << OsRng.gen_range(0..8);
+            assert!(!bool::from(eq(&a, &b)));
+        }
+    }
+
+    #[test]
+    fn selection() {
+        assert_eq!(select(&1u64, &2, Choice::from(0)), 1);
+        assert_eq!(select(&1u64, &2, Choice::from(1)), 2);
+        assert_eq!(select_bytes(&[1, 2], &[3, 4], Choice::from(0)), Some(vec![1, 2]));
+        assert_eq!(select_bytes(&[1, 2], &[3, 4], Choice::from(1)), Some(vec![3, 4]));
+        assert_eq!(select_bytes(&[1, 2], &[3], Choice::from(1)), None);
+    }
+
+    #[test]
+    fn zero() {
+        assert!(bool::from(is_zero(&[])));
+        assert!(bool::from(is_zero(&[0; 100])));
+        for i in 0..100 {
+            let mut bytes = [0u8; 100];
+            bytes[i] = 0x80;
+            assert!(!bool::from(is_zero(&bytes)));
+        }
+    }
+
+    #[test]
+    fn hex_matches_reference() {
+        // Every possible pair of characters
+        for hi in 0..=u8::MAX {
+            for lo in 0..=u8::MAX {
+                let pair = [hi, lo];
+                if let Ok(s) = core::str::from_utf8(&pair) {
+                    let expected = from_hex(s).ok().filter(|_| s.trim() == s && !s.starts_with("0x"));
+                    assert_eq!(hex_decode(s).ok(), expected, "{:?}", s);
+                }
+            }
+        }
+        for _ in 0..100 {
+            let mut bytes = vec![0u8; OsRng.gen_range(0..100)];
+            OsRng.fill_bytes(&mut bytes);
+            assert_eq!(hex_decode(&to_hex(&bytes)).unwrap(), bytes);
+            assert_eq!(hex_decode(&to_hex(&bytes).to_uppercase()).unwrap(), bytes);
+        }
+        for byte in 0..=u8::MAX {
+            assert_eq!(hex_encode(&[byte]), to_hex(&[byte]));
+        }
+        assert_eq!(hex_encode(&[]), "");
+        assert_eq!(hex_decode("abc"), Err(DecodeError::InvalidLength {}));
+        assert_eq!(hex_decode("0x00"), Err(DecodeError::InvalidCharacter {}));
+        assert_eq!(hex_decode(" 00 "), Err(DecodeError::InvalidCharacter {}));
+    }
+
+    #[test]
+    fn base64_matches_reference() {
+        use crate::base64::{from_base64, to_base64, Base64Variant};
+
+        for len in 0..100 {
+            let mut bytes = vec![0u8; len];
+            OsRng.fill_bytes(&mut bytes);
+            let encoded = to_base64(&bytes, Base64Variant::Standard);
+            assert_eq!(base64_decode(&encoded).unwrap(), bytes);
+
+            // Corrupt one character
+            let mut corrupted = encoded.into_bytes();
+            if !corrupted.is_empty() {
+                let i = OsRng.gen_range(0..corrupted.len());
+                corrupted[i] = OsRng.gen();
+                if let Ok(s) = alloc::string::String::from_utf8(corrupted) {
+                    assert_eq!(
+                        base64_decode(&s).ok(),
+                        from_base64(&s, Base64Variant::Standard).ok(),
+                        "{:?}",
+                        s
+                    );
+                }
+            }
+        }
+        assert_eq!(base64_decode("aGk="), Ok(b"hi".to_vec()));
+        assert_eq!(base64_decode("aGl="), Err(DecodeError::InvalidCharacter {}));
+        assert_eq!(base64_decode("aA=="), Ok(b"h".to_vec()));
+        assert_eq!(base64_decode("aB=="), Err(DecodeError::InvalidCharacter {}));
+        assert_eq!(base64_decode("a==="), Err(DecodeError::InvalidCharacter {}));
+        assert_eq!(base64_decode("aGk"), Err(DecodeError::InvalidLength {}));
+        assert_eq!(base64_decode("aG=k"), Err(DecodeError::InvalidCharacter {}));
+    }
+}
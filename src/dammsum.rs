@@ -0,0 +1,414 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Damm-algorithm checksums (DammSum) over strings of symbols from an arbitrary alphabet.
+//!
+//! The Damm algorithm appends a single check symbol that detects every single-symbol error and every transposition of
+//! adjacent symbols, which are the most common mistakes when people copy addresses by hand. It is built on a totally
+//! anti-symmetric quasigroup of the same order as the alphabet. This module supports alphabets of:
+//! - any odd size, using `x * y = 2x + y (mod N)`,
+//! - a power of two up to 256, using `x * y = αx + y` in the field of that size,
+//! - size 10, using the table published by Damm.
+//!
+//! Other sizes are rejected with [DammSumError::UnsupportedAlphabetSize].
+//!
+//! ```edition2018
+//! # use tari_utilities::dammsum::DammSum;
+//! let decimal = DammSum::new(['0', '1', '2', '3', '4', '5', '6', '7', '8', '9']).unwrap();
+//! assert_eq!(decimal.encode("572").unwrap(), "5724");
+//! assert!(decimal.verify("5724").is_ok());
+//! assert!(decimal.verify("7524").is_err());
+//! ```
+
+use alloc::string::String;
+use core::convert::TryFrom;
+
+use snafu::prelude::*;
+
+use crate::checksum::Checksum;
+
+/// The largest supported alphabet, as symbols are represented by their `u8` index in the alphabet.
+pub const MAX_ALPHABET_SIZE: usize = 256;
+
+/// The reduction polynomial of GF(256), used for byte digits.
+const BYTE_POLYNOMIAL: u16 = 0b1_0001_1011;
+
+/// The weakly totally anti-symmetric quasigroup of order 10 from Damm's thesis.
+const DECIMAL_TABLE: [[u8; 10]; 10] = [
+    [0, 3, 1, 7, 5, 9, 8, 6, 4, 2],
+    [7, 0, 9, 2, 1, 5, 4, 8, 6, 3],
+    [4, 2, 0, 6, 8, 7, 1, 3, 5, 9],
+    [1, 7, 5, 0, 9, 8, 3, 4, 2, 6],
+    [6, 1, 2, 3, 0, 4, 5, 9, 7, 8],
+    [3, 6, 7, 4, 2, 0, 9, 5, 8, 1],
+    [5, 8, 6, 9, 7, 2, 0, 1, 3, 4],
+    [8, 9, 4, 5, 3, 6, 2, 0, 1, 7],
+    [9, 4, 3, 8, 6, 1, 7, 2, 0, 5],
+    [2, 5, 8, 1, 4, 3, 6, 7, 9, 0],
+];
+
+/// Errors for [DammSum].
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum DammSumError {
+    /// No quasigroup is available for the alphabet size.
+    #[snafu(display("Alphabets of {size} symbols are not supported"))]
+    UnsupportedAlphabetSize {
+        /// The size of the alphabet
+        size: usize,
+    },
+    /// The alphabet contains the same symbol more than once.
+    #[snafu(display("The alphabet contains `{symbol}' more than once"))]
+    DuplicateSymbol {
+        /// The repeated symbol
+        symbol: char,
+    },
+    /// The input contains a symbol that is not in the alphabet.
+    #[snafu(display("`{symbol}' is not in the alphabet"))]
+    InvalidSymbol {
+        /// The unknown symbol
+        symbol: char,
+    },
+    /// The input contains a digit that is not less than the alphabet size.
+    #[snafu(display("{digit} is not a valid digit for the alphabet"))]
+    InvalidDigit {
+        /// The invalid digit
+        digit: u8,
+    },
+    /// There is no check symbol to verify.
+    #[snafu(display("The input is empty"))]
+    MissingChecksum {},
+    /// The check symbol does not match.
+    #[snafu(display("The checksum is invalid"))]
+    InvalidChecksum {},
+}
+
+/// The quasigroup operation used for an alphabet size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    /// `2x + y (mod N)` for odd `N`.
+    Odd,
+    /// `αx + y` in GF(N) for `N` a power of two, with the given reduction polynomial.
+    Binary { polynomial: u16 },
+    /// Damm's table for `N = 10`.
+    Decimal,
+}
+
+impl Operation {
+    fn for_size(size: usize) -> Option<Self> {
+        // Irreducible polynomials for each field size
+        let polynomial = match size {
+            4 => Some(0b111),
+            8 => Some(0b1011),
+            16 => Some(0b1_0011),
+            32 => Some(0b10_0101),
+            64 => Some(0b100_0011),
+            128 => Some(0b1000_0011),
+            256 => Some(BYTE_POLYNOMIAL),
+            _ => None,
+        };
+        match (size, polynomial) {
+            (_, Some(polynomial)) => Some(Operation::Binary { polynomial }),
+            (10, None) => Some(Operation::Decimal),
+            (3..=MAX_ALPHABET_SIZE, None) if size % 2 == 1 => Some(Operation::Odd),
+            _ => None,
+        }
+    }
+}
+
+/// A Damm checksum over an alphabet of `N` symbols.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DammSum<const N: usize> {
+    alphabet: [char; N],
+    operation: Operation,
+}
+
+impl<const N: usize> DammSum<N> {
+    /// Create a checksum for the alphabet. The position of each symbol in the alphabet is its digit value.
+    ///
+    /// # Errors
+    /// Returns an error if the alphabet size is not supported or a symbol appears more than once.
+    pub fn new(alphabet: [char; N]) -> Result<Self, DammSumError> {
+        let operation = Operation::for_size(N).context(UnsupportedAlphabetSizeSnafu { size: N })?;
+        for (i, symbol) in alphabet.iter().enumerate() {
+            if alphabet.iter().skip(i.saturating_add(1)).any(|other| other == symbol) {
+                return DuplicateSymbolSnafu { symbol: *symbol }.fail();
+            }
+        }
+        Ok(Self { alphabet, operation })
+    }
+
+    /// Return the check digit for the digits, which must each be less than `N`.
+    pub fn checksum_digits(&self, digits: &[u8]) -> Result<u8, DammSumError> {
+        let interim = digits
+            .iter()
+            .try_fold(0, |interim, digit| self.apply(interim, *digit))?;
+        Ok(self.check_digit(interim))
+    }
+
+    /// Verify that the last digit is the check digit of the preceding digits.
+    pub fn verify_digits(&self, digits: &[u8]) -> Result<(), DammSumError> {
+        ensure!(!digits.is_empty(), MissingChecksumSnafu);
+        let interim = digits
+            .iter()
+            .try_fold(0, |interim, digit| self.apply(interim, *digit))?;
+        ensure!(interim == 0, InvalidChecksumSnafu);
+        Ok(())
+    }
+
+    /// Return the string with its check symbol appended.
+    pub fn encode(&self, s: &str) -> Result<String, DammSumError> {
+        let interim = s
+            .chars()
+            .try_fold(0, |interim, symbol| self.apply(interim, self.digit(symbol)?))?;
+        let mut encoded = String::with_capacity(s.len().saturating_add(4));
+        encoded.push_str(s);
+        encoded.extend(self.symbol(self.check_digit(interim)));
+        Ok(encoded)
+    }
+
+    /// Verify that the last symbol of the string is the check symbol of the preceding symbols, returning the string
+    /// without it.
+    pub fn verify<'a>(&self, s: &'a str) -> Result<&'a str, DammSumError> {
+        let (last, _) = s.char_indices().last().context(MissingChecksumSnafu)?;
+        let interim = s
+            .chars()
+            .try_fold(0, |interim, symbol| self.apply(interim, self.digit(symbol)?))?;
+        ensure!(interim == 0, InvalidChecksumSnafu);
+        Ok(s.get(..last).unwrap_or_default())
+    }
+
+    fn digit(&self, symbol: char) -> Result<u8, DammSumError> {
+        self.alphabet
+            .iter()
+            .position(|c| *c == symbol)
+            .and_then(|i| u8::try_from(i).ok())
+            .context(InvalidSymbolSnafu { symbol })
+    }
+
+    fn symbol(&self, digit: u8) -> Option<char> {
+        self.alphabet.get(usize::from(digit)).copied()
+    }
+
+    /// Combine the interim value with the next digit.
+    fn apply(&self, interim: u8, digit: u8) -> Result<u8, DammSumError> {
+        ensure!(usize::from(digit) < N, InvalidDigitSnafu { digit });
+        Ok(self.operate(interim, digit))
+    }
+
+    fn operate(&self, x: u8, y: u8) -> u8 {
+        match self.operation {
+            Operation::Odd => {
+                let sum = u16::from(x).wrapping_mul(2).wrapping_add(u16::from(y));
+                // `N` is odd, so it is non-zero and the result is less than 256
+                sum.checked_rem(N as u16).unwrap_or_default() as u8
+            },
+            Operation::Binary { polynomial } => {
+                let shifted = u16::from(x) << 1;
+                let product = if shifted & (N as u16) == 0 {
+                    shifted
+                } else {
+                    shifted ^ polynomial
+                };
+                (product as u8) ^ y
+            },
+            Operation::Decimal => DECIMAL_TABLE
+                .get(usize::from(x))
+                .and_then(|row| row.get(usize::from(y)))
+                .copied()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Return the digit that brings the interim value to zero.
+    fn check_digit(&self, interim: u8) -> u8 {
+        (0..=u8::MAX)
+            .take(N)
+            .find(|digit| self.operate(interim, *digit) == 0)
+            .unwrap_or_default()
+    }
+}
+
+/// The [DammSum] over raw bytes, where each byte is a digit and the check digit is a single byte.
+const BYTES: DammSum<MAX_ALPHABET_SIZE> = DammSum {
+    alphabet: byte_alphabet(),
+    operation: Operation::Binary {
+        polynomial: BYTE_POLYNOMIAL,
+    },
+};
+
+/// The symbols for byte digits are the characters with the same code point, although they are never rendered.
+// Only evaluated at compile time, where an out of range index would fail the build
+#[allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+const fn byte_alphabet() -> [char; MAX_ALPHABET_SIZE] {
+    let mut alphabet = ['\0'; MAX_ALPHABET_SIZE];
+    let mut i = 0;
+    while i < MAX_ALPHABET_SIZE {
+        alphabet[i] = i as u8 as char;
+        i += 1;
+    }
+    alphabet
+}
+
+/// A DammSum over bytes as a streaming [Checksum], producing a single check byte.
+///
+/// Every byte value is a digit, so any data can be checked. This is the default checksum of
+/// [Checksummed](crate::checksum::Checksummed).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ByteDammSum {
+    interim: u8,
+}
+
+impl Checksum for ByteDammSum {
+    type Output = u8;
+
+    fn update(&mut self, data: &[u8]) {
+        self.interim = data
+            .iter()
+            .fold(self.interim, |interim, byte| BYTES.operate(interim, *byte));
+    }
+
+    fn finalize(self) -> u8 {
+        BYTES.check_digit(self.interim)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{format, vec::Vec};
+    use core::convert::TryInto;
+
+    use super::*;
+
+    fn alphabet<const N: usize>(symbols: &str) -> [char; N] {
+        let symbols = symbols.chars().collect::<Vec<_>>();
+        symbols.try_into().unwrap()
+    }
+
+    /// Check every single-digit error and adjacent transposition of a set of inputs.
+    fn check_detection<const N: usize>(damm: &DammSum<N>) {
+        let n = N as u8;
+        for len in 1..4 {
+            for seed in 0..50u32 {
+                let mut digits = (0..len)
+                    .map(|i| ((seed.wrapping_mul(2_654_435_761) >> (i * 8)) % N as u32) as u8)
+                    .collect::<Vec<_>>();
+                digits.push(damm.checksum_digits(&digits).unwrap());
+                assert!(damm.verify_digits(&digits).is_ok());
+                for i in 0..digits.len() {
+                    for d in 0..n {
+                        if d != digits[i] {
+                            let mut error = digits.clone();
+                            error[i] = d;
+                            assert!(damm.verify_digits(&error).is_err());
+                        }
+                    }
+                    if i + 1 < digits.len() && digits[i] != digits[i + 1] {
+                        let mut swapped = digits.clone();
+                        swapped.swap(i, i + 1);
+                        assert!(damm.verify_digits(&swapped).is_err());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn decimal() {
+        let damm = DammSum::new(alphabet::<10>("0123456789")).unwrap();
+        assert_eq!(damm.checksum_digits(&[5, 7, 2]), Ok(4));
+        assert_eq!(damm.encode("572").unwrap(), "5724");
+        assert_eq!(damm.verify("5724"), Ok("572"));
+        assert_eq!(damm.verify("5274"), Err(DammSumError::InvalidChecksum {}));
+        check_detection(&damm);
+    }
+
+    #[test]
+    fn supported_sizes() {
+        check_detection(&DammSum::new(alphabet::<3>("abc")).unwrap());
+        check_detection(&DammSum::new(alphabet::<4>("ACGT")).unwrap());
+        check_detection(&DammSum::new(alphabet::<16>("0123456789abcdef")).unwrap());
+        check_detection(&DammSum::new(alphabet::<32>("abcdefghijklmnopqrstuvwxyz234567")).unwrap());
+        check_detection(&DammSum::new(alphabet::<35>("123456789abcdefghijklmnopqrstuvwxyz")).unwrap());
+        for n in [9u32, 64, 128, 255, 256] {
+            let symbols = (0..n).map(|i| char::from_u32(0x100 + i).unwrap()).collect::<String>();
+            match n {
+                9 => check_detection(&DammSum::new(alphabet::<9>(&symbols)).unwrap()),
+                64 => check_detection(&DammSum::new(alphabet::<64>(&symbols)).unwrap()),
+                128 => check_detection(&DammSum::new(alphabet::<128>(&symbols)).unwrap()),
+                255 => check_detection(&DammSum::new(alphabet::<255>(&symbols)).unwrap()),
+                _ => check_detection(&DammSum::new(alphabet::<256>(&symbols)).unwrap()),
+            }
+        }
+    }
+
+    #[test]
+    fn unsupported_alphabets() {
+        assert_eq!(
+            DammSum::new(['a', 'b']),
+            Err(DammSumError::UnsupportedAlphabetSize { size: 2 })
+        );
+        assert_eq!(
+            DammSum::new(alphabet::<6>("abcdef")),
+            Err(DammSumError::UnsupportedAlphabetSize { size: 6 })
+        );
+        assert_eq!(
+            DammSum::new(alphabet::<58>(&format!("{:58}", ""))),
+            Err(DammSumError::UnsupportedAlphabetSize { size: 58 })
+        );
+        assert_eq!(
+            DammSum::new(['a', 'b', 'a']),
+            Err(DammSumError::DuplicateSymbol { symbol: 'a' })
+        );
+    }
+
+    #[test]
+    fn invalid_input() {
+        let damm = DammSum::new(alphabet::<16>("0123456789abcdef")).unwrap();
+        assert_eq!(damm.encode("12x"), Err(DammSumError::InvalidSymbol { symbol: 'x' }));
+        assert_eq!(damm.verify(""), Err(DammSumError::MissingChecksum {}));
+        assert_eq!(damm.verify_digits(&[]), Err(DammSumError::MissingChecksum {}));
+        assert_eq!(
+            damm.checksum_digits(&[16]),
+            Err(DammSumError::InvalidDigit { digit: 16 })
+        );
+        let encoded = damm.encode("deadbeef").unwrap();
+        assert_eq!(damm.verify(&encoded), Ok("deadbeef"));
+        assert_eq!(damm.encode("").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn byte_checksum() {
+        let data = [0xde, 0xad, 0xbe, 0xef];
+        let check = ByteDammSum::checksum(&data);
+        assert_eq!(BYTES.checksum_digits(&data), Ok(check));
+
+        let mut streaming = ByteDammSum::default();
+        streaming.update(&data[..1]);
+        streaming.update(&data[1..]);
+        assert_eq!(streaming.finalize(), check);
+
+        let mut digits = data.to_vec();
+        digits.push(check);
+        assert!(BYTES.verify_digits(&digits).is_ok());
+        check_detection(&BYTES);
+    }
+}
@@ -20,13 +20,29 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-//! A trait that handles [base58](https://crates.io/crates/base58-monero) encoding and decoding.
+//! A trait that handles [base58](https://crates.io/crates/base58-monero) encoding and decoding, and a matching trait
+//! for base64.
+//!
+//! [EncodedByteArray] combines these with hex, and can parse input in whichever of them it was given:
+//!
+//! ```edition2018
+//! # use tari_utilities::encoding::EncodedByteArray;
+//! let from_hex = <[u8; 4]>::parse_any("0002fa27").unwrap();
+//! let from_base58 = <[u8; 4]>::parse_any("111zzz").unwrap();
+//! assert_eq!(from_hex, from_base58);
+//! ```
 
 use alloc::string::{String, ToString};
+use core::fmt;
 
 use snafu::prelude::*;
 
-use crate::ByteArray;
+use crate::{
+    base32::{Base32, Base32Variant},
+    hex::Hex,
+    literal::{BASE58_ALPHABET, BASE58_BLOCK_SIZE, BASE58_ENCODED_BLOCK_SIZES},
+    ByteArray,
+};
 
 /// Trait for encoding/decoding to base58.
 pub trait Base58 {
@@ -55,11 +71,190 @@ impl<T: ByteArray> Base58 for T {
         Self::from_canonical_bytes(&bytes).map_err(|e| Base58Error::ByteArrayError { reason: e.to_string() })
     }
 
+    // Encoding arbitrary bytes to base58 cannot fail
+    #[allow(clippy::expect_used)]
     fn to_base58(&self) -> String {
         base58_monero::encode(self.as_bytes()).expect("base58_monero::encode is infallible")
     }
 }
 
+/// Trait for encoding/decoding to base64.
+pub trait Base64 {
+    /// Convert from a standard, padded base64 string.
+    fn from_base64(b64: &str) -> Result<Self, Base64Error>
+    where Self: Sized {
+        Self::from_base64_variant(b64, Base64Variant::Standard)
+    }
+
+    /// Convert from a base64 string in the given variant.
+    fn from_base64_variant(b64: &str, variant: Base64Variant) -> Result<Self, Base64Error>
+    where Self: Sized;
+
+    /// Convert to a standard, padded base64 string.
+    fn to_base64(&self) -> String {
+        self.to_base64_variant(Base64Variant::Standard)
+    }
+
+    /// Convert to a base64 string in the given variant.
+    fn to_base64_variant(&self, variant: Base64Variant) -> String;
+}
+
+pub use crate::base64::Base64Variant;
+
+/// Errors for trait Base64.
+#[derive(Debug, Snafu)]
+#[snafu(context(suffix(Base64Snafu)))]
+#[allow(missing_docs)]
+pub enum Base64Error {
+    #[snafu(display("Byte array error: `{reason}'"))]
+    ByteArrayError { reason: String },
+    #[snafu(display("Decode error: `{reason}'"))]
+    DecodeError { reason: String },
+}
+
+impl<T: ByteArray> Base64 for T {
+    fn from_base64_variant(b64: &str, variant: Base64Variant) -> Result<Self, Base64Error> {
+        let bytes =
+            crate::base64::from_base64(b64, variant).map_err(|e| Base64Error::DecodeError { reason: e.to_string() })?;
+        Self::from_canonical_bytes(&bytes).map_err(|e| Base64Error::ByteArrayError { reason: e.to_string() })
+    }
+
+    fn to_base64_variant(&self, variant: Base64Variant) -> String {
+        crate::base64::to_base64(self.as_bytes(), variant)
+    }
+}
+
+/// A text encoding of bytes, for [EncodedByteArray].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Lowercase hex.
+    Hex,
+    /// Monero base58.
+    Base58,
+    /// Base32 in the given variant.
+    Base32(Base32Variant),
+    /// Base64 in the given variant.
+    Base64(Base64Variant),
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Encoding::Hex => f.write_str("hex"),
+            Encoding::Base58 => f.write_str("base58"),
+            Encoding::Base32(_) => f.write_str("base32"),
+            Encoding::Base64(_) => f.write_str("base64"),
+        }
+    }
+}
+
+/// Errors for trait EncodedByteArray.
+#[derive(Debug, Snafu)]
+pub enum EncodingError {
+    /// The input is not valid in the given encoding, or does not decode to a valid value.
+    #[snafu(display("Invalid {encoding}: `{reason}'"))]
+    InvalidEncoding {
+        /// The encoding of the input
+        encoding: Encoding,
+        /// The reason for the error
+        reason: String,
+    },
+    /// The input does not decode to a valid value in any supported encoding.
+    #[snafu(display("The input is not valid hex, base58 or base64 for the type"))]
+    UnrecognizedEncoding {},
+}
+
+/// The encodings tried by [EncodedByteArray::parse_any], in order.
+const PARSE_ORDER: &[Encoding] = &[
+    Encoding::Hex,
+    Encoding::Base58,
+    Encoding::Base64(Base64Variant::Standard),
+    Encoding::Base64(Base64Variant::UrlSafe),
+];
+
+/// Hex, base58, base32 and base64 conversions through a single trait, so that tools can accept input in any of them.
+pub trait EncodedByteArray: Sized {
+    /// Convert to a string in the given encoding.
+    fn encode(&self, encoding: Encoding) -> String;
+
+    /// Convert from a string in the given encoding.
+    fn decode(s: &str, encoding: Encoding) -> Result<Self, EncodingError>;
+
+    /// Convert from a string in any supported encoding, detecting which one it is. Base32 is not detected, as most
+    /// base32 strings are also valid base58.
+    ///
+    /// The encodings are tried in the order hex, base58, standard base64 and URL-safe base64, and the first that
+    /// decodes to a valid value is used. Some strings are valid in several encodings, for example `111abc` is both
+    /// hex and base58, so this is most reliable for fixed-size types, where the length rules out the wrong readings.
+    fn parse_any(s: &str) -> Result<Self, EncodingError> {
+        PARSE_ORDER
+            .iter()
+            .find_map(|encoding| Self::decode(s, *encoding).ok())
+            .context(UnrecognizedEncodingSnafu)
+    }
+}
+
+impl<T: ByteArray> EncodedByteArray for T {
+    fn encode(&self, encoding: Encoding) -> String {
+        match encoding {
+            Encoding::Hex => self.to_hex(),
+            Encoding::Base58 => self.to_base58(),
+            Encoding::Base32(variant) => self.to_base32_variant(variant),
+            Encoding::Base64(variant) => self.to_base64_variant(variant),
+        }
+    }
+
+    fn decode(s: &str, encoding: Encoding) -> Result<Self, EncodingError> {
+        let result = match encoding {
+            Encoding::Hex => Self::from_hex(s).map_err(|e| e.to_string()),
+            Encoding::Base58 => Self::from_base58(s).map_err(|e| e.to_string()),
+            Encoding::Base32(variant) => Self::from_base32_variant(s, variant).map_err(|e| e.to_string()),
+            Encoding::Base64(variant) => Self::from_base64_variant(s, variant).map_err(|e| e.to_string()),
+        };
+        result.map_err(|reason| EncodingError::InvalidEncoding { encoding, reason })
+    }
+}
+
+/// Write the bytes as base58 to a formatter or any other [fmt::Write], without allocating. The output is identical to
+/// [Base58::to_base58].
+pub fn fmt_base58<W: fmt::Write + ?Sized>(w: &mut W, bytes: &[u8]) -> fmt::Result {
+    // Monero base58 encodes each 8-byte block separately as a big-endian integer
+    for block in bytes.chunks(BASE58_BLOCK_SIZE) {
+        let size = BASE58_ENCODED_BLOCK_SIZES.get(block.len()).copied().unwrap_or_default();
+        let mut value = block.iter().fold(0u64, |value, byte| (value << 8) | u64::from(*byte));
+        let mut buffer = [BASE58_ALPHABET[0]; 11];
+        for c in buffer.iter_mut().take(size).rev() {
+            *c = BASE58_ALPHABET.get((value % 58) as usize).copied().unwrap_or_default();
+            value /= 58;
+        }
+        let encoded = buffer.get(..size).unwrap_or_default();
+        w.write_str(core::str::from_utf8(encoded).map_err(|_| fmt::Error)?)?;
+    }
+    Ok(())
+}
+
+/// Displays bytes as base58 without allocating, for use in log statements and error messages.
+///
+/// ```edition2018
+/// # use tari_utilities::encoding::Base58Display;
+/// let id = [0, 2, 250, 39];
+/// assert_eq!(format!("Peer {}", Base58Display(&id)), "Peer 111zzz");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Base58Display<'a>(pub &'a [u8]);
+
+impl fmt::Display for Base58Display<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_base58(f, self.0)
+    }
+}
+
+impl fmt::Debug for Base58Display<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_base58(f, self.0)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use alloc::vec::Vec;
@@ -82,10 +277,131 @@ mod test {
         assert_eq!(vec![0, 2, 250, 39].to_base58(), "111zzz");
     }
 
+    #[test]
+    fn arbitrary_input_does_not_panic() {
+        use rand::Rng;
+
+        let alphabet = ['1', 'z', 'Q', 'O', '0', ' ', '🖖', '\0'];
+        for _ in 0..10_000 {
+            let len = OsRng.gen_range(0..20);
+            let s = (0..len)
+                .map(|_| alphabet[OsRng.gen_range(0..alphabet.len())])
+                .collect::<String>();
+            let _result = Vec::from_base58(&s);
+            let _result = <[u8; 4]>::from_base58(&s);
+        }
+    }
+
+    #[test]
+    fn display_matches_encoding() {
+        for len in 0..100 {
+            let mut bytes = vec![0; len];
+            OsRng.fill_bytes(&mut bytes);
+            assert_eq!(Base58Display(&bytes).to_string(), bytes.to_base58());
+            assert_eq!(format!("{:?}", Base58Display(&bytes)), bytes.to_base58());
+        }
+        assert_eq!(Base58Display(&[255; 8]).to_string(), "jpXCZedGfVQ");
+    }
+
+    #[test]
+    fn base64() {
+        let bytes = [0xfb, 0xff, 0x01];
+        assert_eq!(bytes.to_base64(), "+/8B");
+        assert_eq!(vec![0xfbu8, 0xff].to_base64(), "+/8=");
+        assert_eq!(
+            vec![0xfbu8, 0xff].to_base64_variant(Base64Variant::StandardNoPad),
+            "+/8"
+        );
+        assert_eq!(vec![0xfbu8, 0xff].to_base64_variant(Base64Variant::UrlSafe), "-_8=");
+        assert_eq!(vec![0xfbu8, 0xff].to_base64_variant(Base64Variant::UrlSafeNoPad), "-_8");
+        assert_eq!(<[u8; 3]>::from_base64("+/8B").unwrap(), bytes);
+        assert_eq!(
+            Vec::from_base64_variant("-_8", Base64Variant::UrlSafeNoPad).unwrap(),
+            vec![0xfb, 0xff]
+        );
+        assert!(matches!(
+            Vec::from_base64_variant("-_8=", Base64Variant::Standard),
+            Err(Base64Error::DecodeError { .. })
+        ));
+        assert!(matches!(
+            <[u8; 4]>::from_base64("+/8B"),
+            Err(Base64Error::ByteArrayError { .. })
+        ));
+        for len in 0..20 {
+            let mut bytes = vec![0; len];
+            OsRng.fill_bytes(&mut bytes);
+            for variant in [
+                Base64Variant::Standard,
+                Base64Variant::StandardNoPad,
+                Base64Variant::UrlSafe,
+                Base64Variant::UrlSafeNoPad,
+            ] {
+                assert_eq!(
+                    Vec::from_base64_variant(&bytes.to_base64_variant(variant), variant).unwrap(),
+                    bytes
+                );
+            }
+        }
+    }
+
     #[test]
     fn inverse_operations() {
         let mut bytes = vec![0; 10];
         OsRng.fill_bytes(&mut bytes);
         assert_eq!(Vec::from_base58(&bytes.to_base58()).unwrap(), bytes);
     }
+
+    #[test]
+    fn encoded_byte_array() {
+        let bytes = [0u8, 2, 250, 39];
+        assert_eq!(bytes.encode(Encoding::Hex), "0002fa27");
+        assert_eq!(bytes.encode(Encoding::Base58), "111zzz");
+        assert_eq!(<[u8; 4]>::decode("0002fa27", Encoding::Hex).unwrap(), bytes);
+        assert_eq!(<[u8; 4]>::parse_any("0002fa27").unwrap(), bytes);
+        assert_eq!(<[u8; 4]>::parse_any("0x0002FA27").unwrap(), bytes);
+        assert_eq!(<[u8; 4]>::parse_any("111zzz").unwrap(), bytes);
+
+        // Valid hex, but of the wrong length, so it is read as base58
+        let ambiguous = <[u8; 4]>::parse_any("111abc").unwrap();
+        assert_eq!(ambiguous, <[u8; 4]>::from_base58("111abc").unwrap());
+
+        assert!(matches!(
+            <[u8; 4]>::decode("111zzz", Encoding::Hex),
+            Err(EncodingError::InvalidEncoding {
+                encoding: Encoding::Hex,
+                ..
+            })
+        ));
+        assert!(matches!(
+            <[u8; 4]>::parse_any("not valid!"),
+            Err(EncodingError::UnrecognizedEncoding {})
+        ));
+        assert_eq!(Encoding::Base58.to_string(), "base58");
+
+        let base32 = Encoding::Base32(Base32Variant::StandardNoPad);
+        assert_eq!(bytes.encode(base32), "AABPUJY");
+        assert_eq!(<[u8; 4]>::decode("AABPUJY", base32).unwrap(), bytes);
+    }
+
+    #[test]
+    fn encoding_variants() {
+        // Every variant is present whatever features are enabled, so this match is exhaustive in every build
+        let name = |encoding: Encoding| match encoding {
+            Encoding::Hex | Encoding::Base58 | Encoding::Base32(_) | Encoding::Base64(_) => encoding.to_string(),
+        };
+        assert_eq!(name(Encoding::Hex), "hex");
+        assert_eq!(name(Encoding::Base32(Base32Variant::Standard)), "base32");
+        assert_eq!(name(Encoding::Base64(Base64Variant::UrlSafeNoPad)), "base64");
+    }
+
+    #[test]
+    fn encoded_byte_array_base64() {
+        let bytes = [0xfbu8, 0xff, 0x00, 0x01, 0x02];
+        let standard = bytes.encode(Encoding::Base64(Base64Variant::Standard));
+        let url_safe = bytes.encode(Encoding::Base64(Base64Variant::UrlSafe));
+        assert_eq!(standard, "+/8AAQI=");
+        assert_eq!(url_safe, "-_8AAQI=");
+        assert_eq!(<[u8; 5]>::parse_any(&standard).unwrap(), bytes);
+        assert_eq!(<[u8; 5]>::parse_any(&url_safe).unwrap(), bytes);
+    }
 }
@@ -0,0 +1,288 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Explicit little- and big-endian encoding of `u128` and 256-bit values.
+//!
+//! [U256] is a 256-bit unsigned value stored as 32 big-endian bytes, so that it orders numerically. It is a container
+//! for amounts and targets that do not fit in a `u64`, not an arithmetic type. Decoding from a slice requires the
+//! exact length, and the [serde adapters](crate::serde::endian) only accept canonical text forms.
+
+use core::{
+    convert::{TryFrom, TryInto},
+    fmt,
+};
+
+use snafu::prelude::*;
+
+use crate::{
+    convert::reverse_bytes,
+    hex::{fmt_hex, hex_nibble},
+};
+
+/// Errors for decoding wide integers.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum EndianError {
+    /// The input is not the size of the value.
+    #[snafu(display("Expected {expected} bytes but got {actual}"))]
+    InvalidLength {
+        /// The size of the value in bytes
+        expected: usize,
+        /// The length of the input
+        actual: usize,
+    },
+    /// The value does not fit in the target type.
+    #[snafu(display("The value does not fit in the target type"))]
+    Overflow {},
+    /// The text is not the canonical form of a value.
+    #[snafu(display("The value is not in canonical form"))]
+    NonCanonical {},
+}
+
+fn to_array<const N: usize>(bytes: &[u8]) -> Result<[u8; N], EndianError> {
+    bytes.try_into().map_err(|_| EndianError::InvalidLength {
+        expected: N,
+        actual: bytes.len(),
+    })
+}
+
+/// Decode a `u128` from exactly 16 little-endian bytes.
+///
+/// # Errors
+/// Returns [EndianError::InvalidLength] if the slice is not 16 bytes long.
+pub fn u128_from_le_slice(bytes: &[u8]) -> Result<u128, EndianError> {
+    to_array(bytes).map(u128::from_le_bytes)
+}
+
+/// Decode a `u128` from exactly 16 big-endian bytes.
+///
+/// # Errors
+/// Returns [EndianError::InvalidLength] if the slice is not 16 bytes long.
+pub fn u128_from_be_slice(bytes: &[u8]) -> Result<u128, EndianError> {
+    to_array(bytes).map(u128::from_be_bytes)
+}
+
+/// Parse a `u128` from its canonical decimal form: ASCII digits only, with no sign, whitespace or leading zeros.
+///
+/// # Errors
+/// Returns [EndianError::NonCanonical] if the string is not in canonical form and [EndianError::Overflow] if the
+/// value does not fit in a `u128`.
+pub fn u128_from_canonical_str(s: &str) -> Result<u128, EndianError> {
+    let canonical = !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) && (s == "0" || !s.starts_with('0'));
+    ensure!(canonical, NonCanonicalSnafu);
+    s.parse().map_err(|_| EndianError::Overflow {})
+}
+
+/// A 256-bit unsigned value, stored as 32 big-endian bytes.
+///
+/// It displays as 64 lowercase hex digits, most significant first. With the `borsh` feature, it is encoded as its 32
+/// big-endian bytes.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+pub struct U256([u8; U256::BYTES]);
+
+impl U256 {
+    /// The size of the value in bytes.
+    pub const BYTES: usize = 32;
+    /// The largest value.
+    pub const MAX: U256 = U256([0xff; U256::BYTES]);
+    /// Zero.
+    pub const ZERO: U256 = U256([0; U256::BYTES]);
+
+    /// Create a value from big-endian bytes.
+    pub const fn from_be_bytes(bytes: [u8; U256::BYTES]) -> Self {
+        Self(bytes)
+    }
+
+    /// Create a value from little-endian bytes.
+    pub const fn from_le_bytes(bytes: [u8; U256::BYTES]) -> Self {
+        Self(reverse_bytes(bytes))
+    }
+
+    /// Return the value as big-endian bytes.
+    pub const fn to_be_bytes(&self) -> [u8; U256::BYTES] {
+        self.0
+    }
+
+    /// Return the value as little-endian bytes.
+    pub const fn to_le_bytes(&self) -> [u8; U256::BYTES] {
+        reverse_bytes(self.0)
+    }
+
+    /// Decode a value from exactly 32 big-endian bytes.
+    ///
+    /// # Errors
+    /// Returns [EndianError::InvalidLength] if the slice is not 32 bytes long.
+    pub fn from_be_slice(bytes: &[u8]) -> Result<Self, EndianError> {
+        to_array(bytes).map(Self::from_be_bytes)
+    }
+
+    /// Decode a value from exactly 32 little-endian bytes.
+    ///
+    /// # Errors
+    /// Returns [EndianError::InvalidLength] if the slice is not 32 bytes long.
+    pub fn from_le_slice(bytes: &[u8]) -> Result<Self, EndianError> {
+        to_array(bytes).map(Self::from_le_bytes)
+    }
+
+    /// Parse a value from its canonical text form: exactly 64 lowercase hex digits, most significant first, as written
+    /// by its `Display` implementation.
+    ///
+    /// # Errors
+    /// Returns [EndianError::NonCanonical] if the string is not in canonical form.
+    pub fn from_canonical_hex(s: &str) -> Result<Self, EndianError> {
+        let digits = s.as_bytes();
+        ensure!(digits.len() == U256::BYTES * 2, NonCanonicalSnafu);
+        let mut bytes = [0u8; U256::BYTES];
+        for (byte, pair) in bytes.iter_mut().zip(digits.chunks_exact(2)) {
+            let mut nibbles = pair.iter().map(|&c| hex_nibble(c).filter(|_| !c.is_ascii_uppercase()));
+            match (nibbles.next().flatten(), nibbles.next().flatten()) {
+                (Some(hi), Some(lo)) => *byte = (hi << 4) | lo,
+                _ => return NonCanonicalSnafu.fail(),
+            }
+        }
+        Ok(Self(bytes))
+    }
+}
+
+impl From<u128> for U256 {
+    fn from(value: u128) -> Self {
+        let mut bytes = [0u8; U256::BYTES];
+        if let Some(low) = bytes.get_mut(16..) {
+            low.copy_from_slice(&value.to_be_bytes());
+        }
+        Self(bytes)
+    }
+}
+
+impl TryFrom<U256> for u128 {
+    type Error = EndianError;
+
+    fn try_from(value: U256) -> Result<Self, Self::Error> {
+        match value.0.split_at(16) {
+            (high, low) if high.iter().all(|&b| b == 0) => u128_from_be_slice(low),
+            _ => OverflowSnafu.fail(),
+        }
+    }
+}
+
+impl fmt::Display for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_hex(f, &self.0)
+    }
+}
+
+impl fmt::Debug for U256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "U256({})", self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::format;
+
+    use super::*;
+
+    #[test]
+    fn u128_slices() {
+        let value = 0x0102030405060708090a0b0c0d0e0f10u128;
+        assert_eq!(u128_from_le_slice(&value.to_le_bytes()), Ok(value));
+        assert_eq!(u128_from_be_slice(&value.to_be_bytes()), Ok(value));
+        assert_eq!(
+            u128_from_le_slice(&[0u8; 15]),
+            Err(EndianError::InvalidLength {
+                expected: 16,
+                actual: 15
+            })
+        );
+        assert!(u128_from_be_slice(&[0u8; 17]).is_err());
+    }
+
+    #[test]
+    fn canonical_decimal() {
+        assert_eq!(u128_from_canonical_str("0"), Ok(0));
+        assert_eq!(u128_from_canonical_str("1234"), Ok(1234));
+        assert_eq!(u128_from_canonical_str(&format!("{}", u128::MAX)), Ok(u128::MAX));
+        assert_eq!(
+            u128_from_canonical_str("340282366920938463463374607431768211456"),
+            Err(EndianError::Overflow {})
+        );
+        for s in ["", "00", "01", "+1", "-1", " 1", "1_000", "1e3"] {
+            assert_eq!(u128_from_canonical_str(s), Err(EndianError::NonCanonical {}), "{s}");
+        }
+    }
+
+    #[test]
+    fn u256_bytes() {
+        let mut be = [0u8; 32];
+        be[0] = 1;
+        be[31] = 2;
+        let value = U256::from_be_bytes(be);
+        assert_eq!(value.to_be_bytes(), be);
+        assert_eq!(value.to_le_bytes()[0], 2);
+        assert_eq!(U256::from_le_bytes(value.to_le_bytes()), value);
+        assert_eq!(U256::from_le_slice(&value.to_le_bytes()), Ok(value));
+        assert_eq!(U256::from_be_slice(&be), Ok(value));
+        const TARGET: U256 = U256::from_le_bytes(U256::MAX.to_le_bytes());
+        assert_eq!(TARGET, U256::MAX);
+        assert_eq!(
+            U256::from_be_slice(&be[1..]),
+            Err(EndianError::InvalidLength {
+                expected: 32,
+                actual: 31
+            })
+        );
+        assert!(U256::ZERO < U256::from(1u128));
+        assert!(U256::from(u128::MAX) < value);
+        assert!(value < U256::MAX);
+    }
+
+    #[test]
+    fn u256_conversions() {
+        assert_eq!(u128::try_from(U256::from(u128::MAX)), Ok(u128::MAX));
+        assert_eq!(u128::try_from(U256::MAX), Err(EndianError::Overflow {}));
+        let hex = format!("{}", U256::from(0xabcdu128));
+        assert_eq!(hex, format!("{:0>64}", "abcd"));
+        assert_eq!(U256::from_canonical_hex(&hex), Ok(U256::from(0xabcdu128)));
+        assert_eq!(format!("{:?}", U256::ZERO), format!("U256({:0>64})", ""));
+        for s in [
+            &hex[1..],
+            &hex.to_uppercase(),
+            &format!("0x{}", &hex[2..]),
+            &format!("{}g", &hex[1..]),
+        ] {
+            assert_eq!(U256::from_canonical_hex(s), Err(EndianError::NonCanonical {}));
+        }
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn borsh() {
+        use borsh::BorshDeserialize;
+
+        let value = U256::from(0x0102_0304u128);
+        let bytes = borsh::to_vec(&value).unwrap();
+        assert_eq!(bytes, value.to_be_bytes());
+        assert_eq!(U256::try_from_slice(&bytes).unwrap(), value);
+        assert!(U256::try_from_slice(&bytes[..31]).is_err());
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Typed access to environment variables for service configuration.
+//!
+//! The errors name the variable and the reason it could not be used, but never include its value, which may be a
+//! secret. Secrets are read with [get_secret] straight into a [SafePassword], and any rejected raw value is zeroized.
+//!
+//! ```edition2018
+//! # use tari_utilities::env::{get_or_default, get_parsed, EnvError};
+//! std::env::set_var("TARI_EXAMPLE_PORT", "18142");
+//! assert_eq!(get_parsed::<u16>("TARI_EXAMPLE_PORT"), Ok(18142));
+//! assert_eq!(get_or_default("TARI_EXAMPLE_THREADS", 4usize), Ok(4));
+//! assert!(matches!(
+//!     get_parsed::<u16>("TARI_EXAMPLE_THREADS"),
+//!     Err(EnvError::Missing { .. })
+//! ));
+//! ```
+
+use alloc::string::{String, ToString};
+use core::{fmt::Display, str::FromStr};
+use std::env::{self, VarError};
+
+use snafu::prelude::*;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+#[cfg(feature = "zeroize")]
+use crate::SafePassword;
+
+/// Errors for reading environment variables.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum EnvError {
+    /// The variable is not set.
+    #[snafu(display("Environment variable {name} is not set"))]
+    Missing {
+        /// The name of the variable
+        name: String,
+    },
+    /// The variable is not valid Unicode.
+    #[snafu(display("Environment variable {name} is not valid Unicode"))]
+    NotUnicode {
+        /// The name of the variable
+        name: String,
+    },
+    /// The variable could not be parsed.
+    #[snafu(display("Environment variable {name} is invalid: {reason}"))]
+    Invalid {
+        /// The name of the variable
+        name: String,
+        /// The parse error
+        reason: String,
+    },
+}
+
+/// Read a variable as a string.
+fn get(name: &str) -> Result<String, EnvError> {
+    env::var(name).map_err(|e| match e {
+        VarError::NotPresent => EnvError::Missing { name: name.to_string() },
+        VarError::NotUnicode(_) => EnvError::NotUnicode { name: name.to_string() },
+    })
+}
+
+/// Read and parse a variable.
+///
+/// # Errors
+/// Returns an error if the variable is missing, not Unicode, or cannot be parsed as `T`.
+pub fn get_parsed<T>(name: &str) -> Result<T, EnvError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    get(name)?.trim().parse().map_err(|e: T::Err| EnvError::Invalid {
+        name: name.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// Read and parse a variable, or return `default` if it is not set.
+///
+/// # Errors
+/// Returns an error if the variable is set but not Unicode, or cannot be parsed as `T`.
+pub fn get_or_default<T>(name: &str, default: T) -> Result<T, EnvError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    match get_parsed(name) {
+        Err(EnvError::Missing { .. }) => Ok(default),
+        result => result,
+    }
+}
+
+/// Read a secret, such as a passphrase or an API key. The value is moved into the returned [SafePassword] without
+/// copies, and it is zeroized if it is rejected for not being Unicode.
+///
+/// # Errors
+/// Returns an error if the variable is missing or not Unicode.
+#[cfg(feature = "zeroize")]
+pub fn get_secret(name: &str) -> Result<SafePassword, EnvError> {
+    let value = env::var_os(name).context(MissingSnafu { name })?;
+    match value.into_string() {
+        Ok(value) => Ok(SafePassword::from(value)),
+        Err(value) => {
+            value.into_encoded_bytes().zeroize();
+            NotUnicodeSnafu { name }.fail()
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Each test uses its own variables, since tests run in parallel in one process
+    #[test]
+    fn parsing() {
+        env::set_var("TARI_UTIL_TEST_NUMBER", " 42 ");
+        env::set_var("TARI_UTIL_TEST_WORD", "forty-two");
+        assert_eq!(get_parsed::<u8>("TARI_UTIL_TEST_NUMBER"), Ok(42));
+        assert_eq!(get_or_default::<u8>("TARI_UTIL_TEST_NUMBER", 7), Ok(42));
+        assert_eq!(get_or_default::<u8>("TARI_UTIL_TEST_UNSET", 7), Ok(7));
+        assert_eq!(
+            get_parsed::<u8>("TARI_UTIL_TEST_UNSET"),
+            Err(EnvError::Missing {
+                name: "TARI_UTIL_TEST_UNSET".into()
+            })
+        );
+        let err = get_or_default::<u8>("TARI_UTIL_TEST_WORD", 7).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Environment variable TARI_UTIL_TEST_WORD is invalid: invalid digit found in string"
+        );
+        assert!(!err.to_string().contains("forty-two"));
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn secrets() {
+        env::set_var("TARI_UTIL_TEST_SECRET", "hunter2");
+        assert_eq!(get_secret("TARI_UTIL_TEST_SECRET").unwrap().reveal(), b"hunter2");
+        assert!(matches!(
+            get_secret("TARI_UTIL_TEST_SECRET_UNSET"),
+            Err(EnvError::Missing { .. })
+        ));
+        #[cfg(unix)]
+        {
+            use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+            env::set_var("TARI_UTIL_TEST_SECRET_BINARY", OsStr::from_bytes(&[0xff, 0xfe]));
+            assert!(matches!(
+                get_secret("TARI_UTIL_TEST_SECRET_BINARY"),
+                Err(EnvError::NotUnicode { .. })
+            ));
+        }
+    }
+}
@@ -20,11 +20,11 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-//! Data structure representing time as a `u64`.
+//! Data structure representing time as a `u64`, and a [TimeProvider] abstraction over the clock.
 
 use std::{
     fmt,
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 #[cfg(feature = "borsh")]
@@ -34,17 +34,13 @@ use borsh::{BorshDeserialize, BorshSerialize};
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[cfg_attr(feature = "borsh", derive(BorshSerialize, BorshDeserialize,))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub struct EpochTime(u64);
 
 impl EpochTime {
     /// Return UTC current as EpochTime.
     pub fn now() -> EpochTime {
-        EpochTime(
-            SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
-        )
+        SystemTimeProvider.now()
     }
 
     /// Creates a new EpochTime representing the number of seconds since the unix epoch (1970-01-01 00:00:00 UTC).
@@ -79,6 +75,49 @@ impl From<u64> for EpochTime {
     }
 }
 
+/// A source of the current time. Code that depends on the time takes a `TimeProvider`, so that tests can fix the
+/// clock.
+pub trait TimeProvider {
+    /// Return the time elapsed since the Unix epoch.
+    fn since_epoch(&self) -> Duration;
+
+    /// Return the current time as an [EpochTime].
+    fn now(&self) -> EpochTime {
+        EpochTime(self.since_epoch().as_secs())
+    }
+}
+
+/// The system clock. Times before the Unix epoch are reported as the epoch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemTimeProvider;
+
+impl TimeProvider for SystemTimeProvider {
+    fn since_epoch(&self) -> Duration {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default()
+    }
+}
+
+/// A clock that is stopped at a fixed time, for tests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FixedTimeProvider(pub Duration);
+
+impl TimeProvider for FixedTimeProvider {
+    fn since_epoch(&self) -> Duration {
+        self.0
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for EpochTime {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        use proptest::strategy::Strategy;
+        proptest::prelude::any::<u64>().prop_map(EpochTime).boxed()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -113,6 +152,14 @@ mod test {
         assert_eq!(b.checked_sub(a), None);
     }
 
+    #[test]
+    fn time_providers() {
+        let fixed = FixedTimeProvider(Duration::from_millis(1_234_567));
+        assert_eq!(fixed.since_epoch(), Duration::from_millis(1_234_567));
+        assert_eq!(fixed.now(), EpochTime::from(1234));
+        assert!(SystemTimeProvider.now() >= EpochTime::from(1_600_000_000));
+    }
+
     #[test]
     fn display() {
         let time = EpochTime::from(1234567);
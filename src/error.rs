@@ -0,0 +1,1355 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A crate-wide error type that every module error converts into, with stable numeric codes.
+//!
+//! FFI layers and RPC error mappings can translate [UtilError::code] through a single table instead of matching on
+//! each module's error type.
+
+use alloc::string::{String, ToString};
+
+use snafu::prelude::*;
+
+use crate::{
+    base32::Base32Error,
+    base64::Base64CodecError,
+    bech32::Bech32Error,
+    byte_array::ByteArrayError,
+    byte_cursor::ByteCursorError,
+    byte_patch::BytePatchError,
+    byte_size::ByteSizeError,
+    dammsum::DammSumError,
+    endian::EndianError,
+    fixed_set::{FixedSetError, SetItemError},
+    fixed_string::FixedStringError,
+    framing::FramingError,
+    hex::HexError,
+    luhn::LuhnError,
+    multibase::MultibaseError,
+    percent::PercentError,
+    semver_lite::SemverError,
+    ulid::UlidError,
+    uuid::UuidError,
+    varint::VarintError,
+    wordlist::WordlistError,
+};
+
+/// Stable numeric codes for every error produced by this crate. Codes are never reused or renumbered.
+#[repr(u32)]
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// [ByteArrayError::ConversionError]
+    ByteArrayConversion = 1001,
+    /// [ByteArrayError::IncorrectLength]
+    ByteArrayIncorrectLength = 1002,
+    /// [HexError::InvalidCharacter]
+    HexInvalidCharacter = 2001,
+    /// [HexError::LengthError]
+    HexLength = 2002,
+    /// [HexError::HexConversionError]
+    HexConversion = 2003,
+    /// [HexError::BufferTooSmall]
+    HexBufferTooSmall = 2004,
+    /// `Base58Error::ByteArrayError`
+    Base58ByteArray = 3001,
+    /// `Base58Error::DecodeError`
+    Base58Decode = 3002,
+    /// `MessageFormatError::BinarySerializeError`
+    MessageFormatBinarySerialize = 4001,
+    /// `MessageFormatError::BinaryDeserializeError`
+    MessageFormatBinaryDeserialize = 4002,
+    /// `MessageFormatError::JSONError`
+    MessageFormatJson = 4003,
+    /// `MessageFormatError::Base64DeserializeError`
+    MessageFormatBase64Deserialize = 4004,
+    /// `JsonError::KeyMustBeString`
+    JsonKeyMustBeString = 5001,
+    /// `JsonError::Custom`
+    JsonCustom = 5002,
+    /// [DammSumError::UnsupportedAlphabetSize]
+    DammSumUnsupportedAlphabetSize = 6001,
+    /// [DammSumError::DuplicateSymbol]
+    DammSumDuplicateSymbol = 6002,
+    /// [DammSumError::InvalidSymbol]
+    DammSumInvalidSymbol = 6003,
+    /// [DammSumError::InvalidDigit]
+    DammSumInvalidDigit = 6004,
+    /// [DammSumError::MissingChecksum]
+    DammSumMissingChecksum = 6005,
+    /// [DammSumError::InvalidChecksum]
+    DammSumInvalidChecksum = 6006,
+    /// [LuhnError::UnsupportedAlphabetSize]
+    LuhnUnsupportedAlphabetSize = 7001,
+    /// [LuhnError::DuplicateSymbol]
+    LuhnDuplicateSymbol = 7002,
+    /// [LuhnError::InvalidSymbol]
+    LuhnInvalidSymbol = 7003,
+    /// [LuhnError::InvalidDigit]
+    LuhnInvalidDigit = 7004,
+    /// [LuhnError::MissingChecksum]
+    LuhnMissingChecksum = 7005,
+    /// [LuhnError::InvalidChecksum]
+    LuhnInvalidChecksum = 7006,
+    /// `ct::DecodeError::InvalidLength`
+    CtInvalidLength = 8001,
+    /// `ct::DecodeError::InvalidCharacter`
+    CtInvalidCharacter = 8002,
+    /// [FixedStringError::CapacityExceeded]
+    FixedStringCapacityExceeded = 9001,
+    /// [PercentError::InvalidEscape]
+    PercentInvalidEscape = 10001,
+    /// [PercentError::InvalidUtf8]
+    PercentInvalidUtf8 = 10002,
+    /// [FramingError::FrameTooLarge]
+    FramingFrameTooLarge = 11001,
+    /// [FramingError::InvalidLengthPrefix]
+    FramingInvalidLengthPrefix = 11002,
+    /// [ByteCursorError::UnexpectedEnd]
+    ByteCursorUnexpectedEnd = 12001,
+    /// [ByteCursorError::InvalidVarint]
+    ByteCursorInvalidVarint = 12002,
+    /// [ByteCursorError::TrailingBytes]
+    ByteCursorTrailingBytes = 12003,
+    /// [EndianError::InvalidLength]
+    EndianInvalidLength = 13001,
+    /// [EndianError::Overflow]
+    EndianOverflow = 13002,
+    /// [EndianError::NonCanonical]
+    EndianNonCanonical = 13003,
+    /// `sealed_file::SealedFileError::InvalidHeader`
+    SealedFileInvalidHeader = 14001,
+    /// `sealed_file::SealedFileError::UnsupportedVersion`
+    SealedFileUnsupportedVersion = 14002,
+    /// `sealed_file::SealedFileError::InvalidParameters`
+    SealedFileInvalidParameters = 14003,
+    /// `sealed_file::SealedFileError::DecryptionFailed`
+    SealedFileDecryptionFailed = 14004,
+    /// `sealed_file::SealedFileError::SerializeFailed`
+    SealedFileSerializeFailed = 14005,
+    /// `sealed_file::SealedFileError::DeserializeFailed`
+    SealedFileDeserializeFailed = 14006,
+    /// `sealed_file::SealedFileError::Io`
+    SealedFileIo = 14007,
+    /// [ByteSizeError::InvalidNumber]
+    ByteSizeInvalidNumber = 15001,
+    /// [ByteSizeError::UnknownSuffix]
+    ByteSizeUnknownSuffix = 15002,
+    /// [ByteSizeError::Overflow]
+    ByteSizeOverflow = 15003,
+    /// [ByteSizeError::FractionalBytes]
+    ByteSizeFractionalBytes = 15004,
+    /// `net::NetError::InvalidIpAddress`
+    NetInvalidIpAddress = 16001,
+    /// `net::NetError::InvalidPort`
+    NetInvalidPort = 16002,
+    /// `net::NetError::InvalidPortRange`
+    NetInvalidPortRange = 16003,
+    /// `net::NetError::InvalidOnionAddress`
+    NetInvalidOnionAddress = 16004,
+    /// `net::NetError::UnsupportedOnionVersion`
+    NetUnsupportedOnionVersion = 16005,
+    /// `net::NetError::OnionChecksumMismatch`
+    NetOnionChecksumMismatch = 16006,
+    /// [SemverError::InvalidVersion]
+    SemverInvalidVersion = 17001,
+    /// [SemverError::InvalidRequirement]
+    SemverInvalidRequirement = 17002,
+    /// [UuidError::InvalidString]
+    UuidInvalidString = 18001,
+    /// [UuidError::InvalidLength]
+    UuidInvalidLength = 18002,
+    /// [UlidError::InvalidLength]
+    UlidInvalidLength = 19001,
+    /// [UlidError::InvalidCharacter]
+    UlidInvalidCharacter = 19002,
+    /// [UlidError::Overflow]
+    UlidOverflow = 19003,
+    /// [UlidError::MonotonicOverflow]
+    UlidMonotonicOverflow = 19004,
+    /// `counter::CounterError::Io`
+    CounterIo = 20001,
+    /// `counter::CounterError::Corrupt`
+    CounterCorrupt = 20002,
+    /// `counter::CounterError::Exhausted`
+    CounterExhausted = 20003,
+    /// `random::RandomError::EmptyAlphabet`
+    RandomEmptyAlphabet = 21001,
+    /// `random::RandomError::DuplicateCharacter`
+    RandomDuplicateCharacter = 21002,
+    /// `secret_sharing::SecretSharingError::InvalidThreshold`
+    SecretSharingInvalidThreshold = 22001,
+    /// `secret_sharing::SecretSharingError::NotEnoughShares`
+    SecretSharingNotEnoughShares = 22002,
+    /// `secret_sharing::SecretSharingError::InvalidShareIndex`
+    SecretSharingInvalidShareIndex = 22003,
+    /// `secret_sharing::SecretSharingError::DuplicateShare`
+    SecretSharingDuplicateShare = 22004,
+    /// `secret_sharing::SecretSharingError::InconsistentShares`
+    SecretSharingInconsistentShares = 22005,
+    /// [WordlistError::InvalidWordlistLength]
+    WordlistInvalidWordlistLength = 23001,
+    /// [WordlistError::DuplicateWord]
+    WordlistDuplicateWord = 23002,
+    /// [WordlistError::InvalidBitLength]
+    WordlistInvalidBitLength = 23003,
+    /// [WordlistError::ChecksumTooShort]
+    WordlistChecksumTooShort = 23004,
+    /// [WordlistError::UnknownWord]
+    WordlistUnknownWord = 23005,
+    /// [WordlistError::InvalidWordCount]
+    WordlistInvalidWordCount = 23006,
+    /// [WordlistError::ChecksumMismatch]
+    WordlistChecksumMismatch = 23007,
+    /// `metrics::MetricsError::RecorderAlreadySet`
+    MetricsRecorderAlreadySet = 24001,
+    /// `env::EnvError::Missing`
+    EnvMissing = 25001,
+    /// `env::EnvError::NotUnicode`
+    EnvNotUnicode = 25002,
+    /// `env::EnvError::Invalid`
+    EnvInvalid = 25003,
+    /// `path::PathError::Io`
+    PathIo = 26001,
+    /// `path::PathError::OutsideBase`
+    PathOutsideBase = 26002,
+    /// `path::PathError::Unresolvable`
+    PathUnresolvable = 26003,
+    /// [BytePatchError::OutOfBounds]
+    BytePatchOutOfBounds = 27001,
+    /// [BytePatchError::Overlap]
+    BytePatchOverlap = 27002,
+    /// `time_locked::TimeLockedError::Locked`
+    TimeLockedLocked = 28001,
+    /// [FixedSetError::IndexOutOfBounds]
+    FixedSetIndexOutOfBounds = 29001,
+    /// [FixedSetError::SizeMismatch]
+    FixedSetSizeMismatch = 29002,
+    /// [FixedSetError::AlreadySet]
+    FixedSetAlreadySet = 29003,
+    /// [FixedSetError::InvalidItem]
+    FixedSetInvalidItem = 29004,
+    /// `encoding::Base64Error::ByteArrayError`
+    Base64ByteArray = 30001,
+    /// `encoding::Base64Error::DecodeError`
+    Base64Decode = 30002,
+    /// `encoding::EncodingError::InvalidEncoding`
+    EncodingInvalid = 31001,
+    /// `encoding::EncodingError::UnrecognizedEncoding`
+    EncodingUnrecognized = 31002,
+    /// [Base32Error::InvalidCharacter]
+    Base32InvalidCharacter = 32001,
+    /// [Base32Error::InvalidLength]
+    Base32InvalidLength = 32002,
+    /// [Base32Error::InvalidPadding]
+    Base32InvalidPadding = 32003,
+    /// [Base32Error::NonZeroTrailingBits]
+    Base32NonZeroTrailingBits = 32004,
+    /// [Base32Error::ByteArrayError]
+    Base32ByteArray = 32005,
+    /// [Bech32Error::InvalidHrp]
+    Bech32InvalidHrp = 33001,
+    /// [Bech32Error::InvalidLength]
+    Bech32InvalidLength = 33002,
+    /// [Bech32Error::MixedCase]
+    Bech32MixedCase = 33003,
+    /// [Bech32Error::MissingSeparator]
+    Bech32MissingSeparator = 33004,
+    /// [Bech32Error::InvalidCharacter]
+    Bech32InvalidCharacter = 33005,
+    /// [Bech32Error::InvalidChecksum]
+    Bech32InvalidChecksum = 33006,
+    /// [Bech32Error::InvalidPadding]
+    Bech32InvalidPadding = 33007,
+    /// [Bech32Error::HrpMismatch]
+    Bech32HrpMismatch = 33008,
+    /// [Bech32Error::VariantMismatch]
+    Bech32VariantMismatch = 33009,
+    /// [Bech32Error::ByteArrayError]
+    Bech32ByteArray = 33010,
+    /// [Base64CodecError::InvalidCharacter]
+    Base64CodecInvalidCharacter = 34001,
+    /// [Base64CodecError::InvalidLength]
+    Base64CodecInvalidLength = 34002,
+    /// [Base64CodecError::InvalidPadding]
+    Base64CodecInvalidPadding = 34003,
+    /// [Base64CodecError::NonZeroTrailingBits]
+    Base64CodecNonZeroTrailingBits = 34004,
+    /// [Base64CodecError::BufferTooSmall]
+    Base64CodecBufferTooSmall = 34005,
+    /// [MultibaseError::MissingPrefix]
+    MultibaseMissingPrefix = 35001,
+    /// [MultibaseError::UnsupportedBase]
+    MultibaseUnsupportedBase = 35002,
+    /// [MultibaseError::InvalidEncoding]
+    MultibaseInvalidEncoding = 35003,
+    /// [MultibaseError::ByteArrayError]
+    MultibaseByteArray = 35004,
+    /// [VarintError::Truncated]
+    VarintTruncated = 36001,
+    /// [VarintError::TooLong]
+    VarintTooLong = 36002,
+    /// [VarintError::Overflow]
+    VarintOverflow = 36003,
+    /// [VarintError::NonMinimal]
+    VarintNonMinimal = 36004,
+    /// [VarintError::BufferTooSmall]
+    VarintBufferTooSmall = 36005,
+}
+
+impl ErrorCode {
+    /// Return the numeric value of the code.
+    pub fn as_u32(self) -> u32 {
+        self as u32
+    }
+}
+
+/// The crate-wide error type. Each variant identifies the module the error came from and carries its stable code and
+/// the original error message as context.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+#[non_exhaustive]
+pub enum UtilError {
+    /// An error from the [byte_array](crate::byte_array) module.
+    #[snafu(display("Byte array error {}: {context}", code.as_u32()))]
+    ByteArray {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [hex](mod@crate::hex) module.
+    #[snafu(display("Hex error {}: {context}", code.as_u32()))]
+    Hex {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the `encoding` module.
+    #[snafu(display("Base58 error {}: {context}", code.as_u32()))]
+    Base58 {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the `message_format` module.
+    #[snafu(display("Message format error {}: {context}", code.as_u32()))]
+    MessageFormat {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the `json` module.
+    #[snafu(display("JSON error {}: {context}", code.as_u32()))]
+    Json {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [dammsum](crate::dammsum) module.
+    #[snafu(display("DammSum error {}: {context}", code.as_u32()))]
+    DammSum {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [luhn](crate::luhn) module.
+    #[snafu(display("Luhn error {}: {context}", code.as_u32()))]
+    Luhn {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the `ct` module.
+    #[snafu(display("Constant-time decoding error {}: {context}", code.as_u32()))]
+    Ct {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [fixed_string](crate::fixed_string) module.
+    #[snafu(display("Fixed string error {}: {context}", code.as_u32()))]
+    FixedString {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [percent](crate::percent) module.
+    #[snafu(display("Percent encoding error {}: {context}", code.as_u32()))]
+    Percent {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [framing](crate::framing) module.
+    #[snafu(display("Framing error {}: {context}", code.as_u32()))]
+    Framing {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [byte_cursor](crate::byte_cursor) module.
+    #[snafu(display("Byte cursor error {}: {context}", code.as_u32()))]
+    ByteCursor {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [endian](crate::endian) module.
+    #[snafu(display("Endian error {}: {context}", code.as_u32()))]
+    Endian {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the `sealed_file` module.
+    #[snafu(display("Sealed file error {}: {context}", code.as_u32()))]
+    SealedFile {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [byte_size](crate::byte_size) module.
+    #[snafu(display("Byte size error {}: {context}", code.as_u32()))]
+    ByteSize {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the `net` module.
+    #[snafu(display("Network address error {}: {context}", code.as_u32()))]
+    Net {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [semver_lite](crate::semver_lite) module.
+    #[snafu(display("Semantic version error {}: {context}", code.as_u32()))]
+    Semver {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [uuid](crate::uuid) module.
+    #[snafu(display("UUID error {}: {context}", code.as_u32()))]
+    Uuid {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [ulid](crate::ulid) module.
+    #[snafu(display("ULID error {}: {context}", code.as_u32()))]
+    Ulid {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the `counter` module.
+    #[snafu(display("Counter error {}: {context}", code.as_u32()))]
+    Counter {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the `random` module.
+    #[snafu(display("Random generation error {}: {context}", code.as_u32()))]
+    Random {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the `secret_sharing` module.
+    #[snafu(display("Secret sharing error {}: {context}", code.as_u32()))]
+    SecretSharing {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [wordlist](crate::wordlist) module.
+    #[snafu(display("Wordlist error {}: {context}", code.as_u32()))]
+    Wordlist {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the `metrics` module.
+    #[snafu(display("Metrics error {}: {context}", code.as_u32()))]
+    Metrics {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the `env` module.
+    #[snafu(display("Environment error {}: {context}", code.as_u32()))]
+    Env {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the `path` module.
+    #[snafu(display("Path error {}: {context}", code.as_u32()))]
+    Path {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [byte_patch](crate::byte_patch) module.
+    #[snafu(display("Byte patch error {}: {context}", code.as_u32()))]
+    BytePatch {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the `time_locked` module.
+    #[snafu(display("Time lock error {}: {context}", code.as_u32()))]
+    TimeLocked {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [fixed_set](crate::fixed_set) module.
+    #[snafu(display("Fixed set error {}: {context}", code.as_u32()))]
+    FixedSet {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the `encoding` module.
+    #[snafu(display("Base64 error {}: {context}", code.as_u32()))]
+    Base64 {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the `encoding` module.
+    #[snafu(display("Encoding error {}: {context}", code.as_u32()))]
+    Encoding {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [base32](crate::base32) module.
+    #[snafu(display("Base32 error {}: {context}", code.as_u32()))]
+    Base32 {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [bech32](crate::bech32) module.
+    #[snafu(display("Bech32 error {}: {context}", code.as_u32()))]
+    Bech32 {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [base64](crate::base64) module.
+    #[snafu(display("Base64 codec error {}: {context}", code.as_u32()))]
+    Base64Codec {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [multibase](crate::multibase) module.
+    #[snafu(display("Multibase error {}: {context}", code.as_u32()))]
+    Multibase {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+    /// An error from the [varint](crate::varint) module.
+    #[snafu(display("Varint error {}: {context}", code.as_u32()))]
+    Varint {
+        /// The stable error code
+        code: ErrorCode,
+        /// A description of the error
+        context: String,
+    },
+}
+
+impl UtilError {
+    /// Return the stable code of the error.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            UtilError::ByteArray { code, .. } |
+            UtilError::Hex { code, .. } |
+            UtilError::Base58 { code, .. } |
+            UtilError::MessageFormat { code, .. } |
+            UtilError::Json { code, .. } |
+            UtilError::DammSum { code, .. } |
+            UtilError::Luhn { code, .. } |
+            UtilError::Ct { code, .. } |
+            UtilError::FixedString { code, .. } |
+            UtilError::Percent { code, .. } |
+            UtilError::Framing { code, .. } |
+            UtilError::ByteCursor { code, .. } |
+            UtilError::Endian { code, .. } |
+            UtilError::SealedFile { code, .. } |
+            UtilError::ByteSize { code, .. } |
+            UtilError::Net { code, .. } |
+            UtilError::Semver { code, .. } |
+            UtilError::Uuid { code, .. } |
+            UtilError::Ulid { code, .. } |
+            UtilError::Counter { code, .. } |
+            UtilError::Random { code, .. } |
+            UtilError::SecretSharing { code, .. } |
+            UtilError::Wordlist { code, .. } |
+            UtilError::Metrics { code, .. } |
+            UtilError::Env { code, .. } |
+            UtilError::Path { code, .. } |
+            UtilError::BytePatch { code, .. } |
+            UtilError::TimeLocked { code, .. } |
+            UtilError::FixedSet { code, .. } |
+            UtilError::Base64 { code, .. } |
+            UtilError::Encoding { code, .. } |
+            UtilError::Base32 { code, .. } |
+            UtilError::Bech32 { code, .. } |
+            UtilError::Base64Codec { code, .. } |
+            UtilError::Multibase { code, .. } |
+            UtilError::Varint { code, .. } => *code,
+        }
+    }
+
+    /// Return the description of the error.
+    pub fn context(&self) -> &str {
+        match self {
+            UtilError::ByteArray { context, .. } |
+            UtilError::Hex { context, .. } |
+            UtilError::Base58 { context, .. } |
+            UtilError::MessageFormat { context, .. } |
+            UtilError::Json { context, .. } |
+            UtilError::DammSum { context, .. } |
+            UtilError::Luhn { context, .. } |
+            UtilError::Ct { context, .. } |
+            UtilError::FixedString { context, .. } |
+            UtilError::Percent { context, .. } |
+            UtilError::Framing { context, .. } |
+            UtilError::ByteCursor { context, .. } |
+            UtilError::Endian { context, .. } |
+            UtilError::SealedFile { context, .. } |
+            UtilError::ByteSize { context, .. } |
+            UtilError::Net { context, .. } |
+            UtilError::Semver { context, .. } |
+            UtilError::Uuid { context, .. } |
+            UtilError::Ulid { context, .. } |
+            UtilError::Counter { context, .. } |
+            UtilError::Random { context, .. } |
+            UtilError::SecretSharing { context, .. } |
+            UtilError::Wordlist { context, .. } |
+            UtilError::Metrics { context, .. } |
+            UtilError::Env { context, .. } |
+            UtilError::Path { context, .. } |
+            UtilError::BytePatch { context, .. } |
+            UtilError::TimeLocked { context, .. } |
+            UtilError::FixedSet { context, .. } |
+            UtilError::Base64 { context, .. } |
+            UtilError::Encoding { context, .. } |
+            UtilError::Base32 { context, .. } |
+            UtilError::Bech32 { context, .. } |
+            UtilError::Base64Codec { context, .. } |
+            UtilError::Multibase { context, .. } |
+            UtilError::Varint { context, .. } => context,
+        }
+    }
+}
+
+impl From<ByteArrayError> for UtilError {
+    fn from(err: ByteArrayError) -> Self {
+        let code = match err {
+            ByteArrayError::ConversionError { .. } => ErrorCode::ByteArrayConversion,
+            ByteArrayError::IncorrectLength { .. } => ErrorCode::ByteArrayIncorrectLength,
+        };
+        UtilError::ByteArray {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<HexError> for UtilError {
+    fn from(err: HexError) -> Self {
+        let code = match err {
+            HexError::InvalidCharacter { .. } => ErrorCode::HexInvalidCharacter,
+            HexError::LengthError {} => ErrorCode::HexLength,
+            HexError::HexConversionError {} => ErrorCode::HexConversion,
+            HexError::BufferTooSmall { .. } => ErrorCode::HexBufferTooSmall,
+        };
+        UtilError::Hex {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<crate::encoding::Base58Error> for UtilError {
+    fn from(err: crate::encoding::Base58Error) -> Self {
+        use crate::encoding::Base58Error;
+        let code = match err {
+            Base58Error::ByteArrayError { .. } => ErrorCode::Base58ByteArray,
+            Base58Error::DecodeError { .. } => ErrorCode::Base58Decode,
+        };
+        UtilError::Base58 {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "serialize")]
+impl From<crate::message_format::MessageFormatError> for UtilError {
+    fn from(err: crate::message_format::MessageFormatError) -> Self {
+        use crate::message_format::MessageFormatError;
+        let code = match err {
+            MessageFormatError::BinarySerializeError {} => ErrorCode::MessageFormatBinarySerialize,
+            MessageFormatError::BinaryDeserializeError {} => ErrorCode::MessageFormatBinaryDeserialize,
+            MessageFormatError::JSONError {} => ErrorCode::MessageFormatJson,
+            MessageFormatError::Base64DeserializeError {} => ErrorCode::MessageFormatBase64Deserialize,
+        };
+        UtilError::MessageFormat {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "json-core")]
+impl From<crate::json::JsonError> for UtilError {
+    fn from(err: crate::json::JsonError) -> Self {
+        use crate::json::JsonError;
+        let code = match err {
+            JsonError::KeyMustBeString {} => ErrorCode::JsonKeyMustBeString,
+            JsonError::Custom { .. } => ErrorCode::JsonCustom,
+        };
+        UtilError::Json {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<DammSumError> for UtilError {
+    fn from(err: DammSumError) -> Self {
+        let code = match err {
+            DammSumError::UnsupportedAlphabetSize { .. } => ErrorCode::DammSumUnsupportedAlphabetSize,
+            DammSumError::DuplicateSymbol { .. } => ErrorCode::DammSumDuplicateSymbol,
+            DammSumError::InvalidSymbol { .. } => ErrorCode::DammSumInvalidSymbol,
+            DammSumError::InvalidDigit { .. } => ErrorCode::DammSumInvalidDigit,
+            DammSumError::MissingChecksum {} => ErrorCode::DammSumMissingChecksum,
+            DammSumError::InvalidChecksum {} => ErrorCode::DammSumInvalidChecksum,
+        };
+        UtilError::DammSum {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<LuhnError> for UtilError {
+    fn from(err: LuhnError) -> Self {
+        let code = match err {
+            LuhnError::UnsupportedAlphabetSize { .. } => ErrorCode::LuhnUnsupportedAlphabetSize,
+            LuhnError::DuplicateSymbol { .. } => ErrorCode::LuhnDuplicateSymbol,
+            LuhnError::InvalidSymbol { .. } => ErrorCode::LuhnInvalidSymbol,
+            LuhnError::InvalidDigit { .. } => ErrorCode::LuhnInvalidDigit,
+            LuhnError::MissingChecksum {} => ErrorCode::LuhnMissingChecksum,
+            LuhnError::InvalidChecksum {} => ErrorCode::LuhnInvalidChecksum,
+        };
+        UtilError::Luhn {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<FixedStringError> for UtilError {
+    fn from(err: FixedStringError) -> Self {
+        let code = match err {
+            FixedStringError::CapacityExceeded { .. } => ErrorCode::FixedStringCapacityExceeded,
+        };
+        UtilError::FixedString {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<PercentError> for UtilError {
+    fn from(err: PercentError) -> Self {
+        let code = match err {
+            PercentError::InvalidEscape { .. } => ErrorCode::PercentInvalidEscape,
+            PercentError::InvalidUtf8 {} => ErrorCode::PercentInvalidUtf8,
+        };
+        UtilError::Percent {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<FramingError> for UtilError {
+    fn from(err: FramingError) -> Self {
+        let code = match err {
+            FramingError::FrameTooLarge { .. } => ErrorCode::FramingFrameTooLarge,
+            FramingError::InvalidLengthPrefix {} => ErrorCode::FramingInvalidLengthPrefix,
+        };
+        UtilError::Framing {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<ByteCursorError> for UtilError {
+    fn from(err: ByteCursorError) -> Self {
+        let code = match err {
+            ByteCursorError::UnexpectedEnd { .. } => ErrorCode::ByteCursorUnexpectedEnd,
+            ByteCursorError::InvalidVarint { .. } => ErrorCode::ByteCursorInvalidVarint,
+            ByteCursorError::TrailingBytes { .. } => ErrorCode::ByteCursorTrailingBytes,
+        };
+        UtilError::ByteCursor {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<EndianError> for UtilError {
+    fn from(err: EndianError) -> Self {
+        let code = match err {
+            EndianError::InvalidLength { .. } => ErrorCode::EndianInvalidLength,
+            EndianError::Overflow {} => ErrorCode::EndianOverflow,
+            EndianError::NonCanonical {} => ErrorCode::EndianNonCanonical,
+        };
+        UtilError::Endian {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<ByteSizeError> for UtilError {
+    fn from(err: ByteSizeError) -> Self {
+        let code = match err {
+            ByteSizeError::InvalidNumber { .. } => ErrorCode::ByteSizeInvalidNumber,
+            ByteSizeError::UnknownSuffix { .. } => ErrorCode::ByteSizeUnknownSuffix,
+            ByteSizeError::Overflow { .. } => ErrorCode::ByteSizeOverflow,
+            ByteSizeError::FractionalBytes { .. } => ErrorCode::ByteSizeFractionalBytes,
+        };
+        UtilError::ByteSize {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<SemverError> for UtilError {
+    fn from(err: SemverError) -> Self {
+        let code = match err {
+            SemverError::InvalidVersion {} => ErrorCode::SemverInvalidVersion,
+            SemverError::InvalidRequirement {} => ErrorCode::SemverInvalidRequirement,
+        };
+        UtilError::Semver {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<UuidError> for UtilError {
+    fn from(err: UuidError) -> Self {
+        let code = match err {
+            UuidError::InvalidString {} => ErrorCode::UuidInvalidString,
+            UuidError::InvalidLength { .. } => ErrorCode::UuidInvalidLength,
+        };
+        UtilError::Uuid {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<UlidError> for UtilError {
+    fn from(err: UlidError) -> Self {
+        let code = match err {
+            UlidError::InvalidLength { .. } => ErrorCode::UlidInvalidLength,
+            UlidError::InvalidCharacter { .. } => ErrorCode::UlidInvalidCharacter,
+            UlidError::Overflow {} => ErrorCode::UlidOverflow,
+            UlidError::MonotonicOverflow {} => ErrorCode::UlidMonotonicOverflow,
+        };
+        UtilError::Ulid {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<WordlistError> for UtilError {
+    fn from(err: WordlistError) -> Self {
+        let code = match err {
+            WordlistError::InvalidWordlistLength { .. } => ErrorCode::WordlistInvalidWordlistLength,
+            WordlistError::DuplicateWord { .. } => ErrorCode::WordlistDuplicateWord,
+            WordlistError::InvalidBitLength { .. } => ErrorCode::WordlistInvalidBitLength,
+            WordlistError::ChecksumTooShort {} => ErrorCode::WordlistChecksumTooShort,
+            WordlistError::UnknownWord { .. } => ErrorCode::WordlistUnknownWord,
+            WordlistError::InvalidWordCount { .. } => ErrorCode::WordlistInvalidWordCount,
+            WordlistError::ChecksumMismatch {} => ErrorCode::WordlistChecksumMismatch,
+        };
+        UtilError::Wordlist {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<BytePatchError> for UtilError {
+    fn from(err: BytePatchError) -> Self {
+        let code = match err {
+            BytePatchError::OutOfBounds { .. } => ErrorCode::BytePatchOutOfBounds,
+            BytePatchError::Overlap { .. } => ErrorCode::BytePatchOverlap,
+        };
+        UtilError::BytePatch {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<FixedSetError> for UtilError {
+    fn from(err: FixedSetError) -> Self {
+        let code = match err {
+            FixedSetError::IndexOutOfBounds { .. } => ErrorCode::FixedSetIndexOutOfBounds,
+            FixedSetError::SizeMismatch { .. } => ErrorCode::FixedSetSizeMismatch,
+            FixedSetError::AlreadySet { .. } => ErrorCode::FixedSetAlreadySet,
+            FixedSetError::InvalidItem { .. } => ErrorCode::FixedSetInvalidItem,
+        };
+        UtilError::FixedSet {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl<T> From<SetItemError<T>> for UtilError {
+    fn from(err: SetItemError<T>) -> Self {
+        UtilError::from(err.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<crate::encoding::EncodingError> for UtilError {
+    fn from(err: crate::encoding::EncodingError) -> Self {
+        use crate::encoding::EncodingError;
+        let code = match err {
+            EncodingError::InvalidEncoding { .. } => ErrorCode::EncodingInvalid,
+            EncodingError::UnrecognizedEncoding { .. } => ErrorCode::EncodingUnrecognized,
+        };
+        UtilError::Encoding {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<Base32Error> for UtilError {
+    fn from(err: Base32Error) -> Self {
+        let code = match err {
+            Base32Error::InvalidCharacter { .. } => ErrorCode::Base32InvalidCharacter,
+            Base32Error::InvalidLength { .. } => ErrorCode::Base32InvalidLength,
+            Base32Error::InvalidPadding { .. } => ErrorCode::Base32InvalidPadding,
+            Base32Error::NonZeroTrailingBits { .. } => ErrorCode::Base32NonZeroTrailingBits,
+            Base32Error::ByteArrayError { .. } => ErrorCode::Base32ByteArray,
+        };
+        UtilError::Base32 {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<Bech32Error> for UtilError {
+    fn from(err: Bech32Error) -> Self {
+        let code = match err {
+            Bech32Error::InvalidHrp { .. } => ErrorCode::Bech32InvalidHrp,
+            Bech32Error::InvalidLength { .. } => ErrorCode::Bech32InvalidLength,
+            Bech32Error::MixedCase { .. } => ErrorCode::Bech32MixedCase,
+            Bech32Error::MissingSeparator { .. } => ErrorCode::Bech32MissingSeparator,
+            Bech32Error::InvalidCharacter { .. } => ErrorCode::Bech32InvalidCharacter,
+            Bech32Error::InvalidChecksum { .. } => ErrorCode::Bech32InvalidChecksum,
+            Bech32Error::InvalidPadding { .. } => ErrorCode::Bech32InvalidPadding,
+            Bech32Error::HrpMismatch { .. } => ErrorCode::Bech32HrpMismatch,
+            Bech32Error::VariantMismatch { .. } => ErrorCode::Bech32VariantMismatch,
+            Bech32Error::ByteArrayError { .. } => ErrorCode::Bech32ByteArray,
+        };
+        UtilError::Bech32 {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<Base64CodecError> for UtilError {
+    fn from(err: Base64CodecError) -> Self {
+        let code = match err {
+            Base64CodecError::InvalidCharacter { .. } => ErrorCode::Base64CodecInvalidCharacter,
+            Base64CodecError::InvalidLength { .. } => ErrorCode::Base64CodecInvalidLength,
+            Base64CodecError::InvalidPadding { .. } => ErrorCode::Base64CodecInvalidPadding,
+            Base64CodecError::NonZeroTrailingBits { .. } => ErrorCode::Base64CodecNonZeroTrailingBits,
+            Base64CodecError::BufferTooSmall { .. } => ErrorCode::Base64CodecBufferTooSmall,
+        };
+        UtilError::Base64Codec {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<MultibaseError> for UtilError {
+    fn from(err: MultibaseError) -> Self {
+        let code = match err {
+            MultibaseError::MissingPrefix { .. } => ErrorCode::MultibaseMissingPrefix,
+            MultibaseError::UnsupportedBase { .. } => ErrorCode::MultibaseUnsupportedBase,
+            MultibaseError::InvalidEncoding { .. } => ErrorCode::MultibaseInvalidEncoding,
+            MultibaseError::ByteArrayError { .. } => ErrorCode::MultibaseByteArray,
+        };
+        UtilError::Multibase {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+impl From<VarintError> for UtilError {
+    fn from(err: VarintError) -> Self {
+        let code = match err {
+            VarintError::Truncated { .. } => ErrorCode::VarintTruncated,
+            VarintError::TooLong { .. } => ErrorCode::VarintTooLong,
+            VarintError::Overflow { .. } => ErrorCode::VarintOverflow,
+            VarintError::NonMinimal { .. } => ErrorCode::VarintNonMinimal,
+            VarintError::BufferTooSmall { .. } => ErrorCode::VarintBufferTooSmall,
+        };
+        UtilError::Varint {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<crate::ct::DecodeError> for UtilError {
+    fn from(err: crate::ct::DecodeError) -> Self {
+        use crate::ct::DecodeError;
+        let code = match err {
+            DecodeError::InvalidLength {} => ErrorCode::CtInvalidLength,
+            DecodeError::InvalidCharacter {} => ErrorCode::CtInvalidCharacter,
+        };
+        UtilError::Ct {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "sealed-file")]
+impl From<crate::sealed_file::SealedFileError> for UtilError {
+    fn from(err: crate::sealed_file::SealedFileError) -> Self {
+        use crate::sealed_file::SealedFileError;
+        let code = match err {
+            SealedFileError::InvalidHeader { .. } => ErrorCode::SealedFileInvalidHeader,
+            SealedFileError::UnsupportedVersion { .. } => ErrorCode::SealedFileUnsupportedVersion,
+            SealedFileError::InvalidParameters { .. } => ErrorCode::SealedFileInvalidParameters,
+            SealedFileError::DecryptionFailed { .. } => ErrorCode::SealedFileDecryptionFailed,
+            SealedFileError::SerializeFailed { .. } => ErrorCode::SealedFileSerializeFailed,
+            SealedFileError::DeserializeFailed { .. } => ErrorCode::SealedFileDeserializeFailed,
+            SealedFileError::Io { .. } => ErrorCode::SealedFileIo,
+        };
+        UtilError::SealedFile {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "net")]
+impl From<crate::net::NetError> for UtilError {
+    fn from(err: crate::net::NetError) -> Self {
+        use crate::net::NetError;
+        let code = match err {
+            NetError::InvalidIpAddress { .. } => ErrorCode::NetInvalidIpAddress,
+            NetError::InvalidPort { .. } => ErrorCode::NetInvalidPort,
+            NetError::InvalidPortRange { .. } => ErrorCode::NetInvalidPortRange,
+            NetError::InvalidOnionAddress { .. } => ErrorCode::NetInvalidOnionAddress,
+            NetError::UnsupportedOnionVersion { .. } => ErrorCode::NetUnsupportedOnionVersion,
+            NetError::OnionChecksumMismatch { .. } => ErrorCode::NetOnionChecksumMismatch,
+        };
+        UtilError::Net {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<crate::counter::CounterError> for UtilError {
+    fn from(err: crate::counter::CounterError) -> Self {
+        use crate::counter::CounterError;
+        let code = match err {
+            CounterError::Io { .. } => ErrorCode::CounterIo,
+            CounterError::Corrupt {} => ErrorCode::CounterCorrupt,
+            CounterError::Exhausted {} => ErrorCode::CounterExhausted,
+        };
+        UtilError::Counter {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "rand")]
+impl From<crate::random::RandomError> for UtilError {
+    fn from(err: crate::random::RandomError) -> Self {
+        use crate::random::RandomError;
+        let code = match err {
+            RandomError::EmptyAlphabet {} => ErrorCode::RandomEmptyAlphabet,
+            RandomError::DuplicateCharacter { .. } => ErrorCode::RandomDuplicateCharacter,
+        };
+        UtilError::Random {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "secret_sharing")]
+impl From<crate::secret_sharing::SecretSharingError> for UtilError {
+    fn from(err: crate::secret_sharing::SecretSharingError) -> Self {
+        use crate::secret_sharing::SecretSharingError;
+        let code = match err {
+            SecretSharingError::InvalidThreshold { .. } => ErrorCode::SecretSharingInvalidThreshold,
+            SecretSharingError::NotEnoughShares { .. } => ErrorCode::SecretSharingNotEnoughShares,
+            SecretSharingError::InvalidShareIndex {} => ErrorCode::SecretSharingInvalidShareIndex,
+            SecretSharingError::DuplicateShare { .. } => ErrorCode::SecretSharingDuplicateShare,
+            SecretSharingError::InconsistentShares {} => ErrorCode::SecretSharingInconsistentShares,
+        };
+        UtilError::SecretSharing {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<crate::metrics::MetricsError> for UtilError {
+    fn from(err: crate::metrics::MetricsError) -> Self {
+        use crate::metrics::MetricsError;
+        let code = match err {
+            MetricsError::RecorderAlreadySet {} => ErrorCode::MetricsRecorderAlreadySet,
+        };
+        UtilError::Metrics {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<crate::env::EnvError> for UtilError {
+    fn from(err: crate::env::EnvError) -> Self {
+        use crate::env::EnvError;
+        let code = match err {
+            EnvError::Missing { .. } => ErrorCode::EnvMissing,
+            EnvError::NotUnicode { .. } => ErrorCode::EnvNotUnicode,
+            EnvError::Invalid { .. } => ErrorCode::EnvInvalid,
+        };
+        UtilError::Env {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<crate::path::PathError> for UtilError {
+    fn from(err: crate::path::PathError) -> Self {
+        use crate::path::PathError;
+        let code = match err {
+            PathError::Io { .. } => ErrorCode::PathIo,
+            PathError::OutsideBase {} => ErrorCode::PathOutsideBase,
+            PathError::Unresolvable {} => ErrorCode::PathUnresolvable,
+        };
+        UtilError::Path {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+#[cfg(all(feature = "std", feature = "zeroize"))]
+impl From<crate::time_locked::TimeLockedError> for UtilError {
+    fn from(err: crate::time_locked::TimeLockedError) -> Self {
+        use crate::time_locked::TimeLockedError;
+        let code = match err {
+            TimeLockedError::Locked { .. } => ErrorCode::TimeLockedLocked,
+        };
+        UtilError::TimeLocked {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<crate::encoding::Base64Error> for UtilError {
+    fn from(err: crate::encoding::Base64Error) -> Self {
+        use crate::encoding::Base64Error;
+        let code = match err {
+            Base64Error::ByteArrayError { .. } => ErrorCode::Base64ByteArray,
+            Base64Error::DecodeError { .. } => ErrorCode::Base64Decode,
+        };
+        UtilError::Base64 {
+            code,
+            context: err.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{hex::from_hex, ByteArray};
+
+    #[test]
+    fn codes_are_stable() {
+        assert_eq!(ErrorCode::ByteArrayConversion.as_u32(), 1001);
+        assert_eq!(ErrorCode::HexInvalidCharacter.as_u32(), 2001);
+        assert_eq!(ErrorCode::Base58Decode.as_u32(), 3002);
+        assert_eq!(ErrorCode::MessageFormatBase64Deserialize.as_u32(), 4004);
+        assert_eq!(ErrorCode::JsonCustom.as_u32(), 5002);
+        assert_eq!(ErrorCode::DammSumInvalidChecksum.as_u32(), 6006);
+        assert_eq!(ErrorCode::LuhnInvalidChecksum.as_u32(), 7006);
+        assert_eq!(ErrorCode::CtInvalidCharacter.as_u32(), 8002);
+        assert_eq!(ErrorCode::FixedStringCapacityExceeded.as_u32(), 9001);
+        assert_eq!(ErrorCode::PercentInvalidUtf8.as_u32(), 10002);
+        assert_eq!(ErrorCode::FramingInvalidLengthPrefix.as_u32(), 11002);
+        assert_eq!(ErrorCode::ByteCursorTrailingBytes.as_u32(), 12003);
+        assert_eq!(ErrorCode::EndianNonCanonical.as_u32(), 13003);
+        assert_eq!(ErrorCode::SealedFileIo.as_u32(), 14007);
+        assert_eq!(ErrorCode::ByteSizeFractionalBytes.as_u32(), 15004);
+        assert_eq!(ErrorCode::NetOnionChecksumMismatch.as_u32(), 16006);
+        assert_eq!(ErrorCode::SemverInvalidRequirement.as_u32(), 17002);
+        assert_eq!(ErrorCode::UuidInvalidLength.as_u32(), 18002);
+        assert_eq!(ErrorCode::UlidMonotonicOverflow.as_u32(), 19004);
+        assert_eq!(ErrorCode::CounterExhausted.as_u32(), 20003);
+        assert_eq!(ErrorCode::RandomDuplicateCharacter.as_u32(), 21002);
+        assert_eq!(ErrorCode::SecretSharingInconsistentShares.as_u32(), 22005);
+        assert_eq!(ErrorCode::WordlistChecksumMismatch.as_u32(), 23007);
+        assert_eq!(ErrorCode::MetricsRecorderAlreadySet.as_u32(), 24001);
+        assert_eq!(ErrorCode::EnvInvalid.as_u32(), 25003);
+        assert_eq!(ErrorCode::PathUnresolvable.as_u32(), 26003);
+        assert_eq!(ErrorCode::BytePatchOverlap.as_u32(), 27002);
+        assert_eq!(ErrorCode::TimeLockedLocked.as_u32(), 28001);
+        assert_eq!(ErrorCode::FixedSetInvalidItem.as_u32(), 29004);
+        assert_eq!(ErrorCode::Base64Decode.as_u32(), 30002);
+        assert_eq!(ErrorCode::EncodingUnrecognized.as_u32(), 31002);
+        assert_eq!(ErrorCode::Base32ByteArray.as_u32(), 32005);
+        assert_eq!(ErrorCode::Bech32ByteArray.as_u32(), 33010);
+        assert_eq!(ErrorCode::Base64CodecBufferTooSmall.as_u32(), 34005);
+        assert_eq!(ErrorCode::MultibaseByteArray.as_u32(), 35004);
+        assert_eq!(ErrorCode::VarintBufferTooSmall.as_u32(), 36005);
+    }
+
+    #[test]
+    fn conversions() {
+        let err = UtilError::from(from_hex("0g").unwrap_err());
+        assert_eq!(err.code(), ErrorCode::HexInvalidCharacter);
+        assert_eq!(
+            err.context(),
+            "Only hexadecimal characters (0-9,a-f) are permitted, found 'g' at position 1"
+        );
+        assert_eq!(
+            err.to_string(),
+            "Hex error 2001: Only hexadecimal characters (0-9,a-f) are permitted, found 'g' at position 1"
+        );
+
+        let err = UtilError::from(<[u8; 4]>::from_canonical_bytes(&[1]).unwrap_err());
+        assert_eq!(err.code(), ErrorCode::ByteArrayIncorrectLength);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn encoding_conversions() {
+        use crate::encoding::{Base58, Base64};
+
+        let err = UtilError::from(<[u8; 4]>::from_base58("11111O").unwrap_err());
+        assert_eq!(err.code(), ErrorCode::Base58Decode);
+        assert!(matches!(err, UtilError::Base58 { .. }));
+
+        let err = UtilError::from(<[u8; 4]>::from_base64("AQID").unwrap_err());
+        assert_eq!(err.code(), ErrorCode::Base64ByteArray);
+        let err = UtilError::from(<[u8; 4]>::from_base64("AQI*").unwrap_err());
+        assert_eq!(err.code(), ErrorCode::Base64Decode);
+        assert!(matches!(err, UtilError::Base64 { .. }));
+    }
+
+    #[test]
+    fn question_mark() {
+        fn parse(hex: &str) -> Result<[u8; 2], UtilError> {
+            let bytes = from_hex(hex)?;
+            Ok(<[u8; 2]>::from_canonical_bytes(&bytes)?)
+        }
+        assert_eq!(parse("0102"), Ok([1, 2]));
+        assert_eq!(parse("010").unwrap_err().code(), ErrorCode::HexLength);
+        assert_eq!(parse("010203").unwrap_err().code(), ErrorCode::ByteArrayIncorrectLength);
+    }
+}
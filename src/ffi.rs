@@ -0,0 +1,390 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A C-compatible interface to the core conversions of this crate, for use by native mobile and desktop layers.
+//!
+//! The functions are `cbindgen`-compatible and all follow the same conventions:
+//! - Input data is passed as a pointer plus an explicit length. Strings do not need to be NUL-terminated and are not
+//!   NUL-terminated on output.
+//! - Output is written into a caller-owned buffer `out` with capacity `out_len`. The number of bytes written is stored
+//!   in `*written`. If `out` is null or too small, nothing is written, `*written` holds the required size and
+//!   [TariUtilErrorCode::BufferTooSmall] is returned, so callers can query the size before allocating.
+//! - Every fallible function returns a [TariUtilErrorCode], where `Ok` is zero.
+
+use alloc::{boxed::Box, vec::Vec};
+use core::{ptr, slice, str};
+
+use zeroize::Zeroize;
+
+use crate::{
+    encoding::Base58,
+    hex::{from_hex, to_hex, MAX_BYTES_SIZE},
+    Hidden,
+};
+
+/// Error codes returned by the FFI functions.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TariUtilErrorCode {
+    /// The call succeeded.
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullPointer = 1,
+    /// The output buffer is too small. The required size has been written to `*written`.
+    BufferTooSmall = 2,
+    /// The input could not be decoded.
+    InvalidInput = 3,
+    /// The input exceeds the maximum size supported by the conversion.
+    InputTooLarge = 4,
+}
+
+/// An opaque, heap-allocated buffer for secret data that is zeroized when wiped or destroyed.
+pub struct TariSecureBuffer {
+    data: Hidden<Vec<u8>>,
+}
+
+/// Build a slice from a pointer and length. A zero length is always valid, even for a null pointer.
+unsafe fn input<'a>(data: *const u8, len: usize) -> Result<&'a [u8], TariUtilErrorCode> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if data.is_null() {
+        return Err(TariUtilErrorCode::NullPointer);
+    }
+    Ok(slice::from_raw_parts(data, len))
+}
+
+/// Build a string slice from a pointer and length, which must contain valid UTF-8.
+unsafe fn input_str<'a>(data: *const u8, len: usize) -> Result<&'a str, TariUtilErrorCode> {
+    str::from_utf8(input(data, len)?).map_err(|_| TariUtilErrorCode::InvalidInput)
+}
+
+/// Copy `bytes` into the caller's output buffer, following the conventions described in the module documentation.
+unsafe fn output(bytes: &[u8], out: *mut u8, out_len: usize, written: *mut usize) -> TariUtilErrorCode {
+    if written.is_null() {
+        return TariUtilErrorCode::NullPointer;
+    }
+    *written = bytes.len();
+    if out.is_null() || out_len < bytes.len() {
+        return TariUtilErrorCode::BufferTooSmall;
+    }
+    ptr::copy_nonoverlapping(bytes.as_ptr(), out, bytes.len());
+    TariUtilErrorCode::Ok
+}
+
+/// Write the result of a decode into the output buffer and zeroize the intermediate copy, since decoded data is often
+/// key material.
+unsafe fn output_decoded(
+    decoded: Result<Vec<u8>, TariUtilErrorCode>,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> TariUtilErrorCode {
+    match decoded {
+        Ok(mut bytes) => {
+            let result = output(&bytes, out, out_len, written);
+            bytes.zeroize();
+            result
+        },
+        Err(e) => e,
+    }
+}
+
+/// Encode `data_len` bytes at `data` as a lowercase hex string.
+///
+/// # Safety
+/// `data` must be valid for reads of `data_len` bytes, `out` must be null or valid for writes of `out_len` bytes and
+/// `written` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn tari_util_hex_encode(
+    data: *const u8,
+    data_len: usize,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> TariUtilErrorCode {
+    let bytes = match input(data, data_len) {
+        Ok(b) => b,
+        Err(e) => return e,
+    };
+    if bytes.len() > MAX_BYTES_SIZE {
+        return TariUtilErrorCode::InputTooLarge;
+    }
+    output(to_hex(bytes).as_bytes(), out, out_len, written)
+}
+
+/// Decode the hex string of `hex_len` bytes at `hex` into bytes.
+///
+/// # Safety
+/// `hex` must be valid for reads of `hex_len` bytes, `out` must be null or valid for writes of `out_len` bytes and
+/// `written` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn tari_util_hex_decode(
+    hex: *const u8,
+    hex_len: usize,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> TariUtilErrorCode {
+    let decoded = input_str(hex, hex_len).and_then(|s| from_hex(s).map_err(|_| TariUtilErrorCode::InvalidInput));
+    output_decoded(decoded, out, out_len, written)
+}
+
+/// Encode `data_len` bytes at `data` as a base58 string.
+///
+/// # Safety
+/// `data` must be valid for reads of `data_len` bytes, `out` must be null or valid for writes of `out_len` bytes and
+/// `written` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn tari_util_base58_encode(
+    data: *const u8,
+    data_len: usize,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> TariUtilErrorCode {
+    let bytes = match input(data, data_len) {
+        Ok(b) => b,
+        Err(e) => return e,
+    };
+    output(bytes.to_vec().to_base58().as_bytes(), out, out_len, written)
+}
+
+/// Decode the base58 string of `b58_len` bytes at `b58` into bytes.
+///
+/// # Safety
+/// `b58` must be valid for reads of `b58_len` bytes, `out` must be null or valid for writes of `out_len` bytes and
+/// `written` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn tari_util_base58_decode(
+    b58: *const u8,
+    b58_len: usize,
+    out: *mut u8,
+    out_len: usize,
+    written: *mut usize,
+) -> TariUtilErrorCode {
+    let decoded =
+        input_str(b58, b58_len).and_then(|s| Vec::<u8>::from_base58(s).map_err(|_| TariUtilErrorCode::InvalidInput));
+    output_decoded(decoded, out, out_len, written)
+}
+
+/// Compare two buffers in constant time, storing the result in `*equal`.
+///
+/// Only the contents are compared in constant time; buffers of different lengths are never equal.
+///
+/// # Safety
+/// `a` and `b` must be valid for reads of `a_len` and `b_len` bytes respectively, and `equal` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn tari_util_ct_eq(
+    a: *const u8,
+    a_len: usize,
+    b: *const u8,
+    b_len: usize,
+    equal: *mut bool,
+) -> TariUtilErrorCode {
+    if equal.is_null() {
+        return TariUtilErrorCode::NullPointer;
+    }
+    let (a, b) = match (input(a, a_len), input(b, b_len)) {
+        (Ok(a), Ok(b)) => (a, b),
+        (Err(e), _) | (_, Err(e)) => return e,
+    };
+    *equal = bool::from(crate::ct::eq(a, b));
+    TariUtilErrorCode::Ok
+}
+
+/// Create a zero-filled secure buffer of `len` bytes. The buffer must be released with
+/// [tari_util_secure_buffer_destroy].
+#[no_mangle]
+pub extern "C" fn tari_util_secure_buffer_create(len: usize) -> *mut TariSecureBuffer {
+    Box::into_raw(Box::new(TariSecureBuffer {
+        data: Hidden::hide(vec![0u8; len]),
+    }))
+}
+
+/// Return a pointer to the contents of the secure buffer, or null if `buffer` is null. The pointer is valid for reads
+/// and writes of [tari_util_secure_buffer_len] bytes until the buffer is destroyed.
+///
+/// # Safety
+/// `buffer` must be null or a pointer returned by [tari_util_secure_buffer_create] that has not been destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn tari_util_secure_buffer_data(buffer: *mut TariSecureBuffer) -> *mut u8 {
+    match buffer.as_mut() {
+        Some(buffer) => buffer.data.reveal_mut().as_mut_ptr(),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Return the length of the secure buffer, or zero if `buffer` is null.
+///
+/// # Safety
+/// `buffer` must be null or a pointer returned by [tari_util_secure_buffer_create] that has not been destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn tari_util_secure_buffer_len(buffer: *const TariSecureBuffer) -> usize {
+    buffer.as_ref().map(|b| b.data.reveal().len()).unwrap_or(0)
+}
+
+/// Overwrite the contents of the secure buffer with zeros, keeping its length.
+///
+/// # Safety
+/// `buffer` must be null or a pointer returned by [tari_util_secure_buffer_create] that has not been destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn tari_util_secure_buffer_wipe(buffer: *mut TariSecureBuffer) -> TariUtilErrorCode {
+    match buffer.as_mut() {
+        Some(buffer) => {
+            buffer.data.reveal_mut().as_mut_slice().zeroize();
+            TariUtilErrorCode::Ok
+        },
+        None => TariUtilErrorCode::NullPointer,
+    }
+}
+
+/// Zeroize and free the secure buffer. Passing null is a no-op.
+///
+/// # Safety
+/// `buffer` must be null or a pointer returned by [tari_util_secure_buffer_create] that has not been destroyed.
+#[no_mangle]
+pub unsafe extern "C" fn tari_util_secure_buffer_destroy(buffer: *mut TariSecureBuffer) {
+    if !buffer.is_null() {
+        drop(Box::from_raw(buffer));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn call(
+        f: unsafe extern "C" fn(*const u8, usize, *mut u8, usize, *mut usize) -> TariUtilErrorCode,
+        input: &[u8],
+    ) -> Result<Vec<u8>, TariUtilErrorCode> {
+        let mut written = 0usize;
+        let code = unsafe { f(input.as_ptr(), input.len(), ptr::null_mut(), 0, &mut written) };
+        if code != TariUtilErrorCode::BufferTooSmall {
+            return Err(code);
+        }
+        let mut out = vec![0u8; written];
+        match unsafe { f(input.as_ptr(), input.len(), out.as_mut_ptr(), out.len(), &mut written) } {
+            TariUtilErrorCode::Ok => Ok(out[..written].to_vec()),
+            e => Err(e),
+        }
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        assert_eq!(call(tari_util_hex_encode, &[0, 1, 255]).unwrap(), b"0001ff");
+        assert_eq!(call(tari_util_hex_decode, b"0001ff").unwrap(), vec![0, 1, 255]);
+        assert_eq!(
+            call(tari_util_hex_decode, b"0g").unwrap_err(),
+            TariUtilErrorCode::InvalidInput
+        );
+        assert_eq!(
+            call(tari_util_hex_decode, &[0xff, 0xfe]).unwrap_err(),
+            TariUtilErrorCode::InvalidInput
+        );
+    }
+
+    #[test]
+    fn base58_round_trip() {
+        assert_eq!(call(tari_util_base58_encode, &[0, 2, 250, 39]).unwrap(), b"111zzz");
+        assert_eq!(call(tari_util_base58_decode, b"111zzz").unwrap(), vec![0, 2, 250, 39]);
+        assert_eq!(
+            call(tari_util_base58_decode, b"11111O").unwrap_err(),
+            TariUtilErrorCode::InvalidInput
+        );
+    }
+
+    #[test]
+    fn output_conventions() {
+        let mut written = 0usize;
+        let mut out = [0u8; 3];
+        let data = [1u8, 2];
+        let code = unsafe { tari_util_hex_encode(data.as_ptr(), data.len(), out.as_mut_ptr(), 3, &mut written) };
+        assert_eq!(code, TariUtilErrorCode::BufferTooSmall);
+        assert_eq!(written, 4);
+        assert_eq!(out, [0u8; 3]);
+
+        let code = unsafe { tari_util_hex_encode(data.as_ptr(), 2, out.as_mut_ptr(), 3, ptr::null_mut()) };
+        assert_eq!(code, TariUtilErrorCode::NullPointer);
+        let code = unsafe { tari_util_hex_encode(ptr::null(), 2, out.as_mut_ptr(), 3, &mut written) };
+        assert_eq!(code, TariUtilErrorCode::NullPointer);
+        let code = unsafe { tari_util_hex_encode(ptr::null(), 0, out.as_mut_ptr(), 3, &mut written) };
+        assert_eq!(code, TariUtilErrorCode::Ok);
+        assert_eq!(written, 0);
+
+        let big = vec![0u8; MAX_BYTES_SIZE + 1];
+        assert_eq!(
+            call(tari_util_hex_encode, &big).unwrap_err(),
+            TariUtilErrorCode::InputTooLarge
+        );
+    }
+
+    #[test]
+    fn constant_time_eq() {
+        let mut equal = false;
+        let (a, b, c) = ([1u8, 2, 3], [1u8, 2, 3], [1u8, 2, 4]);
+        unsafe {
+            assert_eq!(
+                tari_util_ct_eq(a.as_ptr(), 3, b.as_ptr(), 3, &mut equal),
+                TariUtilErrorCode::Ok
+            );
+            assert!(equal);
+            assert_eq!(
+                tari_util_ct_eq(a.as_ptr(), 3, c.as_ptr(), 3, &mut equal),
+                TariUtilErrorCode::Ok
+            );
+            assert!(!equal);
+            assert_eq!(
+                tari_util_ct_eq(a.as_ptr(), 3, b.as_ptr(), 2, &mut equal),
+                TariUtilErrorCode::Ok
+            );
+            assert!(!equal);
+            assert_eq!(
+                tari_util_ct_eq(a.as_ptr(), 3, b.as_ptr(), 3, ptr::null_mut()),
+                TariUtilErrorCode::NullPointer
+            );
+        }
+    }
+
+    #[test]
+    fn secure_buffer() {
+        unsafe {
+            let buffer = tari_util_secure_buffer_create(4);
+            assert_eq!(tari_util_secure_buffer_len(buffer), 4);
+            let data = tari_util_secure_buffer_data(buffer);
+            ptr::copy_nonoverlapping([1u8, 2, 3, 4].as_ptr(), data, 4);
+            assert_eq!(slice::from_raw_parts(data, 4), &[1, 2, 3, 4]);
+            assert_eq!(tari_util_secure_buffer_wipe(buffer), TariUtilErrorCode::Ok);
+            assert_eq!(tari_util_secure_buffer_len(buffer), 4);
+            assert_eq!(slice::from_raw_parts(data, 4), &[0, 0, 0, 0]);
+            tari_util_secure_buffer_destroy(buffer);
+
+            assert!(tari_util_secure_buffer_data(ptr::null_mut()).is_null());
+            assert_eq!(tari_util_secure_buffer_len(ptr::null()), 0);
+            assert_eq!(
+                tari_util_secure_buffer_wipe(ptr::null_mut()),
+                TariUtilErrorCode::NullPointer
+            );
+            tari_util_secure_buffer_destroy(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,204 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Short, human-comparable fingerprints of keys and other data.
+//!
+//! A [Fingerprint] is the digest of some data. It displays as the first [DEFAULT_DISPLAY_BYTES] bytes in colon-grouped
+//! hex, such as `1a2b:3c4d:5e6f:7081`, and can also be shown as a sequence of emoji, which are easier to compare by
+//! eye. [Fingerprint::matches_prefix] checks a fingerprint against a prefix that a user copied or typed.
+//!
+//! ```edition2018
+//! # use blake2::Blake2b512;
+//! # use tari_utilities::fingerprint::fingerprint;
+//! let fp = fingerprint::<Blake2b512>(b"public key");
+//! let shown = fp.to_string();
+//! assert_eq!(shown.len(), 19);
+//! assert!(fp.matches_prefix(&shown));
+//! assert!(fp.matches_prefix(&shown[..4]));
+//! println!("{} {}", fp.hex(4), fp.emoji(6));
+//! ```
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use digest::Digest;
+
+/// The number of bytes shown by the [Display](fmt::Display) implementation of [Fingerprint].
+pub const DEFAULT_DISPLAY_BYTES: usize = 8;
+
+/// The emoji used for emoji fingerprints, each encoding 6 bits. These are the emoji of the Matrix short authentication
+/// string, chosen to be easy to tell apart and to name.
+const EMOJI: [&str; 64] = [
+    "🐶", "🐱", "🦁", "🐎", "🦄", "🐷", "🐘", "🐰", "🐼", "🐓", "🐧", "🐢", "🐟", "🐙", "🦋", "🌷", "🌳", "🌵", "🍄",
+    "🌏", "🌙", "☁️", "🔥", "🍌", "🍎", "🍓", "🌽", "🍕", "🎂", "❤️", "😀", "🤖", "🎩", "👓", "🔧", "🎅", "👍", "☂️",
+    "⌛", "⏰", "🎁", "💡", "📕", "✏️", "📎", "✂️", "🔒", "🔑", "🔨", "☎️", "🏁", "🚂", "🚲", "✈️", "🚀", "🏆", "⚽",
+    "🎸", "🎺", "🔔", "⚓", "🎧", "📁", "📌",
+];
+
+/// Hash `data` with the digest `D` and return its fingerprint.
+pub fn fingerprint<D: Digest>(data: impl AsRef<[u8]>) -> Fingerprint {
+    Fingerprint(D::digest(data.as_ref()).to_vec())
+}
+
+/// The digest of some data, formatted for display.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Fingerprint(Vec<u8>);
+
+impl Fingerprint {
+    /// Wrap an existing digest.
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    /// Return the full digest.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Return the fingerprint shortened to at most `len` bytes.
+    #[must_use]
+    pub fn truncated(&self, len: usize) -> Self {
+        Self(self.0.iter().take(len).copied().collect())
+    }
+
+    /// Format the first `len` bytes (or the whole digest, if shorter) as colon-grouped lowercase hex.
+    pub fn hex(&self, len: usize) -> FingerprintDisplay<'_> {
+        FingerprintDisplay {
+            bytes: &self.0,
+            style: Style::Hex,
+            len,
+        }
+    }
+
+    /// Format the first `count` emoji (or as many as the digest holds, if fewer). Every emoji encodes 6 bits.
+    pub fn emoji(&self, count: usize) -> FingerprintDisplay<'_> {
+        FingerprintDisplay {
+            bytes: &self.0,
+            style: Style::Emoji,
+            len: count,
+        }
+    }
+
+    /// Return `true` if the hex form of the fingerprint starts with `prefix`. Case, colons and whitespace in the prefix
+    /// are ignored, so any truncation of the displayed form matches. An empty prefix never matches.
+    pub fn matches_prefix(&self, prefix: &str) -> bool {
+        let mut digits = prefix.chars().filter(|c| *c != ':' && !c.is_whitespace()).peekable();
+        if digits.peek().is_none() {
+            return false;
+        }
+        let nibbles = self.0.iter().flat_map(|b| [b >> 4, b & 0x0f]);
+        let matched = nibbles
+            .zip(&mut digits)
+            .all(|(nibble, c)| c.to_digit(16) == Some(u32::from(nibble)));
+        // A prefix longer than the fingerprint does not match
+        matched && digits.next().is_none()
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.hex(DEFAULT_DISPLAY_BYTES).fmt(f)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Style {
+    Hex,
+    Emoji,
+}
+
+/// A truncated view of a [Fingerprint] for display, created by [Fingerprint::hex] or [Fingerprint::emoji].
+#[derive(Debug, Clone, Copy)]
+pub struct FingerprintDisplay<'a> {
+    bytes: &'a [u8],
+    style: Style,
+    len: usize,
+}
+
+impl fmt::Display for FingerprintDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.style {
+            Style::Hex => {
+                for (i, byte) in self.bytes.iter().take(self.len).enumerate() {
+                    if i > 0 && i % 2 == 0 {
+                        f.write_str(":")?;
+                    }
+                    write!(f, "{:02x}", byte)?;
+                }
+            },
+            Style::Emoji => {
+                let count = self.len.min(self.bytes.len().saturating_mul(8) / 6);
+                for i in 0..count {
+                    let bit = i.saturating_mul(6);
+                    let byte = |offset: usize| self.bytes.get((bit / 8).saturating_add(offset)).copied();
+                    let pair = u16::from_be_bytes([byte(0).unwrap_or(0), byte(1).unwrap_or(0)]);
+                    let index = pair.checked_shr(10_u32.saturating_sub((bit % 8) as u32)).unwrap_or(0) & 0x3f;
+                    f.write_str(EMOJI.get(usize::from(index)).copied().unwrap_or_default())?;
+                }
+            },
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{string::ToString, vec};
+
+    use blake2::Blake2b512;
+
+    use super::*;
+
+    #[test]
+    fn hex_display() {
+        let fp = Fingerprint::from_bytes(vec![0x1a, 0x2b, 0x3c, 0x4d, 0x5e, 0x6f, 0x70, 0x81, 0x92, 0xa3]);
+        assert_eq!(fp.to_string(), "1a2b:3c4d:5e6f:7081");
+        assert_eq!(fp.hex(3).to_string(), "1a2b:3c");
+        assert_eq!(fp.hex(100).to_string(), "1a2b:3c4d:5e6f:7081:92a3");
+        assert_eq!(fp.hex(0).to_string(), "");
+        assert_eq!(fp.truncated(2).as_bytes(), &[0x1a, 0x2b]);
+        assert_eq!(fingerprint::<Blake2b512>(b"abc").as_bytes().len(), 64);
+    }
+
+    #[test]
+    fn emoji_display() {
+        // 000000 000001 000010 000011 = 0x00 0x10 0x83
+        let fp = Fingerprint::from_bytes(vec![0x00, 0x10, 0x83]);
+        assert_eq!(fp.emoji(10).to_string(), "🐶🐱🦁🐎");
+        assert_eq!(fp.emoji(2).to_string(), "🐶🐱");
+        assert_eq!(Fingerprint::from_bytes(vec![0xff]).emoji(2).to_string(), "📌");
+        assert!(fingerprint::<Blake2b512>(b"abc").emoji(100).to_string().chars().count() >= 85);
+    }
+
+    #[test]
+    fn prefixes() {
+        let fp = Fingerprint::from_bytes(vec![0x1a, 0x2b, 0x3c]);
+        assert!(fp.matches_prefix("1"));
+        assert!(fp.matches_prefix("1A2B:3"));
+        assert!(fp.matches_prefix(" 1a 2b 3c "));
+        assert!(!fp.matches_prefix("1a2c"));
+        assert!(!fp.matches_prefix("1a2b3c4"));
+        assert!(!fp.matches_prefix(""));
+        assert!(!fp.matches_prefix("::"));
+        assert!(!fp.matches_prefix("1g"));
+    }
+}
@@ -21,20 +21,355 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 //! Data structure describing a fixed set of size _n_.
+//!
+//! [FixedSet] stores its slots on the heap and has its size chosen at runtime. [ArrayFixedSet] has its size fixed at
+//! compile time and stores its slots inline, so it needs no allocator. With the `std` feature, [AtomicFixedSet] lets
+//! many threads fill the slots of one set without a lock, each slot at most once.
 
-use alloc::vec::Vec;
-use core::ops::Add;
+#[cfg(feature = "std")]
+use alloc::boxed::Box;
+use alloc::vec::{self, Vec};
+use core::{
+    convert::{Infallible, TryFrom},
+    fmt,
+    iter::FromIterator,
+    ops::{Add, Index, IndexMut},
+    slice,
+};
+#[cfg(feature = "std")]
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    OnceLock,
+};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use snafu::prelude::*;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+/// Errors for fixed set operations.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum FixedSetError {
+    /// The index is not a slot of the set.
+    #[snafu(display("Index {index} is out of bounds for a set of size {size}"))]
+    IndexOutOfBounds {
+        /// The requested index
+        index: usize,
+        /// The size of the set
+        size: usize,
+    },
+    /// Two sets that must be the same size are not.
+    #[snafu(display("Sets of sizes {left} and {right} cannot be combined"))]
+    SizeMismatch {
+        /// The size of the first set
+        left: usize,
+        /// The size of the second set
+        right: usize,
+    },
+    /// The item was rejected by the set's validator.
+    #[snafu(display("Item {index} failed validation"))]
+    InvalidItem {
+        /// The requested index
+        index: usize,
+    },
+    /// The slot already holds an item and cannot be set again.
+    #[snafu(display("Item {index} has already been set"))]
+    AlreadySet {
+        /// The requested index
+        index: usize,
+    },
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FixedSetError {}
+
+/// An item that could not be set, handed back to the caller together with the reason. The item is left out of the
+/// `Debug` output, as it may be secret.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SetItemError<T> {
+    /// Why the item could not be set
+    pub error: FixedSetError,
+    /// The item that was not set
+    pub value: T,
+}
+
+impl<T> fmt::Debug for SetItemError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SetItemError")
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for SetItemError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> std::error::Error for SetItemError<T> {}
+
+impl<T> From<SetItemError<T>> for FixedSetError {
+    fn from(err: SetItemError<T>) -> Self {
+        err.error
+    }
+}
 
 /// Data structure for fixed set of size _n_.
-#[derive(Clone, Debug)]
+///
+/// With the `serde` feature, the set serializes as a sequence of its slots, with `None` for the empty ones, so slot
+/// positions survive a round trip.
+///
+/// Sets compare and hash slot by slot, so two sets are equal only if they have the same size and the same items at
+/// the same indices. With the `borsh` feature, the set is encoded like a `Vec<Option<T>>`.
+///
+/// With the `zeroize` feature, the methods that clear slots, such as [FixedSet::clear_item], require the items to be
+/// [Clearable], which means [Zeroize], and zeroize each item before dropping it. Methods that return items, such as
+/// [FixedSet::take_item], [FixedSet::drain], [FixedSet::truncate] and [FixedSet::into_vec], hand the items, and the
+/// duty to zeroize them, to the caller. Wrap the set in `Zeroizing` to zeroize every item when the set is dropped.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct FixedSet<T> {
     items: Vec<Option<T>>,
 }
 
-impl<T: Clone + PartialEq + Default> FixedSet<T> {
+// The methods that do not change the size of a set, shared by [FixedSet] and [ArrayFixedSet], which both keep their
+// slots in `items`.
+macro_rules! impl_slot_methods {
+    () => {
+        /// Set the `index`-th item to `val`. Any existing item is overwritten. The set takes ownership of `val`.
+        ///
+        /// Use [Self::replace_item] to get the overwritten item back.
+        pub fn set_item(&mut self, index: usize, val: T) -> bool {
+            self.replace_item(index, val).is_ok()
+        }
+
+        /// Set the `index`-th item to `val`, like [Self::set_item], but report why the item could not be set.
+        ///
+        /// # Errors
+        /// Returns [FixedSetError::IndexOutOfBounds] if `index` is not less than the size of the set. The error hands
+        /// `val` back.
+        pub fn try_set_item(&mut self, index: usize, val: T) -> Result<(), SetItemError<T>> {
+            let size = self.size();
+            self.replace_item(index, val)
+                .map(|_| ())
+                .map_err(|value| SetItemError {
+                    error: FixedSetError::IndexOutOfBounds { index, size },
+                    value,
+                })
+        }
+
+        /// Set the `index`-th item to `val`, returning the item it replaces, if any, like `HashMap::insert`. If `index`
+        /// is out of range, the set is unchanged and `val` is handed back as the error.
+        pub fn replace_item(&mut self, index: usize, val: T) -> Result<Option<T>, T> {
+            match self.items.get_mut(index) {
+                Some(item) => Ok(item.replace(val)),
+                None => Err(val),
+            }
+        }
+
+        /// Return a reference to the `index`-th item, or `None` if that item has not been set yet.
+        pub fn get_item(&self, index: usize) -> Option<&T> {
+            match self.items.get(index) {
+                None => None,
+                Some(option) => option.as_ref(),
+            }
+        }
+
+        /// Return a mutable reference to the `index`-th item, or `None` if that item has not been set yet or `index` is
+        /// out of range.
+        pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+            self.items.get_mut(index).and_then(Option::as_mut)
+        }
+
+        /// Delete an item from the set by setting the `index`-th value to `None`. With the `zeroize` feature, the item
+        /// is zeroized first.
+        pub fn clear_item(&mut self, index: usize)
+        where T: Clearable {
+            if let Some(item) = self.items.get_mut(index) {
+                T::clear_slot(item);
+            }
+        }
+
+        /// Remove the `index`-th item from the set and return it, or `None` if that item has not been set.
+        pub fn take_item(&mut self, index: usize) -> Option<T> {
+            self.items.get_mut(index).and_then(Option::take)
+        }
+
+        /// Swap the items at indices `i` and `j`, including empty slots.
+        ///
+        /// # Errors
+        /// Returns [FixedSetError::IndexOutOfBounds] if either index is out of range, leaving the set unchanged.
+        pub fn swap(&mut self, i: usize, j: usize) -> Result<(), FixedSetError> {
+            let size = self.size();
+            if let Some(index) = [i, j].iter().copied().find(|index| *index >= size) {
+                return Err(FixedSetError::IndexOutOfBounds { index, size });
+            }
+            self.items.swap(i, j);
+            Ok(())
+        }
+
+        /// Move every slot `k` places towards the start of the set, wrapping the first `k` slots around to the end. `k`
+        /// may exceed the size of the set.
+        pub fn rotate_left(&mut self, k: usize) {
+            if let Some(k) = k.checked_rem(self.size()) {
+                self.items.rotate_left(k);
+            }
+        }
+
+        /// Move every slot `k` places towards the end of the set, wrapping the last `k` slots around to the start. `k`
+        /// may exceed the size of the set.
+        pub fn rotate_right(&mut self, k: usize) {
+            if let Some(k) = k.checked_rem(self.size()) {
+                self.items.rotate_right(k);
+            }
+        }
+
+        /// Empty every slot of the set, keeping its size. With the `zeroize` feature, the items are zeroized first.
+        pub fn clear_all(&mut self)
+        where T: Clearable {
+            self.items.iter_mut().for_each(T::clear_slot);
+        }
+
+        /// Keep only the items for which `f(index, &item)` returns true, clearing the other slots as
+        /// [Self::clear_item] does.
+        pub fn retain<F>(&mut self, mut f: F)
+        where
+            T: Clearable,
+            F: FnMut(usize, &T) -> bool,
+        {
+            for (index, item) in self.items.iter_mut().enumerate() {
+                if item.as_ref().is_some_and(|val| !f(index, val)) {
+                    T::clear_slot(item);
+                }
+            }
+        }
+
+        /// Returns true if every item in the set has been set. An empty set returns true as well.
+        pub fn is_full(&self) -> bool {
+            self.items.iter().all(Option::is_some)
+        }
+
+        /// Returns the number of items that have been set.
+        pub fn count_filled(&self) -> usize {
+            self.items.iter().filter(|item| item.is_some()).count()
+        }
+
+        /// Returns the number of items that have not been set.
+        pub fn count_empty(&self) -> usize {
+            self.size().saturating_sub(self.count_filled())
+        }
+
+        /// Returns an iterator over the indices of the items that have not been set, in ascending order.
+        pub fn missing_indices(&self) -> impl Iterator<Item = usize> + '_ {
+            self.items
+                .iter()
+                .enumerate()
+                .filter(|(_, item)| item.is_none())
+                .map(|(index, _)| index)
+        }
+
+        /// Returns an iterator over the `(index, item)` pairs of the items that have been set, in ascending index
+        /// order.
+        pub fn iter_filled(&self) -> impl Iterator<Item = (usize, &T)> + '_ {
+            self.items
+                .iter()
+                .enumerate()
+                .filter_map(|(index, item)| item.as_ref().map(|item| (index, item)))
+        }
+
+        /// Returns an iterator over the indices of the items that have not been set, pairing with
+        /// [Self::iter_filled]. This is the same as [Self::missing_indices].
+        pub fn iter_empty_indices(&self) -> impl Iterator<Item = usize> + '_ {
+            self.missing_indices()
+        }
+
+        /// Returns the index of the first item that has not been set, or `None` if the set is full.
+        pub fn first_empty(&self) -> Option<usize> {
+            self.missing_indices().next()
+        }
+
+        /// Set the first empty item to `val` and return its index. If the set is full, `val` is handed back as the
+        /// error.
+        pub fn push_next(&mut self, val: T) -> Result<usize, T> {
+            match self.first_empty() {
+                Some(index) => self.replace_item(index, val).map(|_| index),
+                None => Err(val),
+            }
+        }
+
+        /// Return the first index of the given item in the set by performing a linear search through the set.
+        pub fn search(&self, val: &T) -> Option<usize>
+        where T: PartialEq {
+            self.items
+                .iter()
+                .position(|item| item.as_ref().map(|i| i == val).unwrap_or(false))
+        }
+
+        /// Produces the sum of the values in the set, provided the set is full.
+        // The addition is the item type's own
+        #[allow(clippy::arithmetic_side_effects)]
+        pub fn sum(&self) -> Option<T>
+        where
+            T: Clone + Default,
+            for<'a> &'a T: Add<&'a T, Output = T>,
+        {
+            // This function uses HTRB to work: See https://doc.rust-lang.org/nomicon/hrtb.html
+            // or here https://users.rust-lang.org/t/lifetimes-for-type-constraint-where-one-reference-is-local/11087
+            if self.size() == 0 {
+                return Some(T::default());
+            }
+            self.reduce_full(|sum, v| &sum + v)
+        }
+
+        /// Folds every item of the set into an accumulator, starting from `init`, provided the set is full. An empty
+        /// set returns `init`.
+        pub fn fold_full<Acc, F>(&self, init: Acc, mut f: F) -> Option<Acc>
+        where F: FnMut(Acc, &T) -> Acc {
+            self.items
+                .iter()
+                .try_fold(init, |acc, item| Some(f(acc, item.as_ref()?)))
+        }
+
+        /// Combines the items of the set in index order, starting from a clone of the first item, provided the set is
+        /// full and not empty.
+        pub fn reduce_full<F>(&self, mut f: F) -> Option<T>
+        where
+            T: Clone,
+            F: FnMut(T, &T) -> T,
+        {
+            let (first, rest) = self.items.split_first()?;
+            rest.iter()
+                .try_fold(first.clone()?, |acc, item| Some(f(acc, item.as_ref()?)))
+        }
+
+        /// Returns an iterator that yields one element per slot of the set. An element may be not be set which yields a
+        /// `None`.
+        pub fn iter(&self) -> impl Iterator<Item = Option<&T>> + '_ {
+            self.items.iter().map(|e| e.as_ref())
+        }
+    };
+}
+
+impl<T> FixedSet<T> {
+    impl_slot_methods!();
+
     /// Creates a new fixed set of size n.
     pub fn new(n: usize) -> FixedSet<T> {
-        FixedSet { items: vec![None; n] }
+        FixedSet {
+            items: (0..n).map(|_| None).collect(),
+        }
+    }
+
+    /// Creates a full set of size n, with the `i`-th item set to `f(i)`.
+    pub fn filled_with<F>(n: usize, mut f: F) -> FixedSet<T>
+    where F: FnMut(usize) -> T {
+        (0..n).map(|i| Some(f(i))).collect()
     }
 
     /// Returns the size of the fixed set, NOT the number of items that have been set.
@@ -42,72 +377,545 @@ impl<T: Clone + PartialEq + Default> FixedSet<T> {
         self.items.len()
     }
 
-    /// Set the `index`-th item to `val`. Any existing item is overwritten. The set takes ownership of `val`.
-    pub fn set_item(&mut self, index: usize, val: T) -> bool {
-        if index >= self.items.len() {
-            return false;
+    /// Empty the set, keeping its size, and return an iterator over the `(index, item)` pairs that were set. The set is
+    /// emptied immediately, even if the iterator is not consumed.
+    pub fn drain(&mut self) -> impl Iterator<Item = (usize, T)> {
+        let items = core::mem::replace(self, FixedSet::new(self.size())).items;
+        items
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, item)| item.map(|item| (index, item)))
+    }
+
+    /// Change the size of the set to `n`, adding empty slots or removing slots from the end. Returns the
+    /// `(index, item)` pairs of the items that were removed.
+    pub fn resize(&mut self, n: usize) -> Vec<(usize, T)> {
+        if n > self.size() {
+            self.grow(n);
+            Vec::new()
+        } else {
+            self.truncate(n)
         }
-        self.items[index] = Some(val);
-        true
     }
 
-    /// Return a reference to the `index`-th item, or `None` if that item has not been set yet.
-    pub fn get_item(&self, index: usize) -> Option<&T> {
-        match self.items.get(index) {
-            None => None,
-            Some(option) => option.as_ref(),
+    /// Increase the size of the set to `n` by adding empty slots at the end. Does nothing if the set is already at
+    /// least that big.
+    pub fn grow(&mut self, n: usize) {
+        if n > self.size() {
+            self.items.resize_with(n, || None);
         }
     }
 
-    /// Delete an item from the set by setting the `index`-th value to `None`.
-    pub fn clear_item(&mut self, index: usize) {
-        if index < self.items.len() {
-            self.items[index] = None;
+    /// Decrease the size of the set to `n` by removing slots from the end, returning the `(index, item)` pairs of the
+    /// items that were removed. Does nothing if the set is already at most that big.
+    pub fn truncate(&mut self, n: usize) -> Vec<(usize, T)> {
+        if n >= self.size() {
+            return Vec::new();
         }
+        self.items
+            .split_off(n)
+            .into_iter()
+            .zip(n..)
+            .filter_map(|(item, index)| item.map(|item| (index, item)))
+            .collect()
     }
 
-    /// Returns true if every item in the set has been set. An empty set returns true as well.
-    pub fn is_full(&self) -> bool {
-        self.items.iter().all(Option::is_some)
+    /// Remove the empty slots after the last item that has been set, so that the set ends with a filled slot. No items
+    /// are removed.
+    pub fn truncate_to_filled(&mut self) {
+        let len = self
+            .items
+            .iter()
+            .rposition(Option::is_some)
+            .map_or(0, |last| last.saturating_add(1));
+        self.items.truncate(len);
     }
 
-    /// Return the first index of the given item in the set by performing a linear search through the set.
-    pub fn search(&self, val: &T) -> Option<usize> {
-        self.items
+    /// Collects all non-empty elements of the set into a Vec instance.
+    pub fn into_vec(self) -> Vec<T> {
+        self.items.into_iter().flatten().collect()
+    }
+
+    /// Applies `f` to every set item, returning a set of the same size with each result at the index of its input.
+    /// Unset items remain unset.
+    pub fn map<U, F>(&self, mut f: F) -> FixedSet<U>
+    where F: FnMut(&T) -> U {
+        self.items.iter().map(|item| item.as_ref().map(&mut f)).collect()
+    }
+
+    /// Combines this set with `other` slot by slot. Each slot of the result holds `f` applied to the two items at that
+    /// index, or is unset if either of them is unset.
+    ///
+    /// # Errors
+    /// Returns [FixedSetError::SizeMismatch] if the sets are not the same size.
+    pub fn zip_with<U, V, F>(&self, other: &FixedSet<U>, mut f: F) -> Result<FixedSet<V>, FixedSetError>
+    where F: FnMut(&T, &U) -> V {
+        ensure!(self.size() == other.size(), SizeMismatchSnafu {
+            left: self.size(),
+            right: other.size()
+        });
+        Ok(self
+            .items
             .iter()
-            .position(|item| item.as_ref().map(|i| i == val).unwrap_or(false))
+            .zip(&other.items)
+            .map(|(a, b)| Some(f(a.as_ref()?, b.as_ref()?)))
+            .collect())
     }
 
-    /// Produces the sum of the values in the set, provided the set is full.
-    pub fn sum(&self) -> Option<T>
-    where for<'a> &'a T: Add<&'a T, Output = T> {
-        // This function uses HTRB to work: See https://doc.rust-lang.org/nomicon/hrtb.html
-        // or here https://users.rust-lang.org/t/lifetimes-for-type-constraint-where-one-reference-is-local/11087
-        if self.size() == 0 {
-            return Some(T::default());
+    /// Applies the fallible function `f` to every set item on the `rayon` thread pool, returning a set of the same
+    /// size with each result at the index of its input. Unset items remain unset.
+    ///
+    /// If any call fails, the error of the lowest failing index is returned, so the outcome does not depend on
+    /// scheduling.
+    #[cfg(feature = "rayon")]
+    pub fn try_map_parallel<U, E, F>(&self, f: F) -> Result<FixedSet<U>, E>
+    where
+        T: Sync,
+        U: Send,
+        E: Send,
+        F: Fn(&T) -> Result<U, E> + Sync + Send,
+    {
+        let results = self
+            .items
+            .par_iter()
+            .map(|item| item.as_ref().map(&f).transpose())
+            .collect::<Vec<_>>();
+        let items = results.into_iter().collect::<Result<Vec<_>, E>>()?;
+        Ok(FixedSet { items })
+    }
+}
+
+/// A [FixedSet] that only accepts items passing a validation function, so invalid items never enter the set.
+///
+/// ```edition2018
+/// # use tari_utilities::fixed_set::{FixedSetError, ValidatedFixedSet};
+/// let mut shares = ValidatedFixedSet::new(3, |share: &Vec<u8>| share.len() == 32);
+/// assert!(shares.set_item(0, vec![1; 32]).is_ok());
+/// let rejected = shares.set_item(1, vec![1; 31]).unwrap_err();
+/// assert_eq!(rejected.error, FixedSetError::InvalidItem { index: 1 });
+/// assert_eq!(rejected.value.len(), 31);
+/// assert_eq!(shares.as_set().count_filled(), 1);
+/// ```
+#[derive(Clone)]
+pub struct ValidatedFixedSet<T, V> {
+    set: FixedSet<T>,
+    validator: V,
+}
+
+impl<T, V> ValidatedFixedSet<T, V>
+where V: Fn(&T) -> bool
+{
+    /// Creates a new, empty set of size n whose items must pass `validator`.
+    pub fn new(n: usize, validator: V) -> Self {
+        ValidatedFixedSet {
+            set: FixedSet::new(n),
+            validator,
+        }
+    }
+
+    /// Set the `index`-th item to `val` if it passes validation. Any existing item is overwritten.
+    ///
+    /// # Errors
+    /// Returns [FixedSetError::IndexOutOfBounds] if `index` is not less than the size of the set, or
+    /// [FixedSetError::InvalidItem] if `val` fails validation. The set is unchanged in either case, and the error hands
+    /// `val` back.
+    pub fn set_item(&mut self, index: usize, val: T) -> Result<(), SetItemError<T>> {
+        if index < self.set.size() && !(self.validator)(&val) {
+            return Err(SetItemError {
+                error: FixedSetError::InvalidItem { index },
+                value: val,
+            });
+        }
+        self.set.try_set_item(index, val)
+    }
+
+    /// Delete an item from the set by setting the `index`-th value to `None`, as [FixedSet::clear_item] does.
+    pub fn clear_item(&mut self, index: usize)
+    where T: Clearable {
+        self.set.clear_item(index);
+    }
+
+    /// Remove the `index`-th item from the set and return it, or `None` if that item has not been set.
+    pub fn take_item(&mut self, index: usize) -> Option<T> {
+        self.set.take_item(index)
+    }
+
+    /// The validated items.
+    pub fn as_set(&self) -> &FixedSet<T> {
+        &self.set
+    }
+
+    /// Return the validated items, dropping the validator.
+    pub fn into_inner(self) -> FixedSet<T> {
+        self.set
+    }
+}
+
+impl<T: fmt::Debug, V> fmt::Debug for ValidatedFixedSet<T, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValidatedFixedSet")
+            .field("set", &self.set)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Zeroizes every item and leaves all slots empty. The size of the set is unchanged.
+#[cfg(feature = "zeroize")]
+impl<T: Zeroize> Zeroize for FixedSet<T> {
+    fn zeroize(&mut self) {
+        self.items.iter_mut().for_each(Zeroize::zeroize);
+    }
+}
+
+/// Items that a set can clear. With the `zeroize` feature, these are the [Zeroize] types, and a cleared item is
+/// zeroized before it is dropped. Otherwise every type is clearable.
+pub trait Clearable: Sized {
+    /// Empty the slot, zeroizing its item first if the `zeroize` feature is enabled.
+    fn clear_slot(slot: &mut Option<Self>);
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: Zeroize> Clearable for T {
+    fn clear_slot(slot: &mut Option<Self>) {
+        slot.zeroize();
+    }
+}
+
+#[cfg(not(feature = "zeroize"))]
+impl<T> Clearable for T {
+    fn clear_slot(slot: &mut Option<Self>) {
+        *slot = None;
+    }
+}
+
+/// Accesses the `index`-th slot of the set.
+///
+/// # Panics
+/// Panics if `index` is out of range. Use [FixedSet::get_item] for checked access.
+impl<T> Index<usize> for FixedSet<T> {
+    type Output = Option<T>;
+
+    // Out of range indices panic, as documented
+    #[allow(clippy::indexing_slicing)]
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.items[index]
+    }
+}
+
+/// Mutably accesses the `index`-th slot of the set.
+///
+/// # Panics
+/// Panics if `index` is out of range. Use [FixedSet::get_mut] for checked access.
+impl<T> IndexMut<usize> for FixedSet<T> {
+    // Out of range indices panic, as documented
+    #[allow(clippy::indexing_slicing)]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.items[index]
+    }
+}
+
+/// Yields every slot of the set, including the empty ones.
+impl<T> IntoIterator for FixedSet<T> {
+    type IntoIter = vec::IntoIter<Option<T>>;
+    type Item = Option<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+/// Yields a reference to every slot of the set, including the empty ones.
+impl<'a, T> IntoIterator for &'a FixedSet<T> {
+    type IntoIter = core::iter::Map<slice::Iter<'a, Option<T>>, fn(&'a Option<T>) -> Option<&'a T>>;
+    type Item = Option<&'a T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter().map(Option::as_ref)
+    }
+}
+
+/// Uses the slots as they are, so the set has the same size as the vector.
+impl<T> From<Vec<Option<T>>> for FixedSet<T> {
+    fn from(items: Vec<Option<T>>) -> Self {
+        FixedSet { items }
+    }
+}
+
+/// Creates a full set with one slot per value. This never fails, but is not a `From`, so that `vec.into()` is not
+/// ambiguous with the `From<Vec<Option<T>>>` conversion.
+#[allow(clippy::infallible_try_from)]
+impl<T> TryFrom<Vec<T>> for FixedSet<T> {
+    type Error = Infallible;
+
+    fn try_from(values: Vec<T>) -> Result<Self, Self::Error> {
+        Ok(values.into_iter().collect())
+    }
+}
+
+/// Collects slots into a set with one slot per element.
+impl<T> FromIterator<Option<T>> for FixedSet<T> {
+    fn from_iter<I: IntoIterator<Item = Option<T>>>(iter: I) -> Self {
+        FixedSet {
+            items: iter.into_iter().collect(),
         }
-        if !self.is_full() {
-            return None;
+    }
+}
+
+/// Collects values into a full set with one slot per value.
+impl<T> FromIterator<T> for FixedSet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter().map(Some).collect()
+    }
+}
+
+/// Data structure for a fixed set of size `N`, stored inline without allocation. It has the same slot methods as
+/// [FixedSet], and converts into one with `From`.
+///
+/// With the `serde` feature, the set serializes as a sequence of its `N` slots, the same way a [FixedSet] of that size
+/// does, and deserializing fails unless there are exactly `N` slots. With the `borsh` feature, the set is encoded like
+/// an `[Option<T>; N]`. Clearing and zeroizing behave as they do for [FixedSet].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct ArrayFixedSet<T, const N: usize> {
+    items: [Option<T>; N],
+}
+
+impl<T, const N: usize> ArrayFixedSet<T, N> {
+    impl_slot_methods!();
+
+    /// Creates a new, empty fixed set of size `N`.
+    pub fn new() -> Self {
+        ArrayFixedSet {
+            items: core::array::from_fn(|_| None),
         }
-        let mut iter = self.items.iter().filter_map(Option::as_ref);
-        // Take the first item
-        // unwrap wont fail as we know there is a first item.
-        let mut sum = iter.next().unwrap().clone();
-        for v in iter {
-            sum = &sum + v;
+    }
+
+    /// Returns the size of the fixed set, NOT the number of items that have been set.
+    pub const fn size(&self) -> usize {
+        N
+    }
+
+    /// Empty the set and return an iterator over the `(index, item)` pairs that were set. The set is emptied
+    /// immediately, even if the iterator is not consumed.
+    pub fn drain(&mut self) -> impl Iterator<Item = (usize, T)> {
+        let items = core::mem::take(self).items;
+        IntoIterator::into_iter(items)
+            .enumerate()
+            .filter_map(|(index, item)| item.map(|item| (index, item)))
+    }
+
+    /// Returns the slots of the set.
+    pub fn into_array(self) -> [Option<T>; N] {
+        self.items
+    }
+}
+
+impl<T, const N: usize> Default for ArrayFixedSet<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> From<[Option<T>; N]> for ArrayFixedSet<T, N> {
+    fn from(items: [Option<T>; N]) -> Self {
+        ArrayFixedSet { items }
+    }
+}
+
+impl<T, const N: usize> From<ArrayFixedSet<T, N>> for FixedSet<T> {
+    fn from(set: ArrayFixedSet<T, N>) -> Self {
+        FixedSet {
+            items: Vec::from(set.items),
         }
-        Some(sum)
     }
+}
 
-    /// Collects all non-empty elements of the set into a Vec instance.
-    pub fn into_vec(self) -> Vec<T> {
-        self.items.into_iter().flatten().collect()
+/// Accesses the `index`-th slot of the set.
+///
+/// # Panics
+/// Panics if `index` is out of range. Use [ArrayFixedSet::get_item] for checked access.
+impl<T, const N: usize> Index<usize> for ArrayFixedSet<T, N> {
+    type Output = Option<T>;
+
+    // Out of range indices panic, as documented
+    #[allow(clippy::indexing_slicing)]
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.items[index]
     }
+}
+
+/// Mutably accesses the `index`-th slot of the set.
+///
+/// # Panics
+/// Panics if `index` is out of range. Use [ArrayFixedSet::get_mut] for checked access.
+impl<T, const N: usize> IndexMut<usize> for ArrayFixedSet<T, N> {
+    // Out of range indices panic, as documented
+    #[allow(clippy::indexing_slicing)]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.items[index]
+    }
+}
 
-    /// Returns an iterator that yields exactly `n` elements of the FixedSet. An element may be not be set which yields
-    /// a `None`.
+/// Yields every slot of the set, including the empty ones.
+impl<T, const N: usize> IntoIterator for ArrayFixedSet<T, N> {
+    type IntoIter = core::array::IntoIter<Option<T>, N>;
+    type Item = Option<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIterator::into_iter(self.items)
+    }
+}
+
+/// Yields a reference to every slot of the set, including the empty ones.
+impl<'a, T, const N: usize> IntoIterator for &'a ArrayFixedSet<T, N> {
+    type IntoIter = core::iter::Map<slice::Iter<'a, Option<T>>, fn(&'a Option<T>) -> Option<&'a T>>;
+    type Item = Option<&'a T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.iter().map(Option::as_ref)
+    }
+}
+
+/// Zeroizes every item and leaves all slots empty.
+#[cfg(feature = "zeroize")]
+impl<T: Zeroize, const N: usize> Zeroize for ArrayFixedSet<T, N> {
+    fn zeroize(&mut self) {
+        self.items.iter_mut().for_each(Zeroize::zeroize);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, const N: usize> serde::Serialize for ArrayFixedSet<T, N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(&self.items)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>, const N: usize> serde::Deserialize<'de> for ArrayFixedSet<T, N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let items = Vec::<Option<T>>::deserialize(deserializer)?;
+        let len = items.len();
+        let items = <[Option<T>; N]>::try_from(items)
+            .map_err(|_| <D::Error as serde::de::Error>::invalid_length(len, &"the size of the set"))?;
+        Ok(ArrayFixedSet { items })
+    }
+}
+
+/// Encodes like an `[Option<T>; N]`, without a length.
+#[cfg(feature = "borsh")]
+impl<T: borsh::BorshSerialize, const N: usize> borsh::BorshSerialize for ArrayFixedSet<T, N> {
+    fn serialize<W: borsh::maybestd::io::Write>(&self, writer: &mut W) -> borsh::maybestd::io::Result<()> {
+        self.items.serialize(writer)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl<T: borsh::BorshDeserialize, const N: usize> borsh::BorshDeserialize for ArrayFixedSet<T, N> {
+    fn deserialize_reader<R: borsh::maybestd::io::Read>(reader: &mut R) -> borsh::maybestd::io::Result<Self> {
+        Ok(ArrayFixedSet {
+            items: <[Option<T>; N]>::deserialize_reader(reader)?,
+        })
+    }
+}
+
+/// A fixed set of size _n_ whose slots can be filled concurrently through a shared reference.
+///
+/// Each slot can be set at most once, so items that have been set can be read while other slots are still being
+/// filled. No lock is taken; every slot synchronizes independently.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct AtomicFixedSet<T> {
+    items: Box<[OnceLock<T>]>,
+    filled: AtomicUsize,
+}
+
+#[cfg(feature = "std")]
+impl<T> AtomicFixedSet<T> {
+    /// Creates a new fixed set of size n.
+    pub fn new(n: usize) -> Self {
+        AtomicFixedSet {
+            items: (0..n).map(|_| OnceLock::new()).collect(),
+            filled: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the size of the fixed set, NOT the number of items that have been set.
+    pub fn size(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Set the `index`-th item to `val`. Returns false if `index` is out of range or the item has already been set, in
+    /// which case `val` is dropped.
+    pub fn set_item(&self, index: usize, val: T) -> bool {
+        self.try_set_item(index, val).is_ok()
+    }
+
+    /// Set the `index`-th item to `val`, reporting why the item could not be set.
+    ///
+    /// # Errors
+    /// Returns [FixedSetError::IndexOutOfBounds] if `index` is not less than the size of the set, or
+    /// [FixedSetError::AlreadySet] if another value was set first. The error hands `val` back.
+    pub fn try_set_item(&self, index: usize, val: T) -> Result<(), SetItemError<T>> {
+        let size = self.size();
+        let item = match self.items.get(index) {
+            Some(item) => item,
+            None => {
+                return Err(SetItemError {
+                    error: FixedSetError::IndexOutOfBounds { index, size },
+                    value: val,
+                })
+            },
+        };
+        item.set(val).map_err(|value| SetItemError {
+            error: FixedSetError::AlreadySet { index },
+            value,
+        })?;
+        self.filled.fetch_add(1, Ordering::Release);
+        Ok(())
+    }
+
+    /// Return a reference to the `index`-th item, or `None` if that item has not been set yet.
+    pub fn get_item(&self, index: usize) -> Option<&T> {
+        self.items.get(index).and_then(OnceLock::get)
+    }
+
+    /// Returns the number of items that have been set.
+    pub fn count_filled(&self) -> usize {
+        self.filled.load(Ordering::Acquire)
+    }
+
+    /// Returns true if every item in the set has been set. Once this returns true, every item can be read.
+    pub fn is_full(&self) -> bool {
+        self.count_filled() == self.size()
+    }
+
+    /// Returns an iterator that yields exactly `n` elements of the set. An element that is not set yet yields `None`.
     pub fn iter(&self) -> impl Iterator<Item = Option<&T>> + '_ {
-        self.items.iter().map(|e| e.as_ref())
+        self.items.iter().map(OnceLock::get)
+    }
+
+    /// Converts the set into a [FixedSet] with the same items.
+    pub fn into_fixed_set(self) -> FixedSet<T> {
+        Vec::from(self.items).into_iter().map(OnceLock::into_inner).collect()
+    }
+}
+
+/// Generates sets of up to 32 slots, each of which may or may not be filled.
+#[cfg(feature = "proptest")]
+impl<T> proptest::arbitrary::Arbitrary for FixedSet<T>
+where
+    T: proptest::arbitrary::Arbitrary + 'static,
+    T::Parameters: Clone,
+{
+    type Parameters = T::Parameters;
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        use proptest::{collection::vec, option::of, prelude::any_with, strategy::Strategy};
+        vec(of(any_with::<T>(args)), 0..32)
+            .prop_map(|items| FixedSet { items })
+            .boxed()
     }
 }
 
@@ -115,11 +923,12 @@ impl<T: Clone + PartialEq + Default> FixedSet<T> {
 
 #[cfg(test)]
 mod test {
-    use alloc::string::String;
+    use alloc::string::{String, ToString};
 
     use super::{FixedSet, *};
 
     #[derive(Eq, PartialEq, Clone, Debug, Default)]
+    #[cfg_attr(feature = "zeroize", derive(Zeroize))]
     struct Foo {
         baz: String,
     }
@@ -215,6 +1024,535 @@ mod test {
         assert_eq!(elems, vec![Some(&3), Some(&1), None, Some(&2), None]);
     }
 
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn try_map_parallel() {
+        let mut s = FixedSet::<usize>::new(100);
+        for i in (0..100).step_by(3) {
+            s.set_item(i, i);
+        }
+        let doubled = s.try_map_parallel(|v| Ok::<_, ()>(v * 2)).unwrap();
+        assert_eq!(doubled.size(), 100);
+        for (i, v) in doubled.iter().enumerate() {
+            assert_eq!(v.copied(), s.get_item(i).map(|v| v * 2));
+        }
+
+        let failed = s.try_map_parallel(|v| if v % 2 == 1 { Err(*v) } else { Ok(*v) });
+        assert_eq!(failed.unwrap_err(), 3);
+        assert_eq!(
+            FixedSet::<usize>::new(0)
+                .try_map_parallel(|_| Err::<usize, _>(()))
+                .unwrap()
+                .size(),
+            0
+        );
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn serialization() {
+        let mut s = FixedSet::<u16>::new(4);
+        s.set_item(1, 7);
+        s.set_item(3, 300);
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(json, "[null,7,null,300]");
+        let decoded = serde_json::from_str::<FixedSet<u16>>(&json).unwrap();
+        assert_eq!(decoded.iter().collect::<Vec<_>>(), s.iter().collect::<Vec<_>>());
+        let bytes = bincode::serialize(&s).unwrap();
+        let decoded = bincode::deserialize::<FixedSet<u16>>(&bytes).unwrap();
+        assert_eq!(decoded.iter().collect::<Vec<_>>(), s.iter().collect::<Vec<_>>());
+        assert_eq!(serde_json::from_str::<FixedSet<u16>>("[]").unwrap().size(), 0);
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn array_serialization() {
+        let s = ArrayFixedSet::<u16, 4>::from([None, Some(7), None, Some(300)]);
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(json, "[null,7,null,300]");
+        assert_eq!(serde_json::from_str::<ArrayFixedSet<u16, 4>>(&json).unwrap(), s);
+        // The same encoding as a heap-backed set of the same size
+        assert_eq!(
+            serde_json::from_str::<FixedSet<u16>>(&json).unwrap(),
+            FixedSet::from(s.clone())
+        );
+        let bytes = bincode::serialize(&s).unwrap();
+        assert_eq!(bincode::deserialize::<ArrayFixedSet<u16, 4>>(&bytes).unwrap(), s);
+        assert!(serde_json::from_str::<ArrayFixedSet<u16, 3>>(&json).is_err());
+        assert!(serde_json::from_str::<ArrayFixedSet<u16, 5>>(&json).is_err());
+    }
+
+    #[test]
+    fn replace_item() {
+        let mut s = FixedSet::<usize>::new(2);
+        assert_eq!(s.replace_item(0, 1), Ok(None));
+        assert_eq!(s.replace_item(0, 2), Ok(Some(1)));
+        assert_eq!(s.get_item(0), Some(&2));
+        assert_eq!(s.replace_item(2, 3), Err(3));
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec![Some(&2), None]);
+    }
+
+    #[test]
+    fn try_set_item() {
+        let mut s = FixedSet::<usize>::new(3);
+        assert_eq!(s.try_set_item(2, 1), Ok(()));
+        let err = s.try_set_item(3, 42).unwrap_err();
+        assert_eq!(err.error, FixedSetError::IndexOutOfBounds { index: 3, size: 3 });
+        assert_eq!(err.value, 42);
+        assert_eq!(err.to_string(), "Index 3 is out of bounds for a set of size 3");
+        #[cfg(feature = "std")]
+        {
+            let source: &dyn std::error::Error = &err;
+            assert!(source.source().is_none());
+            let _: &dyn std::error::Error = &err.error;
+        }
+        assert_eq!(FixedSetError::from(err), FixedSetError::IndexOutOfBounds {
+            index: 3,
+            size: 3
+        });
+    }
+
+    #[test]
+    fn take_and_drain() {
+        let mut s = FixedSet::<String>::new(4);
+        s.set_item(1, "a".into());
+        s.set_item(3, "b".into());
+        assert_eq!(s.take_item(1), Some("a".into()));
+        assert_eq!(s.take_item(1), None);
+        assert_eq!(s.take_item(9), None);
+        s.set_item(0, "c".into());
+        let drained = s.drain();
+        assert_eq!(s.count_filled(), 0);
+        assert_eq!(s.size(), 4);
+        assert_eq!(drained.collect::<Vec<_>>(), vec![(0, "c".into()), (3, "b".into())]);
+    }
+
+    #[test]
+    fn map_and_zip() {
+        let nonces = vec![Some(1u32), Some(2), None, Some(4)]
+            .into_iter()
+            .collect::<FixedSet<_>>();
+        let signatures = vec![Some(10u32), None, Some(30), Some(40)]
+            .into_iter()
+            .collect::<FixedSet<_>>();
+        let squared = nonces.map(|v| v * v);
+        assert_eq!(squared.into_vec(), vec![1, 4, 16]);
+        let pairs = nonces.zip_with(&signatures, |n, s| (*n, *s)).unwrap();
+        assert_eq!(pairs.iter().collect::<Vec<_>>(), vec![
+            Some(&(1, 10)),
+            None,
+            None,
+            Some(&(4, 40))
+        ]);
+        assert_eq!(
+            nonces.zip_with(&FixedSet::<u32>::new(3), |n, s| n + s).unwrap_err(),
+            FixedSetError::SizeMismatch { left: 4, right: 3 }
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn atomic_set() {
+        use std::{sync::Arc, thread};
+
+        let set = Arc::new(AtomicFixedSet::<usize>::new(8));
+        let handles = (0..8)
+            .map(|i| {
+                let set = Arc::clone(&set);
+                thread::spawn(move || {
+                    // Every thread races for every slot, but each slot is only set once
+                    (0..8).filter(|j| set.set_item((i + j) % 8, i)).count()
+                })
+            })
+            .collect::<Vec<_>>();
+        let total = handles.into_iter().map(|h| h.join().unwrap()).sum::<usize>();
+        assert_eq!(total, 8);
+        assert!(set.is_full());
+        assert_eq!(set.count_filled(), 8);
+        assert!(set.iter().all(|item| item.is_some()));
+        assert_eq!(
+            set.try_set_item(0, 10),
+            Err(SetItemError {
+                error: FixedSetError::AlreadySet { index: 0 },
+                value: 10
+            })
+        );
+        assert_eq!(
+            set.try_set_item(8, 11),
+            Err(SetItemError {
+                error: FixedSetError::IndexOutOfBounds { index: 8, size: 8 },
+                value: 11
+            })
+        );
+        let set = Arc::try_unwrap(set).unwrap().into_fixed_set();
+        assert!(set.is_full());
+        assert_eq!(set.size(), 8);
+
+        let partial = AtomicFixedSet::new(2);
+        assert!(partial.set_item(1, "x"));
+        assert!(!partial.is_full());
+        assert_eq!(partial.get_item(1), Some(&"x"));
+        assert_eq!(partial.into_fixed_set().iter().collect::<Vec<_>>(), vec![
+            None,
+            Some(&"x")
+        ]);
+    }
+
+    #[test]
+    fn fold_and_reduce() {
+        let mut s = (2..=5u64).collect::<FixedSet<_>>();
+        assert_eq!(s.fold_full(1, |acc, v| acc * v), Some(120));
+        assert_eq!(s.reduce_full(|acc, v| acc.max(*v)), Some(5));
+        s.clear_item(0);
+        assert_eq!(s.fold_full(1, |acc, v| acc * v), None);
+        assert_eq!(s.reduce_full(|acc, v| acc.max(*v)), None);
+        let empty = FixedSet::<u64>::new(0);
+        assert_eq!(empty.fold_full(7, |acc, v| acc + v), Some(7));
+        assert_eq!(empty.reduce_full(|acc, v| acc + v), None);
+    }
+
+    #[test]
+    fn indexing() {
+        let mut s = FixedSet::<String>::new(3);
+        s[1] = Some("a".into());
+        assert_eq!(s[1].as_deref(), Some("a"));
+        assert_eq!(s[0], None);
+        s.get_mut(1).unwrap().push('b');
+        assert_eq!(s.get_item(1).map(String::as_str), Some("ab"));
+        assert!(s.get_mut(0).is_none());
+        assert!(s.get_mut(3).is_none());
+        s[1].take();
+        assert_eq!(s.count_filled(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_range() {
+        let s = FixedSet::<u8>::new(3);
+        let _slot = &s[3];
+    }
+
+    #[test]
+    fn resizing() {
+        let mut s = FixedSet::<u8>::new(2);
+        s.set_item(1, 1);
+        assert!(s.resize(4).is_empty());
+        assert_eq!(s.size(), 4);
+        s.set_item(3, 3);
+        s.grow(3);
+        assert_eq!(s.size(), 4);
+        assert_eq!(s.truncate(5), vec![]);
+        assert_eq!(s.resize(1), vec![(1, 1), (3, 3)]);
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec![None]);
+
+        let mut s = vec![None, Some(1u8), None, Some(2), None, None]
+            .into_iter()
+            .collect::<FixedSet<u8>>();
+        s.truncate_to_filled();
+        assert_eq!(s.size(), 4);
+        s.clear_item(1);
+        s.clear_item(3);
+        s.truncate_to_filled();
+        assert_eq!(s.size(), 0);
+    }
+
+    #[test]
+    fn constructors() {
+        let s = FixedSet::from(vec![None, Some(2u8)]);
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec![None, Some(&2)]);
+        let s = FixedSet::try_from(vec![1u8, 2, 3]).unwrap();
+        assert!(s.is_full());
+        assert_eq!(s.into_vec(), vec![1, 2, 3]);
+        let s = FixedSet::filled_with(4, |i| i * 10);
+        assert!(s.is_full());
+        assert_eq!(s.into_vec(), vec![0, 10, 20, 30]);
+        assert_eq!(FixedSet::filled_with(0, |i| i).size(), 0);
+    }
+
+    #[test]
+    fn push_next() {
+        let mut s = FixedSet::<char>::new(3);
+        s.set_item(0, 'a');
+        assert_eq!(s.first_empty(), Some(1));
+        assert_eq!(s.push_next('b'), Ok(1));
+        assert_eq!(s.push_next('c'), Ok(2));
+        assert_eq!(s.first_empty(), None);
+        assert_eq!(s.push_next('d'), Err('d'));
+        s.clear_item(1);
+        assert_eq!(s.push_next('e'), Ok(1));
+        assert_eq!(s.into_vec(), vec!['a', 'e', 'c']);
+    }
+
+    #[test]
+    fn equality_and_hashing() {
+        use std::collections::HashSet;
+
+        let a = FixedSet::<u8>::from(vec![Some(1u8), None]);
+        let b = FixedSet::<u8>::from(vec![None, Some(1u8)]);
+        let c = FixedSet::<u8>::from(vec![Some(1u8), None, None]);
+        assert_eq!(a, FixedSet::<u8>::from(vec![Some(1u8), None]));
+        assert_ne!(a, b);
+        assert_ne!(a, c);
+        let keys = vec![a.clone(), b, c, a].into_iter().collect::<HashSet<_>>();
+        assert_eq!(keys.len(), 3);
+    }
+
+    #[test]
+    fn iter_filled() {
+        let s = FixedSet::<u8>::from(vec![None, Some(5), Some(6), None]);
+        assert_eq!(s.iter_filled().collect::<Vec<_>>(), vec![(1, &5), (2, &6)]);
+        assert_eq!(s.iter_empty_indices().collect::<Vec<_>>(), vec![0, 3]);
+        let signers = s.iter_filled().fold(0u8, |bitmap, (index, _)| bitmap | (1 << index));
+        assert_eq!(signers, 0b0110);
+    }
+
+    /// A key share whose bytes live on the heap, shared with the test so it can check that they were wiped.
+    #[cfg(feature = "zeroize")]
+    #[derive(Clone, Default)]
+    struct Share(alloc::rc::Rc<core::cell::RefCell<[u8; 32]>>);
+
+    #[cfg(feature = "zeroize")]
+    impl Zeroize for Share {
+        fn zeroize(&mut self) {
+            self.0.borrow_mut().zeroize();
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn zeroizing() {
+        let mut s = FixedSet::<[u8; 4]>::from(vec![Some([1; 4]), Some([2; 4]), None]);
+        s.clear_item(0);
+        s.clear_item(5);
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec![None, Some(&[2; 4]), None]);
+        s.retain(|_, _| false);
+        assert_eq!(s.count_filled(), 0);
+        assert!(s.set_item(1, [3; 4]));
+        s.zeroize();
+        assert_eq!(s.size(), 3);
+        assert_eq!(s.count_filled(), 0);
+
+        let key = Share::default();
+        key.0.replace([7; 32]);
+        let mut s = FixedSet::from(vec![Some(key.clone()), Some(key.clone()), None]);
+        s.clear_item(1);
+        assert_eq!(*key.0.borrow(), [0; 32]);
+        assert_eq!(s.count_filled(), 1);
+        let mut s = ValidatedFixedSet::new(2, |_: &Share| true);
+        key.0.replace([7; 32]);
+        s.set_item(0, key.clone()).unwrap();
+        s.clear_item(0);
+        assert_eq!(*key.0.borrow(), [0; 32]);
+        let mut a = ArrayFixedSet::from([Some(key.clone()), None]);
+        key.0.replace([7; 32]);
+        a.clear_item(0);
+        assert_eq!(*key.0.borrow(), [0; 32]);
+        key.0.replace([7; 32]);
+        a.set_item(1, key.clone());
+        a.zeroize();
+        assert_eq!(*key.0.borrow(), [0; 32]);
+        assert_eq!(a.count_filled(), 0);
+        key.0.replace([7; 32]);
+        drop(zeroize::Zeroizing::new(FixedSet::from(vec![None, Some(key.clone())])));
+        assert_eq!(*key.0.borrow(), [0; 32]);
+        // Items handed back are the caller's to zeroize
+        key.0.replace([7; 32]);
+        let taken = FixedSet::from(vec![Some(key.clone())]).into_vec();
+        assert_eq!(*taken[0].0.borrow(), [7; 32]);
+    }
+
+    #[test]
+    fn swap_and_rotate() {
+        let mut s = FixedSet::<u8>::from(vec![Some(0), Some(1), None, Some(3)]);
+        s.swap(0, 2).unwrap();
+        assert_eq!(s, FixedSet::from(vec![None, Some(1), Some(0), Some(3)]));
+        assert_eq!(s.swap(1, 4), Err(FixedSetError::IndexOutOfBounds { index: 4, size: 4 }));
+        s.rotate_left(1);
+        assert_eq!(s, FixedSet::from(vec![Some(1), Some(0), Some(3), None]));
+        s.rotate_right(6);
+        assert_eq!(s, FixedSet::from(vec![Some(3), None, Some(1), Some(0)]));
+        let mut empty = FixedSet::<u8>::new(0);
+        empty.rotate_left(3);
+        empty.rotate_right(3);
+        assert_eq!(empty.size(), 0);
+    }
+
+    #[test]
+    fn retain_and_clear_all() {
+        let mut s = (10..16u32).collect::<FixedSet<u32>>();
+        s.clear_item(1);
+        let mut visited = Vec::new();
+        s.retain(|index, val| {
+            visited.push(index);
+            index != 2 && val % 2 == 0
+        });
+        assert_eq!(visited, vec![0, 2, 3, 4, 5]);
+        assert_eq!(s.iter_filled().collect::<Vec<_>>(), vec![(0, &10), (4, &14)]);
+        s.clear_all();
+        assert_eq!(s.size(), 6);
+        assert_eq!(s.count_filled(), 0);
+    }
+
+    #[test]
+    fn validated_set() {
+        let mut s = ValidatedFixedSet::new(3, |v: &u32| *v < 100);
+        assert_eq!(s.set_item(0, 7), Ok(()));
+        assert_eq!(
+            s.set_item(1, 100),
+            Err(SetItemError {
+                error: FixedSetError::InvalidItem { index: 1 },
+                value: 100
+            })
+        );
+        assert_eq!(
+            s.set_item(3, 1),
+            Err(SetItemError {
+                error: FixedSetError::IndexOutOfBounds { index: 3, size: 3 },
+                value: 1
+            })
+        );
+        assert_eq!(s.set_item(2, 99), Ok(()));
+        assert_eq!(s.take_item(2), Some(99));
+        s.clear_item(0);
+        assert_eq!(s.as_set().count_filled(), 0);
+        assert!(format!("{:?}", s).starts_with("ValidatedFixedSet { set: FixedSet"));
+        assert_eq!(s.into_inner().size(), 3);
+    }
+
+    #[test]
+    fn non_clone_items() {
+        #[cfg_attr(feature = "zeroize", derive(Zeroize))]
+        struct Session(u8);
+
+        let mut s = FixedSet::<Session>::new(2);
+        assert!(s.set_item(1, Session(7)));
+        assert_eq!(s.get_item(1).map(|session| session.0), Some(7));
+        assert!(!s.is_full());
+        s.clear_item(1);
+        assert!(s.iter().all(|item| item.is_none()));
+        assert!(s.into_vec().is_empty());
+    }
+
+    #[test]
+    fn progress() {
+        let mut s = FixedSet::<usize>::new(5);
+        assert_eq!((s.count_filled(), s.count_empty()), (0, 5));
+        s.set_item(0, 1);
+        s.set_item(3, 1);
+        s.set_item(4, 1);
+        assert_eq!((s.count_filled(), s.count_empty()), (3, 2));
+        assert_eq!(s.missing_indices().collect::<Vec<_>>(), vec![1, 2]);
+        s.set_item(1, 1);
+        s.set_item(2, 1);
+        assert_eq!(s.missing_indices().next(), None);
+        assert_eq!(FixedSet::<usize>::new(0).count_empty(), 0);
+    }
+
+    #[test]
+    fn iterator_traits() {
+        let s = vec![Some(1u32), None, Some(3)].into_iter().collect::<FixedSet<u32>>();
+        assert_eq!(s.size(), 3);
+        assert_eq!(s.get_item(2), Some(&3));
+        let mut refs = Vec::new();
+        for item in &s {
+            refs.push(item);
+        }
+        assert_eq!(refs, vec![Some(&1), None, Some(&3)]);
+        let doubled = s.into_iter().map(|v| v.map(|v| v * 2)).collect::<FixedSet<u32>>();
+        assert_eq!(doubled.into_iter().collect::<Vec<_>>(), vec![Some(2), None, Some(6)]);
+
+        let full = (1..=4u32).collect::<FixedSet<u32>>();
+        assert!(full.is_full());
+        assert_eq!(full.sum(), Some(10));
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn borsh() {
+        use borsh::BorshDeserialize;
+
+        let s = FixedSet::<u16>::from(vec![Some(0x0102), None]);
+        let bytes = borsh::to_vec(&s).unwrap();
+        assert_eq!(bytes, [2, 0, 0, 0, 1, 2, 1, 0]);
+        assert_eq!(FixedSet::<u16>::try_from_slice(&bytes).unwrap(), s);
+
+        let s = ArrayFixedSet::<u16, 2>::from([None, Some(0x0102)]);
+        let bytes = borsh::to_vec(&s).unwrap();
+        assert_eq!(bytes, [0, 1, 2, 1]);
+        assert_eq!(ArrayFixedSet::<u16, 2>::try_from_slice(&bytes).unwrap(), s);
+        assert!(ArrayFixedSet::<u16, 3>::try_from_slice(&bytes).is_err());
+    }
+
+    #[test]
+    fn array_set() {
+        let mut s = ArrayFixedSet::<usize, 4>::new();
+        assert_eq!(s.size(), 4);
+        assert!(!s.is_full());
+        assert_eq!(s.sum(), None);
+        assert!(s.set_item(0, 5));
+        assert!(s.set_item(2, 3));
+        assert!(!s.set_item(4, 1));
+        assert_eq!(s.get_item(2), Some(&3));
+        assert_eq!(s.get_item(1), None);
+        assert_eq!(s.get_item(4), None);
+        assert_eq!(s.search(&3), Some(2));
+        assert_eq!(s.search(&7), None);
+        s.set_item(1, 4);
+        s.set_item(3, 2);
+        assert!(s.is_full());
+        assert_eq!(s.sum(), Some(14));
+        s.clear_item(1);
+        s.clear_item(9);
+        assert_eq!(s.iter().collect::<Vec<_>>(), vec![Some(&5), None, Some(&3), Some(&2)]);
+        assert_eq!(FixedSet::<usize>::from(s.clone()).into_vec(), vec![5, 3, 2]);
+        assert_eq!(s.into_array(), [Some(5), None, Some(3), Some(2)]);
+
+        let empty = ArrayFixedSet::<usize, 0>::default();
+        assert!(empty.is_full());
+        assert_eq!(empty.sum(), Some(0));
+    }
+
+    #[test]
+    fn array_set_slots() {
+        let mut s = ArrayFixedSet::<usize, 3>::new();
+        assert_eq!(s.first_empty(), Some(0));
+        assert_eq!(s.push_next(4), Ok(0));
+        assert_eq!(s.try_set_item(2, 6), Ok(()));
+        assert_eq!(s.try_set_item(3, 8).unwrap_err().value, 8);
+        assert_eq!(s.count_filled(), 2);
+        assert_eq!(s.count_empty(), 1);
+        assert_eq!(s.missing_indices().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(s.iter_filled().collect::<Vec<_>>(), vec![(0, &4), (2, &6)]);
+        assert_eq!(s.replace_item(2, 5), Ok(Some(6)));
+        assert_eq!(s[2], Some(5));
+        s[1] = Some(1);
+        assert_eq!(s.push_next(9), Err(9));
+        assert_eq!(s.reduce_full(|a, b| a * b), Some(20));
+        assert_eq!(s.fold_full(1, |a, b| a + b), Some(11));
+        s.swap(0, 1).unwrap();
+        s.rotate_left(4);
+        assert_eq!((&s).into_iter().collect::<Vec<_>>(), vec![Some(&4), Some(&5), Some(&1)]);
+        *s.get_mut(0).unwrap() += 1;
+        assert_eq!(s.take_item(0), Some(5));
+        assert_eq!(s.take_item(0), None);
+        s.retain(|_, item| *item > 1);
+        assert_eq!(s.clone().into_iter().collect::<Vec<_>>(), vec![None, Some(5), None]);
+        assert_eq!(s.drain().collect::<Vec<_>>(), vec![(1, 5)]);
+        assert_eq!(s.count_filled(), 0);
+        assert_eq!(s.size(), 3);
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn arbitrary_sets(s in proptest::prelude::any::<FixedSet<u8>>()) {
+            let filled = s.iter().filter(Option::is_some).count();
+            proptest::prop_assert!(s.size() < 32);
+            proptest::prop_assert_eq!(s.is_full(), filled == s.size());
+            proptest::prop_assert_eq!(s.clone().into_vec().len(), filled);
+        }
+    }
+
     #[test]
     fn into_vec() {
         let mut s = FixedSet::<usize>::new(5);
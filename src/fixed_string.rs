@@ -0,0 +1,440 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A string with a fixed capacity, stored inline without allocating.
+//!
+//! [FixedString] is intended for short labels in `no_std` contexts where heap strings are not available, such as
+//! device labels, human-readable parts of addresses and currency codes. Pushing text that does not fit is an error
+//! rather than a reallocation, and truncation always happens on a character boundary so the contents remain valid
+//! UTF-8.
+
+use core::{
+    cmp::Ordering,
+    convert::TryFrom,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    str::FromStr,
+};
+
+use snafu::prelude::*;
+
+/// Errors for [FixedString].
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum FixedStringError {
+    /// The text does not fit in the remaining capacity.
+    #[snafu(display("The text does not fit in a string with a capacity of {capacity} bytes"))]
+    CapacityExceeded {
+        /// The capacity of the string in bytes
+        capacity: usize,
+    },
+}
+
+/// A UTF-8 string of at most `N` bytes, stored inline.
+///
+/// ```edition2018
+/// # use tari_utilities::fixed_string::FixedString;
+/// let mut label = FixedString::<8>::new();
+/// label.push_str("Tari").unwrap();
+/// label.push('🚀').unwrap();
+/// assert_eq!(label, "Tari🚀");
+/// assert!(label.push('!').is_err());
+///
+/// // Truncation never splits a character
+/// label.truncate(6);
+/// assert_eq!(label, "Tari");
+/// ```
+#[derive(Clone, Copy)]
+pub struct FixedString<const N: usize> {
+    bytes: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> FixedString<N> {
+    /// Create an empty string.
+    pub const fn new() -> Self {
+        Self {
+            bytes: [0u8; N],
+            len: 0,
+        }
+    }
+
+    /// Create a string from as much of `s` as fits, truncated on a character boundary.
+    pub fn from_str_truncated(s: &str) -> Self {
+        let mut end = s.len().min(N);
+        while !s.is_char_boundary(end) {
+            end = end.saturating_sub(1);
+        }
+        let mut string = Self::new();
+        if let (Some(dst), Some(src)) = (string.bytes.get_mut(..end), s.as_bytes().get(..end)) {
+            dst.copy_from_slice(src);
+            string.len = end;
+        }
+        string
+    }
+
+    /// Return the contents as a string slice.
+    pub fn as_str(&self) -> &str {
+        // The contents are only ever written from `str`s and truncated on character boundaries
+        core::str::from_utf8(&self.bytes[..self.len]).unwrap_or_default()
+    }
+
+    /// Return the length of the string in bytes.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the string is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return the capacity of the string in bytes.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Return the number of bytes that can still be pushed.
+    pub const fn remaining_capacity(&self) -> usize {
+        N.saturating_sub(self.len)
+    }
+
+    /// Append a character.
+    ///
+    /// # Errors
+    /// Returns [FixedStringError::CapacityExceeded] and leaves the string unchanged if the character does not fit.
+    pub fn push(&mut self, c: char) -> Result<(), FixedStringError> {
+        self.push_str(c.encode_utf8(&mut [0u8; 4]))
+    }
+
+    /// Append a string slice.
+    ///
+    /// # Errors
+    /// Returns [FixedStringError::CapacityExceeded] and leaves the string unchanged if the slice does not fit.
+    pub fn push_str(&mut self, s: &str) -> Result<(), FixedStringError> {
+        let end = self.len.saturating_add(s.len());
+        let dst = self
+            .bytes
+            .get_mut(self.len..end)
+            .context(CapacityExceededSnafu { capacity: N })?;
+        dst.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+
+    /// Shorten the string to at most `new_len` bytes. If `new_len` is not on a character boundary, the string is
+    /// shortened further to the preceding boundary. This has no effect if `new_len` is not less than the length.
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len >= self.len {
+            return;
+        }
+        let mut end = new_len;
+        while !self.as_str().is_char_boundary(end) {
+            end = end.saturating_sub(1);
+        }
+        if let Some(tail) = self.bytes.get_mut(end..self.len) {
+            tail.fill(0);
+        }
+        self.len = end;
+    }
+
+    /// Remove the contents of the string.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+}
+
+impl<const N: usize> Default for FixedString<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Deref for FixedString<N> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> AsRef<str> for FixedString<N> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> TryFrom<&str> for FixedString<N> {
+    type Error = FixedStringError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let mut string = Self::new();
+        string.push_str(s)?;
+        Ok(string)
+    }
+}
+
+impl<const N: usize> FromStr for FixedString<N> {
+    type Err = FixedStringError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+
+impl<const N: usize> fmt::Display for FixedString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Debug for FixedString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> fmt::Write for FixedString<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s).map_err(|_| fmt::Error)
+    }
+}
+
+impl<const N: usize, const M: usize> PartialEq<FixedString<M>> for FixedString<N> {
+    fn eq(&self, other: &FixedString<M>) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> Eq for FixedString<N> {}
+
+impl<const N: usize> PartialEq<str> for FixedString<N> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for FixedString<N> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<const N: usize> PartialOrd for FixedString<N> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> Ord for FixedString<N> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<const N: usize> Hash for FixedString<N> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for FixedString<N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for FixedString<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FixedStringVisitor<const N: usize>;
+
+        impl<'de, const N: usize> serde::de::Visitor<'de> for FixedStringVisitor<N> {
+            type Value = FixedString<N>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(formatter, "a string of at most {} bytes", N)
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                FixedString::try_from(v).map_err(|_| E::invalid_length(v.len(), &self))
+            }
+        }
+
+        deserializer.deserialize_str(FixedStringVisitor)
+    }
+}
+
+/// Generates arbitrary text, truncated to the capacity.
+#[cfg(feature = "arbitrary")]
+impl<'a, const N: usize> arbitrary::Arbitrary<'a> for FixedString<N> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        <&str>::arbitrary(u).map(Self::from_str_truncated)
+    }
+}
+
+/// Generates arbitrary text, truncated to the capacity.
+#[cfg(feature = "proptest")]
+impl<const N: usize> proptest::arbitrary::Arbitrary for FixedString<N> {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        use proptest::{prelude::any, strategy::Strategy};
+        any::<alloc::string::String>()
+            .prop_map(|s| Self::from_str_truncated(&s))
+            .boxed()
+    }
+}
+
+/// Encodes in the same format as a borsh `String`: a `u32` length followed by the UTF-8 bytes.
+#[cfg(feature = "borsh")]
+impl<const N: usize> borsh::BorshSerialize for FixedString<N> {
+    fn serialize<W: borsh::maybestd::io::Write>(&self, writer: &mut W) -> borsh::maybestd::io::Result<()> {
+        borsh::BorshSerialize::serialize(self.as_str(), writer)
+    }
+}
+
+/// Rejects strings longer than `N` bytes before reading them.
+#[cfg(feature = "borsh")]
+impl<const N: usize> borsh::BorshDeserialize for FixedString<N> {
+    fn deserialize_reader<R: borsh::maybestd::io::Read>(reader: &mut R) -> borsh::maybestd::io::Result<Self> {
+        use borsh::maybestd::io::{Error, ErrorKind};
+
+        let len = u32::deserialize_reader(reader)?;
+        let len = usize::try_from(len)
+            .ok()
+            .filter(|len| *len <= N)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "string is too long"))?;
+        let mut bytes = [0u8; N];
+        let buf = bytes.get_mut(..len).unwrap_or_default();
+        reader.read_exact(buf)?;
+        let s = core::str::from_utf8(buf).map_err(|_| Error::new(ErrorKind::InvalidData, "string is not UTF-8"))?;
+        Self::try_from(s).map_err(|_| Error::new(ErrorKind::InvalidData, "string is too long"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{format, string::ToString};
+    use core::fmt::Write;
+
+    use super::*;
+
+    #[test]
+    fn push() {
+        let mut s = FixedString::<6>::new();
+        assert!(s.is_empty());
+        s.push_str("ab").unwrap();
+        s.push('é').unwrap();
+        assert_eq!(s.as_str(), "abé");
+        assert_eq!(s.len(), 4);
+        assert_eq!(s.remaining_capacity(), 2);
+        assert_eq!(s.push('🖖'), Err(FixedStringError::CapacityExceeded { capacity: 6 }));
+        assert_eq!(
+            s.push_str("xyz"),
+            Err(FixedStringError::CapacityExceeded { capacity: 6 })
+        );
+        assert_eq!(s, "abé");
+        s.push_str("yz").unwrap();
+        assert_eq!(s, "abéyz");
+        assert!(FixedString::<0>::new().push('a').is_err());
+    }
+
+    #[test]
+    fn truncation() {
+        let mut s = FixedString::<16>::try_from("a🖖b").unwrap();
+        s.truncate(100);
+        assert_eq!(s, "a🖖b");
+        s.truncate(4);
+        assert_eq!(s, "a");
+        s.clear();
+        assert!(s.is_empty());
+
+        assert_eq!(FixedString::<3>::from_str_truncated("héllo"), "hé");
+        assert_eq!(FixedString::<2>::from_str_truncated("héllo"), "h");
+        assert_eq!(FixedString::<4>::from_str_truncated("🖖"), "🖖");
+        assert_eq!(FixedString::<3>::from_str_truncated("🖖"), "");
+    }
+
+    #[test]
+    fn comparison() {
+        let a = FixedString::<8>::try_from("abc").unwrap();
+        let mut b = FixedString::<4>::try_from("abcd").unwrap();
+        assert_ne!(a, b);
+        b.truncate(3);
+        assert_eq!(a, b);
+        let c = FixedString::<8>::try_from("abd").unwrap();
+        assert!(a < c);
+        let mut truncated = FixedString::<8>::try_from("abcdef").unwrap();
+        truncated.truncate(3);
+        assert_eq!(truncated, a);
+        assert_eq!(truncated.cmp(&a), Ordering::Equal);
+    }
+
+    #[test]
+    fn formatting() {
+        let mut s: FixedString<16> = "id".parse().unwrap();
+        write!(s, "-{}", 42).unwrap();
+        assert_eq!(s.to_string(), "id-42");
+        assert_eq!(format!("{:?}", s), "\"id-42\"");
+        assert_eq!(format!("{:>7}", s), "  id-42");
+        assert!(write!(s, "{}", "x".repeat(20)).is_err());
+        assert!("too long for this".parse::<FixedString<4>>().is_err());
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn borsh() {
+        use borsh::BorshDeserialize;
+
+        let s = FixedString::<8>::try_from("tari").unwrap();
+        let bytes = borsh::to_vec(&s).unwrap();
+        assert_eq!(bytes, borsh::to_vec("tari").unwrap());
+        assert_eq!(FixedString::<8>::try_from_slice(&bytes).unwrap(), s);
+        assert!(FixedString::<3>::try_from_slice(&bytes).is_err());
+        assert!(FixedString::<8>::try_from_slice(&[2, 0, 0, 0, 0xff, 0xfe]).is_err());
+        assert!(FixedString::<8>::try_from_slice(&[9, 0, 0, 0]).is_err());
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn serialization() {
+        let s = FixedString::<8>::try_from("tari").unwrap();
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(json, "\"tari\"");
+        assert_eq!(serde_json::from_str::<FixedString<8>>(&json).unwrap(), s);
+        assert!(serde_json::from_str::<FixedString<3>>(&json).is_err());
+        let bytes = bincode::serialize(&s).unwrap();
+        assert_eq!(bincode::deserialize::<FixedString<8>>(&bytes).unwrap(), s);
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn arbitrary_strings(s in proptest::prelude::any::<FixedString<5>>()) {
+            proptest::prop_assert!(s.len() <= 5);
+            proptest::prop_assert_eq!(FixedString::<5>::try_from(s.as_str()), Ok(s));
+        }
+    }
+}
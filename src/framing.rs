@@ -0,0 +1,396 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Length-prefixed framing for byte streams.
+//!
+//! Each frame is the payload length as an unsigned LEB128 varint followed by the payload. Length prefixes must be
+//! minimally encoded, and frames longer than the configured maximum are rejected before any of the payload is
+//! buffered.
+//!
+//! [Framing] writes frames and, with the `std` feature, reads them from blocking [Read](std::io::Read) and
+//! [Write](std::io::Write) streams. For non-blocking or async transports, a [FrameDecoder] accepts bytes as they
+//! arrive and keeps any partial frame until the rest of it is pushed.
+//!
+//! ```edition2018
+//! # use tari_utilities::framing::Framing;
+//! let framing = Framing::new(1024);
+//! let mut stream = framing.encode(b"hello").unwrap();
+//! stream.extend(framing.encode(b"world").unwrap());
+//!
+//! let mut decoder = framing.decoder();
+//! decoder.push(&stream[..4]);
+//! assert_eq!(decoder.next_frame().unwrap(), None);
+//! decoder.push(&stream[4..]);
+//! assert_eq!(decoder.next_frame().unwrap(), Some(b"hello".to_vec()));
+//! assert_eq!(decoder.next_frame().unwrap(), Some(b"world".to_vec()));
+//! assert_eq!(decoder.next_frame().unwrap(), None);
+//! ```
+
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use snafu::prelude::*;
+
+use crate::varint::{self, Varint, VarintError};
+
+/// The maximum frame size used by [Framing::default], 16 MiB.
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+/// The longest length prefix.
+const MAX_VARINT_LEN: usize = <u64 as Varint>::MAX_LEN;
+
+/// Errors for framing.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum FramingError {
+    /// The frame is longer than the maximum frame size.
+    #[snafu(display("Frame of {size} bytes exceeds the maximum of {max_frame_size} bytes"))]
+    FrameTooLarge {
+        /// The length of the frame
+        size: u64,
+        /// The maximum frame size
+        max_frame_size: usize,
+    },
+    /// The length prefix is not a minimally encoded varint that fits in a `u64`.
+    #[snafu(display("Invalid frame length prefix"))]
+    InvalidLengthPrefix {},
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FramingError {}
+
+/// Decode a length prefix from the start of `bytes`, returning the length and the number of bytes read, or `None` if
+/// `bytes` ends in the middle of the prefix.
+fn decode_prefix(bytes: &[u8]) -> Result<Option<(u64, usize)>, FramingError> {
+    match varint::decode(bytes) {
+        Ok(decoded) => Ok(Some(decoded)),
+        Err(VarintError::Truncated {}) => Ok(None),
+        Err(_) => InvalidLengthPrefixSnafu.fail(),
+    }
+}
+
+/// A length-prefixed framing codec with a maximum frame size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Framing {
+    max_frame_size: usize,
+}
+
+impl Framing {
+    /// Create a codec that accepts frames of up to `max_frame_size` bytes.
+    pub const fn new(max_frame_size: usize) -> Self {
+        Self { max_frame_size }
+    }
+
+    /// Return the maximum frame size.
+    pub const fn max_frame_size(&self) -> usize {
+        self.max_frame_size
+    }
+
+    /// Return a push decoder with this codec's maximum frame size.
+    pub fn decoder(&self) -> FrameDecoder {
+        FrameDecoder::new(self.max_frame_size)
+    }
+
+    fn check_size(self, size: u64) -> Result<usize, FramingError> {
+        match usize::try_from(size) {
+            Ok(len) if len <= self.max_frame_size => Ok(len),
+            _ => FrameTooLargeSnafu {
+                size,
+                max_frame_size: self.max_frame_size,
+            }
+            .fail(),
+        }
+    }
+
+    /// Append the frame for `payload` to `out`.
+    ///
+    /// # Errors
+    /// Returns [FramingError::FrameTooLarge] if the payload is longer than the maximum frame size.
+    pub fn encode_into(&self, payload: &[u8], out: &mut Vec<u8>) -> Result<(), FramingError> {
+        let mut prefix = [0u8; MAX_VARINT_LEN];
+        let prefix = prefix_for(self.check_size(payload.len() as u64)?, &mut prefix);
+        out.reserve(prefix.len().saturating_add(payload.len()));
+        out.extend_from_slice(prefix);
+        out.extend_from_slice(payload);
+        Ok(())
+    }
+
+    /// Return the frame for `payload`.
+    ///
+    /// # Errors
+    /// Returns [FramingError::FrameTooLarge] if the payload is longer than the maximum frame size.
+    pub fn encode(&self, payload: &[u8]) -> Result<Vec<u8>, FramingError> {
+        let mut out = Vec::new();
+        self.encode_into(payload, &mut out)?;
+        Ok(out)
+    }
+
+    /// Write the frame for `payload` to `writer`.
+    ///
+    /// # Errors
+    /// Returns an [InvalidInput](std::io::ErrorKind::InvalidInput) error wrapping [FramingError::FrameTooLarge] if the
+    /// payload is too long, or any error from the writer.
+    #[cfg(feature = "std")]
+    pub fn write_frame<W: std::io::Write>(&self, writer: &mut W, payload: &[u8]) -> std::io::Result<()> {
+        let len = self
+            .check_size(payload.len() as u64)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err))?;
+        let mut prefix = [0u8; MAX_VARINT_LEN];
+        writer.write_all(prefix_for(len, &mut prefix))?;
+        writer.write_all(payload)
+    }
+
+    /// Read the next frame from `reader`, returning `None` if the stream ends cleanly between frames.
+    ///
+    /// # Errors
+    /// Returns an [InvalidData](std::io::ErrorKind::InvalidData) error wrapping a [FramingError] for a malformed or
+    /// oversized length prefix, an [UnexpectedEof](std::io::ErrorKind::UnexpectedEof) error if the stream ends
+    /// within a frame, or any error from the reader.
+    #[cfg(feature = "std")]
+    pub fn read_frame<R: std::io::Read>(&self, reader: &mut R) -> std::io::Result<Option<Vec<u8>>> {
+        use std::io::{Error, ErrorKind};
+
+        let mut prefix = [0u8; MAX_VARINT_LEN];
+        let mut prefix_len = 0;
+        let size = loop {
+            let Some(byte) = prefix.get_mut(prefix_len) else {
+                return Err(Error::new(ErrorKind::InvalidData, FramingError::InvalidLengthPrefix {}));
+            };
+            let mut next = [0u8];
+            if reader.read(&mut next)? == 0 {
+                if prefix_len == 0 {
+                    return Ok(None);
+                }
+                return Err(ErrorKind::UnexpectedEof.into());
+            }
+            *byte = next[0];
+            prefix_len = prefix_len.saturating_add(1);
+            match decode_prefix(prefix.get(..prefix_len).unwrap_or_default()) {
+                Ok(Some((size, _))) => break size,
+                Ok(None) => {},
+                Err(err) => return Err(Error::new(ErrorKind::InvalidData, err)),
+            }
+        };
+        let len = self
+            .check_size(size)
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+        let mut payload = alloc::vec![0u8; len];
+        reader.read_exact(&mut payload)?;
+        Ok(Some(payload))
+    }
+}
+
+impl Default for Framing {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_FRAME_SIZE)
+    }
+}
+
+fn prefix_for(len: usize, buf: &mut [u8; MAX_VARINT_LEN]) -> &[u8] {
+    // The buffer holds the longest length prefix
+    let prefix_len = varint::encode_into(len as u64, buf).unwrap_or_default();
+    buf.get(..prefix_len).unwrap_or_default()
+}
+
+/// An incremental frame decoder. Push bytes as they arrive, then call [FrameDecoder::next_frame] until it returns
+/// `None`. Any partial frame is kept until the rest of it is pushed.
+///
+/// An error means the stream is corrupt and cannot be resynchronised, and the same error will be returned by every
+/// later call to [FrameDecoder::next_frame].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameDecoder {
+    framing: Framing,
+    buffer: Vec<u8>,
+    position: usize,
+}
+
+impl FrameDecoder {
+    /// Create a decoder that accepts frames of up to `max_frame_size` bytes.
+    pub fn new(max_frame_size: usize) -> Self {
+        Self {
+            framing: Framing::new(max_frame_size),
+            buffer: Vec::new(),
+            position: 0,
+        }
+    }
+
+    fn pending(&self) -> &[u8] {
+        self.buffer.get(self.position..).unwrap_or_default()
+    }
+
+    /// Append received bytes to the decoder.
+    pub fn push(&mut self, data: &[u8]) {
+        if self.position > 0 {
+            self.buffer.drain(..self.position);
+            self.position = 0;
+        }
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// Return the next complete frame, or `None` if more bytes are needed.
+    ///
+    /// # Errors
+    /// Returns [FramingError::InvalidLengthPrefix] for a malformed length prefix and [FramingError::FrameTooLarge] if
+    /// the announced frame is longer than the maximum frame size. The oversized payload is never buffered past what
+    /// has already been pushed.
+    pub fn next_frame(&mut self) -> Result<Option<Vec<u8>>, FramingError> {
+        let pending = self.pending();
+        let Some((size, prefix_len)) = decode_prefix(pending)? else {
+            return Ok(None);
+        };
+        let len = self.framing.check_size(size)?;
+        let end = prefix_len.saturating_add(len);
+        let Some(frame) = pending.get(prefix_len..end).map(<[u8]>::to_vec) else {
+            return Ok(None);
+        };
+        self.position = self.position.saturating_add(end);
+        Ok(Some(frame))
+    }
+
+    /// Return the length of the frame being received, once its length prefix is complete.
+    pub fn pending_frame_len(&self) -> Option<u64> {
+        decode_prefix(self.pending()).ok().flatten().map(|(size, _)| size)
+    }
+
+    /// Return the number of buffered bytes that have not been returned as frames.
+    pub fn buffered_len(&self) -> usize {
+        self.pending().len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn prefixes() {
+        let cases: [(u64, &[u8]); 5] = [
+            (0, &[0]),
+            (127, &[0x7f]),
+            (128, &[0x80, 0x01]),
+            (300, &[0xac, 0x02]),
+            (u64::MAX, &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]),
+        ];
+        for (value, encoded) in cases {
+            assert_eq!(varint::encode(value), encoded);
+            assert_eq!(decode_prefix(encoded), Ok(Some((value, encoded.len()))));
+            assert_eq!(decode_prefix(&encoded[..encoded.len() - 1]), Ok(None));
+        }
+        let err = Err(FramingError::InvalidLengthPrefix {});
+        assert_eq!(decode_prefix(&[0x80, 0x00]), err);
+        assert_eq!(decode_prefix(&[0xff; 10]), err);
+        assert_eq!(
+            decode_prefix(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02]),
+            err
+        );
+    }
+
+    #[test]
+    fn encoding() {
+        let framing = Framing::new(200);
+        assert_eq!(framing.encode(b"").unwrap(), vec![0]);
+        assert_eq!(framing.encode(b"abc").unwrap(), b"\x03abc".to_vec());
+        let frame = framing.encode(&[7u8; 200]).unwrap();
+        assert_eq!(&frame[..2], &[0xc8, 0x01]);
+        assert_eq!(frame.len(), 202);
+        assert_eq!(
+            framing.encode(&[0u8; 201]),
+            Err(FramingError::FrameTooLarge {
+                size: 201,
+                max_frame_size: 200
+            })
+        );
+        assert_eq!(Framing::default().max_frame_size(), DEFAULT_MAX_FRAME_SIZE);
+    }
+
+    #[test]
+    fn push_decoding() {
+        let framing = Framing::new(1000);
+        let payloads = [vec![], vec![1u8; 5], vec![2u8; 300]];
+        let mut stream = Vec::new();
+        for payload in &payloads {
+            framing.encode_into(payload, &mut stream).unwrap();
+        }
+        // Byte at a time, to exercise every partial state
+        let mut decoder = framing.decoder();
+        let mut frames = Vec::new();
+        for byte in &stream {
+            decoder.push(&[*byte]);
+            while let Some(frame) = decoder.next_frame().unwrap() {
+                frames.push(frame);
+            }
+        }
+        assert_eq!(frames, payloads);
+        assert_eq!(decoder.buffered_len(), 0);
+
+        let mut decoder = framing.decoder();
+        decoder.push(&[0xac, 0x02, 1, 2]);
+        assert_eq!(decoder.next_frame(), Ok(None));
+        assert_eq!(decoder.pending_frame_len(), Some(300));
+        assert_eq!(decoder.buffered_len(), 4);
+    }
+
+    #[test]
+    fn push_decoding_errors() {
+        let mut decoder = FrameDecoder::new(10);
+        decoder.push(&[11]);
+        let err = Err(FramingError::FrameTooLarge {
+            size: 11,
+            max_frame_size: 10,
+        });
+        assert_eq!(decoder.next_frame(), err);
+        assert_eq!(decoder.next_frame(), err);
+
+        let mut decoder = FrameDecoder::new(10);
+        decoder.push(&[0x80, 0x00]);
+        assert_eq!(decoder.next_frame(), Err(FramingError::InvalidLengthPrefix {}));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn streams() {
+        use std::io::{Cursor, ErrorKind};
+
+        let framing = Framing::new(100);
+        let mut stream = Vec::new();
+        framing.write_frame(&mut stream, b"first").unwrap();
+        framing.write_frame(&mut stream, b"").unwrap();
+        let err = framing.write_frame(&mut stream, &[0u8; 101]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+
+        let mut reader = Cursor::new(stream.clone());
+        assert_eq!(framing.read_frame(&mut reader).unwrap(), Some(b"first".to_vec()));
+        assert_eq!(framing.read_frame(&mut reader).unwrap(), Some(vec![]));
+        assert_eq!(framing.read_frame(&mut reader).unwrap(), None);
+
+        let mut truncated = Cursor::new(&stream[..3]);
+        let err = framing.read_frame(&mut truncated).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+        let err = framing.read_frame(&mut Cursor::new([0x80])).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+        let err = framing.read_frame(&mut Cursor::new([101])).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        let err = framing.read_frame(&mut Cursor::new([0xff; 11])).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,207 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Domain-separated hashing over any [Digest].
+//!
+//! A [DomainSeparatedHasher] starts every hash with a versioned domain tag, such as `com.tari.x.v1.challenge`, and
+//! writes every chunk of input with a little-endian `u64` length prefix. Hashes from different domains, versions or
+//! labels can therefore never collide, and neither can different splits of the same bytes into chunks.
+//!
+//! Domains are declared with the [hash_domain](crate::hash_domain) macro:
+//!
+//! ```edition2018
+//! # use blake2::Blake2b512;
+//! # use tari_utilities::{hash_domain, hashing::DomainSeparatedHasher};
+//! hash_domain!(ChallengeDomain, "com.tari.x", 1);
+//!
+//! let challenge =
+//!     DomainSeparatedHasher::<Blake2b512, ChallengeDomain>::new_with_label("challenge")
+//!         .chain(b"public key")
+//!         .chain(b"message")
+//!         .finalize();
+//! ```
+
+use alloc::{format, string::String};
+use core::marker::PhantomData;
+
+pub use digest::{Digest, Output};
+
+/// A hashing domain, with a name and a version. Implement this with the [hash_domain](crate::hash_domain) macro.
+pub trait DomainSeparation {
+    /// The version of the domain. Bump this whenever the contents of the hashes in the domain change.
+    fn version() -> u8;
+
+    /// The name of the domain, conventionally in reverse domain name notation, such as `com.tari.x`.
+    fn domain() -> &'static str;
+
+    /// The tag written at the start of every hash, of the form `{domain}.v{version}` or
+    /// `{domain}.v{version}.{label}`.
+    fn domain_separation_tag(label: &str) -> String {
+        if label.is_empty() {
+            format!("{}.v{}", Self::domain(), Self::version())
+        } else {
+            format!("{}.v{}.{}", Self::domain(), Self::version(), label)
+        }
+    }
+}
+
+/// Declare a unit type implementing [DomainSeparation](crate::hashing::DomainSeparation). The version defaults to
+/// `1`.
+///
+/// ```edition2018
+/// # use tari_utilities::{hash_domain, hashing::DomainSeparation};
+/// hash_domain!(MyDomain, "com.tari.x", 2);
+/// assert_eq!(
+///     MyDomain::domain_separation_tag("label"),
+///     "com.tari.x.v2.label"
+/// );
+/// ```
+#[macro_export]
+macro_rules! hash_domain {
+    ($name:ident, $domain:expr, $version:expr) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct $name;
+
+        impl $crate::hashing::DomainSeparation for $name {
+            fn version() -> u8 {
+                $version
+            }
+
+            fn domain() -> &'static str {
+                $domain
+            }
+        }
+    };
+    ($name:ident, $domain:expr) => {
+        $crate::hash_domain!($name, $domain, 1);
+    };
+}
+
+/// A [Digest] that is bound to the domain `M`. See the [module documentation](self) for the construction.
+#[derive(Debug, Clone)]
+pub struct DomainSeparatedHasher<D, M> {
+    inner: D,
+    _domain: PhantomData<M>,
+}
+
+impl<D: Digest, M: DomainSeparation> DomainSeparatedHasher<D, M> {
+    /// Create a hasher for the domain without a label.
+    pub fn new() -> Self {
+        Self::new_with_label("")
+    }
+
+    /// Create a hasher for the domain with a label, which separates different uses within the same domain.
+    pub fn new_with_label(label: &str) -> Self {
+        let mut hasher = Self {
+            inner: D::new(),
+            _domain: PhantomData,
+        };
+        hasher.update(M::domain_separation_tag(label));
+        hasher
+    }
+
+    /// Add a length-prefixed chunk of data to the hash.
+    pub fn update(&mut self, data: impl AsRef<[u8]>) {
+        let data = data.as_ref();
+        self.inner.update((data.len() as u64).to_le_bytes());
+        self.inner.update(data);
+    }
+
+    /// Add a length-prefixed chunk of data to the hash, returning the hasher for chaining.
+    #[must_use]
+    pub fn chain(mut self, data: impl AsRef<[u8]>) -> Self {
+        self.update(data);
+        self
+    }
+
+    /// Return the hash of everything written so far.
+    pub fn finalize(self) -> Output<D> {
+        self.inner.finalize()
+    }
+}
+
+impl<D: Digest, M: DomainSeparation> Default for DomainSeparatedHasher<D, M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use blake2::{Blake2b512, Blake2s256};
+
+    use super::*;
+    use crate::hex::to_hex;
+
+    hash_domain!(TestDomain, "com.tari.test", 3);
+    hash_domain!(OtherDomain, "com.tari.other");
+
+    type TestHasher = DomainSeparatedHasher<Blake2s256, TestDomain>;
+
+    #[test]
+    fn tags() {
+        assert_eq!(TestDomain::domain_separation_tag(""), "com.tari.test.v3");
+        assert_eq!(TestDomain::domain_separation_tag("label"), "com.tari.test.v3.label");
+        assert_eq!(OtherDomain::version(), 1);
+    }
+
+    #[test]
+    fn matches_the_construction() {
+        let tag = b"com.tari.test.v3.label";
+        let expected = Blake2s256::new()
+            .chain_update((tag.len() as u64).to_le_bytes())
+            .chain_update(tag)
+            .chain_update(5u64.to_le_bytes())
+            .chain_update(b"hello")
+            .finalize();
+        assert_eq!(TestHasher::new_with_label("label").chain(b"hello").finalize(), expected);
+        assert_eq!(
+            to_hex(&TestHasher::new().finalize()),
+            to_hex(
+                &Blake2s256::new()
+                    .chain_update(16u64.to_le_bytes())
+                    .chain_update(b"com.tari.test.v3")
+                    .finalize()
+            )
+        );
+    }
+
+    #[test]
+    fn separation() {
+        let hash = TestHasher::new().chain(b"ab").chain(b"c").finalize();
+        assert_ne!(hash, TestHasher::new().chain(b"a").chain(b"bc").finalize());
+        assert_ne!(hash, TestHasher::new().chain(b"abc").finalize());
+        assert_ne!(
+            hash,
+            TestHasher::new_with_label("x").chain(b"ab").chain(b"c").finalize()
+        );
+        assert_ne!(
+            to_hex(&DomainSeparatedHasher::<Blake2b512, TestDomain>::new().finalize()),
+            to_hex(&DomainSeparatedHasher::<Blake2b512, OtherDomain>::new().finalize())
+        );
+
+        let mut hasher = TestHasher::default();
+        hasher.update(b"ab");
+        hasher.update(b"c");
+        assert_eq!(hasher.finalize(), hash);
+    }
+}
@@ -23,20 +23,25 @@
 //! Functions for conversion between binary and hex string.
 
 use alloc::{string::String, vec::Vec};
-use core::fmt::LowerHex;
+use core::{
+    convert::TryFrom,
+    fmt::{self, LowerHex},
+};
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
 
 #[cfg(feature = "serde")]
 use serde::Serializer;
 use snafu::prelude::*;
+#[cfg(feature = "derive")]
+pub use tari_utilities_derive::Hex;
 
 use crate::alloc::string::ToString;
 
 /// Maximum bytes allowed for parsing to hex.
-const MAX_BYTES_SIZE: usize = 262_144; // 256kb
+pub(crate) const MAX_BYTES_SIZE: usize = 262_144; // 256kb
 
 /// Any object implementing this trait has the ability to represent itself as a hexadecimal string and convert from it.
-
-/// The max len of the hex
 pub trait Hex {
     /// Try to convert the given hexadecimal string to the type.
     ///
@@ -48,18 +53,48 @@ pub trait Hex {
 
     /// Return the hexadecimal string representation of the type.
     fn to_hex(&self) -> String;
+
+    /// Return the hexadecimal string representation of the type with uppercase digits. [Hex::from_hex] accepts
+    /// digits in either case, so this round-trips.
+    fn to_hex_upper(&self) -> String {
+        let mut hex = self.to_hex();
+        hex.make_ascii_uppercase();
+        hex
+    }
+}
+
+/// Implement [Hex] for unsigned integers as their big-endian bytes, so the hex always has two digits per byte of the
+/// type, such as `0000002a` for `42u32`.
+macro_rules! impl_hex_for_uint {
+    ($($ty:ty),*) => {$(
+        impl Hex for $ty {
+            fn from_hex(hex: &str) -> Result<Self, HexError> {
+                from_hex_fixed(hex).map(<$ty>::from_be_bytes)
+            }
+
+            fn to_hex(&self) -> String {
+                HexFmt(&self.to_be_bytes()).to_string()
+            }
+        }
+    )*};
 }
 
+impl_hex_for_uint!(u8, u16, u32, u64, u128);
+
 /// Errors for [Hex] trait.
 #[derive(Debug, Snafu)]
 #[allow(missing_docs)]
 pub enum HexError {
-    #[snafu(display("Only hexadecimal characters (0-9,a-f) are permitted"))]
-    InvalidCharacter {},
+    #[snafu(display(
+        "Only hexadecimal characters (0-9,a-f) are permitted, found {character:?} at position {position}"
+    ))]
+    InvalidCharacter { position: usize, character: char },
     #[snafu(display("Hex string lengths must be a multiple of 2"))]
     LengthError {},
     #[snafu(display("Invalid hex representation for the target type"))]
     HexConversionError {},
+    #[snafu(display("The output buffer is too small; {needed} bytes are needed"))]
+    BufferTooSmall { needed: usize },
 }
 
 /// Encode the provided bytes into a hex string. This will function will not fail, but will print out if it fails
@@ -68,7 +103,7 @@ where T: LowerHex {
     if bytes.len() > MAX_BYTES_SIZE {
         return "**String to large**".to_string();
     }
-    let mut s = String::with_capacity(bytes.len() * 2);
+    let mut s = String::with_capacity(bytes.len().saturating_mul(2));
     for byte in bytes {
         let byte_string = format!("{:02x}", byte);
         s.push_str(&byte_string);
@@ -76,6 +111,558 @@ where T: LowerHex {
     s
 }
 
+/// Encode bytes into a hex string, with the same size limit as [to_hex]. With the `simd` feature, this uses the
+/// fastest implementation supported by the CPU.
+pub(crate) fn encode_bytes(bytes: &[u8]) -> String {
+    #[cfg(feature = "simd")]
+    if bytes.len() <= MAX_BYTES_SIZE {
+        return crate::simd::to_hex(bytes);
+    }
+    to_hex(bytes)
+}
+
+/// The number of bytes formatted at a time by [fmt_hex].
+const FMT_CHUNK_SIZE: usize = 32;
+
+/// Write the bytes as lowercase hex to a formatter or any other [fmt::Write], without allocating. Unlike [to_hex],
+/// there is no size limit.
+pub fn fmt_hex<W: fmt::Write + ?Sized>(w: &mut W, bytes: &[u8]) -> fmt::Result {
+    fmt_hex_case(w, bytes, false)
+}
+
+/// Write the bytes as uppercase hex, like [fmt_hex].
+pub fn fmt_hex_upper<W: fmt::Write + ?Sized>(w: &mut W, bytes: &[u8]) -> fmt::Result {
+    fmt_hex_case(w, bytes, true)
+}
+
+fn fmt_hex_case<W: fmt::Write + ?Sized>(w: &mut W, bytes: &[u8], upper: bool) -> fmt::Result {
+    for chunk in bytes.chunks(FMT_CHUNK_SIZE) {
+        let mut buffer = [0u8; 2 * FMT_CHUNK_SIZE];
+        let len = encode_chunk(chunk, &mut buffer).len();
+        let digits = buffer.get_mut(..len).unwrap_or_default();
+        if upper {
+            digits.make_ascii_uppercase();
+        }
+        w.write_str(core::str::from_utf8(digits).map_err(|_| fmt::Error)?)?;
+    }
+    Ok(())
+}
+
+/// Write the lowercase hex digits of as many bytes as fit into `out`, returning the digits written.
+fn encode_chunk<'a>(bytes: &[u8], out: &'a mut [u8]) -> &'a [u8] {
+    let mut len = 0usize;
+    for (byte, pair) in bytes.iter().zip(out.chunks_exact_mut(2)) {
+        if let [hi, lo] = pair {
+            *hi = hex_char(byte >> 4);
+            *lo = hex_char(byte & 0x0f);
+            len = len.saturating_add(2);
+        }
+    }
+    out.get(..len).unwrap_or_default()
+}
+
+/// The number of hex digits encoding `bytes` bytes.
+pub const fn encoded_len(bytes: usize) -> usize {
+    bytes.saturating_mul(2)
+}
+
+/// The number of bytes encoded by `digits` hex digits, rounded down.
+pub const fn decoded_len(digits: usize) -> usize {
+    digits / 2
+}
+
+/// Encode bytes as lowercase hex digits into the start of `out`, without allocating. Returns the number of digits
+/// written, which is [encoded_len] of the input length.
+///
+/// # Errors
+/// Returns [HexError::BufferTooSmall] if `out` cannot hold all the digits, in which case nothing is written.
+pub fn encode_into(bytes: &[u8], out: &mut [u8]) -> Result<usize, HexError> {
+    let needed = bytes
+        .len()
+        .checked_mul(2)
+        .filter(|needed| *needed <= out.len())
+        .ok_or(HexError::BufferTooSmall {
+            needed: encoded_len(bytes.len()),
+        })?;
+    encode_chunk(bytes, out);
+    Ok(needed)
+}
+
+/// Decode hex digits in either case into the start of `out`, without allocating. The input must consist of hex digits
+/// only, without whitespace or a `0x` prefix. Returns the number of bytes written, which is [decoded_len] of the input
+/// length.
+///
+/// # Errors
+/// Returns [HexError::InvalidCharacter] for the first byte that is not a hex digit, [HexError::LengthError] for an
+/// odd number of digits, or [HexError::BufferTooSmall] if `out` cannot hold the bytes. The contents of `out` are
+/// unspecified after an invalid character.
+pub fn decode_into<T: AsRef<[u8]>>(hex: T, out: &mut [u8]) -> Result<usize, HexError> {
+    let digits = hex.as_ref();
+    if digits.len() % 2 == 1 {
+        return Err(invalid_character(digits, 0));
+    }
+    let needed = decoded_len(digits.len());
+    if needed > out.len() {
+        return Err(HexError::BufferTooSmall { needed });
+    }
+    for (i, (pair, byte)) in digits.chunks_exact(2).zip(out.iter_mut()).enumerate() {
+        match pair {
+            [hi, lo] => *byte = decode_pair(*hi, *lo, i.saturating_mul(2))?,
+            _ => return Err(HexError::LengthError {}),
+        }
+    }
+    Ok(needed)
+}
+
+/// The lowercase hex digits, in order of value.
+pub(crate) const DIGITS: &str = "0123456789abcdef";
+
+/// Return the lowercase hex digit for the low nibble of a byte.
+pub(crate) fn hex_char(nibble: u8) -> u8 {
+    DIGITS
+        .as_bytes()
+        .get(usize::from(nibble & 0x0f))
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Displays bytes as lowercase hex without allocating, for use in log statements and error messages. The `{:x}` and
+/// `{:X}` formats choose the case explicitly, and `{:#x}` and `{:#X}` add a `0x` prefix. The width, fill, alignment
+/// and `0` flag of the format are respected as they are for numbers.
+///
+/// ```edition2018
+/// # use tari_utilities::hex::HexFmt;
+/// let id = [0xde, 0xad, 0xbe, 0xef];
+/// assert_eq!(format!("Peer {}", HexFmt(&id)), "Peer deadbeef");
+/// assert_eq!(format!("{:#X}", HexFmt(&id)), "0xDEADBEEF");
+/// assert_eq!(format!("[{:>10}]", HexFmt(&id)), "[  deadbeef]");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct HexFmt<'a>(pub &'a [u8]);
+
+impl fmt::Display for HexFmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        pad_hex(f, self.0, false, false, fmt::Alignment::Left)
+    }
+}
+
+impl fmt::Debug for HexFmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::LowerHex for HexFmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        pad_hex(f, self.0, false, f.alternate(), fmt::Alignment::Right)
+    }
+}
+
+impl fmt::UpperHex for HexFmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        pad_hex(f, self.0, true, f.alternate(), fmt::Alignment::Right)
+    }
+}
+
+/// Displays bytes as uppercase hex without allocating, like [HexFmt].
+///
+/// ```edition2018
+/// # use tari_utilities::hex::HexUpperFmt;
+/// assert_eq!(
+///     format!("Status {}", HexUpperFmt(&[0xab, 0x01])),
+///     "Status AB01"
+/// );
+/// assert_eq!(format!("{:*^8}", HexUpperFmt(&[0xab, 0x01])), "**AB01**");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct HexUpperFmt<'a>(pub &'a [u8]);
+
+impl fmt::Display for HexUpperFmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        pad_hex(f, self.0, true, false, fmt::Alignment::Left)
+    }
+}
+
+impl fmt::Debug for HexUpperFmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::LowerHex for HexUpperFmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&HexFmt(self.0), f)
+    }
+}
+
+impl fmt::UpperHex for HexUpperFmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&HexFmt(self.0), f)
+    }
+}
+
+/// Write the bytes as hex, after a `0x` prefix if `prefix` is set, padded to the width of the formatter with its fill
+/// and alignment. The `0` flag pads with zeros between the prefix and the digits, as it does for numbers.
+fn pad_hex(
+    f: &mut fmt::Formatter<'_>,
+    bytes: &[u8],
+    upper: bool,
+    prefix: bool,
+    default_alignment: fmt::Alignment,
+) -> fmt::Result {
+    let prefix = if prefix { "0x" } else { "" };
+    let padding = f
+        .width()
+        .unwrap_or_default()
+        .saturating_sub(encoded_len(bytes.len()).saturating_add(prefix.len()));
+    if f.sign_aware_zero_pad() {
+        f.write_str(prefix)?;
+        write_fill(f, '0', padding)?;
+        return fmt_hex_case(f, bytes, upper);
+    }
+    let (before, after) = match f.align().unwrap_or(default_alignment) {
+        fmt::Alignment::Left => (0, padding),
+        fmt::Alignment::Right => (padding, 0),
+        fmt::Alignment::Center => (padding / 2, padding.saturating_sub(padding / 2)),
+    };
+    let fill = f.fill();
+    write_fill(f, fill, before)?;
+    f.write_str(prefix)?;
+    fmt_hex_case(f, bytes, upper)?;
+    write_fill(f, fill, after)
+}
+
+fn write_fill(f: &mut fmt::Formatter<'_>, fill: char, count: usize) -> fmt::Result {
+    for _ in 0..count {
+        fmt::Write::write_char(f, fill)?;
+    }
+    Ok(())
+}
+
+/// Encode bytes as lowercase hex in pieces of `chunk_size` bytes, so a large input can be sent on without holding all
+/// of its hex at once. A `chunk_size` of zero is treated as one, and unlike [to_hex], chunks have no size limit. See
+/// [HexWriter] for encoding from a reader.
+///
+/// ```edition2018
+/// # use tari_utilities::hex::encode_chunks;
+/// let chunks = encode_chunks(&[1, 2, 3, 4, 5], 2).collect::<Vec<_>>();
+/// assert_eq!(chunks, ["0102", "0304", "05"]);
+/// ```
+pub fn encode_chunks(data: &[u8], chunk_size: usize) -> EncodeChunks<'_> {
+    EncodeChunks {
+        chunks: data.chunks(chunk_size.max(1)),
+    }
+}
+
+/// An iterator over the hex of consecutive pieces of a byte slice, created by [encode_chunks].
+#[derive(Debug, Clone)]
+pub struct EncodeChunks<'a> {
+    chunks: core::slice::Chunks<'a, u8>,
+}
+
+impl Iterator for EncodeChunks<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        self.chunks.next().map(|chunk| {
+            if chunk.len() <= MAX_BYTES_SIZE {
+                encode_bytes(chunk)
+            } else {
+                HexFmt(chunk).to_string()
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl ExactSizeIterator for EncodeChunks<'_> {}
+
+/// Decode hex digits in pieces of up to `chunk_size` bytes, the reverse of [encode_chunks]. The input must consist of
+/// hex digits only, without whitespace or a `0x` prefix. A `chunk_size` of zero is treated as one. After the first
+/// error, which reports positions in the whole input, the iterator ends.
+///
+/// ```edition2018
+/// # use tari_utilities::hex::decode_chunks;
+/// let chunks = decode_chunks("0102030405", 2)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(chunks, [vec![1, 2], vec![3, 4], vec![5]]);
+/// ```
+pub fn decode_chunks(hex: &str, chunk_size: usize) -> DecodeChunks<'_> {
+    DecodeChunks {
+        chunks: hex.as_bytes().chunks(encoded_len(chunk_size.max(1))),
+        offset: 0,
+        failed: false,
+    }
+}
+
+/// An iterator over the bytes of consecutive pieces of a hex string, created by [decode_chunks].
+#[derive(Debug, Clone)]
+pub struct DecodeChunks<'a> {
+    chunks: core::slice::Chunks<'a, u8>,
+    offset: usize,
+    failed: bool,
+}
+
+impl Iterator for DecodeChunks<'_> {
+    type Item = Result<Vec<u8>, HexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let digits = self.chunks.next()?;
+        let result = if !digits.is_ascii() {
+            Err(HexError::HexConversionError {})
+        } else if digits.len() % 2 == 1 {
+            Err(invalid_character(digits, self.offset))
+        } else {
+            decode_digits(digits, self.offset)
+        };
+        self.offset = self.offset.saturating_add(digits.len());
+        self.failed = result.is_err();
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.failed {
+            (0, Some(0))
+        } else {
+            (0, self.chunks.size_hint().1)
+        }
+    }
+}
+
+/// The number of bytes encoded or decoded at a time by [HexWriter] and [HexReader].
+#[cfg(feature = "std")]
+const IO_CHUNK_SIZE: usize = 4096;
+
+/// A writer that encodes everything written to it as lowercase hex before passing it on, using a fixed-size buffer.
+///
+/// ```edition2018
+/// # use std::io::Write;
+/// # use tari_utilities::hex::HexWriter;
+/// let mut writer = HexWriter::new(Vec::new());
+/// writer.write_all(&[0xca, 0xfe]).unwrap();
+/// assert_eq!(writer.into_inner(), b"cafe");
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct HexWriter<W> {
+    inner: W,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> HexWriter<W> {
+    /// Wrap a writer that receives the hex digits.
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+
+    /// A reference to the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Return the wrapped writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Write for HexWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let chunk = buf.get(..IO_CHUNK_SIZE).unwrap_or(buf);
+        let mut digits = [0u8; 2 * IO_CHUNK_SIZE];
+        // Both digits of every byte are written before the byte is reported as written
+        self.inner.write_all(encode_chunk(chunk, &mut digits))?;
+        Ok(chunk.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A reader that decodes hex digits read from another reader, using a fixed-size buffer.
+///
+/// The input must consist of hex digits only, in either case. Any other character is an
+/// [InvalidData](io::ErrorKind::InvalidData) error, and input ending halfway through a byte is an
+/// [UnexpectedEof](io::ErrorKind::UnexpectedEof) error.
+///
+/// ```edition2018
+/// # use std::io::Read;
+/// # use tari_utilities::hex::HexReader;
+/// let mut bytes = Vec::new();
+/// HexReader::new(&b"CAfe"[..])
+///     .read_to_end(&mut bytes)
+///     .unwrap();
+/// assert_eq!(bytes, [0xca, 0xfe]);
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct HexReader<R> {
+    inner: R,
+    pending: Option<u8>,
+    position: usize,
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> HexReader<R> {
+    /// Wrap a reader that provides the hex digits.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            pending: None,
+            position: 0,
+        }
+    }
+
+    /// A reference to the wrapped reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Return the wrapped reader. A digit that has been read without the other half of its byte is lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: Read> Read for HexReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Never read more digits than the bytes that fit in `buf`
+        let wanted = buf
+            .len()
+            .min(IO_CHUNK_SIZE)
+            .saturating_mul(2)
+            .saturating_sub(usize::from(self.pending.is_some()));
+        if wanted == 0 {
+            return Ok(0);
+        }
+        let mut digits = [0u8; 2 * IO_CHUNK_SIZE];
+        let digits = digits.get_mut(..wanted).unwrap_or_default();
+        loop {
+            let n = self.inner.read(digits)?;
+            if n == 0 {
+                return match self.pending {
+                    Some(_) => Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        HexError::LengthError {}.to_string(),
+                    )),
+                    None => Ok(0),
+                };
+            }
+            let mut len = 0;
+            for digit in digits.get(..n).unwrap_or_default() {
+                let nibble = hex_nibble(*digit).ok_or_else(|| {
+                    let err = HexError::InvalidCharacter {
+                        position: self.position,
+                        character: char::from(*digit),
+                    };
+                    io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+                })?;
+                self.position = self.position.saturating_add(1);
+                match self.pending.take() {
+                    Some(hi) => {
+                        if let Some(byte) = buf.get_mut(len) {
+                            *byte = (hi << 4) | nibble;
+                        }
+                        len = len.saturating_add(1);
+                    },
+                    None => self.pending = Some(nibble),
+                }
+            }
+            // A single digit completes no byte, and returning zero would signal the end of the input
+            if len > 0 {
+                return Ok(len);
+            }
+        }
+    }
+}
+
+/// Displays bytes as an `xxd`-style hex dump: one row per `width` bytes (16 by default), each with its offset, the
+/// bytes in hex in groups of `group` bytes (1 by default), and the printable ASCII characters, with `.` for the others.
+/// Every row ends with a newline.
+///
+/// ```edition2018
+/// # use tari_utilities::hex::HexDump;
+/// let dump = HexDump::new(b"Hello, world!\n").with_width(8).with_group(2);
+/// assert_eq!(
+///     dump.to_string(),
+///     "00000000: 4865 6c6c 6f2c 2077  Hello, w\n00000008: 6f72 6c64 210a       orld!.\n"
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HexDump<'a> {
+    bytes: &'a [u8],
+    width: usize,
+    group: usize,
+}
+
+impl<'a> HexDump<'a> {
+    /// Dump `bytes` with 16 bytes per row, each byte separated by a space.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            width: 16,
+            group: 1,
+        }
+    }
+
+    /// Set the number of bytes per row. Zero is treated as one.
+    pub fn with_width(self, width: usize) -> Self {
+        Self {
+            width: width.max(1),
+            ..self
+        }
+    }
+
+    /// Set the number of bytes between spaces in the hex column. Zero is treated as one.
+    pub fn with_group(self, group: usize) -> Self {
+        Self {
+            group: group.max(1),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for HexDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let group = self.group.min(self.width);
+        let groups_per_row = self.width.div_ceil(group);
+        let hex_width = encoded_len(self.width).saturating_add(groups_per_row.saturating_sub(1));
+        for (i, row) in self.bytes.chunks(self.width).enumerate() {
+            write!(f, "{:08x}: ", i.saturating_mul(self.width))?;
+            let mut written = 0usize;
+            for (j, chunk) in row.chunks(group).enumerate() {
+                if j > 0 {
+                    f.write_str(" ")?;
+                    written = written.saturating_add(1);
+                }
+                fmt_hex(f, chunk)?;
+                written = written.saturating_add(encoded_len(chunk.len()));
+            }
+            write!(f, "{:pad$}  ", "", pad = hex_width.saturating_sub(written))?;
+            for byte in row {
+                let c = if byte.is_ascii_graphic() || *byte == b' ' {
+                    char::from(*byte)
+                } else {
+                    '.'
+                };
+                write!(f, "{}", c)?;
+            }
+            f.write_str("\n")?;
+        }
+        Ok(())
+    }
+}
+
 /// Encode the provided vector of bytes into a hex string.
 pub fn to_hex_multiple(bytearray: &[Vec<u8>]) -> Vec<String> {
     let mut result = Vec::new();
@@ -87,26 +674,268 @@ pub fn to_hex_multiple(bytearray: &[Vec<u8>]) -> Vec<String> {
 
 /// Decode a hex string into bytes.
 pub fn from_hex(hex_str: &str) -> Result<Vec<u8>, HexError> {
-    let hex_trim = hex_str.trim();
-    if hex_trim.len() % 2 == 1 {
+    let (offset, digits) = hex_digits(hex_str)?;
+    decode_digits(digits, offset)
+}
+
+/// Decode as much of a hex string as possible, for reporting how far a corrupted string is valid. Surrounding
+/// whitespace and a `0x` prefix are ignored as in [from_hex]. Returns the bytes before the first character that is not
+/// a hex digit, together with the error for that character, or [HexError::LengthError] if the string is valid but
+/// ends with half a byte.
+///
+/// ```edition2018
+/// # use tari_utilities::hex::{from_hex_partial, HexError};
+/// let (bytes, err) = from_hex_partial("0xdeadbXef");
+/// assert_eq!(bytes, [0xde, 0xad]);
+/// assert!(matches!(
+///     err,
+///     Some(HexError::InvalidCharacter {
+///         position: 7,
+///         character: 'X'
+///     })
+/// ));
+/// ```
+pub fn from_hex_partial(hex_str: &str) -> (Vec<u8>, Option<HexError>) {
+    let trimmed = hex_str.trim();
+    let mut offset = hex_str.len().saturating_sub(hex_str.trim_start().len());
+    let digits = match trimmed.strip_prefix("0x") {
+        Some(digits) => {
+            offset = offset.saturating_add(2);
+            digits
+        },
+        None => trimmed,
+    };
+    let mut bytes = Vec::with_capacity(decoded_len(digits.len()));
+    let mut high = None;
+    for (i, character) in digits.char_indices() {
+        let nibble = match u8::try_from(character).ok().and_then(hex_nibble) {
+            Some(nibble) => nibble,
+            None => {
+                let position = offset.saturating_add(i);
+                return (bytes, Some(HexError::InvalidCharacter { position, character }));
+            },
+        };
+        match high.take() {
+            Some(hi) => bytes.push((hi << 4) | nibble),
+            None => high = Some(nibble),
+        }
+    }
+    (bytes, high.map(|_| HexError::LengthError {}))
+}
+
+/// Decode a hex string in constant time, for private keys, seeds and other secrets. Digits in either case are
+/// accepted, but unlike [from_hex], surrounding whitespace and a `0x` prefix are not. The timing only depends on the
+/// length of the input and whether it was valid; see [ct](crate::ct) for the leakage model. Unlike [HexError], the
+/// error does not say where the input was invalid.
+#[cfg(feature = "std")]
+pub fn from_hex_ct(hex_str: &str) -> Result<Vec<u8>, crate::ct::DecodeError> {
+    crate::ct::hex_decode(hex_str)
+}
+
+/// Encode bytes as lowercase hex in constant time, for secrets. Unlike [to_hex], there is no size limit.
+#[cfg(feature = "std")]
+pub fn to_hex_ct(bytes: &[u8]) -> String {
+    crate::ct::hex_encode(bytes)
+}
+
+/// Decode a hex string as pasted from explorers and debuggers. Unlike [from_hex], this also accepts a `0x` or `0X`
+/// prefix after leading whitespace, and whitespace, `:` and `,` anywhere between the digits.
+///
+/// ```edition2018
+/// # use tari_utilities::hex::from_hex_lenient;
+/// assert_eq!(from_hex_lenient(" 0xDE:AD be,ef\n").unwrap(), [
+///     0xde, 0xad, 0xbe, 0xef
+/// ]);
+/// ```
+pub fn from_hex_lenient(hex_str: &str) -> Result<Vec<u8>, HexError> {
+    if !hex_str.is_ascii() {
+        return Err(HexError::HexConversionError {});
+    }
+    let trimmed = hex_str.trim_start();
+    let unprefixed = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed);
+    let is_separator = |c: &u8| c.is_ascii_whitespace() || *c == b':' || *c == b',';
+    let offset = hex_str.len().saturating_sub(unprefixed.len());
+    if let Some((i, c)) = unprefixed
+        .bytes()
+        .enumerate()
+        .find(|(_, c)| !is_separator(c) && hex_nibble(*c).is_none())
+    {
+        return Err(HexError::InvalidCharacter {
+            position: offset.saturating_add(i),
+            character: char::from(c),
+        });
+    }
+    let digits = unprefixed.bytes().filter(|c| !is_separator(c)).collect::<Vec<_>>();
+    if digits.len() % 2 == 1 {
         return Err(HexError::LengthError {});
     }
+    decode_digits(&digits, 0)
+}
+
+/// Return the hex digits of the string, without surrounding whitespace or a `0x` prefix, and their offset in the
+/// string. An odd number of digits is only reported as [HexError::LengthError] if they are all valid, so a bad
+/// character is reported at its position whatever the length.
+pub(crate) fn hex_digits(hex_str: &str) -> Result<(usize, &[u8]), HexError> {
     if !hex_str.is_ascii() {
         return Err(HexError::HexConversionError {});
     }
-    let hex_trim = if (hex_trim.len() >= 2) && (&hex_trim[..2] == "0x") {
-        &hex_trim[2..]
-    } else {
-        hex_trim
-    };
-    let num_bytes = hex_trim.len() / 2;
-    let mut result = vec![0u8; num_bytes];
-    for i in 0..num_bytes {
-        result[i] = u8::from_str_radix(&hex_trim[2 * i..2 * (i + 1)], 16).map_err(|_| HexError::InvalidCharacter {})?;
+    let hex_trim = hex_str.trim();
+    let digits = hex_trim.strip_prefix("0x").unwrap_or(hex_trim);
+    let leading = hex_str.len().saturating_sub(hex_str.trim_start().len());
+    let prefix = hex_trim.len().saturating_sub(digits.len());
+    let offset = leading.saturating_add(prefix);
+    if digits.len() % 2 == 1 {
+        return Err(invalid_character(digits.as_bytes(), offset));
+    }
+    Ok((offset, digits.as_bytes()))
+}
+
+/// Decode pairs of hex digits into bytes, reporting invalid characters at their position plus `offset`. With the
+/// `simd` feature, this uses the fastest implementation supported by the CPU.
+pub(crate) fn decode_digits(digits: &[u8], offset: usize) -> Result<Vec<u8>, HexError> {
+    #[cfg(feature = "simd")]
+    return crate::simd::decode_digits(digits, offset);
+    #[cfg(not(feature = "simd"))]
+    digits
+        .chunks_exact(2)
+        .enumerate()
+        .map(|(i, pair)| match pair {
+            [hi, lo] => decode_pair(*hi, *lo, offset.saturating_add(i.saturating_mul(2))),
+            _ => Err(HexError::LengthError {}),
+        })
+        .collect()
+}
+
+/// Decode a pair of hex digits, the first of which is at `position`.
+fn decode_pair(hi: u8, lo: u8, position: usize) -> Result<u8, HexError> {
+    match (hex_nibble(hi), hex_nibble(lo)) {
+        (Some(hi), Some(lo)) => Ok((hi << 4) | lo),
+        (None, _) => Err(HexError::InvalidCharacter {
+            position,
+            character: char::from(hi),
+        }),
+        (_, None) => Err(HexError::InvalidCharacter {
+            position: position.saturating_add(1),
+            character: char::from(lo),
+        }),
+    }
+}
+
+/// The error for the first character of `digits` that is not a hex digit, reported at its position plus `offset`, or
+/// [HexError::LengthError] if they are all hex digits. Used for odd-length input and by decoders that only detect
+/// that there is an invalid character.
+pub(crate) fn invalid_character(digits: &[u8], offset: usize) -> HexError {
+    digits
+        .iter()
+        .enumerate()
+        .find(|(_, c)| hex_nibble(**c).is_none())
+        .map_or(HexError::LengthError {}, |(i, c)| HexError::InvalidCharacter {
+            position: offset.saturating_add(i),
+            character: char::from(*c),
+        })
+}
+
+/// Return the value of a single hex digit, or `None` if the character is not a hex digit.
+pub(crate) const fn hex_nibble(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c.wrapping_sub(b'0')),
+        b'a'..=b'f' => Some(c.wrapping_sub(b'a').wrapping_add(10)),
+        b'A'..=b'F' => Some(c.wrapping_sub(b'A').wrapping_add(10)),
+        _ => None,
+    }
+}
+
+/// Decode a hex string into a fixed-size array, without allocating. The input is read as by [from_hex], so surrounding
+/// whitespace and a `0x` prefix are ignored, and the remaining digits are decoded by [from_hex_array].
+///
+/// ```edition2018
+/// # use tari_utilities::hex::from_hex_fixed;
+/// let nonce: [u8; 4] = from_hex_fixed(" 0x0000002a\n").unwrap();
+/// assert_eq!(nonce, [0, 0, 0, 42]);
+/// ```
+///
+/// # Errors
+/// Returns [HexError::LengthError] for an odd number of digits, [HexError::HexConversionError] for a non-ASCII
+/// string or any number of bytes other than `N`, or [HexError::InvalidCharacter] for the first invalid digit.
+pub fn from_hex_fixed<const N: usize>(hex_str: &str) -> Result<[u8; N], HexError> {
+    let (offset, digits) = hex_digits(hex_str)?;
+    let digits = core::str::from_utf8(digits).map_err(|_| HexError::HexConversionError {})?;
+    from_hex_array(digits).map_err(|e| match e {
+        HexError::InvalidCharacter { position, character } => HexError::InvalidCharacter {
+            position: offset.saturating_add(position),
+            character,
+        },
+        e => e,
+    })
+}
+
+/// Decode a hex string into a fixed-size array. Unlike [from_hex], this is a `const fn` so it can be used to define
+/// constants, and it accepts only the exact number of hex digits, without whitespace or a `0x` prefix.
+// The loop index is bounded by `N` and the input length is checked against `N` up front
+#[allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+pub const fn from_hex_array<const N: usize>(hex: &str) -> Result<[u8; N], HexError> {
+    let bytes = hex.as_bytes();
+    if bytes.len() % 2 == 1 {
+        let mut i = 0;
+        while i < bytes.len() {
+            if hex_nibble(bytes[i]).is_none() {
+                return Err(HexError::InvalidCharacter {
+                    position: i,
+                    character: bytes[i] as char,
+                });
+            }
+            i += 1;
+        }
+        return Err(HexError::LengthError {});
+    }
+    if bytes.len() != N * 2 {
+        return Err(HexError::HexConversionError {});
+    }
+    let mut result = [0u8; N];
+    let mut i = 0;
+    while i < N {
+        let (hi, lo) = (bytes[2 * i], bytes[2 * i + 1]);
+        match (hex_nibble(hi), hex_nibble(lo)) {
+            (Some(hi), Some(lo)) => result[i] = (hi << 4) | lo,
+            (None, _) => {
+                return Err(HexError::InvalidCharacter {
+                    position: 2 * i,
+                    character: hi as char,
+                })
+            },
+            (_, None) => {
+                return Err(HexError::InvalidCharacter {
+                    position: 2 * i + 1,
+                    character: lo as char,
+                })
+            },
+        }
+        i += 1;
     }
     Ok(result)
 }
 
+/// Decode a hex string literal into a byte array at compile time. Invalid literals fail to compile.
+///
+/// ```edition2018
+/// # use tari_utilities::hex;
+/// const MAGIC: [u8; 4] = hex!("deadbeef");
+/// assert_eq!(MAGIC, [0xde, 0xad, 0xbe, 0xef]);
+/// ```
+#[macro_export]
+macro_rules! hex {
+    ($hex:literal) => {{
+        const BYTES: [u8; $hex.len() / 2] = match $crate::hex::from_hex_array::<{ $hex.len() / 2 }>($hex) {
+            Ok(bytes) => bytes,
+            Err(_) => panic!(concat!("Invalid hex literal: ", $hex)),
+        };
+        BYTES
+    }};
+}
+
 /// Use a serde serializer to serialize the hex string of the given object.
 #[cfg(feature = "serde")]
 pub fn serialize_to_hex<S, T>(t: &T, ser: S) -> Result<S::Ok, S::Error>
@@ -129,6 +958,245 @@ mod test {
         assert_eq!(to_hex(&[0, 0, 0, 255]), "000000ff");
     }
 
+    #[test]
+    fn display_matches_to_hex() {
+        let bytes = (0..=255u8).collect::<Vec<_>>();
+        for len in [0, 1, FMT_CHUNK_SIZE - 1, FMT_CHUNK_SIZE, FMT_CHUNK_SIZE + 1, 256] {
+            assert_eq!(HexFmt(&bytes[..len]).to_string(), to_hex(&bytes[..len]));
+            assert_eq!(format!("{:?}", HexFmt(&bytes[..len])), to_hex(&bytes[..len]));
+            assert_eq!(format!("{:x}", HexFmt(&bytes[..len])), to_hex(&bytes[..len]));
+            let upper = to_hex(&bytes[..len]).to_uppercase();
+            assert_eq!(HexUpperFmt(&bytes[..len]).to_string(), upper);
+            assert_eq!(format!("{:X}", HexFmt(&bytes[..len])), upper);
+            assert_eq!(format!("{:#X}", HexUpperFmt(&bytes[..len])), format!("0x{}", upper));
+        }
+        assert_eq!(format!("{:#x}", HexUpperFmt(&[0xab])), "0xab");
+        assert_eq!(format!("{:?}", HexUpperFmt(&[0xab])), "AB");
+    }
+
+    #[test]
+    fn display_width_and_fill() {
+        let bytes = [0xab, 0x01];
+        // Text formats are left-aligned and hex formats right-aligned by default, as for `str` and integers
+        assert_eq!(format!("[{:6}]", HexFmt(&bytes)), "[ab01  ]");
+        assert_eq!(format!("[{:6x}]", HexFmt(&bytes)), "[  ab01]");
+        assert_eq!(format!("[{:>6}]", HexUpperFmt(&bytes)), "[  AB01]");
+        assert_eq!(format!("[{:-^9}]", HexFmt(&bytes)), "[--ab01---]");
+        assert_eq!(format!("[{:*<7X}]", HexFmt(&bytes)), "[AB01***]");
+        assert_eq!(format!("[{:#8x}]", HexFmt(&bytes)), "[  0xab01]");
+        assert_eq!(format!("[{:#08X}]", HexUpperFmt(&bytes)), "[0x00AB01]");
+        assert_eq!(format!("[{:010}]", HexFmt(&bytes)), "[000000ab01]");
+        // A width below the length does not truncate
+        assert_eq!(format!("[{:2}]", HexFmt(&bytes)), "[ab01]");
+        assert_eq!(format!("[{:>w$}]", HexFmt(&bytes), w = 5), "[ ab01]");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn hex_io() {
+        use std::io::{Read, Write};
+
+        let data = (0..10_000u32).map(|i| (i * 7) as u8).collect::<Vec<_>>();
+        let mut writer = HexWriter::new(Vec::new());
+        writer.write_all(&data[..1]).unwrap();
+        writer.write_all(&data[1..]).unwrap();
+        writer.flush().unwrap();
+        let hex = writer.into_inner();
+        assert_eq!(hex, HexFmt(&data).to_string().into_bytes());
+
+        let mut reader = HexReader::new(&hex[..]);
+        let mut decoded = Vec::new();
+        let mut buf = [0u8; 3];
+        loop {
+            let n = reader.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            decoded.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(decoded, data);
+
+        // One digit at a time
+        struct Trickle<'a>(&'a [u8]);
+        impl Read for Trickle<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                match (self.0.split_first(), buf.first_mut()) {
+                    (Some((first, rest)), Some(out)) => {
+                        *out = *first;
+                        self.0 = rest;
+                        Ok(1)
+                    },
+                    _ => Ok(0),
+                }
+            }
+        }
+        let mut decoded = Vec::new();
+        HexReader::new(Trickle(b"00fF10")).read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, [0x00, 0xff, 0x10]);
+
+        let err = HexReader::new(&b"abc"[..]).read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        let err = HexReader::new(&b"ab cd"[..]).read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn simd_fast_path_matches_scalar() {
+        use rand::{rngs::OsRng, RngCore};
+
+        for len in [0, 1, 15, 16, 31, 32, 33, 100, 1000] {
+            let mut bytes = vec![0u8; len];
+            OsRng.fill_bytes(&mut bytes);
+            let hex = bytes.to_hex();
+            assert_eq!(hex, to_hex(&bytes));
+            assert_eq!(hex, HexFmt(&bytes).to_string());
+            assert_eq!(from_hex(&hex).unwrap(), bytes);
+            assert_eq!(from_hex(&hex.to_uppercase()).unwrap(), bytes);
+            assert_eq!(Vec::<u8>::from_hex(&hex).unwrap(), bytes);
+            if len > 0 {
+                let mut invalid = hex.into_bytes();
+                invalid[len] = b'g';
+                let invalid = String::from_utf8(invalid).unwrap();
+                assert!(matches!(
+                    from_hex(&invalid),
+                    Err(HexError::InvalidCharacter { position, character: 'g' }) if position == len
+                ));
+            }
+        }
+        assert_eq!(
+            vec![0u8; MAX_BYTES_SIZE + 1].to_hex(),
+            to_hex(&vec![0u8; MAX_BYTES_SIZE + 1])
+        );
+    }
+
+    #[test]
+    fn uppercase() {
+        let bytes = vec![0xde, 0xad, 0x01, 0xef];
+        assert_eq!(bytes.to_hex_upper(), "DEAD01EF");
+        assert_eq!(Vec::<u8>::from_hex(&bytes.to_hex_upper()).unwrap(), bytes);
+        assert_eq!(<[u8; 4]>::from_hex("0xDeAd01eF").unwrap(), [0xde, 0xad, 0x01, 0xef]);
+    }
+
+    #[test]
+    fn lenient() {
+        assert_eq!(from_hex_lenient("00:11:22").unwrap(), [0x00, 0x11, 0x22]);
+        assert_eq!(from_hex_lenient("0X0a, 0b,\t0c").unwrap(), [10, 11, 12]);
+        assert_eq!(from_hex_lenient("a b c d").unwrap(), [0xab, 0xcd]);
+        assert_eq!(from_hex_lenient("").unwrap(), [0u8; 0]);
+        assert!(matches!(from_hex_lenient("abc"), Err(HexError::LengthError {})));
+        assert!(matches!(
+            from_hex_lenient("a-bc"),
+            Err(HexError::InvalidCharacter {
+                position: 1,
+                character: '-'
+            })
+        ));
+        assert!(matches!(
+            from_hex_lenient(" 0xab0xcd"),
+            Err(HexError::InvalidCharacter {
+                position: 6,
+                character: 'x'
+            })
+        ));
+        assert!(matches!(
+            from_hex_lenient("ab\u{a0}cd"),
+            Err(HexError::HexConversionError {})
+        ));
+        // The strict parser is unchanged
+        assert!(from_hex("00:11").is_err());
+    }
+
+    #[test]
+    fn into_buffers() {
+        let mut digits = [0u8; 9];
+        assert_eq!(encode_into(&[0xab, 0x01, 0xff], &mut digits).unwrap(), 6);
+        assert_eq!(&digits[..6], b"ab01ff");
+        assert!(matches!(
+            encode_into(&[0; 5], &mut digits),
+            Err(HexError::BufferTooSmall { needed: 10 })
+        ));
+        assert_eq!(encode_into(&[], &mut []).unwrap(), 0);
+
+        let mut bytes = [0u8; 4];
+        assert_eq!(decode_into("AB01ff", &mut bytes).unwrap(), 3);
+        assert_eq!(bytes[..3], [0xab, 0x01, 0xff]);
+        assert_eq!(decode_into(b"00", &mut bytes).unwrap(), 1);
+        assert!(matches!(decode_into("abc", &mut bytes), Err(HexError::LengthError {})));
+        assert!(matches!(
+            decode_into("0011223344", &mut bytes),
+            Err(HexError::BufferTooSmall { needed: 5 })
+        ));
+        assert!(matches!(
+            decode_into("0x00", &mut bytes),
+            Err(HexError::InvalidCharacter {
+                position: 1,
+                character: 'x'
+            })
+        ));
+        assert_eq!((encoded_len(3), decoded_len(7)), (6, 3));
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn derived() {
+        #[derive(Debug, PartialEq, Eq, Hex)]
+        struct Key([u8; 4]);
+
+        #[derive(Debug, PartialEq, Eq, Hex)]
+        struct Proof(Vec<u8>);
+
+        let key = Key([0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(key.to_hex(), "deadbeef");
+        assert_eq!(key.to_string(), "deadbeef");
+        assert_eq!(Key::from_hex("DEADBEEF").unwrap(), key);
+        assert_eq!("deadbeef".parse::<Key>().unwrap(), key);
+        assert!("deadbe".parse::<Key>().is_err());
+        assert!("deadbeefff".parse::<Key>().is_err());
+
+        let proof = Proof(vec![1, 2, 3]);
+        assert_eq!(proof.to_string(), "010203");
+        assert_eq!("010203".parse::<Proof>().unwrap(), proof);
+        assert_eq!(Proof::from_hex("").unwrap(), Proof(Vec::new()));
+        assert!("0g".parse::<Proof>().is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn constant_time() {
+        let secret = [0x00, 0x9f, 0xa0, 0xff];
+        assert_eq!(to_hex_ct(&secret), "009fa0ff");
+        assert_eq!(from_hex_ct("009FA0ff").unwrap(), secret);
+        assert_eq!(from_hex_ct("009"), Err(crate::ct::DecodeError::InvalidLength {}));
+        assert_eq!(from_hex_ct("0x00"), Err(crate::ct::DecodeError::InvalidCharacter {}));
+        assert_eq!(from_hex_ct(" 00 "), Err(crate::ct::DecodeError::InvalidCharacter {}));
+    }
+
+    #[test]
+    fn hex_dump() {
+        assert_eq!(HexDump::new(b"").to_string(), "");
+        let expected = [
+            "00000000: 48 65 6c 6c 6f 2c 20 77 6f 72 6c 64 21 0a 00 ff  Hello, world!...\n",
+            "00000010: 6d 6f 72 65                                      more\n",
+        ];
+        assert_eq!(
+            HexDump::new(b"Hello, world!\n\x00\xffmore").to_string(),
+            expected.concat()
+        );
+        assert_eq!(
+            HexDump::new(&[0, 1, 2, 3, 4]).with_width(4).with_group(0).to_string(),
+            "00000000: 00 01 02 03  ....\n00000004: 04           .\n"
+        );
+        assert_eq!(
+            HexDump::new(b"abc").with_width(0).with_group(8).to_string(),
+            "00000000: 61  a\n00000001: 62  b\n00000002: 63  c\n"
+        );
+        assert_eq!(
+            HexDump::new(b"abcde").with_width(5).with_group(2).to_string(),
+            "00000000: 6162 6364 65  abcde\n"
+        );
+    }
+
     #[test]
     fn test_from_hex() {
         assert_eq!(from_hex("00000000").unwrap(), vec![0, 0, 0, 0]);
@@ -159,6 +1227,139 @@ mod test {
         assert_eq!(err.to_string(), "Hex string lengths must be a multiple of 2");
     }
 
+    #[test]
+    fn odd_length_reports_invalid_character() {
+        let invalid_at = |err, expected| matches!(err, HexError::InvalidCharacter { position, character: 'g' } if position == expected);
+        assert!(invalid_at(from_hex(" 0x0a0bg").unwrap_err(), 7));
+        assert!(invalid_at(from_hex("g00").unwrap_err(), 0));
+        assert!(invalid_at(from_hex_fixed::<2>("0ag").unwrap_err(), 2));
+        assert!(invalid_at(decode_into("0a0bg", &mut [0u8; 4]).unwrap_err(), 4));
+        assert!(invalid_at(from_hex_array::<2>("0ag").unwrap_err(), 2));
+        assert!(invalid_at(decode_chunks("0a0bg", 2).nth(1).unwrap().unwrap_err(), 4));
+        assert!(matches!(from_hex("0a0b0").unwrap_err(), HexError::LengthError {}));
+    }
+
+    #[test]
+    fn arbitrary_input_does_not_panic() {
+        use rand::{rngs::OsRng, Rng};
+
+        let alphabet = ['0', 'a', 'F', 'x', ' ', '+', '-', 'g', '🖖', 'é', '\0'];
+        for _ in 0..10_000 {
+            let len = OsRng.gen_range(0..12);
+            let s = (0..len)
+                .map(|_| alphabet[OsRng.gen_range(0..alphabet.len())])
+                .collect::<String>();
+            let _result = from_hex(&s);
+            let _result = from_hex_array::<2>(&s);
+            let _result = <[u8; 2]>::from_hex(&s);
+        }
+        assert!(from_hex("+a").is_err());
+    }
+
+    #[test]
+    fn chunked() {
+        let bytes = (0..=255u8).collect::<Vec<_>>();
+        for chunk_size in [0, 1, 7, 256, 1000] {
+            let chunks = encode_chunks(&bytes, chunk_size).collect::<Vec<_>>();
+            assert_eq!(chunks.len(), encode_chunks(&bytes, chunk_size).len());
+            assert!(chunks.iter().all(|chunk| chunk.len() <= 2 * chunk_size.max(1)));
+            let hex = chunks.concat();
+            assert_eq!(hex, to_hex(&bytes));
+            let decoded = decode_chunks(&hex, chunk_size).collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(decoded.concat(), bytes);
+        }
+        assert_eq!(encode_chunks(&[], 4).count(), 0);
+        let large = (0..2 * MAX_BYTES_SIZE + 1).map(|i| i as u8).collect::<Vec<_>>();
+        let chunks = encode_chunks(&large, MAX_BYTES_SIZE + 1).collect::<Vec<_>>();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0], HexFmt(&large[..=MAX_BYTES_SIZE]).to_string());
+        assert_eq!(chunks.concat(), HexFmt(&large).to_string());
+        assert_eq!(decode_chunks("", 4).count(), 0);
+
+        let mut chunks = decode_chunks("0001020g0405", 2);
+        assert_eq!(chunks.next().unwrap().unwrap(), [0, 1]);
+        assert!(matches!(
+            chunks.next(),
+            Some(Err(HexError::InvalidCharacter {
+                position: 7,
+                character: 'g'
+            }))
+        ));
+        assert!(chunks.next().is_none());
+        let results = decode_chunks("00010", 2).collect::<Vec<_>>();
+        assert!(matches!(results[1], Err(HexError::LengthError {})));
+        assert!(matches!(
+            decode_chunks("00é0", 1).nth(1),
+            Some(Err(HexError::HexConversionError {}))
+        ));
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(0xabu8.to_hex(), "ab");
+        assert_eq!(42u32.to_hex(), "0000002a");
+        assert_eq!(u64::MAX.to_hex_upper(), "FFFFFFFFFFFFFFFF");
+        assert_eq!(1u128.to_hex(), format!("{}01", "0".repeat(30)));
+        assert_eq!(u16::from_hex("0x0102").unwrap(), 0x0102);
+        assert_eq!(u64::from_hex(&12345u64.to_hex()).unwrap(), 12345);
+        assert_eq!(u128::from_hex(&u128::MAX.to_hex()).unwrap(), u128::MAX);
+        assert!(matches!(u32::from_hex("2a"), Err(HexError::HexConversionError {})));
+        assert!(matches!(u8::from_hex("0g"), Err(HexError::InvalidCharacter { .. })));
+    }
+
+    #[test]
+    fn fixed_size() {
+        assert_eq!(from_hex_fixed::<2>("0a0B").unwrap(), [0x0a, 0x0b]);
+        assert_eq!(from_hex_fixed::<2>(" 0x0a0b ").unwrap(), [0x0a, 0x0b]);
+        assert_eq!(from_hex_fixed::<0>("").unwrap(), [0u8; 0]);
+        assert!(matches!(from_hex_fixed::<2>("0a0"), Err(HexError::LengthError {})));
+        assert!(matches!(
+            from_hex_fixed::<2>("0a"),
+            Err(HexError::HexConversionError {})
+        ));
+        assert!(matches!(
+            from_hex_fixed::<2>("0a0b0c"),
+            Err(HexError::HexConversionError {})
+        ));
+        assert!(matches!(
+            from_hex_fixed::<2>(" 0x0a0g"),
+            Err(HexError::InvalidCharacter {
+                position: 6,
+                character: 'g'
+            })
+        ));
+    }
+
+    #[test]
+    fn const_from_hex_array() {
+        const BYTES: [u8; 4] = match from_hex_array("0a0B0c0D") {
+            Ok(bytes) => bytes,
+            Err(_) => panic!(),
+        };
+        assert_eq!(BYTES, [10, 11, 12, 13]);
+        assert_eq!(crate::hex!("000000ff"), [0, 0, 0, 255]);
+        assert_eq!(crate::hex!(""), [0u8; 0]);
+        assert!(matches!(from_hex_array::<2>("abc"), Err(HexError::LengthError {})));
+        assert!(matches!(
+            from_hex_array::<2>("ab"),
+            Err(HexError::HexConversionError {})
+        ));
+        assert!(matches!(
+            from_hex_array::<2>("0xab"),
+            Err(HexError::InvalidCharacter {
+                position: 1,
+                character: 'x'
+            })
+        ));
+        assert!(matches!(
+            from_hex_array::<1>("+a"),
+            Err(HexError::InvalidCharacter {
+                position: 0,
+                character: '+'
+            })
+        ));
+    }
+
     #[test]
     fn max_length_error() {
         let bytes = [0; 262_144];
@@ -177,7 +1378,38 @@ mod test {
         let result = from_hex("1234567890ABCDEFG1");
         assert!(result.is_err());
         let err = result.unwrap_err();
-        assert!(matches!(err, HexError::InvalidCharacter { .. }));
-        assert_eq!(err.to_string(), "Only hexadecimal characters (0-9,a-f) are permitted");
+        assert!(matches!(err, HexError::InvalidCharacter {
+            position: 16,
+            character: 'G'
+        }));
+        assert_eq!(
+            err.to_string(),
+            "Only hexadecimal characters (0-9,a-f) are permitted, found 'G' at position 16"
+        );
+        assert!(matches!(
+            from_hex(" 0x00zz "),
+            Err(HexError::InvalidCharacter {
+                position: 5,
+                character: 'z'
+            })
+        ));
+    }
+
+    #[test]
+    fn partial_decode() {
+        assert_eq!(from_hex_partial("").0, Vec::<u8>::new());
+        assert!(from_hex_partial(" 0xdead ").1.is_none());
+        let (bytes, err) = from_hex_partial("dead0");
+        assert_eq!(bytes, [0xde, 0xad]);
+        assert!(matches!(err, Some(HexError::LengthError {})));
+        let (bytes, err) = from_hex_partial(" deé");
+        assert_eq!(bytes, [0xde]);
+        assert!(matches!(
+            err,
+            Some(HexError::InvalidCharacter {
+                position: 3,
+                character: 'é'
+            })
+        ));
     }
 }
@@ -203,6 +203,28 @@ where T: Zeroize
     }
 }
 
+/// Compare the hidden data in constant time
+#[cfg(feature = "std")]
+impl<T> subtle::ConstantTimeEq for Hidden<T>
+where T: Zeroize + subtle::ConstantTimeEq
+{
+    fn ct_eq(&self, other: &Self) -> subtle::Choice {
+        self.reveal().ct_eq(other.reveal())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> PartialEq for Hidden<T>
+where T: Zeroize + subtle::ConstantTimeEq
+{
+    fn eq(&self, other: &Self) -> bool {
+        subtle::ConstantTimeEq::ct_eq(self, other).into()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> Eq for Hidden<T> where T: Zeroize + subtle::ConstantTimeEq {}
+
 /// Zeroize the hidden data
 impl<T> Zeroize for Hidden<T>
 where T: Zeroize
@@ -221,6 +243,28 @@ where T: Zeroize
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a, T> arbitrary::Arbitrary<'a> for Hidden<T>
+where T: Zeroize + arbitrary::Arbitrary<'a>
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        T::arbitrary(u).map(Self::hide)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl<T> proptest::arbitrary::Arbitrary for Hidden<T>
+where T: Zeroize + proptest::arbitrary::Arbitrary + 'static
+{
+    type Parameters = T::Parameters;
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        use proptest::{prelude::any_with, strategy::Strategy};
+        any_with::<T>(args).prop_map(Self::hide).boxed()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::string::ToString;
@@ -258,6 +302,12 @@ mod tests {
         assert_eq!(formatted, expected);
     }
 
+    #[test]
+    fn equality() {
+        assert_eq!(Hidden::hide(1u8), Hidden::hide(1u8));
+        assert_ne!(Hidden::hide(1u8), Hidden::hide(2u8));
+    }
+
     #[test]
     fn macro_types() {
         hidden_type!(TypeA, [u8; 32]);
@@ -0,0 +1,185 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A thread-safe string interning pool.
+//!
+//! An [InternPool] stores each distinct string once and hands out [Interned] handles, which are a `u32` wide, `Copy`,
+//! and compare and hash in constant time. Maps keyed by peer IDs, domain labels or metric names can hold handles
+//! instead of repeated `String`s and resolve them back to `&str` when needed.
+//!
+//! Strings are never removed from a pool, so it suits sets of identifiers that stay bounded over the life of the pool.
+//!
+//! ```edition2018
+//! # use tari_utilities::intern::InternPool;
+//! let pool = InternPool::new();
+//! let a = pool.intern("com.tari.base_node");
+//! let b = pool.intern("com.tari.base_node");
+//! assert_eq!(a, b);
+//! assert_eq!(pool.resolve(a), Some("com.tari.base_node"));
+//! ```
+
+use alloc::{sync::Arc, vec::Vec};
+use core::convert::TryFrom;
+use std::{
+    collections::HashMap,
+    sync::{PoisonError, RwLock},
+};
+
+/// A handle to a string in an [InternPool]. A handle is only meaningful to the pool that issued it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interned(u32);
+
+impl Interned {
+    /// The index of the string in its pool.
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    ids: HashMap<Arc<str>, Interned>,
+    strings: Vec<Arc<str>>,
+}
+
+/// A pool of interned strings, safe to share between threads.
+#[derive(Debug, Default)]
+pub struct InternPool {
+    inner: RwLock<Inner>,
+}
+
+impl InternPool {
+    /// Create an empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return the handle for `s`, adding it to the pool if it is not there yet.
+    ///
+    /// # Panics
+    /// Panics if the pool would hold more than `u32::MAX` strings. Use [InternPool::intern_checked] for strings from
+    /// untrusted sources.
+    // The panic is documented, and the checked variant is available
+    #[allow(clippy::expect_used)]
+    pub fn intern(&self, s: &str) -> Interned {
+        self.intern_checked(s).expect("intern pool is full")
+    }
+
+    /// Return the handle for `s`, adding it to the pool if it is not there yet, or `None` if the pool is full.
+    pub fn intern_checked(&self, s: &str) -> Option<Interned> {
+        if let Some(id) = self.get(s) {
+            return Some(id);
+        }
+        let mut inner = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        // Another thread may have added the string between the two locks
+        if let Some(id) = inner.ids.get(s) {
+            return Some(*id);
+        }
+        let id = Interned(u32::try_from(inner.strings.len()).ok()?);
+        let s = Arc::<str>::from(s);
+        inner.strings.push(s.clone());
+        inner.ids.insert(s, id);
+        Some(id)
+    }
+
+    /// Return the handle for `s` if it is in the pool, without adding it.
+    pub fn get(&self, s: &str) -> Option<Interned> {
+        let inner = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+        inner.ids.get(s).copied()
+    }
+
+    /// Return the string for a handle, or `None` if the handle is out of range for this pool. Handles are plain indices
+    /// and do not record their pool, so a handle from a different pool may resolve to an unrelated string.
+    pub fn resolve(&self, id: Interned) -> Option<&str> {
+        let inner = self.inner.read().unwrap_or_else(PoisonError::into_inner);
+        let s: *const str = &**inner.strings.get(id.0 as usize)?;
+        // SAFETY: the string is behind an `Arc` owned by the pool, so it does not move when `strings` grows, and
+        // strings are never removed or changed while the pool is alive, which the returned lifetime is tied to.
+        Some(unsafe { &*s })
+    }
+
+    /// The number of strings in the pool.
+    pub fn len(&self) -> usize {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner).strings.len()
+    }
+
+    /// Return `true` if the pool holds no strings.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{format, vec::Vec};
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn interning() {
+        let pool = InternPool::new();
+        assert!(pool.is_empty());
+        let a = pool.intern("alpha");
+        let b = pool.intern("beta");
+        assert_ne!(a, b);
+        assert_eq!(pool.intern("alpha"), a);
+        assert_eq!(pool.intern_checked("alpha"), Some(a));
+        assert_eq!(pool.get("beta"), Some(b));
+        assert_eq!(pool.get("gamma"), None);
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.resolve(b), Some("beta"));
+        assert_eq!(pool.resolve(Interned(2)), None);
+        assert_eq!((a.as_u32(), b.as_u32()), (0, 1));
+    }
+
+    #[test]
+    fn resolved_strings_outlive_growth() {
+        let pool = InternPool::new();
+        let first = pool.resolve(pool.intern("first")).unwrap();
+        for i in 0..1000 {
+            pool.intern(&format!("label {}", i));
+        }
+        assert_eq!(first, "first");
+    }
+
+    #[test]
+    fn concurrent_interning() {
+        let pool = Arc::new(InternPool::new());
+        let handles = (0..8)
+            .map(|_| {
+                let pool = pool.clone();
+                thread::spawn(move || {
+                    (0..100)
+                        .map(|i| pool.intern(&format!("peer-{}", i)))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        let results = handles.into_iter().map(|h| h.join().unwrap()).collect::<Vec<_>>();
+        assert!(results.windows(2).all(|pair| pair[0] == pair[1]));
+        assert_eq!(pool.len(), 100);
+        for (i, id) in results[0].iter().enumerate() {
+            assert_eq!(pool.resolve(*id), Some(format!("peer-{}", i).as_str()));
+        }
+    }
+}
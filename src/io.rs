@@ -0,0 +1,243 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Pass-through adapters for [io::Read] and [io::Write] streams.
+//!
+//! With the `digest` feature, `HashingReader` and `HashingWriter` compute a running digest of the bytes that pass
+//! through them, and [TeeWriter] copies everything written to two writers. This lets a download be verified, or an
+//! export be checksummed, in the same pass that moves the data.
+//!
+//! ```edition2018
+//! # use std::io::Write;
+//! # use tari_utilities::io::TeeWriter;
+//! let mut tee = TeeWriter::new(Vec::new(), Vec::new());
+//! tee.write_all(b"backup").unwrap();
+//! let (first, second) = tee.into_inner();
+//! assert_eq!(first, second);
+//! ```
+
+#[cfg(feature = "digest")]
+use std::io::Read;
+use std::io::{self, Write};
+
+#[cfg(feature = "digest")]
+use digest::{Digest, Output};
+
+/// A reader that hashes every byte read through it.
+///
+/// Only the bytes returned to the caller are hashed, so the digest covers exactly what has been read so far.
+#[cfg(feature = "digest")]
+#[derive(Debug, Clone)]
+pub struct HashingReader<R, D> {
+    inner: R,
+    digest: D,
+    bytes: u64,
+}
+
+#[cfg(feature = "digest")]
+impl<R: Read, D: Digest> HashingReader<R, D> {
+    /// Wrap `inner` with a fresh digest.
+    pub fn new(inner: R) -> Self {
+        Self::with_digest(inner, D::new())
+    }
+
+    /// Wrap `inner`, continuing from an existing digest state.
+    pub fn with_digest(inner: R, digest: D) -> Self {
+        Self {
+            inner,
+            digest,
+            bytes: 0,
+        }
+    }
+
+    /// The number of bytes read so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes
+    }
+
+    /// A reference to the wrapped reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Finish hashing, returning the wrapped reader and the digest of all bytes read.
+    pub fn finalize(self) -> (R, Output<D>) {
+        (self.inner, self.digest.finalize())
+    }
+}
+
+#[cfg(feature = "digest")]
+impl<R: Read, D: Digest> Read for HashingReader<R, D> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        let read = buf.get(..n).unwrap_or_default();
+        self.digest.update(read);
+        self.bytes = self.bytes.saturating_add(read.len() as u64);
+        Ok(n)
+    }
+}
+
+/// A writer that hashes every byte written through it.
+///
+/// Only the bytes accepted by the wrapped writer are hashed, so the digest covers exactly what has been written.
+#[cfg(feature = "digest")]
+#[derive(Debug, Clone)]
+pub struct HashingWriter<W, D> {
+    inner: W,
+    digest: D,
+    bytes: u64,
+}
+
+#[cfg(feature = "digest")]
+impl<W: Write, D: Digest> HashingWriter<W, D> {
+    /// Wrap `inner` with a fresh digest.
+    pub fn new(inner: W) -> Self {
+        Self::with_digest(inner, D::new())
+    }
+
+    /// Wrap `inner`, continuing from an existing digest state.
+    pub fn with_digest(inner: W, digest: D) -> Self {
+        Self {
+            inner,
+            digest,
+            bytes: 0,
+        }
+    }
+
+    /// The number of bytes written so far.
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes
+    }
+
+    /// A reference to the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Finish hashing, returning the wrapped writer and the digest of all bytes written. The writer is not flushed.
+    pub fn finalize(self) -> (W, Output<D>) {
+        (self.inner, self.digest.finalize())
+    }
+}
+
+#[cfg(feature = "digest")]
+impl<W: Write, D: Digest> Write for HashingWriter<W, D> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        let written = buf.get(..n).unwrap_or_default();
+        self.digest.update(written);
+        self.bytes = self.bytes.saturating_add(written.len() as u64);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A writer that writes everything to two writers.
+///
+/// Each call writes the whole buffer to both writers, so they always receive the same bytes. If either writer fails,
+/// the error is returned, and the writers may then have received different amounts of the buffer.
+#[derive(Debug, Clone)]
+pub struct TeeWriter<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: Write, B: Write> TeeWriter<A, B> {
+    /// Create a writer that copies to `first` and `second`.
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+
+    /// References to the two writers.
+    pub fn get_ref(&self) -> (&A, &B) {
+        (&self.first, &self.second)
+    }
+
+    /// Return the two writers.
+    pub fn into_inner(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A: Write, B: Write> Write for TeeWriter<A, B> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.first.write_all(buf)?;
+        self.second.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.first.flush()?;
+        self.second.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[cfg(feature = "digest")]
+    #[test]
+    fn hashing_reader() {
+        use sha2::Sha256;
+
+        let data = (0..10_000u32).map(|i| i as u8).collect::<Vec<_>>();
+        let mut reader = HashingReader::<_, Sha256>::new(&data[..]);
+        let mut buf = [0u8; 7];
+        reader.read_exact(&mut buf).unwrap();
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(reader.bytes_read(), 10_000);
+        let (remaining, hash) = reader.finalize();
+        assert!(remaining.is_empty());
+        assert_eq!(hash, Sha256::digest(&data));
+    }
+
+    #[cfg(feature = "digest")]
+    #[test]
+    fn hashing_writer_with_tee() {
+        use sha2::Sha256;
+
+        let mut writer = HashingWriter::<_, Sha256>::new(TeeWriter::new(Vec::new(), Vec::new()));
+        writer.write_all(b"hello ").unwrap();
+        writer.write_all(b"world").unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.bytes_written(), 11);
+        let (tee, hash) = writer.finalize();
+        assert_eq!(hash, Sha256::digest(b"hello world"));
+        assert_eq!(tee.get_ref(), (&b"hello world".to_vec(), &b"hello world".to_vec()));
+    }
+
+    #[test]
+    fn tee_stops_on_error() {
+        let mut space = [0u8; 2];
+        let mut tee = TeeWriter::new(Vec::new(), &mut space[..]);
+        assert!(tee.write_all(b"abc").is_err());
+        let (first, _) = tee.into_inner();
+        assert_eq!(first, b"abc");
+    }
+}
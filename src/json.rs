@@ -0,0 +1,700 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A minimal JSON encoder that only requires `alloc`.
+//!
+//! [to_string] serializes any `serde::Serialize` value to compact JSON, producing the same output as
+//! `serde_json::to_string` for strings, integers, sequences, maps, structs and enums. The `json-core` feature only
+//! needs `serde` with `alloc`, so this module can be used directly in `no_std` environments where `serde_json` is not
+//! available. When the `serialize` feature is enabled as well, it becomes the `MessageFormat::to_json` backend;
+//! parsing still uses `serde_json`.
+
+use alloc::string::{String, ToString};
+use core::fmt::{self, Display, Write};
+
+use serde::{ser, ser::Impossible, Serialize};
+use snafu::prelude::*;
+
+/// Errors produced while encoding JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum JsonError {
+    /// A map key was not a string, character, integer or boolean.
+    #[snafu(display("JSON map keys must be strings"))]
+    KeyMustBeString {},
+    /// A `Serialize` implementation reported an error.
+    #[snafu(display("Could not serialize to JSON: `{reason}'"))]
+    Custom {
+        /// The reason for the error
+        reason: String,
+    },
+}
+
+impl ser::StdError for JsonError {}
+
+impl ser::Error for JsonError {
+    fn custom<T: Display>(msg: T) -> Self {
+        JsonError::Custom {
+            reason: msg.to_string(),
+        }
+    }
+}
+
+/// Serialize the given value as a compact JSON string.
+pub fn to_string<T>(value: &T) -> Result<String, JsonError>
+where T: Serialize + ?Sized {
+    let mut serializer = Serializer { out: String::new() };
+    value.serialize(&mut serializer)?;
+    Ok(serializer.out)
+}
+
+/// Write `s` as a quoted JSON string, escaping quotes, backslashes and control characters.
+fn write_str(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            c if c < '\u{20}' => {
+                let _ = write!(out, "\\u{:04x}", u32::from(c));
+            },
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Write a value using its `Display` implementation. Writing to a `String` cannot fail.
+fn write_display<T: Display>(out: &mut String, value: T) {
+    let _ = write!(out, "{}", value);
+}
+
+/// Write a float, or `null` if it is not finite, as JSON has no representation for NaN or infinity.
+fn write_float<T: fmt::Debug>(out: &mut String, value: T, is_finite: bool) {
+    if is_finite {
+        let _ = write!(out, "{:?}", value);
+    } else {
+        out.push_str("null");
+    }
+}
+
+/// The JSON serializer, which appends to an output string.
+struct Serializer {
+    out: String,
+}
+
+/// The state of a sequence, map or struct that is being serialized.
+struct Compound<'a> {
+    ser: &'a mut Serializer,
+    first: bool,
+    close: &'static str,
+}
+
+impl<'a> Compound<'a> {
+    fn new(ser: &'a mut Serializer, open: &str, close: &'static str) -> Self {
+        ser.out.push_str(open);
+        Compound {
+            ser,
+            first: true,
+            close,
+        }
+    }
+
+    fn separator(&mut self) {
+        if !self.first {
+            self.ser.out.push(',');
+        }
+        self.first = false;
+    }
+
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        self.separator();
+        value.serialize(&mut *self.ser)
+    }
+
+    fn field<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), JsonError> {
+        self.separator();
+        write_str(&mut self.ser.out, key);
+        self.ser.out.push(':');
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        self.ser.out.push_str(self.close);
+        Ok(())
+    }
+}
+
+impl<'a> ser::Serializer for &'a mut Serializer {
+    type Error = JsonError;
+    type Ok = ();
+    type SerializeMap = Compound<'a>;
+    type SerializeSeq = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), JsonError> {
+        self.out.push_str(if v { "true" } else { "false" });
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), JsonError> {
+        write_display(&mut self.out, v);
+        Ok(())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), JsonError> {
+        write_display(&mut self.out, v);
+        Ok(())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), JsonError> {
+        write_display(&mut self.out, v);
+        Ok(())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), JsonError> {
+        write_display(&mut self.out, v);
+        Ok(())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), JsonError> {
+        write_display(&mut self.out, v);
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), JsonError> {
+        write_display(&mut self.out, v);
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), JsonError> {
+        write_display(&mut self.out, v);
+        Ok(())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), JsonError> {
+        write_display(&mut self.out, v);
+        Ok(())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), JsonError> {
+        write_display(&mut self.out, v);
+        Ok(())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), JsonError> {
+        write_display(&mut self.out, v);
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), JsonError> {
+        write_float(&mut self.out, v, v.is_finite());
+        Ok(())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), JsonError> {
+        write_float(&mut self.out, v, v.is_finite());
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), JsonError> {
+        self.serialize_str(v.encode_utf8(&mut [0u8; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), JsonError> {
+        write_str(&mut self.out, v);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), JsonError> {
+        let mut seq = Compound::new(self, "[", "]");
+        for byte in v {
+            seq.element(byte)?;
+        }
+        seq.end()
+    }
+
+    fn serialize_none(self) -> Result<(), JsonError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), JsonError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), JsonError> {
+        self.out.push_str("null");
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), JsonError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), JsonError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<(), JsonError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), JsonError> {
+        let mut object = Compound::new(self, "{", "}");
+        object.field(variant, value)?;
+        object.end()
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a>, JsonError> {
+        Ok(Compound::new(self, "[", "]"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'a>, JsonError> {
+        Ok(Compound::new(self, "[", "]"))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'a>, JsonError> {
+        Ok(Compound::new(self, "[", "]"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, JsonError> {
+        self.out.push('{');
+        write_str(&mut self.out, variant);
+        Ok(Compound::new(self, ":[", "]}"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'a>, JsonError> {
+        Ok(Compound::new(self, "{", "}"))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Compound<'a>, JsonError> {
+        Ok(Compound::new(self, "{", "}"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, JsonError> {
+        self.out.push('{');
+        write_str(&mut self.out, variant);
+        Ok(Compound::new(self, ":{", "}}"))
+    }
+}
+
+impl<'a> ser::SerializeSeq for Compound<'a> {
+    type Error = JsonError;
+    type Ok = ();
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeTuple for Compound<'a> {
+    type Error = JsonError;
+    type Ok = ();
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for Compound<'a> {
+    type Error = JsonError;
+    type Ok = ();
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeTupleVariant for Compound<'a> {
+    type Error = JsonError;
+    type Ok = ();
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeMap for Compound<'a> {
+    type Error = JsonError;
+    type Ok = ();
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), JsonError> {
+        self.separator();
+        key.serialize(MapKeySerializer { out: &mut self.ser.out })?;
+        self.ser.out.push(':');
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), JsonError> {
+        value.serialize(&mut *self.ser)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeStruct for Compound<'a> {
+    type Error = JsonError;
+    type Ok = ();
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), JsonError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        Compound::end(self)
+    }
+}
+
+impl<'a> ser::SerializeStructVariant for Compound<'a> {
+    type Error = JsonError;
+    type Ok = ();
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), JsonError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), JsonError> {
+        Compound::end(self)
+    }
+}
+
+/// Serializes map keys, which JSON requires to be strings. Integers and booleans are quoted.
+struct MapKeySerializer<'a> {
+    out: &'a mut String,
+}
+
+impl<'a> MapKeySerializer<'a> {
+    fn quoted<T: Display>(self, value: T) -> Result<(), JsonError> {
+        self.out.push('"');
+        write_display(self.out, value);
+        self.out.push('"');
+        Ok(())
+    }
+}
+
+impl<'a> ser::Serializer for MapKeySerializer<'a> {
+    type Error = JsonError;
+    type Ok = ();
+    type SerializeMap = Impossible<(), JsonError>;
+    type SerializeSeq = Impossible<(), JsonError>;
+    type SerializeStruct = Impossible<(), JsonError>;
+    type SerializeStructVariant = Impossible<(), JsonError>;
+    type SerializeTuple = Impossible<(), JsonError>;
+    type SerializeTupleStruct = Impossible<(), JsonError>;
+    type SerializeTupleVariant = Impossible<(), JsonError>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), JsonError> {
+        self.quoted(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), JsonError> {
+        self.quoted(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), JsonError> {
+        self.quoted(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), JsonError> {
+        self.quoted(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), JsonError> {
+        self.quoted(v)
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), JsonError> {
+        self.quoted(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), JsonError> {
+        self.quoted(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), JsonError> {
+        self.quoted(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), JsonError> {
+        self.quoted(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), JsonError> {
+        self.quoted(v)
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), JsonError> {
+        self.quoted(v)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<(), JsonError> {
+        Err(JsonError::KeyMustBeString {})
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<(), JsonError> {
+        Err(JsonError::KeyMustBeString {})
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), JsonError> {
+        write_str(self.out, v.encode_utf8(&mut [0u8; 4]));
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), JsonError> {
+        write_str(self.out, v);
+        Ok(())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<(), JsonError> {
+        Err(JsonError::KeyMustBeString {})
+    }
+
+    fn serialize_none(self) -> Result<(), JsonError> {
+        Err(JsonError::KeyMustBeString {})
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<(), JsonError> {
+        Err(JsonError::KeyMustBeString {})
+    }
+
+    fn serialize_unit(self) -> Result<(), JsonError> {
+        Err(JsonError::KeyMustBeString {})
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), JsonError> {
+        Err(JsonError::KeyMustBeString {})
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), JsonError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<(), JsonError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), JsonError> {
+        Err(JsonError::KeyMustBeString {})
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, JsonError> {
+        Err(JsonError::KeyMustBeString {})
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, JsonError> {
+        Err(JsonError::KeyMustBeString {})
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct, JsonError> {
+        Err(JsonError::KeyMustBeString {})
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, JsonError> {
+        Err(JsonError::KeyMustBeString {})
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, JsonError> {
+        Err(JsonError::KeyMustBeString {})
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct, JsonError> {
+        Err(JsonError::KeyMustBeString {})
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, JsonError> {
+        Err(JsonError::KeyMustBeString {})
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::collections::BTreeMap;
+
+    use super::*;
+
+    /// The output is compared with `serde_json`, which needs the `serialize` feature.
+    #[cfg(feature = "serialize")]
+    mod serde_json_parity {
+        use alloc::{boxed::Box, collections::BTreeMap, vec::Vec};
+
+        use serde::Serialize;
+
+        use super::*;
+
+        #[derive(Serialize)]
+        struct Unit;
+
+        #[derive(Serialize)]
+        struct Newtype(u32);
+
+        #[derive(Serialize)]
+        struct Tuple(i8, String);
+
+        #[derive(Serialize)]
+        enum Enum {
+            Unit,
+            Newtype(u8),
+            Tuple(u8, bool),
+            Struct { a: i64, b: Option<Box<Enum>> },
+        }
+
+        #[derive(Serialize)]
+        struct Everything {
+            text: String,
+            chars: Vec<char>,
+            numbers: (u8, i16, u32, i64, u128, i128),
+            floats: Vec<f64>,
+            flags: [bool; 2],
+            bytes: &'static [u8],
+            nothing: Option<u8>,
+            unit: (),
+            unit_struct: Unit,
+            newtype: Newtype,
+            tuple: Tuple,
+            enums: Vec<Enum>,
+            map: BTreeMap<u16, Vec<String>>,
+            string_map: BTreeMap<String, Option<bool>>,
+        }
+
+        fn assert_same_as_serde_json<T: Serialize>(value: &T) {
+            assert_eq!(to_string(value).unwrap(), serde_json::to_string(value).unwrap());
+        }
+
+        #[test]
+        fn matches_serde_json() {
+            let mut map = BTreeMap::new();
+            map.insert(1, vec!["one".to_string()]);
+            map.insert(200, vec![]);
+            let mut string_map = BTreeMap::new();
+            string_map.insert("a\"b".to_string(), Some(true));
+            string_map.insert(String::new(), None);
+            let value = Everything {
+                text: "quote \" backslash \\ controls \n\r\t\u{08}\u{0c}\u{01}\u{1f} unicode 🖖 é \u{7f}".to_string(),
+                chars: vec!['a', '"', '\u{0}', 'é'],
+                numbers: (255, -32768, u32::MAX, i64::MIN, u128::MAX, i128::MIN),
+                floats: vec![0.0, -1.5, 0.1, 3.25],
+                flags: [true, false],
+                bytes: &[0, 1, 255],
+                nothing: None,
+                unit: (),
+                unit_struct: Unit,
+                newtype: Newtype(7),
+                tuple: Tuple(-1, "x".to_string()),
+                enums: vec![Enum::Unit, Enum::Newtype(1), Enum::Tuple(2, true), Enum::Struct {
+                    a: -3,
+                    b: Some(Box::new(Enum::Unit)),
+                }],
+                map,
+                string_map,
+            };
+            assert_same_as_serde_json(&value);
+            assert_same_as_serde_json(&Vec::<u8>::new());
+            assert_same_as_serde_json(&BTreeMap::<String, u8>::new());
+            assert_same_as_serde_json(&"");
+        }
+    }
+
+    #[test]
+    fn non_finite_floats_are_null() {
+        assert_eq!(to_string(&[f64::NAN, f64::INFINITY]).unwrap(), "[null,null]");
+        assert_eq!(to_string(&f32::NEG_INFINITY).unwrap(), "null");
+    }
+
+    #[test]
+    fn invalid_keys() {
+        let mut map = BTreeMap::new();
+        map.insert(vec![1u8], 1u8);
+        assert_eq!(to_string(&map).unwrap_err(), JsonError::KeyMustBeString {});
+    }
+}
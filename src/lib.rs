@@ -21,40 +21,133 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 //! A set of useful and commonly used utilities that are used in several places in the Tari project.
+//!
+//! # Panic freedom
+//! Every function that parses or decodes external data (hex, base58, byte arrays, bit arrays, serialized messages and
+//! the FFI entry points) reports failures through a `Result` or `Option` and never panics, regardless of the input.
+//! This is enforced for the whole crate by denying the clippy lints for indexing, slicing, unchecked arithmetic and
+//! `unwrap`/`expect`/`panic` below. The few exceptions are `const fn`s evaluated at compile time, and convenience
+//! functions whose panics are documented and which have a `_checked` variant returning a `Result`, such as
+//! `InternPool::intern_checked`.
 
 #![no_std]
+#![cfg_attr(
+    not(test),
+    deny(
+        clippy::indexing_slicing,
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::panic,
+        clippy::unreachable,
+        clippy::arithmetic_side_effects
+    )
+)]
 // This is to allow no_std use
 #[allow(unused_imports)]
 #[macro_use]
 extern crate alloc;
 
-#[cfg(any(feature = "std", feature = "test"))]
+#[cfg(any(feature = "std", test))]
 #[macro_use]
 extern crate std;
 
+// The derive macros refer to this crate by name
+#[cfg(all(test, feature = "derive"))]
+extern crate self as tari_utilities;
+
+pub mod base32;
+pub mod base64;
+pub mod bech32;
 pub mod bit;
 pub mod byte_array;
+pub mod byte_cursor;
+pub mod byte_diff;
+pub mod byte_newtype;
+pub mod byte_patch;
+pub mod byte_size;
+pub mod checksum;
 pub mod convert;
 #[cfg(feature = "std")]
+pub mod counter;
+#[cfg(feature = "std")]
+pub mod ct;
+pub mod dammsum;
+#[cfg(feature = "std")]
 pub mod encoding;
+pub mod endian;
+#[cfg(feature = "std")]
+pub mod env;
 #[cfg(feature = "std")]
 pub mod epoch_time;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "digest")]
+pub mod fingerprint;
 pub mod fixed_set;
+pub mod fixed_string;
+pub mod framing;
 pub mod hash;
+#[cfg(feature = "digest")]
+pub mod hashing;
 pub mod hex;
 #[cfg(feature = "zeroize")]
 pub mod hidden;
+#[cfg(feature = "std")]
+pub mod intern;
+#[cfg(feature = "std")]
+pub mod io;
+#[cfg(feature = "json-core")]
+pub mod json;
+pub mod literal;
 pub mod locks;
-#[cfg(feature = "serde")]
+pub mod luhn;
+pub mod max_size_bytes;
+#[cfg(feature = "serialize")]
 pub mod message_format;
+#[cfg(feature = "std")]
+pub mod metrics;
+pub mod multibase;
+#[cfg(feature = "net")]
+pub mod net;
+#[cfg(feature = "rayon")]
+pub mod parallel;
 #[cfg(feature = "zeroize")]
 pub mod password;
 #[cfg(feature = "std")]
+pub mod path;
+pub mod percent;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "rand")]
+pub mod random;
+#[cfg(feature = "std")]
 pub mod safe_array;
+pub mod sanitize;
+#[cfg(feature = "sealed-file")]
+pub mod sealed_file;
+#[cfg(feature = "secret_sharing")]
+pub mod secret_sharing;
+pub mod semver_lite;
 #[cfg(feature = "serde")]
 pub mod serde;
+#[cfg(feature = "simd")]
+pub mod simd;
+pub mod spsc;
+#[cfg(all(feature = "std", feature = "zeroize"))]
+pub mod time_locked;
+#[cfg(feature = "tracing")]
+pub mod trace;
+pub mod ulid;
+pub mod uuid;
+pub mod varint;
+pub mod wordlist;
+#[cfg(feature = "derive")]
+pub use tari_utilities_derive::{ByteArray, ByteArrayRef, ByteNewtype};
+
 pub use self::{
-    byte_array::{ByteArray, ByteArrayError},
+    byte_array::{ByteArray, ByteArrayError, ByteArrayRef},
+    error::{ErrorCode, UtilError},
     hash::Hashable,
 };
 #[cfg(feature = "zeroize")]
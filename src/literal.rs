@@ -0,0 +1,312 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Compile-time decoding of base58 and base32 literals.
+//!
+//! The [b58](crate::b58) and [b32](crate::b32) macros decode a string literal into a byte array while compiling, so a
+//! mistyped well-known constant is a compile error rather than a panic at startup. Like the [hex](macro@crate::hex)
+//! macro, they are backed by `const fn` decoders that can also be used directly.
+//!
+//! Base58 uses the block-based Monero variant, matching [Base58](crate::encoding::Base58). Base32 uses the RFC 4648
+//! alphabet of [Base32Variant::Standard], in either case, with or without `=` padding.
+//!
+//! ```edition2018
+//! # use tari_utilities::{b32, b58};
+//! const NETWORK_ID: [u8; 6] = b32!("MZXW6YTBOI");
+//! const ADDRESS: [u8; 3] = b58!("11Ldp");
+//! assert_eq!(&NETWORK_ID, b"foobar");
+//! assert_eq!(ADDRESS, [1, 2, 3]);
+//! ```
+
+use crate::base32::Base32Variant;
+
+/// The base58 alphabet, which the Monero and Bitcoin variants share.
+pub(crate) const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+/// The number of characters encoding 0 to 8 bytes of a Monero base58 block.
+pub(crate) const BASE58_ENCODED_BLOCK_SIZES: [usize; BASE58_BLOCK_SIZE + 1] = [0, 2, 3, 5, 6, 7, 9, 10, 11];
+/// The number of bytes in a full Monero base58 block.
+pub(crate) const BASE58_BLOCK_SIZE: usize = 8;
+const BASE58_ENCODED_BLOCK_SIZE: usize = 11;
+
+const fn base58_digit(c: u8) -> Option<u64> {
+    let mut rest: &[u8] = BASE58_ALPHABET;
+    let mut digit = 0;
+    while let [first, tail @ ..] = rest {
+        if *first == c {
+            return Some(digit);
+        }
+        rest = tail;
+        digit = digit.saturating_add(1);
+    }
+    None
+}
+
+const fn base58_len(encoded_len: usize) -> Option<usize> {
+    let rem = encoded_len % BASE58_ENCODED_BLOCK_SIZE;
+    let mut rest: &[usize] = &BASE58_ENCODED_BLOCK_SIZES;
+    let mut size = 0;
+    while let [first, tail @ ..] = rest {
+        if *first == rem {
+            let blocks = (encoded_len / BASE58_ENCODED_BLOCK_SIZE).saturating_mul(BASE58_BLOCK_SIZE);
+            return Some(blocks.saturating_add(size));
+        }
+        rest = tail;
+        size = size.saturating_add(1);
+    }
+    None
+}
+
+/// The number of bytes encoded by a Monero base58 string, or zero if its length is not valid.
+pub const fn base58_decoded_len(encoded: &str) -> usize {
+    match base58_len(encoded.len()) {
+        Some(len) => len,
+        None => 0,
+    }
+}
+
+/// Decode a Monero base58 string into a fixed-size array, or return `None` if it is invalid or does not decode to
+/// exactly `N` bytes.
+// Indices are bounded by the input length, which is checked against `N` up front
+#[allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+pub const fn decode_base58_array<const N: usize>(encoded: &str) -> Option<[u8; N]> {
+    let chars = encoded.as_bytes();
+    match base58_len(chars.len()) {
+        Some(len) if len == N => {},
+        _ => return None,
+    }
+    let mut result = [0u8; N];
+    let mut start = 0;
+    let mut pos = 0;
+    while start < chars.len() {
+        let block_len = if chars.len() - start < BASE58_ENCODED_BLOCK_SIZE {
+            chars.len() - start
+        } else {
+            BASE58_ENCODED_BLOCK_SIZE
+        };
+        // Only the last block can be partial, and it fills the rest of the output
+        let size = if block_len == BASE58_ENCODED_BLOCK_SIZE {
+            BASE58_BLOCK_SIZE
+        } else {
+            N - pos
+        };
+        let mut value = 0u64;
+        let mut i = 0;
+        while i < block_len {
+            let digit = match base58_digit(chars[start + i]) {
+                Some(digit) => digit,
+                None => return None,
+            };
+            value = match value.checked_mul(58) {
+                Some(value) => value,
+                None => return None,
+            };
+            value = match value.checked_add(digit) {
+                Some(value) => value,
+                None => return None,
+            };
+            i += 1;
+        }
+        if size < BASE58_BLOCK_SIZE && value >> (8 * size) != 0 {
+            return None;
+        }
+        let mut j = 0;
+        while j < size {
+            result[pos + j] = (value >> (8 * (size - 1 - j))) as u8;
+            j += 1;
+        }
+        pos += size;
+        start += block_len;
+    }
+    Some(result)
+}
+
+const fn base32_digit(c: u8) -> Option<u32> {
+    match Base32Variant::Standard.decode_symbol(c) {
+        Some(digit) => Some(digit as u32),
+        None => None,
+    }
+}
+
+/// The number of characters before any `=` padding.
+const fn base32_unpadded_len(mut chars: &[u8]) -> usize {
+    while let [rest @ .., b'='] = chars {
+        chars = rest;
+    }
+    chars.len()
+}
+
+const fn base32_len(chars: &[u8]) -> Option<usize> {
+    let len = base32_unpadded_len(chars);
+    // Padding, if present, must complete the last group of 8 characters
+    if len != chars.len() && chars.len() != len.div_ceil(8).saturating_mul(8) {
+        return None;
+    }
+    match len % 8 {
+        0 | 2 | 4 | 5 | 7 => Some(len.saturating_mul(5) / 8),
+        _ => None,
+    }
+}
+
+/// The number of bytes encoded by an RFC 4648 base32 string, or zero if its length is not valid.
+pub const fn base32_decoded_len(encoded: &str) -> usize {
+    match base32_len(encoded.as_bytes()) {
+        Some(len) => len,
+        None => 0,
+    }
+}
+
+/// Decode an RFC 4648 base32 string into a fixed-size array, or return `None` if it is invalid or does not decode to
+/// exactly `N` bytes. Unused bits in the last character must be zero.
+// Indices are bounded by the input length, which is checked against `N` up front
+#[allow(clippy::indexing_slicing, clippy::arithmetic_side_effects)]
+pub const fn decode_base32_array<const N: usize>(encoded: &str) -> Option<[u8; N]> {
+    let chars = encoded.as_bytes();
+    match base32_len(chars) {
+        Some(len) if len == N => {},
+        _ => return None,
+    }
+    let mut result = [0u8; N];
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    let mut pos = 0;
+    let mut i = 0;
+    while i < base32_unpadded_len(chars) {
+        let digit = match base32_digit(chars[i]) {
+            Some(digit) => digit,
+            None => return None,
+        };
+        buffer = (buffer << 5) | digit;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            result[pos] = (buffer >> bits) as u8;
+            pos += 1;
+        }
+        buffer &= (1 << bits) - 1;
+        i += 1;
+    }
+    if buffer != 0 {
+        return None;
+    }
+    Some(result)
+}
+
+/// Decode a Monero base58 string literal into a byte array at compile time. Invalid literals fail to compile.
+///
+/// ```edition2018
+/// # use tari_utilities::b58;
+/// const KEY: [u8; 8] = b58!("1111111111A");
+/// assert_eq!(KEY, [0, 0, 0, 0, 0, 0, 0, 9]);
+/// ```
+///
+/// ```compile_fail
+/// # use tari_utilities::b58;
+/// const KEY: [u8; 1] = b58!("0O");
+/// ```
+#[macro_export]
+macro_rules! b58 {
+    ($encoded:literal) => {{
+        const BYTES: [u8; $crate::literal::base58_decoded_len($encoded)] =
+            match $crate::literal::decode_base58_array::<{ $crate::literal::base58_decoded_len($encoded) }>($encoded) {
+                Some(bytes) => bytes,
+                None => panic!(concat!("Invalid base58 literal: ", $encoded)),
+            };
+        BYTES
+    }};
+}
+
+/// Decode an RFC 4648 base32 string literal into a byte array at compile time. Invalid literals fail to compile.
+///
+/// ```edition2018
+/// # use tari_utilities::b32;
+/// assert_eq!(b32!("mzxw6==="), *b"foo");
+/// ```
+///
+/// ```compile_fail
+/// # use tari_utilities::b32;
+/// const ID: [u8; 1] = b32!("MZ1=====");
+/// ```
+#[macro_export]
+macro_rules! b32 {
+    ($encoded:literal) => {{
+        const BYTES: [u8; $crate::literal::base32_decoded_len($encoded)] =
+            match $crate::literal::decode_base32_array::<{ $crate::literal::base32_decoded_len($encoded) }>($encoded) {
+                Some(bytes) => bytes,
+                None => panic!(concat!("Invalid base32 literal: ", $encoded)),
+            };
+        BYTES
+    }};
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn base32_vectors() {
+        assert_eq!(crate::b32!(""), [0u8; 0]);
+        assert_eq!(crate::b32!("MY======"), *b"f");
+        assert_eq!(crate::b32!("MZXQ===="), *b"fo");
+        assert_eq!(crate::b32!("MZXW6==="), *b"foo");
+        assert_eq!(crate::b32!("MZXW6YQ="), *b"foob");
+        assert_eq!(crate::b32!("MZXW6YTB"), *b"fooba");
+        assert_eq!(crate::b32!("mzxw6ytboi"), *b"foobar");
+    }
+
+    #[test]
+    fn invalid_base32() {
+        assert_eq!(decode_base32_array::<1>("MY"), Some(*b"f"));
+        assert_eq!(decode_base32_array::<1>("MZ"), None); // non-zero trailing bits
+        assert_eq!(decode_base32_array::<1>("M1"), None);
+        assert_eq!(decode_base32_array::<1>("MY="), None);
+        assert_eq!(decode_base32_array::<1>("MYA"), None);
+        assert_eq!(decode_base32_array::<2>("MY"), None);
+        assert_eq!(base32_decoded_len("MYA"), 0);
+    }
+
+    #[test]
+    fn base58_literals() {
+        assert_eq!(crate::b58!(""), [0u8; 0]);
+        assert_eq!(crate::b58!("11"), [0]);
+        assert_eq!(crate::b58!("5Q"), [0xff]);
+        assert_eq!(decode_base58_array::<1>("5R"), None); // overflows one byte
+        assert_eq!(decode_base58_array::<8>("jpXCZedGfVQ"), Some([0xff; 8]));
+        assert_eq!(decode_base58_array::<8>("jpXCZedGfVR"), None); // overflows a full block
+        assert_eq!(decode_base58_array::<1>("1l"), None);
+        assert_eq!(decode_base58_array::<1>("1"), None);
+        assert_eq!(base58_decoded_len("1"), 0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn base58_matches_encoding() {
+        let data = (0..=40u8).map(|i| i.wrapping_mul(97)).collect::<alloc::vec::Vec<_>>();
+        macro_rules! check {
+            ($($len:literal),*) => {$(
+                let encoded = base58_monero::encode(&data[..$len]).unwrap();
+                assert_eq!(base58_decoded_len(&encoded), $len);
+                assert_eq!(decode_base58_array::<$len>(&encoded).unwrap()[..], data[..$len]);
+            )*};
+        }
+        check!(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 15, 16, 17, 33, 41);
+    }
+}
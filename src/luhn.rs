@@ -0,0 +1,226 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Luhn mod N check characters over strings of symbols from an arbitrary alphabet.
+//!
+//! The Luhn mod N algorithm generalises the Luhn check digit used for card numbers to alphabets of any size. It
+//! appends a single check character that detects every single-character error and most transpositions of adjacent
+//! characters. For numeric codes use the ten decimal digits as the alphabet, which gives the classic Luhn algorithm.
+//!
+//! ```edition2018
+//! # use tari_utilities::luhn::LuhnModN;
+//! let luhn = LuhnModN::new(['0', '1', '2', '3', '4', '5', '6', '7', '8', '9']).unwrap();
+//! assert_eq!(luhn.encode("7992739871").unwrap(), "79927398713");
+//! assert!(luhn.verify("79927398713").is_ok());
+//! assert!(luhn.verify("79927398710").is_err());
+//! ```
+
+use alloc::string::String;
+use core::convert::TryFrom;
+
+use snafu::prelude::*;
+
+/// The largest supported alphabet, as characters are represented by their `u8` index in the alphabet.
+pub const MAX_ALPHABET_SIZE: usize = 256;
+
+/// Errors for [LuhnModN].
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum LuhnError {
+    /// The alphabet has fewer than 2 or more than [MAX_ALPHABET_SIZE] symbols.
+    #[snafu(display("Alphabets of {size} symbols are not supported"))]
+    UnsupportedAlphabetSize {
+        /// The size of the alphabet
+        size: usize,
+    },
+    /// The alphabet contains the same symbol more than once.
+    #[snafu(display("The alphabet contains `{symbol}' more than once"))]
+    DuplicateSymbol {
+        /// The repeated symbol
+        symbol: char,
+    },
+    /// The input contains a symbol that is not in the alphabet.
+    #[snafu(display("`{symbol}' is not in the alphabet"))]
+    InvalidSymbol {
+        /// The unknown symbol
+        symbol: char,
+    },
+    /// The input contains a digit that is not less than the alphabet size.
+    #[snafu(display("{digit} is not a valid digit for the alphabet"))]
+    InvalidDigit {
+        /// The invalid digit
+        digit: u8,
+    },
+    /// There is no check character to verify.
+    #[snafu(display("The input is empty"))]
+    MissingChecksum {},
+    /// The check character does not match.
+    #[snafu(display("The check character is invalid"))]
+    InvalidChecksum {},
+}
+
+/// A Luhn mod N check character over an alphabet of `N` symbols.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LuhnModN<const N: usize> {
+    alphabet: [char; N],
+}
+
+impl<const N: usize> LuhnModN<N> {
+    /// Create a check character generator for the alphabet. The position of each symbol in the alphabet is its digit
+    /// value.
+    ///
+    /// # Errors
+    /// Returns an error if the alphabet size is not supported or a symbol appears more than once.
+    pub fn new(alphabet: [char; N]) -> Result<Self, LuhnError> {
+        ensure!((2..=MAX_ALPHABET_SIZE).contains(&N), UnsupportedAlphabetSizeSnafu {
+            size: N
+        });
+        for (i, symbol) in alphabet.iter().enumerate() {
+            if alphabet.iter().skip(i.saturating_add(1)).any(|other| other == symbol) {
+                return DuplicateSymbolSnafu { symbol: *symbol }.fail();
+            }
+        }
+        Ok(Self { alphabet })
+    }
+
+    /// Return the check digit for the digits, which must each be less than `N`.
+    pub fn checksum_digits(&self, digits: &[u8]) -> Result<u8, LuhnError> {
+        let sum = Self::sum(digits.iter().copied().map(Ok), 2)?;
+        // The sum is reduced modulo `N`, so the check digit is less than `N` and fits in a `u8`
+        Ok(u8::try_from(N.saturating_sub(sum).checked_rem(N).unwrap_or_default()).unwrap_or_default())
+    }
+
+    /// Verify that the last digit is the check digit of the preceding digits.
+    pub fn verify_digits(&self, digits: &[u8]) -> Result<(), LuhnError> {
+        ensure!(!digits.is_empty(), MissingChecksumSnafu);
+        let sum = Self::sum(digits.iter().copied().map(Ok), 1)?;
+        ensure!(sum == 0, InvalidChecksumSnafu);
+        Ok(())
+    }
+
+    /// Return the string with its check character appended.
+    pub fn encode(&self, s: &str) -> Result<String, LuhnError> {
+        let sum = Self::sum(s.chars().map(|symbol| self.digit(symbol)), 2)?;
+        let check = N.saturating_sub(sum).checked_rem(N).unwrap_or_default();
+        let mut encoded = String::with_capacity(s.len().saturating_add(4));
+        encoded.push_str(s);
+        encoded.extend(self.alphabet.get(check).copied());
+        Ok(encoded)
+    }
+
+    /// Verify that the last character of the string is the check character of the preceding characters, returning
+    /// the string without it.
+    pub fn verify<'a>(&self, s: &'a str) -> Result<&'a str, LuhnError> {
+        let (last, _) = s.char_indices().last().context(MissingChecksumSnafu)?;
+        let sum = Self::sum(s.chars().map(|symbol| self.digit(symbol)), 1)?;
+        ensure!(sum == 0, InvalidChecksumSnafu);
+        Ok(s.get(..last).unwrap_or_default())
+    }
+
+    fn digit(&self, symbol: char) -> Result<u8, LuhnError> {
+        self.alphabet
+            .iter()
+            .position(|c| *c == symbol)
+            .and_then(|i| u8::try_from(i).ok())
+            .context(InvalidSymbolSnafu { symbol })
+    }
+
+    /// Return the Luhn sum of the digits modulo `N`. Digits are processed from the right, with the rightmost digit
+    /// multiplied by `first_factor` and the factor alternating between 2 and 1.
+    fn sum<I>(digits: I, first_factor: usize) -> Result<usize, LuhnError>
+    where I: DoubleEndedIterator<Item = Result<u8, LuhnError>> {
+        let mut factor = first_factor;
+        let mut sum = 0usize;
+        for digit in digits.rev() {
+            let digit = digit?;
+            ensure!(usize::from(digit) < N, InvalidDigitSnafu { digit });
+            // The digit is less than `N` and `N` is at most 256, so none of this can overflow
+            let addend = factor.saturating_mul(usize::from(digit));
+            let addend = addend
+                .checked_div(N)
+                .unwrap_or_default()
+                .saturating_add(addend.checked_rem(N).unwrap_or_default());
+            sum = sum.saturating_add(addend).checked_rem(N).unwrap_or_default();
+            factor = if factor == 2 { 1 } else { 2 };
+        }
+        Ok(sum)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decimal() {
+        let luhn = LuhnModN::new(['0', '1', '2', '3', '4', '5', '6', '7', '8', '9']).unwrap();
+        assert_eq!(luhn.checksum_digits(&[7, 9, 9, 2, 7, 3, 9, 8, 7, 1]), Ok(3));
+        assert_eq!(luhn.encode("7992739871").unwrap(), "79927398713");
+        assert_eq!(luhn.verify("79927398713"), Ok("7992739871"));
+        assert_eq!(luhn.verify_digits(&[7, 9, 9, 2, 7, 3, 9, 8, 7, 1, 3]), Ok(()));
+        for wrong in 0..10 {
+            if wrong != 3 {
+                let mut digits = [7, 9, 9, 2, 7, 3, 9, 8, 7, 1, wrong];
+                assert_eq!(luhn.verify_digits(&digits), Err(LuhnError::InvalidChecksum {}));
+                digits[10] = 3;
+                digits.swap(0, 1);
+                assert!(luhn.verify_digits(&digits).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn other_alphabets() {
+        let luhn = LuhnModN::new(['a', 'b', 'c', 'd', 'e', 'f']).unwrap();
+        assert_eq!(luhn.encode("abcdef").unwrap(), "abcdefe");
+
+        let mut base32 = ['a'; 32];
+        for (c, symbol) in base32.iter_mut().zip("abcdefghijklmnopqrstuvwxyz234567".chars()) {
+            *c = symbol;
+        }
+        let luhn = LuhnModN::new(base32).unwrap();
+        assert_eq!(luhn.encode("invitecode").unwrap(), "invitecodev");
+        assert_eq!(luhn.verify("invitecodev"), Ok("invitecode"));
+        assert_eq!(luhn.verify("invitecodew"), Err(LuhnError::InvalidChecksum {}));
+        assert_eq!(luhn.verify("invitecoxev"), Err(LuhnError::InvalidChecksum {}));
+        assert_eq!(
+            luhn.verify("invitecod!v"),
+            Err(LuhnError::InvalidSymbol { symbol: '!' })
+        );
+        assert_eq!(luhn.encode("").unwrap(), "a");
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert_eq!(
+            LuhnModN::new(['a']),
+            Err(LuhnError::UnsupportedAlphabetSize { size: 1 })
+        );
+        assert_eq!(
+            LuhnModN::new(['a', 'b', 'a']),
+            Err(LuhnError::DuplicateSymbol { symbol: 'a' })
+        );
+        let luhn = LuhnModN::new(['0', '1']).unwrap();
+        assert_eq!(luhn.verify(""), Err(LuhnError::MissingChecksum {}));
+        assert_eq!(luhn.verify_digits(&[]), Err(LuhnError::MissingChecksum {}));
+        assert_eq!(luhn.checksum_digits(&[2]), Err(LuhnError::InvalidDigit { digit: 2 }));
+    }
+}
@@ -0,0 +1,256 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A byte vector with a maximum length fixed at compile time, for consensus data with size limits.
+//!
+//! Every constructor, parser and deserializer of [MaxSizeBytes] rejects data longer than `MAX`, and Borsh checks the
+//! length prefix before allocating, so fields of this type cannot be used to smuggle in oversized data.
+//!
+//! ```edition2018
+//! # use core::convert::TryFrom;
+//! # use tari_utilities::max_size_bytes::MaxSizeBytes;
+//! type Script = MaxSizeBytes<4>;
+//!
+//! let script = Script::try_from(vec![1, 2, 3]).unwrap();
+//! assert_eq!(&*script, &[1, 2, 3]);
+//! assert!(Script::try_from(vec![0; 5]).is_err());
+//! ```
+
+use alloc::vec::Vec;
+use core::{convert::TryFrom, ops::Deref};
+
+use crate::{ByteArray, ByteArrayError};
+
+/// A `Vec<u8>` of at most `MAX` bytes. It dereferences to a byte slice, and gets hex, base58 and the other byte array
+/// encodings through [ByteArray].
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MaxSizeBytes<const MAX: usize>(Vec<u8>);
+
+impl<const MAX: usize> MaxSizeBytes<MAX> {
+    /// The maximum length in bytes.
+    pub const MAX_LEN: usize = MAX;
+
+    /// An empty value.
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Wrap the given bytes, failing if they are longer than `MAX`.
+    pub fn try_new(bytes: Vec<u8>) -> Result<Self, ByteArrayError> {
+        check_len::<MAX>(bytes.len())?;
+        Ok(Self(bytes))
+    }
+
+    /// Wrap the given bytes, keeping only the first `MAX`.
+    pub fn from_vec_truncated(mut bytes: Vec<u8>) -> Self {
+        bytes.truncate(MAX);
+        Self(bytes)
+    }
+
+    /// Append bytes, failing without changing the value if the result would be longer than `MAX`.
+    pub fn try_extend_from_slice(&mut self, bytes: &[u8]) -> Result<(), ByteArrayError> {
+        check_len::<MAX>(self.0.len().saturating_add(bytes.len()))?;
+        self.0.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    /// Return the wrapped bytes.
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+fn check_len<const MAX: usize>(len: usize) -> Result<(), ByteArrayError> {
+    if len > MAX {
+        return Err(ByteArrayError::incorrect_length(MAX, len).with_context("MaxSizeBytes"));
+    }
+    Ok(())
+}
+
+impl<const MAX: usize> ByteArray for MaxSizeBytes<MAX> {
+    fn from_canonical_bytes(bytes: &[u8]) -> Result<Self, ByteArrayError> {
+        check_len::<MAX>(bytes.len())?;
+        Ok(Self(bytes.to_vec()))
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const MAX: usize> Deref for MaxSizeBytes<MAX> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const MAX: usize> AsRef<[u8]> for MaxSizeBytes<MAX> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const MAX: usize> TryFrom<Vec<u8>> for MaxSizeBytes<MAX> {
+    type Error = ByteArrayError;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::try_new(bytes)
+    }
+}
+
+impl<const MAX: usize> TryFrom<&[u8]> for MaxSizeBytes<MAX> {
+    type Error = ByteArrayError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_canonical_bytes(bytes)
+    }
+}
+
+impl<const MAX: usize> From<MaxSizeBytes<MAX>> for Vec<u8> {
+    fn from(bytes: MaxSizeBytes<MAX>) -> Self {
+        bytes.0
+    }
+}
+
+/// Hex for human readable formats and bytes for binary formats, as [serde::hex](crate::serde::hex).
+#[cfg(feature = "serde")]
+impl<const MAX: usize> serde::Serialize for MaxSizeBytes<MAX> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serde::hex::serialize(self, serializer)
+    }
+}
+
+/// Rejects data longer than `MAX` bytes.
+#[cfg(feature = "serde")]
+impl<'de, const MAX: usize> serde::Deserialize<'de> for MaxSizeBytes<MAX> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serde::hex::deserialize(deserializer)
+    }
+}
+
+/// Encodes in the same format as a borsh `Vec<u8>`: a `u32` length followed by the bytes.
+#[cfg(feature = "borsh")]
+impl<const MAX: usize> borsh::BorshSerialize for MaxSizeBytes<MAX> {
+    fn serialize<W: borsh::maybestd::io::Write>(&self, writer: &mut W) -> borsh::maybestd::io::Result<()> {
+        borsh::BorshSerialize::serialize(&self.0, writer)
+    }
+}
+
+/// Rejects data longer than `MAX` bytes before reading it.
+#[cfg(feature = "borsh")]
+impl<const MAX: usize> borsh::BorshDeserialize for MaxSizeBytes<MAX> {
+    fn deserialize_reader<R: borsh::maybestd::io::Read>(reader: &mut R) -> borsh::maybestd::io::Result<Self> {
+        use borsh::maybestd::io::{Error, ErrorKind};
+
+        let len = u32::deserialize_reader(reader)?;
+        let len = usize::try_from(len)
+            .ok()
+            .filter(|len| *len <= MAX)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "bytes are too long"))?;
+        let mut bytes = alloc::vec![0u8; len];
+        reader.read_exact(&mut bytes)?;
+        Ok(Self(bytes))
+    }
+}
+
+/// Generates arbitrary bytes, truncated to `MAX`.
+#[cfg(feature = "arbitrary")]
+impl<'a, const MAX: usize> arbitrary::Arbitrary<'a> for MaxSizeBytes<MAX> {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Vec::<u8>::arbitrary(u).map(Self::from_vec_truncated)
+    }
+}
+
+/// Generates up to `MAX` arbitrary bytes.
+#[cfg(feature = "proptest")]
+impl<const MAX: usize> proptest::arbitrary::Arbitrary for MaxSizeBytes<MAX> {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        use proptest::{collection::vec, prelude::any, strategy::Strategy};
+        vec(any::<u8>(), 0..=MAX).prop_map(Self).boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::hex::Hex;
+
+    type Script = MaxSizeBytes<4>;
+
+    #[test]
+    fn construction() {
+        assert!(Script::new().is_empty());
+        assert_eq!(Script::MAX_LEN, 4);
+        let script = Script::try_new(vec![1, 2, 3, 4]).unwrap();
+        assert_eq!(script.len(), 4);
+        assert_eq!(
+            Script::try_new(vec![0; 5]).unwrap_err(),
+            ByteArrayError::IncorrectLength {
+                expected: 4,
+                actual: 5,
+                context: Some("MaxSizeBytes")
+            }
+        );
+        assert!(Script::try_from(&[0u8; 5][..]).is_err());
+        assert!(Script::from_canonical_bytes(&[0; 5]).is_err());
+        assert_eq!(Script::from_vec_truncated(vec![1, 2, 3, 4, 5]), script);
+        assert_eq!(Vec::from(script.clone()), vec![1, 2, 3, 4]);
+        assert_eq!(script.to_hex(), "01020304");
+        assert!(Script::from_hex("0102030405").is_err());
+
+        let mut script = Script::try_from(&[1u8][..]).unwrap();
+        script.try_extend_from_slice(&[2, 3]).unwrap();
+        assert!(script.try_extend_from_slice(&[4, 5]).is_err());
+        assert_eq!(script.into_vec(), vec![1, 2, 3]);
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn serde() {
+        let script = Script::try_new(vec![0xab, 0xcd]).unwrap();
+        assert_eq!(serde_json::to_string(&script).unwrap(), "\"abcd\"");
+        assert_eq!(serde_json::from_str::<Script>("\"abcd\"").unwrap(), script);
+        assert!(serde_json::from_str::<Script>("\"0102030405\"").is_err());
+        let binary = bincode::serialize(&script).unwrap();
+        assert_eq!(bincode::deserialize::<Script>(&binary).unwrap(), script);
+        let too_long = bincode::serialize(&MaxSizeBytes::<5>::try_new(vec![0; 5]).unwrap()).unwrap();
+        assert!(bincode::deserialize::<Script>(&too_long).is_err());
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn borsh() {
+        use borsh::{BorshDeserialize, BorshSerialize};
+
+        let script = Script::try_new(vec![0xab, 0xcd]).unwrap();
+        let bytes = script.try_to_vec().unwrap();
+        assert_eq!(bytes, [2, 0, 0, 0, 0xab, 0xcd]);
+        assert_eq!(Script::try_from_slice(&bytes).unwrap(), script);
+        assert!(Script::try_from_slice(&[5, 0, 0, 0, 1, 2, 3, 4, 5]).is_err());
+        assert!(Script::try_from_slice(&[255, 255, 255, 255]).is_err());
+    }
+}
@@ -24,11 +24,12 @@
 
 use alloc::{string::String, vec::Vec};
 
-use base64;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json;
 use snafu::prelude::*;
 
+use crate::base64::{from_base64, to_base64, Base64Variant};
+
 /// Errors for [MessageFormat] trait.
 #[derive(Debug, Snafu)]
 #[allow(missing_docs)]
@@ -67,13 +68,19 @@ where T: DeserializeOwned + Serialize
         bincode::serialize(self).map_err(|_| MessageFormatError::BinarySerializeError {})
     }
 
+    #[cfg(not(feature = "json-core"))]
     fn to_json(&self) -> Result<String, MessageFormatError> {
         serde_json::to_string(self).map_err(|_| MessageFormatError::JSONError {})
     }
 
+    #[cfg(feature = "json-core")]
+    fn to_json(&self) -> Result<String, MessageFormatError> {
+        crate::json::to_string(self).map_err(|_| MessageFormatError::JSONError {})
+    }
+
     fn to_base64(&self) -> Result<String, MessageFormatError> {
         let val = self.to_binary()?;
-        Ok(base64::encode(val))
+        Ok(to_base64(&val, Base64Variant::Standard))
     }
 
     fn from_binary(msg: &[u8]) -> Result<Self, MessageFormatError> {
@@ -86,7 +93,8 @@ where T: DeserializeOwned + Serialize
     }
 
     fn from_base64(msg: &str) -> Result<Self, MessageFormatError> {
-        let buf = base64::decode(msg).map_err(|_| MessageFormatError::Base64DeserializeError {})?;
+        let buf =
+            from_base64(msg, Base64Variant::Standard).map_err(|_| MessageFormatError::Base64DeserializeError {})?;
         Self::from_binary(&buf)
     }
 }
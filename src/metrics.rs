@@ -0,0 +1,248 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A minimal metrics facade.
+//!
+//! Code emits metrics through [Counter], [Gauge] and [Histogram] handles, which do nothing until an application
+//! installs a [Recorder] with [set_recorder]. Libraries can therefore report cache hit rates, decode failures or
+//! dropped requests without tying their users to a metrics framework: the application forwards the calls to the
+//! framework of its choice in its recorder. A recorder can be installed only once per process.
+//!
+//! ```edition2018
+//! # use tari_utilities::metrics::Counter;
+//! static DECODE_FAILURES: Counter = Counter::new("tari_util_decode_failures");
+//!
+//! // Does nothing unless a recorder is installed
+//! DECODE_FAILURES.increment();
+//! ```
+
+use alloc::boxed::Box;
+use std::sync::OnceLock;
+
+use snafu::prelude::*;
+
+/// Errors for installing a recorder.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum MetricsError {
+    /// A recorder has already been installed.
+    #[snafu(display("A metrics recorder is already installed"))]
+    RecorderAlreadySet {},
+}
+
+/// Receives the metrics emitted through the handles in this module.
+pub trait Recorder: Send + Sync {
+    /// Add `value` to the counter `name`.
+    fn increment_counter(&self, name: &'static str, value: u64);
+
+    /// Set the gauge `name` to `value`.
+    fn set_gauge(&self, name: &'static str, value: f64);
+
+    /// Add `delta`, which may be negative, to the gauge `name`.
+    fn adjust_gauge(&self, name: &'static str, delta: f64);
+
+    /// Record `value` in the histogram `name`.
+    fn record_histogram(&self, name: &'static str, value: f64);
+}
+
+static RECORDER: OnceLock<&'static dyn Recorder> = OnceLock::new();
+
+/// Install the recorder for the rest of the process.
+///
+/// # Errors
+/// Returns [MetricsError::RecorderAlreadySet] if a recorder was installed before.
+pub fn set_recorder(recorder: &'static dyn Recorder) -> Result<(), MetricsError> {
+    RECORDER.set(recorder).map_err(|_| MetricsError::RecorderAlreadySet {})
+}
+
+/// Install an owned recorder for the rest of the process. The recorder is leaked, even if installing it fails.
+///
+/// # Errors
+/// Returns [MetricsError::RecorderAlreadySet] if a recorder was installed before.
+pub fn set_boxed_recorder(recorder: Box<dyn Recorder>) -> Result<(), MetricsError> {
+    set_recorder(Box::leak(recorder))
+}
+
+/// Return the installed recorder, if any.
+pub fn recorder() -> Option<&'static dyn Recorder> {
+    RECORDER.get().copied()
+}
+
+/// A monotonically increasing count, such as the number of decode failures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Counter {
+    name: &'static str,
+}
+
+impl Counter {
+    /// Create a handle for the counter `name`.
+    pub const fn new(name: &'static str) -> Self {
+        Self { name }
+    }
+
+    /// The name of the counter.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Add one to the counter.
+    pub fn increment(&self) {
+        self.increment_by(1);
+    }
+
+    /// Add `value` to the counter.
+    pub fn increment_by(&self, value: u64) {
+        if let Some(recorder) = recorder() {
+            recorder.increment_counter(self.name, value);
+        }
+    }
+}
+
+/// A value that can go up and down, such as the number of cached entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gauge {
+    name: &'static str,
+}
+
+impl Gauge {
+    /// Create a handle for the gauge `name`.
+    pub const fn new(name: &'static str) -> Self {
+        Self { name }
+    }
+
+    /// The name of the gauge.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Set the gauge to `value`.
+    pub fn set(&self, value: f64) {
+        if let Some(recorder) = recorder() {
+            recorder.set_gauge(self.name, value);
+        }
+    }
+
+    /// Add `delta` to the gauge.
+    pub fn increment(&self, delta: f64) {
+        if let Some(recorder) = recorder() {
+            recorder.adjust_gauge(self.name, delta);
+        }
+    }
+
+    /// Subtract `delta` from the gauge.
+    pub fn decrement(&self, delta: f64) {
+        self.increment(-delta);
+    }
+}
+
+/// A distribution of values, such as decode times or payload sizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Histogram {
+    name: &'static str,
+}
+
+impl Histogram {
+    /// Create a handle for the histogram `name`.
+    pub const fn new(name: &'static str) -> Self {
+        Self { name }
+    }
+
+    /// The name of the histogram.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Record a value.
+    pub fn record(&self, value: f64) {
+        if let Some(recorder) = recorder() {
+            recorder.record_histogram(self.name, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct TestRecorder {
+        events: Mutex<Vec<(&'static str, &'static str, f64)>>,
+    }
+
+    impl Recorder for TestRecorder {
+        fn increment_counter(&self, name: &'static str, value: u64) {
+            self.events.lock().unwrap().push(("counter", name, value as f64));
+        }
+
+        fn set_gauge(&self, name: &'static str, value: f64) {
+            self.events.lock().unwrap().push(("gauge", name, value));
+        }
+
+        fn adjust_gauge(&self, name: &'static str, delta: f64) {
+            self.events.lock().unwrap().push(("gauge+", name, delta));
+        }
+
+        fn record_histogram(&self, name: &'static str, value: f64) {
+            self.events.lock().unwrap().push(("histogram", name, value));
+        }
+    }
+
+    // The recorder is global, so everything that installs it is in one test
+    #[test]
+    fn recording() {
+        let counter = Counter::new("test_counter");
+        assert_eq!(counter.name(), "test_counter");
+        counter.increment();
+
+        let recorder: &'static TestRecorder = Box::leak(Box::default());
+        set_recorder(recorder).unwrap();
+        assert_eq!(
+            set_boxed_recorder(Box::new(TestRecorder::default())),
+            Err(MetricsError::RecorderAlreadySet {})
+        );
+
+        counter.increment();
+        counter.increment_by(5);
+        let gauge = Gauge::new("test_gauge");
+        gauge.set(2.5);
+        gauge.increment(1.0);
+        gauge.decrement(0.5);
+        Histogram::new("test_histogram").record(7.0);
+
+        let events = recorder.events.lock().unwrap();
+        let ours = events
+            .iter()
+            .filter(|(_, name, _)| name.starts_with("test_"))
+            .copied()
+            .collect::<Vec<_>>();
+        assert_eq!(ours, [
+            ("counter", "test_counter", 1.0),
+            ("counter", "test_counter", 5.0),
+            ("gauge", "test_gauge", 2.5),
+            ("gauge+", "test_gauge", 1.0),
+            ("gauge+", "test_gauge", -0.5),
+            ("histogram", "test_histogram", 7.0),
+        ]);
+    }
+}
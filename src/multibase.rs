@@ -0,0 +1,450 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//! [Multibase](https://github.com/multiformats/multibase) encoding, where a one-character prefix names the base of
+//! the rest of the string, as used by IPFS content identifiers and DIDs.
+//!
+//! The hex, base32, z-base-32, base58btc and base64 bases are supported. Base58btc is the Bitcoin flavour of base58,
+//! which encodes the whole input as one number, unlike the block-based Monero flavour in [encoding](crate::encoding).
+//! As that takes time quadratic in the length, base58btc input longer than [MAX_BASE58_LEN] characters is rejected.
+//!
+//! ```edition2018
+//! # use tari_utilities::multibase::{decode, encode, Base};
+//! assert_eq!(encode(Base::Base58Btc, b"yes mani !"), "z7paNL19xttacUY");
+//! assert_eq!(
+//!     decode("f796573206d616e692021").unwrap(),
+//!     (Base::Base16, b"yes mani !".to_vec())
+//! );
+//! ```
+
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use snafu::prelude::*;
+
+use crate::{
+    base32::{fmt_base32, fmt_base32_lower, from_base32, Base32Variant},
+    base64::{fmt_base64, from_base64, Base64Variant},
+    hex::{decode_into, fmt_hex, fmt_hex_upper},
+    literal::BASE58_ALPHABET,
+    ByteArray,
+};
+
+/// The longest base58btc string that will be decoded, in characters.
+pub const MAX_BASE58_LEN: usize = 1024;
+
+/// Trait for encoding/decoding to multibase.
+pub trait Multibase {
+    /// Convert from a multibase string in any supported base.
+    fn from_multibase(s: &str) -> Result<Self, MultibaseError>
+    where Self: Sized;
+
+    /// Convert to a multibase string in the given base.
+    fn to_multibase(&self, base: Base) -> String;
+}
+
+/// A multibase base, identified by its prefix character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Base {
+    /// Lowercase hex, prefix `f`.
+    Base16,
+    /// Uppercase hex, prefix `F`.
+    Base16Upper,
+    /// Lowercase RFC 4648 base32 without padding, prefix `b`.
+    Base32,
+    /// Uppercase RFC 4648 base32 without padding, prefix `B`.
+    Base32Upper,
+    /// Lowercase RFC 4648 base32 with padding, prefix `c`.
+    Base32Pad,
+    /// Uppercase RFC 4648 base32 with padding, prefix `C`.
+    Base32PadUpper,
+    /// Lowercase base32 with the extended hex alphabet, without padding, prefix `v`.
+    Base32Hex,
+    /// Uppercase base32 with the extended hex alphabet, without padding, prefix `V`.
+    Base32HexUpper,
+    /// Lowercase base32 with the extended hex alphabet and padding, prefix `t`.
+    Base32HexPad,
+    /// Uppercase base32 with the extended hex alphabet and padding, prefix `T`.
+    Base32HexPadUpper,
+    /// Z-base-32, prefix `h`.
+    Base32Z,
+    /// Bitcoin base58, prefix `z`.
+    Base58Btc,
+    /// Standard base64 without padding, prefix `m`.
+    Base64,
+    /// Standard base64 with padding, prefix `M`.
+    Base64Pad,
+    /// URL-safe base64 without padding, prefix `u`.
+    Base64Url,
+    /// URL-safe base64 with padding, prefix `U`.
+    Base64UrlPad,
+}
+
+/// Every supported base, for looking up prefixes.
+const BASES: [Base; 16] = [
+    Base::Base16,
+    Base::Base16Upper,
+    Base::Base32,
+    Base::Base32Upper,
+    Base::Base32Pad,
+    Base::Base32PadUpper,
+    Base::Base32Hex,
+    Base::Base32HexUpper,
+    Base::Base32HexPad,
+    Base::Base32HexPadUpper,
+    Base::Base32Z,
+    Base::Base58Btc,
+    Base::Base64,
+    Base::Base64Pad,
+    Base::Base64Url,
+    Base::Base64UrlPad,
+];
+
+impl Base {
+    /// The prefix character of the base.
+    pub const fn code(self) -> char {
+        match self {
+            Base::Base16 => 'f',
+            Base::Base16Upper => 'F',
+            Base::Base32 => 'b',
+            Base::Base32Upper => 'B',
+            Base::Base32Pad => 'c',
+            Base::Base32PadUpper => 'C',
+            Base::Base32Hex => 'v',
+            Base::Base32HexUpper => 'V',
+            Base::Base32HexPad => 't',
+            Base::Base32HexPadUpper => 'T',
+            Base::Base32Z => 'h',
+            Base::Base58Btc => 'z',
+            Base::Base64 => 'm',
+            Base::Base64Pad => 'M',
+            Base::Base64Url => 'u',
+            Base::Base64UrlPad => 'U',
+        }
+    }
+
+    /// The base with the given prefix character, if it is supported.
+    pub fn from_code(code: char) -> Option<Self> {
+        BASES.iter().copied().find(|base| base.code() == code)
+    }
+
+    /// The name of the base in the multibase table, such as `base58btc`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Base::Base16 => "base16",
+            Base::Base16Upper => "base16upper",
+            Base::Base32 => "base32",
+            Base::Base32Upper => "base32upper",
+            Base::Base32Pad => "base32pad",
+            Base::Base32PadUpper => "base32padupper",
+            Base::Base32Hex => "base32hex",
+            Base::Base32HexUpper => "base32hexupper",
+            Base::Base32HexPad => "base32hexpad",
+            Base::Base32HexPadUpper => "base32hexpadupper",
+            Base::Base32Z => "base32z",
+            Base::Base58Btc => "base58btc",
+            Base::Base64 => "base64",
+            Base::Base64Pad => "base64pad",
+            Base::Base64Url => "base64url",
+            Base::Base64UrlPad => "base64urlpad",
+        }
+    }
+
+    /// The base32 variant and whether it is written in lowercase, for the base32 bases.
+    const fn base32(self) -> Option<(Base32Variant, bool)> {
+        match self {
+            Base::Base32 => Some((Base32Variant::StandardNoPad, true)),
+            Base::Base32Upper => Some((Base32Variant::StandardNoPad, false)),
+            Base::Base32Pad => Some((Base32Variant::Standard, true)),
+            Base::Base32PadUpper => Some((Base32Variant::Standard, false)),
+            Base::Base32Hex => Some((Base32Variant::ExtendedHexNoPad, true)),
+            Base::Base32HexUpper => Some((Base32Variant::ExtendedHexNoPad, false)),
+            Base::Base32HexPad => Some((Base32Variant::ExtendedHex, true)),
+            Base::Base32HexPadUpper => Some((Base32Variant::ExtendedHex, false)),
+            Base::Base32Z => Some((Base32Variant::ZBase32, true)),
+            _ => None,
+        }
+    }
+
+    /// The base64 variant, for the base64 bases.
+    const fn base64(self) -> Option<Base64Variant> {
+        match self {
+            Base::Base64 => Some(Base64Variant::StandardNoPad),
+            Base::Base64Pad => Some(Base64Variant::Standard),
+            Base::Base64Url => Some(Base64Variant::UrlSafeNoPad),
+            Base::Base64UrlPad => Some(Base64Variant::UrlSafe),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Base {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Errors for multibase decoding.
+#[derive(Debug, Clone, Snafu, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum MultibaseError {
+    #[snafu(display("The input is empty, so it has no base prefix"))]
+    MissingPrefix {},
+    #[snafu(display("The base prefix {code:?} is not supported"))]
+    UnsupportedBase { code: char },
+    #[snafu(display("Invalid {base}: `{reason}'"))]
+    InvalidEncoding { base: Base, reason: String },
+    #[snafu(display("Byte array error: `{reason}'"))]
+    ByteArrayError { reason: String },
+}
+
+impl<T: ByteArray> Multibase for T {
+    fn from_multibase(s: &str) -> Result<Self, MultibaseError> {
+        let (_, bytes) = decode(s)?;
+        Self::from_canonical_bytes(&bytes).map_err(|e| MultibaseError::ByteArrayError { reason: e.to_string() })
+    }
+
+    fn to_multibase(&self, base: Base) -> String {
+        encode(base, self.as_bytes())
+    }
+}
+
+/// Encode the bytes in the given base, with its prefix.
+pub fn encode(base: Base, bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    // Writing to a string cannot fail
+    let _result = fmt_multibase(&mut encoded, base, bytes);
+    encoded
+}
+
+/// Write the bytes in the given base, with its prefix, to a formatter or any other [fmt::Write]. The output is
+/// identical to [encode].
+pub fn fmt_multibase<W: fmt::Write + ?Sized>(w: &mut W, base: Base, bytes: &[u8]) -> fmt::Result {
+    w.write_char(base.code())?;
+    if let Some((variant, lowercase)) = base.base32() {
+        return if lowercase {
+            fmt_base32_lower(w, bytes, variant)
+        } else {
+            fmt_base32(w, bytes, variant)
+        };
+    }
+    if let Some(variant) = base.base64() {
+        return fmt_base64(w, bytes, variant);
+    }
+    match base {
+        Base::Base16Upper => fmt_hex_upper(w, bytes),
+        Base::Base58Btc => {
+            for symbol in base58btc_encode(bytes) {
+                w.write_char(char::from(symbol))?;
+            }
+            Ok(())
+        },
+        _ => fmt_hex(w, bytes),
+    }
+}
+
+/// Displays bytes as multibase without allocating the string, for use in log statements and error messages.
+///
+/// ```edition2018
+/// # use tari_utilities::multibase::{Base, MultibaseDisplay};
+/// assert_eq!(
+///     format!("cid {}", MultibaseDisplay(Base::Base32, &[0x01, 0x70])),
+///     "cid bafya"
+/// );
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct MultibaseDisplay<'a>(pub Base, pub &'a [u8]);
+
+impl fmt::Display for MultibaseDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_multibase(f, self.0, self.1)
+    }
+}
+
+impl fmt::Debug for MultibaseDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_multibase(f, self.0, self.1)
+    }
+}
+
+/// Decode a multibase string, detecting the base from its prefix.
+///
+/// # Errors
+/// Returns [MultibaseError::MissingPrefix] for an empty string, [MultibaseError::UnsupportedBase] for an unknown
+/// prefix, and [MultibaseError::InvalidEncoding] if the rest of the string is not valid in the base.
+pub fn decode(s: &str) -> Result<(Base, Vec<u8>), MultibaseError> {
+    let mut chars = s.chars();
+    let code = chars.next().context(MissingPrefixSnafu)?;
+    let base = Base::from_code(code).context(UnsupportedBaseSnafu { code })?;
+    let data = chars.as_str();
+    let bytes = if let Some((variant, _)) = base.base32() {
+        from_base32(data, variant).map_err(|e| e.to_string())
+    } else if let Some(variant) = base.base64() {
+        from_base64(data, variant).map_err(|e| e.to_string())
+    } else if base == Base::Base58Btc {
+        base58btc_decode(data)
+    } else {
+        let mut bytes = alloc::vec![0; data.len() / 2];
+        decode_into(data, &mut bytes).map(|_| bytes).map_err(|e| e.to_string())
+    };
+    let bytes = bytes.map_err(|reason| MultibaseError::InvalidEncoding { base, reason })?;
+    Ok((base, bytes))
+}
+
+/// Encode bytes as Bitcoin base58: a `1` for each leading zero byte, then the rest as a big-endian number.
+fn base58btc_encode(bytes: &[u8]) -> impl Iterator<Item = u8> {
+    let zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+    // Little-endian base58 digits of the number
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len().saturating_mul(138) / 100);
+    for byte in bytes.iter().skip(zeros) {
+        let mut carry = u32::from(*byte);
+        for digit in &mut digits {
+            carry = carry.wrapping_add(u32::from(*digit) << 8);
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    core::iter::repeat_n(BASE58_ALPHABET[0], zeros).chain(
+        digits
+            .into_iter()
+            .rev()
+            .map(|digit| BASE58_ALPHABET.get(usize::from(digit)).copied().unwrap_or_default()),
+    )
+}
+
+/// Decode Bitcoin base58, the inverse of [base58btc_encode].
+fn base58btc_decode(s: &str) -> Result<Vec<u8>, String> {
+    if s.len() > MAX_BASE58_LEN {
+        return Err(alloc::format!(
+            "Base58btc input is limited to {} characters",
+            MAX_BASE58_LEN
+        ));
+    }
+    let zeros = s.bytes().take_while(|c| *c == BASE58_ALPHABET[0]).count();
+    // Little-endian bytes of the number
+    let mut bytes: Vec<u8> = Vec::with_capacity(s.len());
+    for (position, c) in s.bytes().enumerate().skip(zeros) {
+        let value = BASE58_ALPHABET.iter().position(|symbol| *symbol == c).ok_or_else(|| {
+            alloc::format!(
+                "Only base58 characters are permitted, found {:?} at position {}",
+                char::from(c),
+                position
+            )
+        })?;
+        let mut carry = value as u32;
+        for byte in &mut bytes {
+            carry = carry.wrapping_add(u32::from(*byte).wrapping_mul(58));
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let mut decoded = alloc::vec![0; zeros];
+    decoded.extend(bytes.into_iter().rev());
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{format, vec};
+
+    use super::*;
+
+    #[test]
+    fn spec_vectors() {
+        let data = b"yes mani !";
+        let vectors = [
+            (Base::Base16, "f796573206d616e692021"),
+            (Base::Base16Upper, "F796573206D616E692021"),
+            (Base::Base32, "bpfsxgidnmfxgsibb"),
+            (Base::Base32Upper, "BPFSXGIDNMFXGSIBB"),
+            (Base::Base32Pad, "cpfsxgidnmfxgsibb"),
+            (Base::Base32PadUpper, "CPFSXGIDNMFXGSIBB"),
+            (Base::Base32Hex, "vf5in683dc5n6i811"),
+            (Base::Base32HexUpper, "VF5IN683DC5N6I811"),
+            (Base::Base32HexPad, "tf5in683dc5n6i811"),
+            (Base::Base32HexPadUpper, "TF5IN683DC5N6I811"),
+            (Base::Base32Z, "hxf1zgedpcfzg1ebb"),
+            (Base::Base58Btc, "z7paNL19xttacUY"),
+            (Base::Base64, "meWVzIG1hbmkgIQ"),
+            (Base::Base64Pad, "MeWVzIG1hbmkgIQ=="),
+            (Base::Base64Url, "ueWVzIG1hbmkgIQ"),
+            (Base::Base64UrlPad, "UeWVzIG1hbmkgIQ=="),
+        ];
+        for (base, encoded) in vectors {
+            assert_eq!(encode(base, data), encoded, "{}", base);
+            assert_eq!(decode(encoded).unwrap(), (base, data.to_vec()), "{}", base);
+            assert_eq!(Base::from_code(base.code()), Some(base));
+            assert_eq!(MultibaseDisplay(base, data).to_string(), encoded);
+        }
+    }
+
+    #[test]
+    fn base58btc() {
+        assert_eq!(encode(Base::Base58Btc, &[]), "z");
+        assert_eq!(encode(Base::Base58Btc, &[0, 0, 0x28, 0x7f, 0xb4, 0xcd]), "z11233QC4");
+        assert_eq!(decode("z11233QC4").unwrap().1, [0, 0, 0x28, 0x7f, 0xb4, 0xcd]);
+        assert_eq!(decode("z").unwrap().1, Vec::<u8>::new());
+        for len in 0..40 {
+            let bytes = (0..len).map(|i| (i * 131 + 7) as u8).collect::<Vec<_>>();
+            assert_eq!(decode(&encode(Base::Base58Btc, &bytes)).unwrap().1, bytes);
+        }
+        assert!(matches!(
+            decode("z0"),
+            Err(MultibaseError::InvalidEncoding {
+                base: Base::Base58Btc,
+                ..
+            })
+        ));
+        assert!(decode(&format!("z{}", "2".repeat(MAX_BASE58_LEN + 1))).is_err());
+    }
+
+    #[test]
+    fn errors_and_trait() {
+        assert_eq!(decode(""), Err(MultibaseError::MissingPrefix {}));
+        assert_eq!(decode("9123"), Err(MultibaseError::UnsupportedBase { code: '9' }));
+        assert!(matches!(
+            decode("f0"),
+            Err(MultibaseError::InvalidEncoding { base: Base::Base16, .. })
+        ));
+        assert!(decode("f 0x00").is_err());
+        let key = [9u8; 4];
+        assert_eq!(
+            <[u8; 4]>::from_multibase(&key.to_multibase(Base::Base64Url)).unwrap(),
+            key
+        );
+        assert!(matches!(
+            <[u8; 3]>::from_multibase(&key.to_multibase(Base::Base16)),
+            Err(MultibaseError::ByteArrayError { .. })
+        ));
+        assert_eq!(decode("F0A0b").unwrap().1, vec![0x0a, 0x0b]);
+    }
+}
@@ -0,0 +1,448 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Parsing, validation and classification of peer network addresses.
+//!
+//! IP literals are parsed strictly, without leading zeros or zone identifiers, and [classify_ip] sorts them into the
+//! special-purpose ranges from the IANA registries so that peers advertising loopback, private or documentation
+//! addresses can be filtered out. IPv4-mapped IPv6 addresses are classified by the IPv4 address they contain.
+//!
+//! Tor v3 onion addresses are checked in full: length, base32 alphabet, version byte and checksum.
+//!
+//! ```edition2018
+//! # use tari_utilities::net::{classify_ip, parse_ip, parse_onion_v3, AddressClass};
+//! assert_eq!(
+//!     classify_ip(&parse_ip("192.168.1.10").unwrap()),
+//!     AddressClass::Private
+//! );
+//! assert_eq!(
+//!     classify_ip(&parse_ip("[::ffff:127.0.0.1]").unwrap()),
+//!     AddressClass::Loopback
+//! );
+//! assert!(classify_ip(&parse_ip("1.1.1.1").unwrap()).is_global());
+//! assert!(
+//!     parse_onion_v3("duckduckgogg42xjoc72x3sjasowoarfbgcmvfimaftt6twagswzczad.onion").is_ok()
+//! );
+//! ```
+
+use alloc::string::String;
+use core::{
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use sha3::{Digest, Sha3_256};
+use snafu::prelude::*;
+
+use crate::base32::{fmt_base32_lower, Base32, Base32Variant};
+
+/// Errors for network address parsing.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum NetError {
+    /// The string is not an IPv4 or IPv6 literal.
+    #[snafu(display("Invalid IP address"))]
+    InvalidIpAddress {},
+    /// The string is not a port number from 1 to 65535.
+    #[snafu(display("Invalid port"))]
+    InvalidPort {},
+    /// The string is not a port or a range of ports `start-end` with `start <= end`.
+    #[snafu(display("Invalid port range"))]
+    InvalidPortRange {},
+    /// The string is not 56 base32 characters, optionally followed by `.onion`.
+    #[snafu(display("Invalid onion address"))]
+    InvalidOnionAddress {},
+    /// The onion address has a version other than 3.
+    #[snafu(display("Unsupported onion address version {version}"))]
+    UnsupportedOnionVersion {
+        /// The version byte of the address
+        version: u8,
+    },
+    /// The onion address checksum does not match its public key.
+    #[snafu(display("Onion address checksum mismatch"))]
+    OnionChecksumMismatch {},
+}
+
+/// The special-purpose class of an IP address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AddressClass {
+    /// `0.0.0.0/8` or `::`
+    Unspecified,
+    /// `127.0.0.0/8` or `::1`
+    Loopback,
+    /// Private and shared address space: `10.0.0.0/8`, `172.16.0.0/12`, `192.168.0.0/16`, `100.64.0.0/10` and
+    /// `fc00::/7`
+    Private,
+    /// `169.254.0.0/16` or `fe80::/10`
+    LinkLocal,
+    /// `224.0.0.0/4` or `ff00::/8`
+    Multicast,
+    /// `255.255.255.255`
+    Broadcast,
+    /// `192.0.2.0/24`, `198.51.100.0/24`, `203.0.113.0/24` or `2001:db8::/32`
+    Documentation,
+    /// Other reserved ranges: `192.0.0.0/24`, `198.18.0.0/15`, `240.0.0.0/4`, and the unassigned IPv6 space outside
+    /// `2000::/3` apart from the ranges above
+    Reserved,
+    /// A globally routable address
+    Global,
+}
+
+impl AddressClass {
+    /// Return `true` for globally routable addresses.
+    pub fn is_global(&self) -> bool {
+        *self == AddressClass::Global
+    }
+}
+
+/// Return the special-purpose class of an IP address.
+pub fn classify_ip(addr: &IpAddr) -> AddressClass {
+    match addr {
+        IpAddr::V4(addr) => classify_ipv4(*addr),
+        IpAddr::V6(addr) => classify_ipv6(addr),
+    }
+}
+
+fn classify_ipv4(addr: Ipv4Addr) -> AddressClass {
+    match addr.octets() {
+        [0, ..] => AddressClass::Unspecified,
+        [127, ..] => AddressClass::Loopback,
+        [10, ..] | [172, 16..=31, ..] | [192, 168, ..] | [100, 64..=127, ..] => AddressClass::Private,
+        [169, 254, ..] => AddressClass::LinkLocal,
+        [224..=239, ..] => AddressClass::Multicast,
+        [255, 255, 255, 255] => AddressClass::Broadcast,
+        [192, 0, 2, _] | [198, 51, 100, _] | [203, 0, 113, _] => AddressClass::Documentation,
+        [192, 0, 0, _] | [198, 18..=19, ..] | [240..=255, ..] => AddressClass::Reserved,
+        _ => AddressClass::Global,
+    }
+}
+
+fn classify_ipv6(addr: &Ipv6Addr) -> AddressClass {
+    if let Some(v4) = addr.to_ipv4_mapped() {
+        return classify_ipv4(v4);
+    }
+    match addr.segments() {
+        [0, 0, 0, 0, 0, 0, 0, 0] => AddressClass::Unspecified,
+        [0, 0, 0, 0, 0, 0, 0, 1] => AddressClass::Loopback,
+        [0x2001, 0x0db8, ..] => AddressClass::Documentation,
+        [0xfc00..=0xfdff, ..] => AddressClass::Private,
+        [0xfe80..=0xfebf, ..] => AddressClass::LinkLocal,
+        [0xff00..=0xffff, ..] => AddressClass::Multicast,
+        [0x2000..=0x3fff, ..] => AddressClass::Global,
+        _ => AddressClass::Reserved,
+    }
+}
+
+/// Parse an IPv4 or IPv6 literal. IPv6 addresses may be enclosed in brackets, as they are in URIs.
+///
+/// # Errors
+/// Returns [NetError::InvalidIpAddress] if the string is not an IP literal. Leading zeros in IPv4 octets and IPv6
+/// zone identifiers are rejected.
+pub fn parse_ip(s: &str) -> Result<IpAddr, NetError> {
+    if let Some(inner) = s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+        return Ipv6Addr::from_str(inner)
+            .map(IpAddr::V6)
+            .map_err(|_| NetError::InvalidIpAddress {});
+    }
+    IpAddr::from_str(s).map_err(|_| NetError::InvalidIpAddress {})
+}
+
+/// Parse a port number from 1 to 65535, without a sign or leading zeros.
+///
+/// # Errors
+/// Returns [NetError::InvalidPort] for anything else, including port 0.
+pub fn parse_port(s: &str) -> Result<u16, NetError> {
+    let canonical = !s.is_empty() && !s.starts_with('0') && s.bytes().all(|b| b.is_ascii_digit());
+    ensure!(canonical, InvalidPortSnafu);
+    s.parse().map_err(|_| NetError::InvalidPort {})
+}
+
+/// An inclusive range of ports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PortRange {
+    start: u16,
+    end: u16,
+}
+
+impl PortRange {
+    /// Create a range from `start` to `end` inclusive.
+    ///
+    /// # Errors
+    /// Returns [NetError::InvalidPortRange] if either port is 0 or `start` is greater than `end`.
+    pub fn new(start: u16, end: u16) -> Result<Self, NetError> {
+        ensure!(start > 0 && start <= end, InvalidPortRangeSnafu);
+        Ok(Self { start, end })
+    }
+
+    /// Return the first port in the range.
+    pub fn start(&self) -> u16 {
+        self.start
+    }
+
+    /// Return the last port in the range.
+    pub fn end(&self) -> u16 {
+        self.end
+    }
+
+    /// Return `true` if the port is in the range.
+    pub fn contains(&self, port: u16) -> bool {
+        (self.start..=self.end).contains(&port)
+    }
+
+    /// Return the number of ports in the range.
+    pub fn len(&self) -> usize {
+        usize::from(self.end.saturating_sub(self.start)).saturating_add(1)
+    }
+
+    /// Return `false`, as a range always contains at least one port.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Iterate over the ports in the range.
+    pub fn iter(&self) -> impl Iterator<Item = u16> {
+        self.start..=self.end
+    }
+}
+
+impl FromStr for PortRange {
+    type Err = NetError;
+
+    /// Parse a single port such as `18141`, or a range such as `18000-18100`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |_| NetError::InvalidPortRange {};
+        match s.split_once('-') {
+            Some((start, end)) => Self::new(parse_port(start).map_err(invalid)?, parse_port(end).map_err(invalid)?),
+            None => {
+                let port = parse_port(s).map_err(invalid)?;
+                Self::new(port, port)
+            },
+        }
+    }
+}
+
+impl fmt::Display for PortRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start == self.end {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}-{}", self.start, self.end)
+        }
+    }
+}
+
+const ONION_V3_VERSION: u8 = 3;
+const ONION_V3_LEN: usize = 56;
+
+/// A Tor v3 onion service address, identified by its ed25519 public key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OnionV3Address {
+    public_key: [u8; 32],
+}
+
+impl OnionV3Address {
+    /// Create the address of the onion service with the given public key.
+    pub fn from_public_key(public_key: [u8; 32]) -> Self {
+        Self { public_key }
+    }
+
+    /// Return the public key of the onion service.
+    pub fn public_key(&self) -> &[u8; 32] {
+        &self.public_key
+    }
+
+    fn checksum(public_key: &[u8; 32]) -> [u8; 2] {
+        let hash = Sha3_256::new()
+            .chain_update(b".onion checksum")
+            .chain_update(public_key)
+            .chain_update([ONION_V3_VERSION])
+            .finalize();
+        let mut checksum = [0u8; 2];
+        checksum.copy_from_slice(hash.get(..2).unwrap_or_default());
+        checksum
+    }
+}
+
+impl FromStr for OnionV3Address {
+    type Err = NetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_onion_v3(s)
+    }
+}
+
+impl fmt::Display for OnionV3Address {
+    /// Write the address in lowercase with the `.onion` suffix.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bytes = [0u8; 35];
+        let (key, rest) = bytes.split_at_mut(32);
+        key.copy_from_slice(&self.public_key);
+        let (checksum, version) = rest.split_at_mut(2);
+        checksum.copy_from_slice(&Self::checksum(&self.public_key));
+        version.fill(ONION_V3_VERSION);
+        fmt_base32_lower(f, &bytes, Base32Variant::StandardNoPad)?;
+        f.write_str(".onion")
+    }
+}
+
+/// Parse and verify a Tor v3 onion address. The `.onion` suffix is optional, and upper and lower case are accepted.
+///
+/// # Errors
+/// Returns [NetError::InvalidOnionAddress] if the address is not 56 base32 characters,
+/// [NetError::UnsupportedOnionVersion] if its version is not 3, and [NetError::OnionChecksumMismatch] if its
+/// checksum is wrong.
+pub fn parse_onion_v3(s: &str) -> Result<OnionV3Address, NetError> {
+    let host = s
+        .strip_suffix(".onion")
+        .or_else(|| s.strip_suffix(".ONION"))
+        .unwrap_or(s);
+    ensure!(host.len() == ONION_V3_LEN, InvalidOnionAddressSnafu);
+
+    let bytes = <[u8; 35]>::from_base32_variant(host, Base32Variant::StandardNoPad)
+        .map_err(|_| NetError::InvalidOnionAddress {})?;
+    let (public_key, rest) = bytes.split_at(32);
+    let (checksum, version) = rest.split_at(2);
+    let version = version.first().copied().unwrap_or_default();
+    ensure!(version == ONION_V3_VERSION, UnsupportedOnionVersionSnafu { version });
+    let mut key = [0u8; 32];
+    key.copy_from_slice(public_key);
+    ensure!(checksum == OnionV3Address::checksum(&key), OnionChecksumMismatchSnafu);
+    Ok(OnionV3Address::from_public_key(key))
+}
+
+/// Return `true` if the string is a valid Tor v3 onion address.
+pub fn is_onion_v3(s: &str) -> bool {
+    parse_onion_v3(s).is_ok()
+}
+
+/// Return the lowercase onion address for a public key, with the `.onion` suffix.
+pub fn onion_v3_address(public_key: [u8; 32]) -> String {
+    alloc::string::ToString::to_string(&OnionV3Address::from_public_key(public_key))
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{format, string::ToString, vec::Vec};
+
+    use super::*;
+
+    fn class(s: &str) -> AddressClass {
+        classify_ip(&parse_ip(s).unwrap())
+    }
+
+    #[test]
+    fn ipv4_classes() {
+        assert_eq!(class("0.0.0.0"), AddressClass::Unspecified);
+        assert_eq!(class("127.0.0.53"), AddressClass::Loopback);
+        for private in ["10.1.2.3", "172.16.0.1", "172.31.255.255", "192.168.0.1", "100.64.0.1"] {
+            assert_eq!(class(private), AddressClass::Private, "{}", private);
+        }
+        assert_eq!(class("169.254.1.1"), AddressClass::LinkLocal);
+        assert_eq!(class("224.0.0.251"), AddressClass::Multicast);
+        assert_eq!(class("255.255.255.255"), AddressClass::Broadcast);
+        assert_eq!(class("203.0.113.7"), AddressClass::Documentation);
+        assert_eq!(class("198.18.0.1"), AddressClass::Reserved);
+        assert_eq!(class("240.0.0.1"), AddressClass::Reserved);
+        for global in ["1.1.1.1", "172.32.0.1", "100.128.0.1", "8.8.8.8"] {
+            assert_eq!(class(global), AddressClass::Global, "{}", global);
+        }
+    }
+
+    #[test]
+    fn ipv6_classes() {
+        assert_eq!(class("::"), AddressClass::Unspecified);
+        assert_eq!(class("[::1]"), AddressClass::Loopback);
+        assert_eq!(class("fd12::1"), AddressClass::Private);
+        assert_eq!(class("fe80::1"), AddressClass::LinkLocal);
+        assert_eq!(class("ff02::1"), AddressClass::Multicast);
+        assert_eq!(class("2001:db8::1"), AddressClass::Documentation);
+        assert_eq!(class("::ffff:10.0.0.1"), AddressClass::Private);
+        assert_eq!(class("::ffff:8.8.8.8"), AddressClass::Global);
+        assert_eq!(class("2606:4700::1111"), AddressClass::Global);
+        assert_eq!(class("100::1"), AddressClass::Reserved);
+    }
+
+    #[test]
+    fn ip_parsing() {
+        for bad in [
+            "",
+            "1.2.3",
+            "01.2.3.4",
+            "1.2.3.256",
+            "[1.2.3.4]",
+            "::1]",
+            "fe80::1%eth0",
+            " 1.2.3.4",
+            "::g",
+        ] {
+            assert_eq!(parse_ip(bad), Err(NetError::InvalidIpAddress {}), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn ports() {
+        assert_eq!(parse_port("18141"), Ok(18141));
+        assert_eq!(parse_port("65535"), Ok(65535));
+        for bad in ["", "0", "080", "+80", "65536", "8 0"] {
+            assert_eq!(parse_port(bad), Err(NetError::InvalidPort {}), "{}", bad);
+        }
+        let range = "18000-18002".parse::<PortRange>().unwrap();
+        assert_eq!((range.start(), range.end(), range.len()), (18000, 18002, 3));
+        assert!(range.contains(18001) && !range.contains(18003));
+        assert_eq!(range.iter().collect::<Vec<_>>(), [18000, 18001, 18002]);
+        assert_eq!(range.to_string(), "18000-18002");
+        assert_eq!("80".parse::<PortRange>().unwrap().to_string(), "80");
+        for bad in ["2-1", "0-5", "1-", "-1", "1-2-3", "a"] {
+            assert_eq!(bad.parse::<PortRange>(), Err(NetError::InvalidPortRange {}), "{}", bad);
+        }
+        assert_eq!(PortRange::new(1, 65535).unwrap().len(), 65535);
+    }
+
+    #[test]
+    fn onion_addresses() {
+        let address = "duckduckgogg42xjoc72x3sjasowoarfbgcmvfimaftt6twagswzczad.onion";
+        let onion = parse_onion_v3(address).unwrap();
+        assert_eq!(onion.to_string(), address);
+        assert_eq!(parse_onion_v3(&address[..56]), Ok(onion));
+        assert_eq!(parse_onion_v3(&address.to_uppercase()), Ok(onion));
+        assert!(is_onion_v3(address));
+
+        let generated = onion_v3_address([7u8; 32]);
+        assert_eq!(generated.parse::<OnionV3Address>().unwrap().public_key(), &[7u8; 32]);
+
+        // A changed character breaks the checksum, or the version if it is in the last two characters
+        let tampered = format!("e{}", &address[1..]);
+        assert_eq!(parse_onion_v3(&tampered), Err(NetError::OnionChecksumMismatch {}));
+        let tampered = format!("{}b.onion", &address[..55]);
+        assert!(matches!(
+            parse_onion_v3(&tampered),
+            Err(NetError::UnsupportedOnionVersion { .. })
+        ));
+        for bad in [
+            "",
+            &address[1..],
+            "duckduckgogg42xjoc72x3sjasowoarfbgcmvfimaftt6twagswzcza1.onion",
+        ] {
+            assert_eq!(parse_onion_v3(bad), Err(NetError::InvalidOnionAddress {}));
+        }
+    }
+}
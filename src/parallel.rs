@@ -0,0 +1,138 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Parallel encoding and checksums of large payloads using `rayon`.
+//!
+//! The input is split into fixed-size chunks which are processed on the `rayon` thread pool and joined again, so the
+//! result is identical to the sequential functions in [hex](mod@crate::hex) and [checksum](crate::checksum). Unlike
+//! [to_hex](crate::hex::to_hex), [par_to_hex] has no size limit, as it is intended for multi-megabyte payloads such as
+//! block archives and snapshot exports.
+
+use alloc::{string::String, vec, vec::Vec};
+
+use rayon::prelude::*;
+
+use crate::{
+    checksum::{crc32, crc32_combine},
+    hex::{decode_digits, encode_into, encoded_len, hex_digits, HexError},
+};
+
+/// The number of bytes encoded or decoded by each task.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Encode the provided bytes into a lowercase hex string in parallel.
+///
+/// # Errors
+/// Returns [HexError::BufferTooSmall] if the encoded length does not fit in a `usize`.
+pub fn par_to_hex(bytes: &[u8]) -> Result<String, HexError> {
+    let len = bytes.len().checked_mul(2).ok_or(HexError::BufferTooSmall {
+        needed: encoded_len(bytes.len()),
+    })?;
+    let mut hex = vec![0u8; len];
+    hex.par_chunks_mut(encoded_len(CHUNK_SIZE))
+        .zip(bytes.par_chunks(CHUNK_SIZE))
+        .try_for_each(|(output, input)| encode_into(input, output).map(|_| ()))?;
+    String::from_utf8(hex).map_err(|_| HexError::HexConversionError {})
+}
+
+/// Decode a hex string into bytes in parallel. The string is validated the same way as
+/// [from_hex](crate::hex::from_hex), so surrounding whitespace and a `0x` prefix are accepted.
+///
+/// # Errors
+/// Returns the same [HexError]s as [from_hex](crate::hex::from_hex).
+pub fn par_from_hex(hex: &str) -> Result<Vec<u8>, HexError> {
+    let (offset, digits) = hex_digits(hex)?;
+    let chunks = digits
+        .par_chunks(encoded_len(CHUNK_SIZE))
+        .enumerate()
+        .map(|(i, chunk)| decode_digits(chunk, offset.saturating_add(i.saturating_mul(encoded_len(CHUNK_SIZE)))))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(chunks.concat())
+}
+
+/// Compute the CRC32 checksum of the data in parallel.
+pub fn par_crc32(bytes: &[u8]) -> u32 {
+    bytes
+        .par_chunks(CHUNK_SIZE)
+        .map(|chunk| (crc32(chunk), chunk.len() as u64))
+        .reduce(
+            || (0, 0),
+            |(crc_a, len_a), (crc_b, len_b)| (crc32_combine(crc_a, crc_b, len_b), len_a.saturating_add(len_b)),
+        )
+        .0
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::OsRng, RngCore};
+
+    use super::*;
+    use crate::hex::{from_hex, to_hex};
+
+    #[test]
+    fn matches_sequential() {
+        // Lengths around the chunk boundaries
+        for len in [0, 1, CHUNK_SIZE - 1, CHUNK_SIZE, CHUNK_SIZE + 1, 3 * CHUNK_SIZE + 17] {
+            let mut bytes = vec![0u8; len];
+            OsRng.fill_bytes(&mut bytes);
+            let hex = par_to_hex(&bytes).unwrap();
+            assert_eq!(hex.len(), 2 * len);
+            if len <= CHUNK_SIZE {
+                assert_eq!(hex, to_hex(&bytes));
+            }
+            assert_eq!(par_from_hex(&hex).unwrap(), bytes);
+            assert_eq!(par_from_hex(&hex.to_uppercase()).unwrap(), bytes);
+            assert_eq!(from_hex(&hex).unwrap(), bytes);
+            assert_eq!(par_crc32(&bytes), crc32(&bytes));
+        }
+    }
+
+    #[test]
+    fn invalid_input() {
+        assert_eq!(par_from_hex(" 0xabcd\n").unwrap(), vec![0xab, 0xcd]);
+        assert!(matches!(par_from_hex("abc"), Err(HexError::LengthError {})));
+        assert!(matches!(
+            par_from_hex("zz"),
+            Err(HexError::InvalidCharacter {
+                position: 0,
+                character: 'z'
+            })
+        ));
+        assert!(matches!(par_from_hex("é"), Err(HexError::HexConversionError {})));
+
+        // A `0x` at a chunk boundary is not a prefix
+        let mut hex = "00".repeat(CHUNK_SIZE);
+        hex.push_str("0x");
+        assert!(matches!(
+            par_from_hex(&hex),
+            Err(HexError::InvalidCharacter { position, character: 'x' }) if position == 2 * CHUNK_SIZE + 1
+        ));
+
+        // An error in a later chunk is detected
+        let mut hex = "00".repeat(4 * CHUNK_SIZE);
+        hex.replace_range(5 * CHUNK_SIZE..5 * CHUNK_SIZE + 1, "g");
+        assert!(matches!(
+            par_from_hex(&hex),
+            Err(HexError::InvalidCharacter { position, character: 'g' }) if position == 5 * CHUNK_SIZE
+        ));
+    }
+}
@@ -67,6 +67,24 @@ impl SafePassword {
     }
 }
 
+/// Compare passphrases in constant time
+#[cfg(feature = "std")]
+impl subtle::ConstantTimeEq for SafePassword {
+    fn ct_eq(&self, other: &Self) -> subtle::Choice {
+        crate::ct::eq(self.reveal(), other.reveal())
+    }
+}
+
+#[cfg(feature = "std")]
+impl PartialEq for SafePassword {
+    fn eq(&self, other: &Self) -> bool {
+        subtle::ConstantTimeEq::ct_eq(self, other).into()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Eq for SafePassword {}
+
 impl FromStr for SafePassword {
     type Err = String;
 
@@ -97,6 +115,24 @@ impl Serialize for SafePassword {
     }
 }
 
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for SafePassword {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        String::arbitrary(u).map(Self::from)
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for SafePassword {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        use proptest::{prelude::any, strategy::Strategy};
+        any::<String>().prop_map(Self::from).boxed()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::string::ToString;
@@ -116,6 +152,13 @@ mod tests {
         assert_eq!(from_string.reveal(), from_string_ref.reveal());
     }
 
+    #[test]
+    fn equality() {
+        assert_eq!(SafePassword::from("password"), SafePassword::from("password"));
+        assert_ne!(SafePassword::from("password"), SafePassword::from("passw0rd"));
+        assert_ne!(SafePassword::from("password"), SafePassword::from("pass"));
+    }
+
     #[test]
     fn serialization() {
         let safe_password = SafePassword::from("password");
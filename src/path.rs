@@ -0,0 +1,250 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Defences against path traversal when handling user-supplied file names and paths.
+//!
+//! [sanitize_file_name] turns an untrusted name, such as the name of an uploaded backup, into a single safe path
+//! component. [ensure_within_base] resolves a path relative to a base directory, following `..` and symbolic links,
+//! and rejects it if the result escapes the base.
+//!
+//! ```edition2018
+//! # use tari_utilities::path::sanitize_file_name;
+//! assert_eq!(sanitize_file_name("../../etc/passwd"), "etcpasswd");
+//! assert_eq!(
+//!     sanitize_file_name("wallet backup.json"),
+//!     "wallet backup.json"
+//! );
+//! assert_eq!(sanitize_file_name("CON.txt"), "_CON.txt");
+//! ```
+
+use alloc::string::{String, ToString};
+use std::{
+    fs,
+    io,
+    path::{Component, Path, PathBuf},
+};
+
+use snafu::prelude::*;
+
+/// Errors for resolving paths.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum PathError {
+    /// The base directory could not be resolved.
+    #[snafu(display("Could not resolve the base directory: {reason}"))]
+    Io {
+        /// The underlying I/O error
+        reason: String,
+    },
+    /// The path resolves to a location outside the base directory.
+    #[snafu(display("Path is outside the base directory"))]
+    OutsideBase {},
+    /// The path goes up with `..` through directories that do not exist, or through a symbolic link whose target does
+    /// not exist, so it cannot be resolved safely.
+    #[snafu(display("Path cannot be resolved"))]
+    Unresolvable {},
+}
+
+impl From<io::Error> for PathError {
+    fn from(err: io::Error) -> Self {
+        PathError::Io {
+            reason: err.to_string(),
+        }
+    }
+}
+
+/// The longest file name, in bytes, that common file systems accept.
+pub const MAX_FILE_NAME_LEN: usize = 255;
+
+/// Names that Windows reserves for devices, with or without an extension.
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9", "LPT1", "LPT2",
+    "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Turn an untrusted name into a file name that is safe on Unix and Windows.
+///
+/// Path separators, control characters and the characters Windows forbids (`<>:"|?*`) are removed, as are leading
+/// and trailing spaces and dots, so the result is never `.` or `..` or a hidden file. Reserved Windows device names
+/// such as `CON` or `com1.txt` get a `_` prefix. The result is truncated to [MAX_FILE_NAME_LEN] bytes, and an empty
+/// result becomes `_`.
+pub fn sanitize_file_name(name: &str) -> String {
+    let cleaned = name
+        .chars()
+        .filter(|c| !c.is_control() && !matches!(c, '/' | '\\' | '<' | '>' | ':' | '"' | '|' | '?' | '*'))
+        .collect::<String>();
+    let mut cleaned = cleaned.trim_matches([' ', '.']).to_string();
+
+    let stem = cleaned.split('.').next().unwrap_or_default().trim_end();
+    if RESERVED_NAMES
+        .iter()
+        .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+    {
+        cleaned.insert(0, '_');
+    }
+    if cleaned.len() > MAX_FILE_NAME_LEN {
+        let mut end = MAX_FILE_NAME_LEN;
+        while !cleaned.is_char_boundary(end) {
+            end = end.saturating_sub(1);
+        }
+        cleaned.truncate(end);
+        // Truncation may have exposed a trailing space or dot
+        cleaned.truncate(cleaned.trim_end_matches([' ', '.']).len());
+    }
+    if cleaned.is_empty() {
+        cleaned.push('_');
+    }
+    cleaned
+}
+
+/// Resolve `candidate` against the directory `base` and return the resolved path if it lies within `base`.
+///
+/// A relative candidate is taken relative to `base`; an absolute one is used as it is. The longest existing part of
+/// the path is canonicalized, which resolves `..`, `.` and symbolic links, and the rest is appended. Going up with
+/// `..` through directories that do not exist is rejected, since it cannot be resolved reliably, and so is a dangling
+/// symbolic link, since writing to it would create its target wherever that is.
+///
+/// The result is only valid as long as the file system does not change: a symbolic link created afterwards can still
+/// redirect it.
+///
+/// # Errors
+/// Returns [PathError::Io] if `base` cannot be canonicalized, [PathError::OutsideBase] if the path escapes `base`, or
+/// [PathError::Unresolvable] if it cannot be resolved.
+pub fn ensure_within_base<B: AsRef<Path>, C: AsRef<Path>>(base: B, candidate: C) -> Result<PathBuf, PathError> {
+    let base = fs::canonicalize(base)?;
+    let full = base.join(candidate);
+    let (mut resolved, rest) = full
+        .ancestors()
+        .find_map(|ancestor| {
+            let canonical = fs::canonicalize(ancestor).ok()?;
+            Some((canonical, full.strip_prefix(ancestor).ok()?))
+        })
+        .context(UnresolvableSnafu)?;
+    for component in rest.components() {
+        match component {
+            Component::Normal(part) => {
+                resolved.push(part);
+                // Anything that exists here failed to canonicalize, so a link cannot be followed to check its target
+                if let Ok(metadata) = fs::symlink_metadata(&resolved) {
+                    ensure!(!metadata.file_type().is_symlink(), UnresolvableSnafu);
+                }
+            },
+            Component::CurDir => {},
+            _ => return UnresolvableSnafu.fail(),
+        }
+    }
+    ensure!(resolved.starts_with(&base), OutsideBaseSnafu);
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::format;
+
+    use super::*;
+
+    #[test]
+    fn file_names() {
+        assert_eq!(sanitize_file_name("report.pdf"), "report.pdf");
+        assert_eq!(sanitize_file_name(".."), "_");
+        assert_eq!(sanitize_file_name(""), "_");
+        assert_eq!(sanitize_file_name(".bashrc"), "bashrc");
+        assert_eq!(sanitize_file_name("a/b\\c"), "abc");
+        assert_eq!(sanitize_file_name("C:\\Windows"), "CWindows");
+        assert_eq!(sanitize_file_name("bad\u{0}name\n?.txt "), "badname.txt");
+        assert_eq!(sanitize_file_name("nul"), "_nul");
+        assert_eq!(sanitize_file_name("Com1 .tar.gz"), "_Com1 .tar.gz");
+        assert_eq!(sanitize_file_name("console"), "console");
+        let long = sanitize_file_name(&"é".repeat(200));
+        assert_eq!(long.len(), 254);
+        assert_eq!(sanitize_file_name(&format!("{}.{}", "a".repeat(254), "b")).len(), 254);
+    }
+
+    #[test]
+    fn containment() {
+        let base = std::env::temp_dir().join(format!("tari_path_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        fs::create_dir_all(base.join("backups")).unwrap();
+        let canonical = fs::canonicalize(&base).unwrap();
+
+        assert_eq!(
+            ensure_within_base(&base, "backups/wallet.json").unwrap(),
+            canonical.join("backups/wallet.json")
+        );
+        assert_eq!(
+            ensure_within_base(&base, "backups/../new/./file").unwrap(),
+            canonical.join("new/file")
+        );
+        assert_eq!(
+            ensure_within_base(&base, "../escape").unwrap_err(),
+            PathError::OutsideBase {}
+        );
+        assert_eq!(
+            ensure_within_base(&base, "backups/../../escape").unwrap_err(),
+            PathError::OutsideBase {}
+        );
+        assert_eq!(
+            ensure_within_base(&base, "missing/../../escape").unwrap_err(),
+            PathError::Unresolvable {}
+        );
+        assert_eq!(
+            ensure_within_base(&base, "/etc").unwrap_err(),
+            PathError::OutsideBase {}
+        );
+        assert!(matches!(
+            ensure_within_base(base.join("missing"), "x").unwrap_err(),
+            PathError::Io { .. }
+        ));
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(std::env::temp_dir(), base.join("link")).unwrap();
+            assert_eq!(
+                ensure_within_base(&base, "link/secret").unwrap_err(),
+                PathError::OutsideBase {}
+            );
+
+            let outside = std::env::temp_dir().join(format!("tari_path_target_{}", std::process::id()));
+            let _ = fs::remove_file(&outside);
+            std::os::unix::fs::symlink(&outside, base.join("dangling")).unwrap();
+            assert_eq!(
+                ensure_within_base(&base, "dangling").unwrap_err(),
+                PathError::Unresolvable {}
+            );
+            assert_eq!(
+                ensure_within_base(&base, "backups/../dangling").unwrap_err(),
+                PathError::Unresolvable {}
+            );
+            assert_eq!(
+                ensure_within_base(&base, "dangling/file").unwrap_err(),
+                PathError::Unresolvable {}
+            );
+            // Even a dangling link to a missing file inside the base is rejected
+            std::os::unix::fs::symlink(base.join("missing"), base.join("backups/inside")).unwrap();
+            assert_eq!(
+                ensure_within_base(&base, "backups/inside").unwrap_err(),
+                PathError::Unresolvable {}
+            );
+            assert!(!outside.exists());
+        }
+        fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -0,0 +1,310 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! RFC 3986 percent-encoding and a small query string builder and parser, for constructing URIs such as payment
+//! requests.
+//!
+//! Which characters are encoded is controlled by an [EncodeSet]. Non-ASCII bytes and ASCII control characters are
+//! always encoded, and escapes use uppercase hex digits as recommended by the RFC. `+` has no special meaning: it is
+//! not decoded as a space, and spaces are encoded as `%20`.
+//!
+//! ```edition2018
+//! # use tari_utilities::percent::{parse_query, QueryBuilder};
+//! let query = QueryBuilder::new()
+//!     .param("amount", "1.5")
+//!     .param("message", "Coffee & cake")
+//!     .build();
+//! assert_eq!(query, "amount=1.5&message=Coffee%20%26%20cake");
+//! assert_eq!(parse_query(&query).unwrap(), vec![
+//!     ("amount".to_string(), "1.5".to_string()),
+//!     ("message".to_string(), "Coffee & cake".to_string())
+//! ]);
+//! ```
+
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt::Write;
+
+use snafu::prelude::*;
+
+use crate::hex::hex_nibble;
+
+/// Errors for percent-decoding.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum PercentError {
+    /// A `%` is not followed by two hex digits.
+    #[snafu(display("Invalid percent escape at byte {position}"))]
+    InvalidEscape {
+        /// The byte offset of the `%`
+        position: usize,
+    },
+    /// The decoded bytes are not valid UTF-8.
+    #[snafu(display("The decoded text is not valid UTF-8"))]
+    InvalidUtf8 {},
+}
+
+/// The RFC 3986 unreserved characters: `A-Z a-z 0-9 - . _ ~`.
+const UNRESERVED: u128 = ascii_mask(b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-._~");
+
+/// Return a mask with the bit for each ASCII byte set. Non-ASCII bytes are ignored.
+const fn ascii_mask(mut bytes: &[u8]) -> u128 {
+    let mut mask = 0u128;
+    while let [byte, rest @ ..] = bytes {
+        if let Some(bit) = ascii_bit(*byte) {
+            mask |= bit;
+        }
+        bytes = rest;
+    }
+    mask
+}
+
+const fn ascii_bit(byte: u8) -> Option<u128> {
+    1u128.checked_shl(byte as u32)
+}
+
+/// The set of ASCII characters that are percent-encoded. Bytes outside the ASCII range are always encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncodeSet {
+    mask: u128,
+}
+
+impl EncodeSet {
+    /// Encode everything except the unreserved characters. This is safe for any URI component.
+    pub const COMPONENT: EncodeSet = EncodeSet { mask: !UNRESERVED };
+    /// Encode everything that may not appear in a path segment, leaving the sub-delimiters, `:` and `@` as they are.
+    pub const PATH_SEGMENT: EncodeSet = EncodeSet::COMPONENT.remove_all(b"!$&'()*+,;=:@");
+    /// Encode everything that may not appear in a query parameter name or value. Unlike the rest of the query, `&`,
+    /// `=`, `+` and `;` are encoded, as they are commonly used as separators.
+    pub const QUERY_PARAM: EncodeSet = EncodeSet::COMPONENT.remove_all(b"!$'()*,:@/?");
+
+    /// Return the set with the ASCII bytes also encoded.
+    pub const fn add_all(self, bytes: &[u8]) -> Self {
+        Self {
+            mask: self.mask | ascii_mask(bytes),
+        }
+    }
+
+    /// Return the set with the ASCII bytes no longer encoded. Control characters are encoded regardless.
+    pub const fn remove_all(self, bytes: &[u8]) -> Self {
+        Self {
+            mask: self.mask & !ascii_mask(bytes),
+        }
+    }
+
+    /// Return `true` if the byte is encoded by this set.
+    pub const fn contains(&self, byte: u8) -> bool {
+        if byte < 0x20 || byte >= 0x7f {
+            return true;
+        }
+        match ascii_bit(byte) {
+            Some(bit) => self.mask & bit != 0,
+            None => true,
+        }
+    }
+}
+
+/// Percent-encode the bytes of `input` that are in the set.
+pub fn percent_encode<'a>(input: &'a str, set: &EncodeSet) -> Cow<'a, str> {
+    if !input.bytes().any(|byte| set.contains(byte)) {
+        return Cow::Borrowed(input);
+    }
+    let mut encoded = String::with_capacity(input.len().saturating_mul(3));
+    for byte in input.bytes() {
+        if set.contains(byte) {
+            // Writing to a `String` cannot fail
+            let _ = write!(encoded, "%{:02X}", byte);
+        } else {
+            encoded.push(char::from(byte));
+        }
+    }
+    Cow::Owned(encoded)
+}
+
+/// Decode the percent escapes in `input`. Both uppercase and lowercase hex digits are accepted.
+///
+/// # Errors
+/// Returns [PercentError::InvalidEscape] if a `%` is not followed by two hex digits.
+pub fn percent_decode(input: &str) -> Result<Cow<'_, [u8]>, PercentError> {
+    if !input.contains('%') {
+        return Ok(Cow::Borrowed(input.as_bytes()));
+    }
+    let mut decoded = Vec::with_capacity(input.len());
+    let mut bytes = input.bytes().enumerate();
+    while let Some((position, byte)) = bytes.next() {
+        if byte != b'%' {
+            decoded.push(byte);
+            continue;
+        }
+        let hi = bytes.next().and_then(|(_, c)| hex_nibble(c));
+        let lo = bytes.next().and_then(|(_, c)| hex_nibble(c));
+        match (hi, lo) {
+            (Some(hi), Some(lo)) => decoded.push((hi << 4) | lo),
+            _ => return InvalidEscapeSnafu { position }.fail(),
+        }
+    }
+    Ok(Cow::Owned(decoded))
+}
+
+/// Decode the percent escapes in `input` and check that the result is valid UTF-8.
+///
+/// # Errors
+/// Returns [PercentError::InvalidEscape] for a malformed escape and [PercentError::InvalidUtf8] if the decoded bytes
+/// are not UTF-8.
+pub fn percent_decode_str(input: &str) -> Result<Cow<'_, str>, PercentError> {
+    match percent_decode(input)? {
+        Cow::Borrowed(_) => Ok(Cow::Borrowed(input)),
+        Cow::Owned(bytes) => String::from_utf8(bytes)
+            .map(Cow::Owned)
+            .map_err(|_| PercentError::InvalidUtf8 {}),
+    }
+}
+
+/// Builds a query string from name and value pairs, encoding them with [EncodeSet::QUERY_PARAM].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryBuilder {
+    query: String,
+}
+
+impl QueryBuilder {
+    /// Create an empty query.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a parameter.
+    pub fn param(mut self, name: &str, value: &str) -> Self {
+        if !self.query.is_empty() {
+            self.query.push('&');
+        }
+        self.query.push_str(&percent_encode(name, &EncodeSet::QUERY_PARAM));
+        self.query.push('=');
+        self.query.push_str(&percent_encode(value, &EncodeSet::QUERY_PARAM));
+        self
+    }
+
+    /// Return the query string, without a leading `?`.
+    pub fn build(self) -> String {
+        self.query
+    }
+}
+
+/// Parse a query string into decoded name and value pairs, in order. A leading `?` is ignored, as are empty
+/// parameters, and a parameter without `=` has an empty value.
+///
+/// # Errors
+/// Returns an error if a name or value has a malformed escape or does not decode to UTF-8.
+pub fn parse_query(query: &str) -> Result<Vec<(String, String)>, PercentError> {
+    let query = query.strip_prefix('?').unwrap_or(query);
+    query
+        .split('&')
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            let (name, value) = param.split_once('=').unwrap_or((param, ""));
+            Ok((
+                percent_decode_str(name)?.to_string(),
+                percent_decode_str(value)?.to_string(),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn encoding() {
+        assert!(matches!(
+            percent_encode("safe-_.~09", &EncodeSet::COMPONENT),
+            Cow::Borrowed(_)
+        ));
+        assert_eq!(percent_encode("a b/c?d", &EncodeSet::COMPONENT), "a%20b%2Fc%3Fd");
+        assert_eq!(percent_encode("a b/c?d", &EncodeSet::PATH_SEGMENT), "a%20b%2Fc%3Fd");
+        assert_eq!(percent_encode("a:b@c+d", &EncodeSet::PATH_SEGMENT), "a:b@c+d");
+        assert_eq!(
+            percent_encode("a/b?c&d=e+f", &EncodeSet::QUERY_PARAM),
+            "a/b?c%26d%3De%2Bf"
+        );
+        assert_eq!(percent_encode("é\n\x7f", &EncodeSet::PATH_SEGMENT), "%C3%A9%0A%7F");
+        assert_eq!(percent_encode("%", &EncodeSet::PATH_SEGMENT), "%25");
+    }
+
+    #[test]
+    fn custom_sets() {
+        let set = EncodeSet::PATH_SEGMENT.add_all(b"@");
+        assert_eq!(percent_encode("a@b", &set), "a%40b");
+        let set = EncodeSet::COMPONENT.remove_all(b"/\n");
+        assert_eq!(percent_encode("a/b\n", &set), "a/b%0A");
+        assert!(set.contains(b'\n'));
+        assert!(set.contains(0x80));
+        assert!(!set.contains(b'/'));
+    }
+
+    #[test]
+    fn decoding() {
+        assert!(matches!(percent_decode("plain"), Ok(Cow::Borrowed(b"plain"))));
+        assert_eq!(percent_decode("a%20b%2fc%2F").unwrap().as_ref(), b"a b/c/");
+        assert_eq!(percent_decode("%00%FF").unwrap().as_ref(), &[0, 255]);
+        assert_eq!(percent_decode("a+b").unwrap().as_ref(), b"a+b");
+        assert_eq!(percent_decode("ab%"), Err(PercentError::InvalidEscape { position: 2 }));
+        assert_eq!(percent_decode("%4"), Err(PercentError::InvalidEscape { position: 0 }));
+        assert_eq!(percent_decode("x%zz"), Err(PercentError::InvalidEscape { position: 1 }));
+        assert_eq!(percent_decode("%é"), Err(PercentError::InvalidEscape { position: 0 }));
+        assert_eq!(percent_decode_str("%C3%A9").unwrap(), "é");
+        assert_eq!(percent_decode_str("%C3"), Err(PercentError::InvalidUtf8 {}));
+    }
+
+    #[test]
+    fn round_trip() {
+        let all = (0..=127u8).map(char::from).chain("é🖖".chars()).collect::<String>();
+        for set in [EncodeSet::COMPONENT, EncodeSet::PATH_SEGMENT, EncodeSet::QUERY_PARAM] {
+            assert_eq!(percent_decode_str(&percent_encode(&all, &set)).unwrap(), all);
+        }
+    }
+
+    #[test]
+    fn queries() {
+        let query = QueryBuilder::new()
+            .param("amount", "100")
+            .param("message", "a=b&c+d 🖖")
+            .param("empty", "")
+            .build();
+        assert_eq!(query, "amount=100&message=a%3Db%26c%2Bd%20%F0%9F%96%96&empty=");
+        assert_eq!(parse_query(&query).unwrap(), vec![
+            ("amount".to_string(), "100".to_string()),
+            ("message".to_string(), "a=b&c+d 🖖".to_string()),
+            ("empty".to_string(), String::new()),
+        ]);
+        assert_eq!(QueryBuilder::new().build(), "");
+        assert_eq!(parse_query("?a&&b=1=2").unwrap(), vec![
+            ("a".to_string(), String::new()),
+            ("b".to_string(), "1=2".to_string()),
+        ]);
+        assert!(parse_query("").unwrap().is_empty());
+        assert_eq!(parse_query("a=%"), Err(PercentError::InvalidEscape { position: 0 }));
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Python bindings for the encoding, checksum and time utilities of this crate.
+//!
+//! The module is exposed as `tari_utilities` and mirrors the Rust API, so that test harnesses and scripts use exactly
+//! the same conversions as the node. To build an importable extension, compile a `cdylib` that depends on this crate
+//! with the `python` and `pyo3/extension-module` features enabled and re-exports [tari_utilities_module].
+
+// The `#[pyfunction]` expansion converts `PyErr` into itself
+#![allow(clippy::useless_conversion)]
+
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use pyo3::{exceptions::PyValueError, prelude::*, pyclass::CompareOp, types::PyBytes};
+
+use crate::{checksum, encoding::Base58, epoch_time::EpochTime, hex};
+
+/// Encode bytes as a lowercase hex string.
+#[pyfunction]
+fn to_hex(data: &[u8]) -> String {
+    hex::to_hex(data)
+}
+
+/// Decode a hex string into bytes, raising `ValueError` on invalid input.
+#[pyfunction]
+fn from_hex<'py>(py: Python<'py>, hex: &str) -> PyResult<Bound<'py, PyBytes>> {
+    let bytes = hex::from_hex(hex).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(PyBytes::new_bound(py, &bytes))
+}
+
+/// Encode bytes as a base58 string.
+#[pyfunction]
+fn to_base58(data: &[u8]) -> String {
+    data.to_vec().to_base58()
+}
+
+/// Decode a base58 string into bytes, raising `ValueError` on invalid input.
+#[pyfunction]
+fn from_base58<'py>(py: Python<'py>, b58: &str) -> PyResult<Bound<'py, PyBytes>> {
+    let bytes = Vec::<u8>::from_base58(b58).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(PyBytes::new_bound(py, &bytes))
+}
+
+/// Compute the CRC32 (IEEE) checksum of bytes.
+#[pyfunction]
+fn crc32(data: &[u8]) -> u32 {
+    checksum::crc32(data)
+}
+
+/// Compute the CRC16-CCITT checksum of bytes.
+#[pyfunction]
+fn crc16_ccitt(data: &[u8]) -> u16 {
+    checksum::crc16_ccitt(data)
+}
+
+/// Compute the xxHash64 hash of bytes.
+#[pyfunction]
+#[pyo3(signature = (data, seed = 0))]
+fn xxhash64(data: &[u8], seed: u64) -> u64 {
+    checksum::xxhash64(data, seed)
+}
+
+/// Python class mirroring [EpochTime].
+#[pyclass(name = "EpochTime", frozen)]
+#[derive(Clone, Copy)]
+pub struct PyEpochTime(EpochTime);
+
+// Python methods always receive the object by reference
+#[allow(clippy::trivially_copy_pass_by_ref)]
+#[pymethods]
+impl PyEpochTime {
+    #[new]
+    fn new(secs: u64) -> Self {
+        Self(EpochTime::from_secs_since_epoch(secs))
+    }
+
+    #[staticmethod]
+    fn now() -> Self {
+        Self(EpochTime::now())
+    }
+
+    #[staticmethod]
+    fn from_secs_since_epoch(secs: u64) -> Self {
+        Self(EpochTime::from_secs_since_epoch(secs))
+    }
+
+    fn as_u64(&self) -> u64 {
+        self.0.as_u64()
+    }
+
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+
+    fn __int__(&self) -> u64 {
+        self.0.as_u64()
+    }
+
+    fn __hash__(&self) -> u64 {
+        self.0.as_u64()
+    }
+
+    fn __richcmp__(&self, other: &Self, op: CompareOp) -> bool {
+        op.matches(self.0.cmp(&other.0))
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("EpochTime({})", self.0)
+    }
+}
+
+impl From<EpochTime> for PyEpochTime {
+    fn from(value: EpochTime) -> Self {
+        Self(value)
+    }
+}
+
+impl From<PyEpochTime> for EpochTime {
+    fn from(value: PyEpochTime) -> Self {
+        value.0
+    }
+}
+
+/// The `tari_utilities` Python module.
+#[pymodule]
+#[pyo3(name = "tari_utilities")]
+pub fn tari_utilities_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(to_hex, m)?)?;
+    m.add_function(wrap_pyfunction!(from_hex, m)?)?;
+    m.add_function(wrap_pyfunction!(to_base58, m)?)?;
+    m.add_function(wrap_pyfunction!(from_base58, m)?)?;
+    m.add_function(wrap_pyfunction!(crc32, m)?)?;
+    m.add_function(wrap_pyfunction!(crc16_ccitt, m)?)?;
+    m.add_function(wrap_pyfunction!(xxhash64, m)?)?;
+    m.add_class::<PyEpochTime>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use pyo3::types::PyDict;
+
+    use super::*;
+
+    fn run(code: &str) {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let module = PyModule::new_bound(py, "tari_utilities").unwrap();
+            tari_utilities_module(&module).unwrap();
+            let locals = PyDict::new_bound(py);
+            locals.set_item("tu", module).unwrap();
+            py.run_bound(code, None, Some(&locals)).unwrap();
+        });
+    }
+
+    #[test]
+    fn encoding() {
+        run(r#"
+assert tu.to_hex(b"\x00\x01\xff") == "0001ff"
+assert tu.from_hex("0001ff") == b"\x00\x01\xff"
+assert tu.to_base58(bytes([0, 2, 250, 39])) == "111zzz"
+assert tu.from_base58("111zzz") == bytes([0, 2, 250, 39])
+for f, arg in [(tu.from_hex, "0g"), (tu.from_base58, "11111O")]:
+    try:
+        f(arg)
+        assert False
+    except ValueError:
+        pass
+"#);
+    }
+
+    #[test]
+    fn checksums() {
+        run(r#"
+assert tu.crc32(b"123456789") == 0xcbf43926
+assert tu.crc16_ccitt(b"123456789") == 0x29b1
+assert tu.xxhash64(b"abc") == 0x44bc2cf5ad770999
+assert tu.xxhash64(b"abc", 0x9e3779b97f4a7c15) == 0x2ed0f59d6b43ac8b
+"#);
+    }
+
+    #[test]
+    fn epoch_time() {
+        run(r#"
+a = tu.EpochTime(1111)
+b = tu.EpochTime.from_secs_since_epoch(123)
+assert a.checked_add(b) == tu.EpochTime(1234)
+assert b.checked_sub(a) is None
+assert b < a and a.as_u64() == 1111 and int(a) == 1111
+assert str(a) == "1111" and repr(a) == "EpochTime(1111)"
+assert tu.EpochTime.now() >= a
+"#);
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Random strings and tokens for API keys and one-time codes.
+//!
+//! Characters are drawn with [uniform_below], which uses rejection sampling rather than reducing a random number
+//! modulo the alphabet size, so every character of the alphabet is exactly equally likely. Custom alphabets are
+//! checked for duplicate characters, which would otherwise make some characters more likely than others.
+//!
+//! ```edition2018
+//! # use tari_utilities::random::{random_string, Alphabet};
+//! let code = random_string(6, Alphabet::Custom("0123456789"), &mut rand::rngs::OsRng).unwrap();
+//! assert_eq!(code.len(), 6);
+//! assert!(code.chars().all(|c| c.is_ascii_digit()));
+//! ```
+
+use alloc::{string::String, vec, vec::Vec};
+use core::convert::TryFrom;
+
+use rand::{rngs::OsRng, RngCore};
+use snafu::prelude::*;
+
+use crate::hex;
+
+/// Errors for generating random strings.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum RandomError {
+    /// The alphabet has no characters.
+    #[snafu(display("The alphabet is empty"))]
+    EmptyAlphabet {},
+    /// The alphabet contains a character more than once.
+    #[snafu(display("The alphabet contains '{character}' more than once"))]
+    DuplicateCharacter {
+        /// The repeated character
+        character: char,
+    },
+}
+
+const ALPHANUMERIC: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// The characters a random string is drawn from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alphabet<'a> {
+    /// ASCII letters of both cases and digits.
+    Alphanumeric,
+    /// Lowercase hexadecimal digits.
+    Hex,
+    /// The characters of the given string, which must be non-empty and contain no duplicates.
+    Custom(&'a str),
+}
+
+impl Alphabet<'_> {
+    /// Return the characters of the alphabet.
+    ///
+    /// # Errors
+    /// Returns an error if a custom alphabet is empty or repeats a character.
+    pub fn chars(&self) -> Result<Vec<char>, RandomError> {
+        let chars = match self {
+            Alphabet::Alphanumeric => ALPHANUMERIC,
+            Alphabet::Hex => hex::DIGITS,
+            Alphabet::Custom(s) => s,
+        }
+        .chars()
+        .collect::<Vec<_>>();
+        ensure!(!chars.is_empty(), EmptyAlphabetSnafu);
+        let mut sorted = chars.clone();
+        sorted.sort_unstable();
+        for pair in sorted.windows(2) {
+            if let [a, b] = pair {
+                ensure!(a != b, DuplicateCharacterSnafu { character: *a });
+            }
+        }
+        Ok(chars)
+    }
+}
+
+/// Return a uniformly distributed number in `0..bound`, or 0 if `bound` is 0.
+///
+/// Random numbers from the top of the `u32` range that would make the smaller results more likely are rejected and
+/// redrawn, so the result has no modulo bias. At most half of all draws are rejected.
+pub fn uniform_below<R: RngCore + ?Sized>(rng: &mut R, bound: u32) -> u32 {
+    // `2^32 % bound`, the number of values at the top of the range that would bias the result
+    let excess = match bound.wrapping_neg().checked_rem(bound) {
+        Some(excess) => excess,
+        None => return 0,
+    };
+    // The largest multiple of `bound` that fits, minus one: `x % bound` is uniform for every `x <= limit`
+    let limit = u32::MAX.wrapping_sub(excess);
+    loop {
+        let x = rng.next_u32();
+        if x <= limit {
+            return x.checked_rem(bound).unwrap_or_default();
+        }
+    }
+}
+
+/// Generate a string of `len` characters, each chosen uniformly from the alphabet.
+///
+/// # Errors
+/// Returns an error if a custom alphabet is empty or repeats a character.
+pub fn random_string<R: RngCore + ?Sized>(
+    len: usize,
+    alphabet: Alphabet<'_>,
+    rng: &mut R,
+) -> Result<String, RandomError> {
+    let chars = alphabet.chars()?;
+    // An alphabet cannot have more than `u32::MAX` characters, since there are fewer distinct `char`s than that
+    let bound = u32::try_from(chars.len()).unwrap_or(u32::MAX);
+    Ok((0..len)
+        .filter_map(|_| chars.get(uniform_below(rng, bound) as usize))
+        .collect())
+}
+
+/// Generate a URL-safe token from `bytes` random bytes of the operating system's random number generator. The token
+/// is the bytes in unpadded URL-safe base64, so it is `ceil(4 * bytes / 3)` characters long.
+pub fn random_token_urlsafe(bytes: usize) -> String {
+    let mut buf = zeroize::Zeroizing::new(vec![0u8; bytes]);
+    OsRng.fill_bytes(&mut buf);
+    crate::base64::to_base64(&buf, crate::base64::Base64Variant::UrlSafeNoPad)
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn alphabets() {
+        assert_eq!(Alphabet::Alphanumeric.chars().unwrap().len(), 62);
+        assert_eq!(Alphabet::Hex.chars().unwrap().len(), 16);
+        assert_eq!(Alphabet::Custom("äb").chars(), Ok(vec!['ä', 'b']));
+        assert_eq!(Alphabet::Custom("").chars(), Err(RandomError::EmptyAlphabet {}));
+        assert_eq!(
+            Alphabet::Custom("abca").chars(),
+            Err(RandomError::DuplicateCharacter { character: 'a' })
+        );
+    }
+
+    #[test]
+    fn strings() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let s = random_string(32, Alphabet::Hex, &mut rng).unwrap();
+        assert_eq!(s.len(), 32);
+        assert!(s.chars().all(|c| hex::DIGITS.contains(c)));
+        assert_eq!(random_string(0, Alphabet::Alphanumeric, &mut rng).unwrap(), "");
+        assert_eq!(random_string(3, Alphabet::Custom("x"), &mut rng).unwrap(), "xxx");
+        assert!(random_string(3, Alphabet::Custom(""), &mut rng).is_err());
+    }
+
+    #[test]
+    fn uniformity() {
+        // An RNG that only returns the largest u32, which must always be rejected for a bound of 3
+        struct Sequence(Vec<u32>);
+        impl RngCore for Sequence {
+            fn next_u32(&mut self) -> u32 {
+                self.0.pop().unwrap()
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                unimplemented!()
+            }
+
+            fn fill_bytes(&mut self, _dest: &mut [u8]) {
+                unimplemented!()
+            }
+
+            fn try_fill_bytes(&mut self, _dest: &mut [u8]) -> Result<(), rand::Error> {
+                unimplemented!()
+            }
+        }
+        let mut rng = Sequence(vec![7, u32::MAX]);
+        assert_eq!(uniform_below(&mut rng, 3), 1);
+        assert_eq!(uniform_below(&mut rng, 0), 0);
+        assert_eq!(uniform_below(&mut Sequence(vec![u32::MAX]), 1 << 31), (1 << 31) - 1);
+        assert_eq!(uniform_below(&mut Sequence(vec![u32::MAX]), 1), 0);
+
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut counts = [0u32; 6];
+        for _ in 0..60_000 {
+            counts[uniform_below(&mut rng, 6) as usize] += 1;
+        }
+        assert!(counts.iter().all(|&c| (9_000..11_000).contains(&c)), "{:?}", counts);
+    }
+
+    #[test]
+    fn tokens() {
+        let token = random_token_urlsafe(32);
+        assert_eq!(token.len(), 43);
+        assert!(token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_ne!(token, random_token_urlsafe(32));
+        assert_eq!(random_token_urlsafe(0), "");
+    }
+}
@@ -123,7 +123,7 @@ impl<T, const N: usize> ConstantTimeEq for SafeArray<T, N>
 where T: ConstantTimeEq
 {
     fn ct_eq(&self, other: &Self) -> subtle::Choice {
-        self.0.ct_eq(&other.0)
+        crate::ct::eq(&self.0, &other.0)
     }
 }
 
@@ -136,6 +136,33 @@ where T: ConstantTimeEq
     }
 }
 
+/// Generates exactly `N` arbitrary elements.
+#[cfg(feature = "arbitrary")]
+impl<'a, T, const N: usize> arbitrary::Arbitrary<'a> for SafeArray<T, N>
+where T: arbitrary::Arbitrary<'a>
+{
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        (0..N)
+            .map(|_| T::arbitrary(u))
+            .collect::<arbitrary::Result<_>>()
+            .map(Self)
+    }
+}
+
+/// Generates exactly `N` arbitrary elements.
+#[cfg(feature = "proptest")]
+impl<T, const N: usize> proptest::arbitrary::Arbitrary for SafeArray<T, N>
+where T: proptest::arbitrary::Arbitrary + 'static
+{
+    type Parameters = T::Parameters;
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(args: Self::Parameters) -> Self::Strategy {
+        use proptest::{collection::vec, prelude::any_with, strategy::Strategy};
+        vec(any_with::<T>(args), N).prop_map(Self).boxed()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -164,6 +191,26 @@ mod tests {
         assert_ne!(key_c.reveal().as_ref(), &[0u8; 32]);
     }
 
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_len() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let data = [7u8; 100];
+        let array = SafeArray::<u8, 32>::arbitrary(&mut Unstructured::new(&data)).unwrap();
+        assert_eq!(array.len(), 32);
+        let array = SafeArray::<u8, 32>::arbitrary(&mut Unstructured::new(&[])).unwrap();
+        assert_eq!(array.len(), 32);
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn proptest_len(array in proptest::prelude::any::<SafeArray<u8, 16>>()) {
+            proptest::prop_assert_eq!(array.len(), 16);
+        }
+    }
+
     #[test]
     fn len() {
         const N: usize = 64;
@@ -0,0 +1,262 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Helpers for safely displaying and logging untrusted strings, such as peer user agents and memo fields.
+//!
+//! Slicing a `str` at an arbitrary byte offset panics if the offset is inside a character, and printing terminal
+//! escape sequences from a peer lets it rewrite the operator's screen. These functions truncate on character boundaries
+//! and remove control characters and escape sequences. They borrow the input when there is nothing to change.
+//!
+//! Bytes that should be UTF-8 but may be corrupt can be decoded with [decode_utf8_lossy_with_report], which also
+//! reports where invalid sequences were replaced.
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::iter::Peekable;
+
+/// The replacement character used by [decode_utf8_lossy_with_report].
+pub const REPLACEMENT_CHARACTER: &str = "\u{fffd}";
+
+/// The invalid UTF-8 found by [decode_utf8_lossy_with_report] or [decode_utf8_with_replacement].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Utf8Report {
+    /// The total number of bytes that were replaced
+    pub replaced_bytes: usize,
+    /// The offset in the input of each invalid sequence that was replaced
+    pub invalid_offsets: Vec<usize>,
+}
+
+impl Utf8Report {
+    /// Return the number of invalid sequences that were replaced.
+    pub fn replaced_sequences(&self) -> usize {
+        self.invalid_offsets.len()
+    }
+
+    /// Return `true` if the input was valid UTF-8.
+    pub fn is_clean(&self) -> bool {
+        self.invalid_offsets.is_empty()
+    }
+}
+
+/// Decode UTF-8, replacing each invalid sequence with U+FFFD as [String::from_utf8_lossy] does, and report what
+/// was replaced so that corrupt input can be flagged as well as displayed.
+///
+/// ```edition2018
+/// # use tari_utilities::sanitize::decode_utf8_lossy_with_report;
+/// let (text, report) = decode_utf8_lossy_with_report(b"memo \xff\xfe!");
+/// assert_eq!(text, "memo \u{fffd}\u{fffd}!");
+/// assert_eq!(report.invalid_offsets, vec![5, 6]);
+/// assert_eq!(report.replaced_bytes, 2);
+/// ```
+pub fn decode_utf8_lossy_with_report(bytes: &[u8]) -> (Cow<'_, str>, Utf8Report) {
+    decode_utf8_with_replacement(bytes, REPLACEMENT_CHARACTER)
+}
+
+/// Decode UTF-8 like [decode_utf8_lossy_with_report], but replace each invalid sequence with `replacement`, which may
+/// be empty to drop them.
+pub fn decode_utf8_with_replacement<'a>(bytes: &'a [u8], replacement: &str) -> (Cow<'a, str>, Utf8Report) {
+    let mut report = Utf8Report::default();
+    if let Ok(s) = core::str::from_utf8(bytes) {
+        return (Cow::Borrowed(s), report);
+    }
+    let mut decoded = String::with_capacity(bytes.len());
+    let mut offset = 0usize;
+    for chunk in bytes.utf8_chunks() {
+        decoded.push_str(chunk.valid());
+        offset = offset.saturating_add(chunk.valid().len());
+        let invalid = chunk.invalid().len();
+        if invalid > 0 {
+            decoded.push_str(replacement);
+            report.invalid_offsets.push(offset);
+            report.replaced_bytes = report.replaced_bytes.saturating_add(invalid);
+            offset = offset.saturating_add(invalid);
+        }
+    }
+    (Cow::Owned(decoded), report)
+}
+
+/// Return the longest prefix of `s` that is at most `max_bytes` long and ends on a character boundary.
+///
+/// ```edition2018
+/// # use tari_utilities::sanitize::truncate_at_char_boundary;
+/// assert_eq!(truncate_at_char_boundary("héllo", 2), "h");
+/// assert_eq!(truncate_at_char_boundary("héllo", 3), "hé");
+/// ```
+pub fn truncate_at_char_boundary(s: &str, max_bytes: usize) -> &str {
+    let end = s
+        .char_indices()
+        .map(|(i, c)| i.saturating_add(c.len_utf8()))
+        .take_while(|end| *end <= max_bytes)
+        .last()
+        .unwrap_or_default();
+    s.get(..end).unwrap_or_default()
+}
+
+/// Remove all control characters (Unicode category `Cc`, including newlines and tabs) from the string.
+pub fn strip_control_chars(s: &str) -> Cow<'_, str> {
+    if s.chars().any(char::is_control) {
+        Cow::Owned(s.chars().filter(|c| !c.is_control()).collect())
+    } else {
+        Cow::Borrowed(s)
+    }
+}
+
+/// Make a string safe to print to a terminal or log. This removes:
+/// - ANSI escape sequences (CSI, OSC and other control strings) in their entirety, so that no fragments are left
+///   behind,
+/// - all other control characters, and
+/// - Unicode bidirectional formatting characters, which can make text display in a different order to how it reads.
+///
+/// ```edition2018
+/// # use tari_utilities::sanitize::sanitize_for_terminal;
+/// assert_eq!(
+///     sanitize_for_terminal("\x1b[31mred\x1b[0m alert\x07"),
+///     "red alert"
+/// );
+/// assert_eq!(sanitize_for_terminal("plain text"), "plain text");
+/// ```
+pub fn sanitize_for_terminal(s: &str) -> Cow<'_, str> {
+    if !s.chars().any(is_unsafe_for_terminal) {
+        return Cow::Borrowed(s);
+    }
+    let mut sanitized = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                Some('[') => skip_csi(&mut chars),
+                Some(']' | 'P' | 'X' | '^' | '_') => skip_control_string(&mut chars),
+                // Any other escape sequence is the escape and one character
+                _ => {},
+            },
+            '\u{9b}' => skip_csi(&mut chars),
+            '\u{90}' | '\u{98}' | '\u{9d}' | '\u{9e}' | '\u{9f}' => skip_control_string(&mut chars),
+            c if is_unsafe_for_terminal(c) => {},
+            c => sanitized.push(c),
+        }
+    }
+    Cow::Owned(sanitized)
+}
+
+fn is_unsafe_for_terminal(c: char) -> bool {
+    c.is_control() || is_bidi_control(c)
+}
+
+/// The Unicode bidirectional formatting characters.
+fn is_bidi_control(c: char) -> bool {
+    matches!(c, '\u{061c}' | '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}')
+}
+
+/// Skip the parameters, intermediates and final character of a control sequence.
+fn skip_csi<I: Iterator<Item = char>>(chars: &mut Peekable<I>) {
+    for c in chars.by_ref() {
+        if ('\u{40}'..='\u{7e}').contains(&c) {
+            return;
+        }
+    }
+}
+
+/// Skip a control string, which is terminated by BEL or the string terminator (`ESC \` or U+009C).
+fn skip_control_string<I: Iterator<Item = char>>(chars: &mut Peekable<I>) {
+    while let Some(c) = chars.next() {
+        match c {
+            '\x07' | '\u{9c}' => return,
+            '\x1b' => {
+                if chars.peek() == Some(&'\\') {
+                    chars.next();
+                }
+                return;
+            },
+            _ => {},
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn truncation() {
+        assert_eq!(truncate_at_char_boundary("", 10), "");
+        assert_eq!(truncate_at_char_boundary("abc", 0), "");
+        assert_eq!(truncate_at_char_boundary("abc", 2), "ab");
+        assert_eq!(truncate_at_char_boundary("abc", 10), "abc");
+        let s = "a🖖b";
+        assert_eq!(truncate_at_char_boundary(s, 1), "a");
+        assert_eq!(truncate_at_char_boundary(s, 4), "a");
+        assert_eq!(truncate_at_char_boundary(s, 5), "a🖖");
+        assert_eq!(truncate_at_char_boundary(s, 6), s);
+        for max in 0..10 {
+            assert!(truncate_at_char_boundary(s, max).len() <= max);
+        }
+    }
+
+    #[test]
+    fn control_chars() {
+        assert!(matches!(strip_control_chars("tari/0.1"), Cow::Borrowed("tari/0.1")));
+        assert_eq!(strip_control_chars("a\nb\tc\0d\x7f\u{85}e"), "abcde");
+        assert_eq!(strip_control_chars("\u{202e}é"), "\u{202e}é");
+    }
+
+    #[test]
+    fn terminal() {
+        assert!(matches!(sanitize_for_terminal("héllo wörld"), Cow::Borrowed(_)));
+        assert_eq!(sanitize_for_terminal("\x1b[1;31mbold red\x1b[0m"), "bold red");
+        assert_eq!(sanitize_for_terminal("\x1b]0;new title\x07text"), "text");
+        assert_eq!(
+            sanitize_for_terminal("\x1b]8;;http://evil\x1b\\link\x1b]8;;\x1b\\"),
+            "link"
+        );
+        assert_eq!(sanitize_for_terminal("\x1bPdevice control\x1b\\ok"), "ok");
+        assert_eq!(sanitize_for_terminal("\u{9b}2Jcleared"), "cleared");
+        assert_eq!(sanitize_for_terminal("\x1bcreset"), "reset");
+        assert_eq!(sanitize_for_terminal("line\r\nbreak\x08"), "linebreak");
+        assert_eq!(sanitize_for_terminal("abc\u{202e}fed\u{2066}"), "abcfed");
+        // Unterminated sequences swallow the rest of the input rather than leaking it
+        assert_eq!(sanitize_for_terminal("ok\x1b[12"), "ok");
+        assert_eq!(sanitize_for_terminal("ok\x1b]title"), "ok");
+        assert_eq!(sanitize_for_terminal("ok\x1b"), "ok");
+    }
+
+    #[test]
+    fn lossy_utf8() {
+        let (text, report) = decode_utf8_lossy_with_report("héllo".as_bytes());
+        assert!(matches!(text, Cow::Borrowed("héllo")));
+        assert!(report.is_clean());
+
+        // A truncated sequence is one replacement, a lone continuation byte is another
+        let input = b"a\xf0\x9f\x96b\x80\xc3";
+        let (text, report) = decode_utf8_lossy_with_report(input);
+        assert_eq!(text, String::from_utf8_lossy(input));
+        assert_eq!(report, Utf8Report {
+            replaced_bytes: 5,
+            invalid_offsets: alloc::vec![1, 5, 6],
+        });
+        assert_eq!(report.replaced_sequences(), 3);
+        assert!(!report.is_clean());
+
+        let (text, report) = decode_utf8_with_replacement(b"\xffok\xff", "");
+        assert_eq!(text, "ok");
+        assert_eq!(report.invalid_offsets, alloc::vec![0, 3]);
+        assert_eq!(decode_utf8_with_replacement(b"a\xffb", "?").0, "a?b");
+    }
+}
@@ -0,0 +1,411 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Passphrase-encrypted files for wallet backups and keys.
+//!
+//! A value is serialized with [MessageFormat::to_binary], and encrypted with XChaCha20-Poly1305 under a key derived
+//! from a [SafePassword] with Argon2id. A sealed file is laid out as:
+//!
+//! | Field          | Size     |
+//! |----------------|----------|
+//! | Magic `TARISEAL` | 8      |
+//! | Version (1)    | 1        |
+//! | Argon2 memory cost in KiB, little-endian | 4 |
+//! | Argon2 iterations, little-endian | 4 |
+//! | Argon2 parallelism, little-endian | 4 |
+//! | Salt           | 16       |
+//! | Nonce          | 24       |
+//! | Ciphertext and tag | rest |
+//!
+//! The whole header is authenticated along with the ciphertext. The Argon2 parameters in a file are capped when it is
+//! opened, so that a crafted file cannot make the reader allocate unbounded memory.
+//!
+//! ```edition2018
+//! # use tari_utilities::{sealed_file::{seal_with_params, unseal, KdfParams}, SafePassword};
+//! let password = SafePassword::from("correct horse battery staple");
+//! # let params = KdfParams { memory_kib: 8, iterations: 1, parallelism: 1 };
+//! let sealed = seal_with_params(&vec![1u8, 2, 3], &password, params).unwrap();
+//! let opened: Vec<u8> = unseal(&sealed, &password).unwrap();
+//! assert_eq!(opened, vec![1, 2, 3]);
+//! ```
+
+use alloc::{string::ToString, vec::Vec};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, KeyInit, OsRng, Payload},
+    XChaCha20Poly1305,
+    XNonce,
+};
+use snafu::prelude::*;
+use zeroize::Zeroizing;
+
+use crate::{byte_cursor::ByteCursor, message_format::MessageFormat, SafePassword};
+
+/// The magic bytes at the start of every sealed file.
+pub const MAGIC: [u8; 8] = *b"TARISEAL";
+/// The current format version.
+pub const VERSION: u8 = 1;
+/// The largest Argon2 memory cost accepted when opening a file, 1 GiB.
+pub const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+/// The largest Argon2 iteration count accepted when opening a file.
+pub const MAX_ITERATIONS: u32 = 64;
+/// The largest Argon2 parallelism accepted when opening a file.
+pub const MAX_PARALLELISM: u32 = 16;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 12 + SALT_LEN + NONCE_LEN;
+
+/// Errors for sealed files.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum SealedFileError {
+    /// The data is not a sealed file.
+    #[snafu(display("Not a sealed file"))]
+    InvalidHeader {},
+    /// The file was written by a newer format version.
+    #[snafu(display("Unsupported sealed file version {version}"))]
+    UnsupportedVersion {
+        /// The version in the header
+        version: u8,
+    },
+    /// The key derivation parameters are invalid or exceed the limits for opening files.
+    #[snafu(display("Invalid key derivation parameters"))]
+    InvalidParameters {},
+    /// The passphrase is wrong or the file has been modified.
+    #[snafu(display("Decryption failed: wrong passphrase or corrupt file"))]
+    DecryptionFailed {},
+    /// The value could not be serialized.
+    #[snafu(display("The value could not be serialized"))]
+    SerializeFailed {},
+    /// The decrypted payload is not a valid value of the expected type.
+    #[snafu(display("The decrypted payload could not be deserialized"))]
+    DeserializeFailed {},
+    /// The file could not be read or written.
+    #[snafu(display("Sealed file I/O error: {reason}"))]
+    Io {
+        /// The I/O error
+        reason: alloc::string::String,
+    },
+}
+
+/// Argon2id parameters for deriving the file key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// The memory cost in KiB
+    pub memory_kib: u32,
+    /// The number of iterations
+    pub iterations: u32,
+    /// The degree of parallelism
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    /// The Argon2id parameters recommended by OWASP: 19 MiB, 2 iterations and no parallelism.
+    fn default() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl KdfParams {
+    fn derive_key(&self, password: &SafePassword, salt: &[u8]) -> Result<Zeroizing<[u8; KEY_LEN]>, SealedFileError> {
+        ensure!(
+            self.memory_kib <= MAX_MEMORY_KIB &&
+                self.iterations <= MAX_ITERATIONS &&
+                self.parallelism <= MAX_PARALLELISM,
+            InvalidParametersSnafu
+        );
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(KEY_LEN))
+            .map_err(|_| SealedFileError::InvalidParameters {})?;
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(password.reveal(), salt, key.as_mut())
+            .map_err(|_| SealedFileError::InvalidParameters {})?;
+        Ok(key)
+    }
+}
+
+/// Seal a value with the default key derivation parameters.
+///
+/// # Errors
+/// Returns [SealedFileError::SerializeFailed] if the value cannot be serialized.
+pub fn seal<T: MessageFormat>(value: &T, password: &SafePassword) -> Result<Vec<u8>, SealedFileError> {
+    seal_with_params(value, password, KdfParams::default())
+}
+
+/// Seal a value with the given key derivation parameters.
+///
+/// # Errors
+/// Returns [SealedFileError::InvalidParameters] if the parameters are rejected by Argon2 or exceed the limits for
+/// opening files, and [SealedFileError::SerializeFailed] if the value cannot be serialized.
+pub fn seal_with_params<T: MessageFormat>(
+    value: &T,
+    password: &SafePassword,
+    params: KdfParams,
+) -> Result<Vec<u8>, SealedFileError> {
+    let plaintext = Zeroizing::new(value.to_binary().map_err(|_| SealedFileError::SerializeFailed {})?);
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let key = params.derive_key(password, &salt)?;
+
+    let mut sealed = Vec::with_capacity(HEADER_LEN.saturating_add(plaintext.len()).saturating_add(16));
+    sealed.extend_from_slice(&MAGIC);
+    sealed.push(VERSION);
+    sealed.extend_from_slice(&params.memory_kib.to_le_bytes());
+    sealed.extend_from_slice(&params.iterations.to_le_bytes());
+    sealed.extend_from_slice(&params.parallelism.to_le_bytes());
+    sealed.extend_from_slice(&salt);
+    sealed.extend_from_slice(&nonce);
+    let ciphertext = XChaCha20Poly1305::new(key.as_ref().into())
+        .encrypt(XNonce::from_slice(&nonce), Payload {
+            msg: &plaintext,
+            aad: &sealed,
+        })
+        .map_err(|_| SealedFileError::SerializeFailed {})?;
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Open a sealed value.
+///
+/// # Errors
+/// Returns [SealedFileError::DecryptionFailed] if the passphrase is wrong or the data has been modified, and an error
+/// describing the problem if the header is malformed or the payload is not a `T`.
+pub fn unseal<T: MessageFormat>(sealed: &[u8], password: &SafePassword) -> Result<T, SealedFileError> {
+    let mut cursor = ByteCursor::new(sealed);
+    let invalid = |_| SealedFileError::InvalidHeader {};
+    ensure!(cursor.read_array::<8>().map_err(invalid)? == MAGIC, InvalidHeaderSnafu);
+    let version = cursor.read_u8().map_err(invalid)?;
+    ensure!(version == VERSION, UnsupportedVersionSnafu { version });
+    let params = KdfParams {
+        memory_kib: cursor.read_u32_le().map_err(invalid)?,
+        iterations: cursor.read_u32_le().map_err(invalid)?,
+        parallelism: cursor.read_u32_le().map_err(invalid)?,
+    };
+    let salt = cursor.read_array::<SALT_LEN>().map_err(invalid)?;
+    let nonce = cursor.read_array::<NONCE_LEN>().map_err(invalid)?;
+    let header = sealed.get(..cursor.position()).unwrap_or_default();
+    let ciphertext = cursor.remaining_bytes();
+
+    let key = params.derive_key(password, &salt)?;
+    let plaintext = Zeroizing::new(
+        XChaCha20Poly1305::new(key.as_ref().into())
+            .decrypt(XNonce::from_slice(&nonce), Payload {
+                msg: ciphertext,
+                aad: header,
+            })
+            .map_err(|_| SealedFileError::DecryptionFailed {})?,
+    );
+    T::from_binary(&plaintext).map_err(|_| SealedFileError::DeserializeFailed {})
+}
+
+/// Seal a value with the default key derivation parameters and write it to a file.
+///
+/// The file is replaced atomically: the sealed value is written and synced to a temporary file next to it, which is
+/// then renamed over `path`, so a crash leaves either the old or the new file. On Unix, the file is only readable and
+/// writable by its owner.
+///
+/// # Errors
+/// Returns an error if the value cannot be sealed or the file cannot be written.
+pub fn write_sealed_file<T: MessageFormat, P: AsRef<Path>>(
+    path: P,
+    value: &T,
+    password: &SafePassword,
+) -> Result<(), SealedFileError> {
+    let sealed = seal(value, password)?;
+    replace_file(path.as_ref(), &sealed).map_err(|e| SealedFileError::Io { reason: e.to_string() })
+}
+
+/// Atomically replace the file at `path` with `contents`, creating it with owner-only permissions.
+fn replace_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_os_string();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    // A temporary file left behind by a crash may have other permissions, so it is never reused
+    match fs::remove_file(&tmp) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {},
+    }
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    {
+        let mut file = options.open(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, path)?;
+    // Make the rename itself durable. Directories cannot be opened for syncing on every platform.
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// Read a sealed file and open it.
+///
+/// # Errors
+/// Returns an error if the file cannot be read or opened.
+pub fn read_sealed_file<T: MessageFormat, P: AsRef<Path>>(
+    path: P,
+    password: &SafePassword,
+) -> Result<T, SealedFileError> {
+    let sealed = fs::read(path).map_err(|e| SealedFileError::Io { reason: e.to_string() })?;
+    unseal(&sealed, password)
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{string::String, vec};
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    const FAST: KdfParams = KdfParams {
+        memory_kib: 8,
+        iterations: 1,
+        parallelism: 1,
+    };
+
+    #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+    struct Backup {
+        label: String,
+        seed: Vec<u8>,
+    }
+
+    fn backup() -> Backup {
+        Backup {
+            label: "wallet".into(),
+            seed: vec![7; 32],
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let password = SafePassword::from("passphrase");
+        let sealed = seal_with_params(&backup(), &password, FAST).unwrap();
+        assert_eq!(&sealed[..8], b"TARISEAL");
+        assert_eq!(sealed[8], VERSION);
+        assert_eq!(&sealed[9..13], &8u32.to_le_bytes());
+        assert_eq!(unseal::<Backup>(&sealed, &password).unwrap(), backup());
+        // Every seal uses a fresh salt and nonce
+        assert_ne!(sealed, seal_with_params(&backup(), &password, FAST).unwrap());
+    }
+
+    #[test]
+    fn rejects_wrong_password_and_tampering() {
+        let password = SafePassword::from("passphrase");
+        let sealed = seal_with_params(&backup(), &password, FAST).unwrap();
+        assert_eq!(
+            unseal::<Backup>(&sealed, &SafePassword::from("wrong")),
+            Err(SealedFileError::DecryptionFailed {})
+        );
+        for i in [HEADER_LEN - 1, HEADER_LEN, sealed.len() - 1] {
+            let mut tampered = sealed.clone();
+            tampered[i] ^= 1;
+            assert_eq!(
+                unseal::<Backup>(&tampered, &password),
+                Err(SealedFileError::DecryptionFailed {})
+            );
+        }
+        assert_eq!(
+            unseal::<Vec<u64>>(&sealed, &password),
+            Err(SealedFileError::DeserializeFailed {})
+        );
+    }
+
+    #[test]
+    fn rejects_bad_headers() {
+        let password = SafePassword::from("passphrase");
+        let sealed = seal_with_params(&backup(), &password, FAST).unwrap();
+        assert_eq!(
+            unseal::<Backup>(&sealed[..HEADER_LEN - 1], &password),
+            Err(SealedFileError::InvalidHeader {})
+        );
+        let mut bad = sealed.clone();
+        bad[0] = b'X';
+        assert_eq!(
+            unseal::<Backup>(&bad, &password),
+            Err(SealedFileError::InvalidHeader {})
+        );
+        let mut bad = sealed.clone();
+        bad[8] = 2;
+        assert_eq!(
+            unseal::<Backup>(&bad, &password),
+            Err(SealedFileError::UnsupportedVersion { version: 2 })
+        );
+        let mut bad = sealed;
+        bad[9..13].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            unseal::<Backup>(&bad, &password),
+            Err(SealedFileError::InvalidParameters {})
+        );
+        let params = KdfParams { parallelism: 0, ..FAST };
+        assert_eq!(
+            seal_with_params(&backup(), &password, params),
+            Err(SealedFileError::InvalidParameters {})
+        );
+    }
+
+    #[test]
+    fn files() {
+        let password = SafePassword::from("passphrase");
+        let path = std::env::temp_dir().join(alloc::format!("tari_sealed_file_{}", std::process::id()));
+        fs::write(&path, b"old contents").unwrap();
+        replace_file(&path, &seal_with_params(&backup(), &password, FAST).unwrap()).unwrap();
+        assert_eq!(read_sealed_file::<Backup, _>(&path, &password).unwrap(), backup());
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+        assert!(!Path::new(&tmp).exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            // A stale temporary file does not pass on its permissions
+            fs::write(&tmp, b"stale").unwrap();
+            fs::set_permissions(&tmp, fs::Permissions::from_mode(0o644)).unwrap();
+            fs::remove_file(&path).unwrap();
+            replace_file(&path, b"new contents").unwrap();
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+            assert_eq!(fs::read(&path).unwrap(), b"new contents");
+        }
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            read_sealed_file::<Backup, _>(&path, &password),
+            Err(SealedFileError::Io { .. })
+        ));
+    }
+}
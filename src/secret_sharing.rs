@@ -0,0 +1,307 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Shamir secret sharing over GF(256) for secret byte arrays.
+//!
+//! [split] turns a secret into `shares` shares, any `threshold` of which [combine] back into the secret, while fewer
+//! reveal nothing about it. Every byte of the secret is shared independently with a random polynomial of degree
+//! `threshold - 1` over GF(256), using the AES field polynomial. Secrets and shares are kept in
+//! `Hidden<SafeArray<u8, N>>` buffers, the field arithmetic is constant-time, and the random coefficients are zeroized
+//! after use.
+//!
+//! ```edition2018
+//! # use rand::rngs::OsRng;
+//! # use tari_utilities::{hidden::Hidden, safe_array::SafeArray, secret_sharing::{combine, split}};
+//! let mut seed = SafeArray::<u8, 32>::default();
+//! seed[0] = 42;
+//! let seed = Hidden::hide(seed);
+//!
+//! let shares = split(&seed, 2, 3, &mut OsRng).unwrap();
+//! let recovered = combine(&shares[1..]).unwrap();
+//! assert_eq!(recovered.reveal(), seed.reveal());
+//! ```
+
+use alloc::{vec, vec::Vec};
+
+use rand::{CryptoRng, RngCore};
+use snafu::prelude::*;
+use zeroize::Zeroizing;
+
+use crate::{hidden::Hidden, safe_array::SafeArray};
+
+/// Errors for splitting and combining secrets.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum SecretSharingError {
+    /// The threshold is zero or larger than the number of shares.
+    #[snafu(display("Invalid threshold {threshold} for {shares} shares"))]
+    InvalidThreshold {
+        /// The requested threshold
+        threshold: u8,
+        /// The requested number of shares
+        shares: u8,
+    },
+    /// Fewer shares were provided than their threshold requires.
+    #[snafu(display("{needed} shares are needed but only {provided} were provided"))]
+    NotEnoughShares {
+        /// The threshold of the shares
+        needed: u8,
+        /// The number of shares provided
+        provided: usize,
+    },
+    /// A share has index zero, which would hold the secret itself.
+    #[snafu(display("Share index 0 is invalid"))]
+    InvalidShareIndex {},
+    /// Two shares have the same index.
+    #[snafu(display("Share {index} was provided more than once"))]
+    DuplicateShare {
+        /// The repeated index
+        index: u8,
+    },
+    /// The shares do not all have the same threshold, so they cannot come from the same split.
+    #[snafu(display("The shares have different thresholds"))]
+    InconsistentShares {},
+}
+
+/// One share of a secret of `N` bytes.
+#[derive(Debug, Clone)]
+pub struct Share<const N: usize> {
+    index: u8,
+    threshold: u8,
+    value: Hidden<SafeArray<u8, N>>,
+}
+
+impl<const N: usize> Share<N> {
+    /// Create a share from its parts, for example after loading it from a backup.
+    pub fn new(index: u8, threshold: u8, value: Hidden<SafeArray<u8, N>>) -> Self {
+        Self {
+            index,
+            threshold,
+            value,
+        }
+    }
+
+    /// The index of the share, from 1 to 255.
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// The number of shares needed to recover the secret.
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// The share data.
+    pub fn value(&self) -> &Hidden<SafeArray<u8, N>> {
+        &self.value
+    }
+}
+
+/// Multiply in GF(256) modulo x^8 + x^4 + x^3 + x + 1, without branches on the operands.
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (0x1b & carry);
+        b >>= 1;
+    }
+    product
+}
+
+/// Invert in GF(256) as `a^254`. The inverse of 0 is 0.
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exponent = 254u8;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Split a secret into `shares` shares, any `threshold` of which recover it.
+///
+/// # Errors
+/// Returns [SecretSharingError::InvalidThreshold] if `threshold` is zero or larger than `shares`.
+pub fn split<R, const N: usize>(
+    secret: &Hidden<SafeArray<u8, N>>,
+    threshold: u8,
+    shares: u8,
+    rng: &mut R,
+) -> Result<Vec<Share<N>>, SecretSharingError>
+where
+    R: RngCore + CryptoRng + ?Sized,
+{
+    ensure!(threshold > 0 && threshold <= shares, InvalidThresholdSnafu {
+        threshold,
+        shares
+    });
+    let mut result = (1..=shares)
+        .map(|index| Share::new(index, threshold, Hidden::hide(SafeArray::default())))
+        .collect::<Vec<_>>();
+    // The coefficients of x, x^2, ... for the current byte; the constant term is the secret byte
+    let mut coefficients = Zeroizing::new(vec![0u8; usize::from(threshold.saturating_sub(1))]);
+    for (i, &byte) in secret.reveal().iter().enumerate() {
+        rng.fill_bytes(&mut coefficients);
+        for share in &mut result {
+            let x = share.index;
+            let y = coefficients.iter().rev().fold(0, |y, &c| gf_mul(y, x) ^ c);
+            if let Some(value) = share.value.reveal_mut().get_mut(i) {
+                *value = gf_mul(y, x) ^ byte;
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Recover a secret from at least `threshold` of its shares. Shares beyond the threshold are ignored.
+///
+/// Combining shares from different secrets, or shares that were tampered with, silently produces a wrong secret;
+/// store a checksum or MAC of the secret alongside the shares if that must be detected.
+///
+/// # Errors
+/// Returns an error if too few shares are provided, or if the shares are malformed, repeated or inconsistent.
+pub fn combine<const N: usize>(shares: &[Share<N>]) -> Result<Hidden<SafeArray<u8, N>>, SecretSharingError> {
+    let threshold = shares.first().map_or(1, |share| share.threshold);
+    ensure!(
+        shares.iter().all(|share| share.threshold == threshold),
+        InconsistentSharesSnafu
+    );
+    ensure!(
+        shares.len() >= usize::from(threshold) && threshold > 0,
+        NotEnoughSharesSnafu {
+            needed: threshold.max(1),
+            provided: shares.len()
+        }
+    );
+    let shares = shares.get(..usize::from(threshold)).unwrap_or_default();
+    for (i, share) in shares.iter().enumerate() {
+        ensure!(share.index != 0, InvalidShareIndexSnafu);
+        if shares.iter().take(i).any(|other| other.index == share.index) {
+            return DuplicateShareSnafu { index: share.index }.fail();
+        }
+    }
+
+    let mut secret = Hidden::hide(SafeArray::<u8, N>::default());
+    for share in shares {
+        // The Lagrange basis polynomial of this share, evaluated at zero. Subtraction in GF(256) is XOR.
+        let basis = shares
+            .iter()
+            .filter(|other| other.index != share.index)
+            .fold(1, |basis, other| {
+                gf_mul(basis, gf_mul(other.index, gf_inv(other.index ^ share.index)))
+            });
+        for (out, &y) in secret.reveal_mut().iter_mut().zip(share.value.reveal().iter()) {
+            *out ^= gf_mul(basis, y);
+        }
+    }
+    Ok(secret)
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+
+    use super::*;
+
+    fn secret() -> Hidden<SafeArray<u8, 32>> {
+        let mut secret = SafeArray::<u8, 32>::default();
+        OsRng.fill_bytes(&mut secret);
+        Hidden::hide(secret)
+    }
+
+    #[test]
+    fn field_arithmetic() {
+        assert_eq!(gf_mul(0x53, 0xca), 0x01);
+        assert_eq!(gf_mul(0x57, 0x83), 0xc1);
+        assert_eq!(gf_inv(0x53), 0xca);
+        assert_eq!(gf_inv(0), 0);
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1);
+        }
+    }
+
+    #[test]
+    fn split_and_combine() {
+        let secret = secret();
+        let shares = split(&secret, 3, 5, &mut OsRng).unwrap();
+        assert_eq!(shares.len(), 5);
+        assert_eq!(shares.iter().map(Share::index).collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+        for subset in [[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+            let chosen = subset.iter().map(|&i| shares[i].clone()).collect::<Vec<_>>();
+            assert_eq!(combine(&chosen).unwrap().reveal(), secret.reveal());
+        }
+        assert_eq!(combine(&shares).unwrap().reveal(), secret.reveal());
+
+        let single = split(&secret, 1, 2, &mut OsRng).unwrap();
+        assert_eq!(single[1].value().reveal(), secret.reveal());
+        assert_eq!(combine(&single[1..]).unwrap().reveal(), secret.reveal());
+        assert_eq!(split(&secret, 255, 255, &mut OsRng).unwrap().len(), 255);
+    }
+
+    #[test]
+    fn validation() {
+        let secret = secret();
+        assert_eq!(
+            split(&secret, 0, 3, &mut OsRng).unwrap_err(),
+            SecretSharingError::InvalidThreshold {
+                threshold: 0,
+                shares: 3
+            }
+        );
+        assert_eq!(
+            split(&secret, 4, 3, &mut OsRng).unwrap_err(),
+            SecretSharingError::InvalidThreshold {
+                threshold: 4,
+                shares: 3
+            }
+        );
+
+        let shares = split(&secret, 2, 3, &mut OsRng).unwrap();
+        assert_eq!(
+            combine(&shares[..1]).unwrap_err(),
+            SecretSharingError::NotEnoughShares { needed: 2, provided: 1 }
+        );
+        assert_eq!(combine::<32>(&[]).unwrap_err(), SecretSharingError::NotEnoughShares {
+            needed: 1,
+            provided: 0
+        });
+        assert_eq!(
+            combine(&[shares[0].clone(), shares[0].clone()]).unwrap_err(),
+            SecretSharingError::DuplicateShare { index: 1 }
+        );
+        let zero = Share::new(0, 2, shares[1].value().clone());
+        assert_eq!(
+            combine(&[zero, shares[1].clone()]).unwrap_err(),
+            SecretSharingError::InvalidShareIndex {}
+        );
+        let other = split(&secret, 3, 3, &mut OsRng).unwrap();
+        assert_eq!(
+            combine(&[shares[0].clone(), other[1].clone(), other[2].clone()]).unwrap_err(),
+            SecretSharingError::InconsistentShares {}
+        );
+    }
+}
@@ -0,0 +1,581 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Lightweight semantic versions and version requirements for protocol and user agent negotiation.
+//!
+//! [Version] parses `MAJOR.MINOR.PATCH[-pre][+build]` as defined by [Semantic Versioning 2.0.0](https://semver.org),
+//! and orders versions by their precedence. [VersionReq] supports the common single requirements with Cargo's
+//! semantics: caret (`^1.2.3`, the default), tilde (`~1.2.3`), exact (`=1.2.3`) and any (`*`), with partial versions
+//! such as `^1.2`.
+//!
+//! ```edition2018
+//! # use tari_utilities::semver_lite::{Version, VersionReq};
+//! let version: Version = "1.4.0-rc.1+build.5".parse().unwrap();
+//! assert!(version < "1.4.0".parse().unwrap());
+//!
+//! let req: VersionReq = "^1.2".parse().unwrap();
+//! assert!(req.matches(&"1.9.3".parse().unwrap()));
+//! assert!(!req.matches(&"2.0.0".parse().unwrap()));
+//! // Pre-releases only match requirements on the same version that also have a pre-release
+//! assert!(!req.matches(&version));
+//! ```
+
+use alloc::string::String;
+use core::{cmp::Ordering, fmt, str::FromStr};
+
+use snafu::prelude::*;
+
+/// Errors for parsing versions and requirements.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum SemverError {
+    /// The string is not a semantic version.
+    #[snafu(display("Invalid semantic version"))]
+    InvalidVersion {},
+    /// The string is not a supported version requirement.
+    #[snafu(display("Invalid version requirement"))]
+    InvalidRequirement {},
+}
+
+/// A semantic version.
+///
+/// Versions are ordered by precedence. Versions that differ only in their build metadata have the same precedence,
+/// so the build metadata is compared last, as a plain string, to keep the ordering consistent with equality. Use
+/// [Version::cmp_precedence] to ignore it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Version {
+    /// The major version
+    pub major: u64,
+    /// The minor version
+    pub minor: u64,
+    /// The patch version
+    pub patch: u64,
+    /// The dot-separated pre-release identifiers, or empty for a release
+    pub pre: String,
+    /// The dot-separated build metadata, or empty if there is none
+    pub build: String,
+}
+
+impl Version {
+    /// Create a release version.
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+            pre: String::new(),
+            build: String::new(),
+        }
+    }
+
+    /// Return `true` if this is a pre-release.
+    pub fn is_prerelease(&self) -> bool {
+        !self.pre.is_empty()
+    }
+
+    /// Compare the precedence of two versions, ignoring build metadata.
+    pub fn cmp_precedence(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (self.pre.is_empty(), other.pre.is_empty()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => cmp_pre(&self.pre, &other.pre),
+            })
+    }
+}
+
+/// Compare pre-release identifiers: numeric identifiers numerically and below alphanumeric ones, alphanumeric ones
+/// in ASCII order, and a shorter list of identifiers below a longer one that it is a prefix of.
+fn cmp_pre(a: &str, b: &str) -> Ordering {
+    let mut a = a.split('.');
+    let mut b = b.split('.');
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => {
+                let is_numeric = |s: &str| s.bytes().all(|c| c.is_ascii_digit());
+                let ordering = match (is_numeric(a), is_numeric(b)) {
+                    // Numeric identifiers have no leading zeros, so the longer one is larger
+                    (true, true) => a.len().cmp(&b.len()).then_with(|| a.cmp(b)),
+                    (true, false) => Ordering::Less,
+                    (false, true) => Ordering::Greater,
+                    (false, false) => a.cmp(b),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            },
+        }
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_precedence(other).then_with(|| self.build.cmp(&other.build))
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Parse a numeric version component, which may not have leading zeros.
+fn parse_number(s: &str) -> Option<u64> {
+    let canonical = !s.is_empty() && s.bytes().all(|c| c.is_ascii_digit()) && (s == "0" || !s.starts_with('0'));
+    if canonical {
+        s.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Check dot-separated identifiers, which are non-empty and alphanumeric or `-`. Numeric pre-release identifiers may
+/// not have leading zeros.
+fn valid_identifiers(s: &str, is_pre: bool) -> bool {
+    s.split('.').all(|id| {
+        !id.is_empty() &&
+            id.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-') &&
+            !(is_pre && id.len() > 1 && id.starts_with('0') && id.bytes().all(|c| c.is_ascii_digit()))
+    })
+}
+
+/// Split `MAJOR[.MINOR[.PATCH]][-pre][+build]` into its parts, checking everything but the presence of the minor
+/// and patch versions.
+#[allow(clippy::type_complexity)]
+fn parse_parts(s: &str) -> Option<(u64, Option<u64>, Option<u64>, &str, &str)> {
+    let (s, build) = match s.split_once('+') {
+        Some((s, build)) if valid_identifiers(build, false) => (s, build),
+        Some(_) => return None,
+        None => (s, ""),
+    };
+    let (core, pre) = match s.split_once('-') {
+        Some((core, pre)) if valid_identifiers(pre, true) => (core, pre),
+        Some(_) => return None,
+        None => (s, ""),
+    };
+    let mut numbers = core.split('.');
+    let major = parse_number(numbers.next()?)?;
+    let minor = match numbers.next() {
+        Some(minor) => Some(parse_number(minor)?),
+        None => None,
+    };
+    let patch = match numbers.next() {
+        Some(patch) => Some(parse_number(patch)?),
+        None => None,
+    };
+    if numbers.next().is_some() {
+        return None;
+    }
+    Some((major, minor, patch, pre, build))
+}
+
+impl FromStr for Version {
+    type Err = SemverError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match parse_parts(s) {
+            Some((major, Some(minor), Some(patch), pre, build)) => Ok(Self {
+                major,
+                minor,
+                patch,
+                pre: pre.into(),
+                build: build.into(),
+            }),
+            _ => InvalidVersionSnafu.fail(),
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if !self.pre.is_empty() {
+            write!(f, "-{}", self.pre)?;
+        }
+        if !self.build.is_empty() {
+            write!(f, "+{}", self.build)?;
+        }
+        Ok(())
+    }
+}
+
+/// The operator of a [VersionReq].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Op {
+    /// `*`: any release
+    Any,
+    /// `=`: the given version, or any version with the given components if it is partial
+    Exact,
+    /// `^`: versions that Semantic Versioning considers compatible, changing only components to the right of the
+    /// first non-zero one
+    Caret,
+    /// `~`: patch updates, or minor updates if only the major version is given
+    Tilde,
+}
+
+/// A requirement on a version, such as `^1.2.3`, `~1.2`, `=1.2.3` or `*`. A version without an operator is a caret
+/// requirement.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VersionReq {
+    op: Op,
+    major: u64,
+    minor: Option<u64>,
+    patch: Option<u64>,
+    pre: String,
+}
+
+impl VersionReq {
+    /// A requirement that matches any release.
+    pub const ANY: VersionReq = VersionReq {
+        op: Op::Any,
+        major: 0,
+        minor: None,
+        patch: None,
+        pre: String::new(),
+    };
+
+    /// Return the operator of the requirement.
+    pub fn op(&self) -> Op {
+        self.op
+    }
+
+    /// Return `true` if the version satisfies the requirement.
+    ///
+    /// As in Cargo, a pre-release version only matches a requirement with a pre-release on the same
+    /// `MAJOR.MINOR.PATCH`, so that pre-releases are only selected on purpose.
+    pub fn matches(&self, version: &Version) -> bool {
+        if version.is_prerelease() {
+            let same_release = !self.pre.is_empty() &&
+                Some(version.minor) == self.minor &&
+                Some(version.patch) == self.patch &&
+                version.major == self.major;
+            if !same_release {
+                return false;
+            }
+        }
+        let lower = Version {
+            major: self.major,
+            minor: self.minor.unwrap_or(0),
+            patch: self.patch.unwrap_or(0),
+            pre: self.pre.clone(),
+            build: String::new(),
+        };
+        // The upper bound excludes its own pre-releases, which sort below it
+        let in_range = |(major, minor, patch): (u64, u64, u64)| {
+            let upper = Version {
+                pre: "0".into(),
+                ..Version::new(major, minor, patch)
+            };
+            version.cmp_precedence(&lower) != Ordering::Less && version.cmp_precedence(&upper) == Ordering::Less
+        };
+        let next = |n: u64| n.saturating_add(1);
+        match (self.op, self.minor, self.patch) {
+            (Op::Any, ..) => true,
+            (Op::Exact, Some(_), Some(_)) => version.cmp_precedence(&lower) == Ordering::Equal,
+            (Op::Exact, Some(minor), None) | (Op::Tilde, Some(minor), _) => in_range((self.major, next(minor), 0)),
+            (Op::Exact | Op::Tilde | Op::Caret, None, _) => in_range((next(self.major), 0, 0)),
+            (Op::Caret, Some(minor), patch) => match (self.major, minor, patch) {
+                (0, 0, Some(patch)) => in_range((0, 0, next(patch))),
+                (0, minor, _) => in_range((0, next(minor), 0)),
+                (major, ..) => in_range((next(major), 0, 0)),
+            },
+        }
+    }
+}
+
+impl FromStr for VersionReq {
+    type Err = SemverError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s == "*" {
+            return Ok(Self::ANY);
+        }
+        let (op, version) = match s.as_bytes().first() {
+            Some(b'=') => (Op::Exact, s.get(1..)),
+            Some(b'^') => (Op::Caret, s.get(1..)),
+            Some(b'~') => (Op::Tilde, s.get(1..)),
+            _ => (Op::Caret, Some(s)),
+        };
+        let version = version.unwrap_or_default().trim_start();
+        match parse_parts(version) {
+            // A pre-release may only be given on a full version, and build metadata is meaningless in a requirement
+            Some((major, minor, patch, pre, "")) if pre.is_empty() || patch.is_some() => Ok(Self {
+                op,
+                major,
+                minor,
+                patch,
+                pre: pre.into(),
+            }),
+            _ => InvalidRequirementSnafu.fail(),
+        }
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.op {
+            Op::Any => return f.write_str("*"),
+            Op::Exact => f.write_str("=")?,
+            Op::Caret => f.write_str("^")?,
+            Op::Tilde => f.write_str("~")?,
+        }
+        write!(f, "{}", self.major)?;
+        if let Some(minor) = self.minor {
+            write!(f, ".{}", minor)?;
+        }
+        if let Some(patch) = self.patch {
+            write!(f, ".{}", patch)?;
+        }
+        if !self.pre.is_empty() {
+            write!(f, "-{}", self.pre)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Version {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Version {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct VersionVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for VersionVisitor {
+            type Value = Version;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a semantic version")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse()
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+            }
+        }
+
+        deserializer.deserialize_str(VersionVisitor)
+    }
+}
+
+/// Encodes as the version string, in the same format as a borsh `String`.
+#[cfg(feature = "borsh")]
+impl borsh::BorshSerialize for Version {
+    fn serialize<W: borsh::maybestd::io::Write>(&self, writer: &mut W) -> borsh::maybestd::io::Result<()> {
+        borsh::BorshSerialize::serialize(&alloc::string::ToString::to_string(self), writer)
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl borsh::BorshDeserialize for Version {
+    fn deserialize_reader<R: borsh::maybestd::io::Read>(reader: &mut R) -> borsh::maybestd::io::Result<Self> {
+        use borsh::maybestd::io::{Error, ErrorKind};
+
+        String::deserialize_reader(reader)?
+            .parse()
+            .map_err(|_| Error::new(ErrorKind::InvalidData, "invalid semantic version"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::{string::ToString, vec::Vec};
+
+    use super::*;
+
+    fn v(s: &str) -> Version {
+        s.parse().unwrap()
+    }
+
+    fn req(s: &str) -> VersionReq {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parsing() {
+        let version = v("1.2.3-alpha.1+build.7-x");
+        assert_eq!((version.major, version.minor, version.patch), (1, 2, 3));
+        assert_eq!(version.pre, "alpha.1");
+        assert_eq!(version.build, "build.7-x");
+        assert_eq!(version.to_string(), "1.2.3-alpha.1+build.7-x");
+        assert_eq!(v("0.0.0"), Version::new(0, 0, 0));
+        assert_eq!(v("1.0.0-x-y").pre, "x-y");
+        assert_eq!(v("1.0.0+001").build, "001");
+        for bad in [
+            "",
+            "1",
+            "1.2",
+            "1.2.3.4",
+            "01.2.3",
+            "1.02.3",
+            "1.2.3-",
+            "1.2.3+",
+            "1.2.3-a..b",
+            "1.2.3-01",
+            "1.2.3-a+",
+            "1.2.3+a+b",
+            "v1.2.3",
+            " 1.2.3",
+            "1.2.3-é",
+            "-1.2.3",
+            "1.2.99999999999999999999",
+        ] {
+            assert_eq!(bad.parse::<Version>(), Err(SemverError::InvalidVersion {}), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn ordering() {
+        // The precedence example from the specification
+        let ordered = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+            "1.0.1",
+            "1.1.0",
+            "2.0.0",
+            "10.0.0",
+        ]
+        .iter()
+        .map(|s| v(s))
+        .collect::<Vec<_>>();
+        for pair in ordered.windows(2) {
+            assert!(pair[0] < pair[1], "{} < {}", pair[0], pair[1]);
+        }
+        assert_eq!(v("1.0.0+a").cmp_precedence(&v("1.0.0+b")), Ordering::Equal);
+        assert!(v("1.0.0+a") < v("1.0.0+b"));
+        assert!(v("1.0.0-2") < v("1.0.0-10"));
+    }
+
+    #[test]
+    fn caret() {
+        let cases = [
+            ("^1.2.3", &["1.2.3", "1.9.0"][..], &["1.2.2", "2.0.0", "2.0.0-rc.1"][..]),
+            ("1.2", &["1.2.0", "1.99.99"], &["1.1.9", "2.0.0"]),
+            ("^1", &["1.0.0", "1.5.0"], &["0.9.9", "2.0.0"]),
+            ("^0.2.3", &["0.2.3", "0.2.9"], &["0.2.2", "0.3.0"]),
+            ("^0.0.3", &["0.0.3"], &["0.0.4", "0.0.2"]),
+            ("^0.0", &["0.0.0", "0.0.9"], &["0.1.0"]),
+            ("^0", &["0.0.0", "0.9.9"], &["1.0.0"]),
+        ];
+        for (r, matching, not_matching) in cases {
+            let r = req(r);
+            for m in matching {
+                assert!(r.matches(&v(m)), "{} should match {}", r, m);
+            }
+            for m in not_matching {
+                assert!(!r.matches(&v(m)), "{} should not match {}", r, m);
+            }
+        }
+    }
+
+    #[test]
+    fn tilde_exact_and_any() {
+        assert!(req("~1.2.3").matches(&v("1.2.9")));
+        assert!(!req("~1.2.3").matches(&v("1.3.0")));
+        assert!(!req("~1.2.3").matches(&v("1.2.2")));
+        assert!(req("~1.2").matches(&v("1.2.0")));
+        assert!(req("~1").matches(&v("1.9.0")));
+        assert!(!req("~1").matches(&v("2.0.0")));
+        assert!(req("=1.2.3").matches(&v("1.2.3+build")));
+        assert!(!req("=1.2.3").matches(&v("1.2.4")));
+        assert!(req("=1.2").matches(&v("1.2.7")));
+        assert!(!req("=1.2").matches(&v("1.3.0")));
+        assert!(req("=1").matches(&v("1.7.0")));
+        assert!(req("*").matches(&v("0.0.1")));
+        assert!(!req("*").matches(&v("1.0.0-rc.1")));
+        assert_eq!(req(" = 1.2 ").to_string(), "=1.2");
+    }
+
+    #[test]
+    fn prereleases() {
+        let r = req("^1.2.3-rc.1");
+        assert!(r.matches(&v("1.2.3-rc.1")));
+        assert!(r.matches(&v("1.2.3-rc.2")));
+        assert!(r.matches(&v("1.2.3")));
+        assert!(r.matches(&v("1.3.0")));
+        assert!(!r.matches(&v("1.2.3-beta")));
+        assert!(!r.matches(&v("1.3.0-rc.1")));
+        assert!(req("=1.2.3-rc.1").matches(&v("1.2.3-rc.1")));
+        assert!(!req("=1.2.3-rc.1").matches(&v("1.2.3")));
+        assert_eq!(req("~1.2.3-rc.1").to_string(), "~1.2.3-rc.1");
+    }
+
+    #[test]
+    fn invalid_requirements() {
+        for bad in [
+            "",
+            "^",
+            "1.2-rc",
+            ">=1.2.3",
+            "^1.2.3+build",
+            "1.x",
+            "^^1",
+            "1.2.3, 1.4",
+            "*1",
+        ] {
+            assert_eq!(
+                bad.parse::<VersionReq>(),
+                Err(SemverError::InvalidRequirement {}),
+                "{}",
+                bad
+            );
+        }
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn serialization() {
+        let version = v("1.2.3-rc.1+b");
+        let json = serde_json::to_string(&version).unwrap();
+        assert_eq!(json, "\"1.2.3-rc.1+b\"");
+        assert_eq!(serde_json::from_str::<Version>(&json).unwrap(), version);
+        assert!(serde_json::from_str::<Version>("\"1.2\"").is_err());
+    }
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn borsh() {
+        use borsh::BorshDeserialize;
+
+        let version = v("1.2.3-rc.1+b");
+        let bytes = borsh::to_vec(&version).unwrap();
+        assert_eq!(bytes, borsh::to_vec("1.2.3-rc.1+b").unwrap());
+        assert_eq!(Version::try_from_slice(&bytes).unwrap(), version);
+        assert!(Version::try_from_slice(&borsh::to_vec("1.2").unwrap()).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Serde adapters for `u128` and [U256](crate::endian::U256) with an explicit byte order.
+//!
+//! Binary formats store the value as fixed-size bytes in the adapter's byte order. Human-readable formats, which may
+//! not be able to hold the number, store a `u128` as a decimal string and a `U256` as 64 lowercase hex digits, most
+//! significant first. Only the canonical form of each is accepted when deserializing.
+//!
+//! ```edition2018
+//! # use serde::{Deserialize, Serialize};
+//! # use tari_utilities::endian::U256;
+//! #[derive(Serialize, Deserialize)]
+//! struct Block {
+//!     #[serde(with = "tari_utilities::serde::endian::u128_le")]
+//!     amount: u128,
+//!     #[serde(with = "tari_utilities::serde::endian::u256_be")]
+//!     target: U256,
+//! }
+//! ```
+
+use alloc::string::ToString;
+use core::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{Error, Visitor},
+    Deserializer,
+    Serializer,
+};
+
+use crate::endian::EndianError;
+
+/// A value with a fixed-size encoding in each byte order and a canonical text form.
+trait Codec: Sized {
+    const EXPECTING: &'static str;
+    type Bytes: AsRef<[u8]>;
+
+    fn to_bytes(&self, little_endian: bool) -> Self::Bytes;
+    fn from_slice(bytes: &[u8], little_endian: bool) -> Result<Self, EndianError>;
+    fn serialize_text<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error>;
+    fn from_text(s: &str) -> Result<Self, EndianError>;
+}
+
+impl Codec for u128 {
+    type Bytes = [u8; 16];
+
+    const EXPECTING: &'static str = "16 bytes or a canonical decimal string";
+
+    fn to_bytes(&self, little_endian: bool) -> Self::Bytes {
+        if little_endian {
+            self.to_le_bytes()
+        } else {
+            self.to_be_bytes()
+        }
+    }
+
+    fn from_slice(bytes: &[u8], little_endian: bool) -> Result<Self, EndianError> {
+        if little_endian {
+            crate::endian::u128_from_le_slice(bytes)
+        } else {
+            crate::endian::u128_from_be_slice(bytes)
+        }
+    }
+
+    fn serialize_text<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        ser.collect_str(self)
+    }
+
+    fn from_text(s: &str) -> Result<Self, EndianError> {
+        crate::endian::u128_from_canonical_str(s)
+    }
+}
+
+impl Codec for crate::endian::U256 {
+    type Bytes = [u8; 32];
+
+    const EXPECTING: &'static str = "32 bytes or 64 lowercase hex digits";
+
+    fn to_bytes(&self, little_endian: bool) -> Self::Bytes {
+        if little_endian {
+            self.to_le_bytes()
+        } else {
+            self.to_be_bytes()
+        }
+    }
+
+    fn from_slice(bytes: &[u8], little_endian: bool) -> Result<Self, EndianError> {
+        if little_endian {
+            Self::from_le_slice(bytes)
+        } else {
+            Self::from_be_slice(bytes)
+        }
+    }
+
+    fn serialize_text<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        ser.collect_str(self)
+    }
+
+    fn from_text(s: &str) -> Result<Self, EndianError> {
+        Self::from_canonical_hex(s)
+    }
+}
+
+fn serialize<T: Codec, S: Serializer>(value: &T, ser: S, little_endian: bool) -> Result<S::Ok, S::Error> {
+    if ser.is_human_readable() {
+        value.serialize_text(ser)
+    } else {
+        ser.serialize_bytes(value.to_bytes(little_endian).as_ref())
+    }
+}
+
+fn deserialize<'de, T: Codec, D: Deserializer<'de>>(de: D, little_endian: bool) -> Result<T, D::Error> {
+    let visitor = CodecVisitor {
+        little_endian,
+        _target: PhantomData,
+    };
+    if de.is_human_readable() {
+        de.deserialize_str(visitor)
+    } else {
+        de.deserialize_bytes(visitor)
+    }
+}
+
+struct CodecVisitor<T> {
+    little_endian: bool,
+    _target: PhantomData<T>,
+}
+
+impl<'de, T: Codec> Visitor<'de> for CodecVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(T::EXPECTING)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where E: Error {
+        T::from_text(v).map_err(|e| E::custom(e.to_string()))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where E: Error {
+        T::from_slice(v, self.little_endian).map_err(|e| E::custom(e.to_string()))
+    }
+}
+
+macro_rules! adapter {
+    ($(#[$doc:meta])* $name:ident, $ty:ty, $little_endian:expr) => {
+        $(#[$doc])*
+        pub mod $name {
+            use serde::{Deserializer, Serializer};
+
+            /// Serialize the value.
+            pub fn serialize<S: Serializer>(value: &$ty, ser: S) -> Result<S::Ok, S::Error> {
+                super::serialize(value, ser, $little_endian)
+            }
+
+            /// Deserialize the value, accepting only its canonical form.
+            pub fn deserialize<'de, D: Deserializer<'de>>(de: D) -> Result<$ty, D::Error> {
+                super::deserialize(de, $little_endian)
+            }
+        }
+    };
+}
+
+adapter!(
+    /// Serialize a `u128` as 16 little-endian bytes, or a decimal string.
+    u128_le, u128, true
+);
+adapter!(
+    /// Serialize a `u128` as 16 big-endian bytes, or a decimal string.
+    u128_be, u128, false
+);
+adapter!(
+    /// Serialize a [U256](crate::endian::U256) as 32 little-endian bytes, or a hex string.
+    u256_le, crate::endian::U256, true
+);
+adapter!(
+    /// Serialize a [U256](crate::endian::U256) as 32 big-endian bytes, or a hex string.
+    u256_be, crate::endian::U256, false
+);
+
+#[cfg(all(test, feature = "serialize"))]
+mod test {
+    use alloc::{format, vec::Vec};
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::endian::U256;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+    struct Wide {
+        #[serde(with = "super::u128_le")]
+        le: u128,
+        #[serde(with = "super::u128_be")]
+        be: u128,
+        #[serde(with = "super::u256_le")]
+        target_le: U256,
+        #[serde(with = "super::u256_be")]
+        target_be: U256,
+    }
+
+    fn sample() -> Wide {
+        Wide {
+            le: u128::MAX,
+            be: 0x0102,
+            target_le: U256::from(0x0102u128),
+            target_be: U256::MAX,
+        }
+    }
+
+    #[test]
+    fn human_readable() {
+        let json = serde_json::to_string(&sample()).unwrap();
+        assert_eq!(
+            json,
+            format!(
+                r#"{{"le":"{}","be":"258","target_le":"{:0>64}","target_be":"{}"}}"#,
+                u128::MAX,
+                "0102",
+                "f".repeat(64)
+            )
+        );
+        assert_eq!(serde_json::from_str::<Wide>(&json).unwrap(), sample());
+        let non_canonical = json.replace("\"258\"", "\"0258\"");
+        assert!(serde_json::from_str::<Wide>(&non_canonical).is_err());
+        let uppercase = json.replace(&"f".repeat(64), &"F".repeat(64));
+        assert!(serde_json::from_str::<Wide>(&uppercase).is_err());
+    }
+
+    #[test]
+    fn binary() {
+        let bytes = bincode::serialize(&sample()).unwrap();
+        let fields = [(0, 16), (24, 16), (48, 32), (88, 32)];
+        let field = |(start, len): (usize, usize)| {
+            assert_eq!(bytes[start..start + 8], (len as u64).to_le_bytes());
+            bytes[start + 8..start + 8 + len].to_vec()
+        };
+        assert_eq!(field(fields[0]), u128::MAX.to_le_bytes().to_vec());
+        assert_eq!(field(fields[1]), 0x0102u128.to_be_bytes().to_vec());
+        let mut target_le = alloc::vec![0u8; 32];
+        target_le[..2].copy_from_slice(&[2, 1]);
+        assert_eq!(field(fields[2]), target_le);
+        assert_eq!(field(fields[3]), [0xff; 32].to_vec());
+        assert_eq!(bincode::deserialize::<Wide>(&bytes).unwrap(), sample());
+
+        // A field of the wrong length is rejected
+        let mut short: Vec<u8> = bytes.clone();
+        short[0] = 15;
+        short.remove(8);
+        assert!(bincode::deserialize::<Wide>(&short).is_err());
+    }
+}
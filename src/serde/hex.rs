@@ -23,14 +23,30 @@
 //! A module for smart bytes serialization.
 //!
 //! It stores bytes as hex for human readable formats and
-//! uses bytes for binary formats.
+//! uses bytes for binary formats. Use it on any [`ByteArray`] field, such as `Vec<u8>` or `[u8; N]`, and the
+//! [option] submodule on `Option`s of them. [hex_upper](super::hex_upper) writes uppercase digits instead.
+//!
+//! ```edition2018
+//! # use serde::{Deserialize, Serialize};
+//! #[derive(Serialize, Deserialize)]
+//! struct Transfer {
+//!     #[serde(with = "tari_utilities::serde::hex")]
+//!     payload: Vec<u8>,
+//!     #[serde(with = "tari_utilities::serde::hex")]
+//!     key: [u8; 32],
+//!     #[serde(with = "tari_utilities::serde::hex::option")]
+//!     memo: Option<Vec<u8>>,
+//! }
+//! ```
 
 use alloc::string::{String, ToString};
 use core::{fmt, marker::PhantomData};
 
 use serde::{
     de::{Error, Visitor},
+    Deserialize,
     Deserializer,
+    Serialize,
     Serializer,
 };
 
@@ -45,10 +61,20 @@ where
     S: Serializer,
     T: ByteArray,
 {
-    if ser.is_human_readable() {
-        ser.serialize_str(&data.to_hex())
-    } else {
+    serialize_with_case(data, ser, false)
+}
+
+pub(super) fn serialize_with_case<S, T>(data: &T, ser: S, upper: bool) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: ByteArray,
+{
+    if !ser.is_human_readable() {
         ser.serialize_bytes(data.as_bytes())
+    } else if upper {
+        ser.serialize_str(&data.to_hex_upper())
+    } else {
+        ser.serialize_str(&data.to_hex())
     }
 }
 
@@ -66,6 +92,59 @@ where
     }
 }
 
+/// The same encoding for `Option` fields, with `None` stored as the format's null.
+pub mod option {
+    use super::*;
+
+    /// Serializes an optional [`ByteArray`] to a hex string or a binary array.
+    pub fn serialize<S, T>(data: &Option<T>, ser: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: ByteArray,
+    {
+        serialize_option_with_case(data, ser, false)
+    }
+
+    /// Deserializes an optional [`ByteArray`] from a hex string or a binary array.
+    pub fn deserialize<'de, D, T>(de: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: ByteArray,
+    {
+        Ok(Option::<HexBytes<T>>::deserialize(de)?.map(|bytes| bytes.0))
+    }
+}
+
+pub(super) fn serialize_option_with_case<S, T>(data: &Option<T>, ser: S, upper: bool) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: ByteArray,
+{
+    match data {
+        Some(data) => ser.serialize_some(&HexRef { data, upper }),
+        None => ser.serialize_none(),
+    }
+}
+
+struct HexRef<'a, T> {
+    data: &'a T,
+    upper: bool,
+}
+
+impl<T: ByteArray> Serialize for HexRef<'_, T> {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        serialize_with_case(self.data, ser, self.upper)
+    }
+}
+
+struct HexBytes<T>(T);
+
+impl<'de, T: ByteArray> Deserialize<'de> for HexBytes<T> {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        deserialize(de).map(HexBytes)
+    }
+}
+
 struct HexVisitor<T> {
     _target: PhantomData<T>,
 }
@@ -107,7 +186,7 @@ where T: ByteArray
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "serialize"))]
 mod tests {
     use alloc::vec::Vec;
     use std::io::Write;
@@ -117,6 +196,14 @@ mod tests {
     #[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
     struct HexOrBytes(#[serde(with = "super")] [u8; 4]);
 
+    #[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+    struct Fields {
+        #[serde(with = "super")]
+        payload: Vec<u8>,
+        #[serde(with = "super::option")]
+        memo: Option<Vec<u8>>,
+    }
+
     #[test]
     fn check_serde_hex_human_readable() {
         let hex_or_bytes = HexOrBytes([1, 2, 3, 255]);
@@ -136,4 +223,32 @@ mod tests {
         let restored: HexOrBytes = bincode::deserialize(&expected).unwrap();
         assert_eq!(hex_or_bytes, restored);
     }
+
+    #[test]
+    fn check_serde_hex_vec_and_option() {
+        let fields = Fields {
+            payload: vec![0xde, 0xad],
+            memo: Some(vec![0xbe, 0xef]),
+        };
+        let json = serde_json::to_string(&fields).unwrap();
+        assert_eq!(json, r#"{"payload":"dead","memo":"beef"}"#);
+        assert_eq!(serde_json::from_str::<Fields>(&json).unwrap(), fields);
+        assert_eq!(
+            bincode::deserialize::<Fields>(&bincode::serialize(&fields).unwrap()).unwrap(),
+            fields
+        );
+
+        let fields = Fields {
+            payload: Vec::new(),
+            memo: None,
+        };
+        let json = serde_json::to_string(&fields).unwrap();
+        assert_eq!(json, r#"{"payload":"","memo":null}"#);
+        assert_eq!(serde_json::from_str::<Fields>(&json).unwrap(), fields);
+        assert_eq!(
+            bincode::deserialize::<Fields>(&bincode::serialize(&fields).unwrap()).unwrap(),
+            fields
+        );
+        assert!(serde_json::from_str::<Fields>(r#"{"payload":"","memo":"xy"}"#).is_err());
+    }
 }
@@ -0,0 +1,121 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Like [hex](super::hex), but human readable formats store uppercase hex digits. Deserializing accepts digits in
+//! either case.
+//!
+//! ```edition2018
+//! # use serde::{Deserialize, Serialize};
+//! #[derive(Serialize, Deserialize)]
+//! struct Registers {
+//!     #[serde(with = "tari_utilities::serde::hex_upper")]
+//!     status: [u8; 2],
+//!     #[serde(with = "tari_utilities::serde::hex_upper::option")]
+//!     fault: Option<Vec<u8>>,
+//! }
+//! ```
+
+use serde::{Deserializer, Serializer};
+
+use crate::byte_array::ByteArray;
+
+/// Serializes a [`ByteArray`] to an uppercase hex string or a binary array.
+pub fn serialize<S, T>(data: &T, ser: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: ByteArray,
+{
+    super::hex::serialize_with_case(data, ser, true)
+}
+
+/// Deserializes a [`ByteArray`] from a hex string in either case or a binary array.
+pub fn deserialize<'de, D, T>(de: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: ByteArray,
+{
+    super::hex::deserialize(de)
+}
+
+/// The same encoding for `Option` fields, with `None` stored as the format's null.
+pub mod option {
+    use super::*;
+
+    /// Serializes an optional [`ByteArray`] to an uppercase hex string or a binary array.
+    pub fn serialize<S, T>(data: &Option<T>, ser: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: ByteArray,
+    {
+        super::super::hex::serialize_option_with_case(data, ser, true)
+    }
+
+    /// Deserializes an optional [`ByteArray`] from a hex string in either case or a binary array.
+    pub fn deserialize<'de, D, T>(de: D) -> Result<Option<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: ByteArray,
+    {
+        super::super::hex::option::deserialize(de)
+    }
+}
+
+#[cfg(all(test, feature = "serialize"))]
+mod tests {
+    use alloc::vec::Vec;
+
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq, Eq)]
+    struct Registers {
+        #[serde(with = "super")]
+        status: [u8; 2],
+        #[serde(with = "super::option")]
+        fault: Option<Vec<u8>>,
+    }
+
+    #[test]
+    fn round_trips_uppercase() {
+        let registers = Registers {
+            status: [0xab, 0x01],
+            fault: Some(vec![0xcd]),
+        };
+        let json = serde_json::to_string(&registers).unwrap();
+        assert_eq!(json, r#"{"status":"AB01","fault":"CD"}"#);
+        assert_eq!(serde_json::from_str::<Registers>(&json).unwrap(), registers);
+        let lower = r#"{"status":"ab01","fault":"cd"}"#;
+        assert_eq!(serde_json::from_str::<Registers>(lower).unwrap(), registers);
+        assert_eq!(
+            bincode::deserialize::<Registers>(&bincode::serialize(&registers).unwrap()).unwrap(),
+            registers
+        );
+
+        let registers = Registers {
+            status: [0, 0],
+            fault: None,
+        };
+        let json = serde_json::to_string(&registers).unwrap();
+        assert_eq!(json, r#"{"status":"0000","fault":null}"#);
+        assert_eq!(serde_json::from_str::<Registers>(&json).unwrap(), registers);
+        assert!(serde_json::from_str::<Registers>(r#"{"status":"AB","fault":null}"#).is_err());
+    }
+}
@@ -22,4 +22,6 @@
 
 //! A module with serialization utilities.
 
+pub mod endian;
 pub mod hex;
+pub mod hex_upper;
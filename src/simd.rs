@@ -0,0 +1,896 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! SIMD-accelerated hex and base64 encoding/decoding, CRC32 and constant-time comparison.
+//!
+//! The fastest implementation supported by the running CPU is selected at runtime: AVX2 with PCLMULQDQ on `x86_64`
+//! and NEON with the CRC32 instructions on `aarch64`, with a portable scalar fallback. All backends produce identical
+//! results, including the errors for invalid input.
+
+use alloc::{string::String, vec::Vec};
+
+use crate::{
+    base64::{Base64CodecError, Base64Variant},
+    hex::HexError,
+};
+
+/// The implementation used by the functions in this module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// Portable implementation.
+    Scalar,
+    /// x86_64 AVX2 implementation, using PCLMULQDQ for CRC32.
+    Avx2,
+    /// aarch64 NEON implementation, using the CRC32 instructions for CRC32.
+    Neon,
+}
+
+/// Return the fastest backend supported by the running CPU.
+pub fn backend() -> Backend {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if std::is_x86_feature_detected!("avx2") &&
+            std::is_x86_feature_detected!("pclmulqdq") &&
+            std::is_x86_feature_detected!("sse4.1")
+        {
+            return Backend::Avx2;
+        }
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        if std::arch::is_aarch64_feature_detected!("neon") && std::arch::is_aarch64_feature_detected!("crc") {
+            return Backend::Neon;
+        }
+    }
+    Backend::Scalar
+}
+
+/// Encode the provided bytes into a lowercase hex string.
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut out = vec![0u8; bytes.len().saturating_mul(2)];
+    encode(backend(), bytes, &mut out);
+    // The output only contains ASCII hex digits, which map to the same `char`s
+    out.into_iter().map(char::from).collect()
+}
+
+/// Decode a hex string into bytes. Leading and trailing whitespace and a `0x` prefix are ignored, and both lowercase
+/// and uppercase digits are accepted.
+pub fn from_hex(hex_str: &str) -> Result<Vec<u8>, HexError> {
+    let (offset, digits) = crate::hex::hex_digits(hex_str)?;
+    decode_digits(digits, offset)
+}
+
+/// Decode pairs of hex digits, without whitespace or a prefix, into bytes, reporting invalid characters at their
+/// position plus `offset`.
+pub(crate) fn decode_digits(digits: &[u8], offset: usize) -> Result<Vec<u8>, HexError> {
+    if digits.len() % 2 == 1 {
+        return Err(crate::hex::invalid_character(digits, offset));
+    }
+    let mut out = vec![0u8; digits.len() / 2];
+    if decode(backend(), digits, &mut out) {
+        Ok(out)
+    } else {
+        Err(crate::hex::invalid_character(digits, offset))
+    }
+}
+
+/// Compare two byte slices in constant time with respect to their contents. Slices of different lengths are never
+/// equal.
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    match backend() {
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2 => unsafe { avx2::ct_eq(a, b) },
+        #[cfg(target_arch = "aarch64")]
+        Backend::Neon => unsafe { neon::ct_eq(a, b) },
+        _ => scalar::ct_eq(a, b),
+    }
+}
+
+/// Encode bytes as base64 in the given variant, with the same output as [to_base64](crate::base64::to_base64).
+pub fn to_base64(bytes: &[u8], variant: Base64Variant) -> String {
+    let mut out = vec![0u8; crate::base64::encoded_len(bytes.len(), variant)];
+    let consumed = base64_encode(backend(), bytes, &mut out, variant);
+    let written = (consumed / 3).saturating_mul(4);
+    if let (Some(rest), Some(out)) = (bytes.get(consumed..), out.get_mut(written..)) {
+        // The output was sized for the whole input, so the rest always fits
+        let _written = crate::base64::encode_into(rest, out, variant);
+    }
+    // The output only contains ASCII base64 symbols, which map to the same `char`s
+    out.into_iter().map(char::from).collect()
+}
+
+/// Decode a base64 string in the given variant into bytes, with the same result and errors as
+/// [from_base64](crate::base64::from_base64).
+pub fn from_base64(b64: &str, variant: Base64Variant) -> Result<Vec<u8>, Base64CodecError> {
+    let symbols = b64.as_bytes();
+    // Blocks of 32 symbols are decoded here, leaving the last group and any padding to the scalar decoder
+    let body_len = (symbols.len().saturating_sub(1) / 32).saturating_mul(32);
+    let (body, tail) = symbols.split_at(body_len);
+    let body_bytes = (body_len / 4).saturating_mul(3);
+    let mut out = vec![0u8; body_bytes.saturating_add(decoded_capacity(tail.len()))];
+    if let Some((body_out, tail_out)) = out.get_mut(..).map(|out| out.split_at_mut(body_bytes)) {
+        if base64_decode(backend(), body, body_out, variant) {
+            if let Ok(len) = crate::base64::decode_into(tail, tail_out, variant) {
+                out.truncate(body_bytes.saturating_add(len));
+                return Ok(out);
+            }
+        }
+    }
+    // Decode everything again to report the same error as the scalar decoder
+    let mut out = vec![0u8; decoded_capacity(symbols.len())];
+    let len = crate::base64::decode_into(symbols, &mut out, variant)?;
+    out.truncate(len);
+    Ok(out)
+}
+
+/// An upper bound on the number of bytes `len` base64 symbols decode to.
+fn decoded_capacity(len: usize) -> usize {
+    (len / 4).saturating_add(1).saturating_mul(3)
+}
+
+/// Compute the CRC32 (IEEE) checksum of the data, the same as [crc32](crate::checksum::crc32).
+pub fn crc32(data: &[u8]) -> u32 {
+    !crc32_update(u32::MAX, data)
+}
+
+/// Feed `data` through the CRC32 register `state`, without the initial and final inversions.
+pub(crate) fn crc32_update(state: u32, data: &[u8]) -> u32 {
+    match backend() {
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2 => unsafe { avx2::crc32_update(state, data) },
+        #[cfg(target_arch = "aarch64")]
+        Backend::Neon => unsafe { neon::crc32_update(state, data) },
+        _ => scalar::crc32_update(state, data),
+    }
+}
+
+/// Encode `bytes` into `out`, which should be exactly twice as long. Excess input or output is ignored.
+fn encode(backend: Backend, bytes: &[u8], out: &mut [u8]) {
+    match backend {
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2 => unsafe { avx2::hex_encode(bytes, out) },
+        #[cfg(target_arch = "aarch64")]
+        Backend::Neon => unsafe { neon::hex_encode(bytes, out) },
+        _ => scalar::hex_encode(bytes, out),
+    }
+}
+
+/// Decode `hex` into `out`, which should be exactly half as long. Returns false if an invalid character was found.
+fn decode(backend: Backend, hex: &[u8], out: &mut [u8]) -> bool {
+    match backend {
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2 => unsafe { avx2::hex_decode(hex, out) },
+        #[cfg(target_arch = "aarch64")]
+        Backend::Neon => unsafe { neon::hex_decode(hex, out) },
+        _ => scalar::hex_decode(hex, out),
+    }
+}
+
+/// Encode whole blocks from the start of `bytes` as base64 into `out`, returning the number of bytes encoded, which
+/// is a multiple of 3. The rest is left to the caller.
+fn base64_encode(backend: Backend, bytes: &[u8], out: &mut [u8], variant: Base64Variant) -> usize {
+    match backend {
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2 => unsafe { avx2::base64_encode(bytes, out, variant.alphabet()) },
+        #[cfg(target_arch = "aarch64")]
+        Backend::Neon => unsafe { neon::base64_encode(bytes, out, variant.alphabet()) },
+        _ => 0,
+    }
+}
+
+/// Decode base64 symbols without padding, whose length is a multiple of 4, into `out`, which should be exactly three
+/// quarters as long. Returns false if an invalid symbol was found.
+fn base64_decode(backend: Backend, b64: &[u8], out: &mut [u8], variant: Base64Variant) -> bool {
+    match backend {
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2 => unsafe { avx2::base64_decode(b64, out, variant) },
+        #[cfg(target_arch = "aarch64")]
+        Backend::Neon => unsafe { neon::base64_decode(b64, out, variant) },
+        _ => scalar::base64_decode(b64, out, variant),
+    }
+}
+
+mod scalar {
+    use crate::hex::hex_char;
+
+    fn nibble(c: u8) -> Option<u8> {
+        match c {
+            b'0'..=b'9' => Some(c.wrapping_sub(b'0')),
+            b'a'..=b'f' => Some(c.wrapping_sub(b'a').wrapping_add(10)),
+            b'A'..=b'F' => Some(c.wrapping_sub(b'A').wrapping_add(10)),
+            _ => None,
+        }
+    }
+
+    fn digit(nibble: u8) -> u8 {
+        hex_char(nibble)
+    }
+
+    pub fn hex_encode(bytes: &[u8], out: &mut [u8]) {
+        for (byte, pair) in bytes.iter().zip(out.chunks_exact_mut(2)) {
+            if let [hi, lo] = pair {
+                *hi = digit(byte >> 4);
+                *lo = digit(*byte);
+            }
+        }
+    }
+
+    pub fn hex_decode(hex: &[u8], out: &mut [u8]) -> bool {
+        for (pair, byte) in hex.chunks_exact(2).zip(out.iter_mut()) {
+            match pair {
+                [hi, lo] => match (nibble(*hi), nibble(*lo)) {
+                    (Some(hi), Some(lo)) => *byte = (hi << 4) | lo,
+                    _ => return false,
+                },
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+        crate::ct::eq(a, b).into()
+    }
+
+    pub fn base64_decode(b64: &[u8], out: &mut [u8], variant: super::Base64Variant) -> bool {
+        crate::base64::decode_into(b64, out, variant).is_ok()
+    }
+
+    pub fn crc32_update(state: u32, data: &[u8]) -> u32 {
+        crate::checksum::crc32_update(state, data)
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use core::arch::x86_64::*;
+
+    use super::{scalar, Base64Variant};
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn hex_encode(bytes: &[u8], out: &mut [u8]) {
+        let lut = _mm256_loadu_si256(b"0123456789abcdef0123456789abcdef".as_ptr().cast());
+        let mask = _mm256_set1_epi8(0x0f);
+        let mut src = bytes.chunks_exact(32);
+        let mut dst = out.chunks_exact_mut(64);
+        for (src, dst) in (&mut src).zip(&mut dst) {
+            let v = _mm256_loadu_si256(src.as_ptr().cast());
+            let hi = _mm256_shuffle_epi8(lut, _mm256_and_si256(_mm256_srli_epi16(v, 4), mask));
+            let lo = _mm256_shuffle_epi8(lut, _mm256_and_si256(v, mask));
+            // Interleaving works within 128-bit lanes, so the lanes are reordered afterwards
+            let a = _mm256_unpacklo_epi8(hi, lo);
+            let b = _mm256_unpackhi_epi8(hi, lo);
+            _mm256_storeu_si256(dst.as_mut_ptr().cast(), _mm256_permute2x128_si256(a, b, 0x20));
+            _mm256_storeu_si256(dst.as_mut_ptr().add(32).cast(), _mm256_permute2x128_si256(a, b, 0x31));
+        }
+        scalar::hex_encode(src.remainder(), dst.into_remainder());
+    }
+
+    /// Convert 32 hex characters to nibbles, returning `None` if any character is invalid.
+    #[target_feature(enable = "avx2")]
+    unsafe fn nibbles(c: __m256i) -> Option<__m256i> {
+        // Unsigned `x <= limit` is computed as `max(x, limit) == limit`
+        let digit = _mm256_sub_epi8(c, _mm256_set1_epi8(b'0' as i8));
+        let is_digit = _mm256_cmpeq_epi8(_mm256_max_epu8(digit, _mm256_set1_epi8(9)), _mm256_set1_epi8(9));
+        let alpha = _mm256_sub_epi8(_mm256_or_si256(c, _mm256_set1_epi8(0x20)), _mm256_set1_epi8(b'a' as i8));
+        let is_alpha = _mm256_cmpeq_epi8(_mm256_max_epu8(alpha, _mm256_set1_epi8(5)), _mm256_set1_epi8(5));
+        if _mm256_movemask_epi8(_mm256_or_si256(is_digit, is_alpha)) != -1 {
+            return None;
+        }
+        let alpha = _mm256_add_epi8(alpha, _mm256_set1_epi8(10));
+        Some(_mm256_blendv_epi8(alpha, digit, is_digit))
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn hex_decode(hex: &[u8], out: &mut [u8]) -> bool {
+        // Each pair of nibbles `hi, lo` becomes the 16-bit value `hi * 16 + lo`
+        let weights = _mm256_set1_epi16(0x0110);
+        let mut src = hex.chunks_exact(64);
+        let mut dst = out.chunks_exact_mut(32);
+        for (src, dst) in (&mut src).zip(&mut dst) {
+            let n0 = nibbles(_mm256_loadu_si256(src.as_ptr().cast()));
+            let n1 = nibbles(_mm256_loadu_si256(src.as_ptr().add(32).cast()));
+            let (n0, n1) = match (n0, n1) {
+                (Some(n0), Some(n1)) => (n0, n1),
+                _ => return false,
+            };
+            let packed = _mm256_packus_epi16(_mm256_maddubs_epi16(n0, weights), _mm256_maddubs_epi16(n1, weights));
+            let bytes = _mm256_permute4x64_epi64(packed, 0b11_01_10_00);
+            _mm256_storeu_si256(dst.as_mut_ptr().cast(), bytes);
+        }
+        scalar::hex_decode(src.remainder(), dst.into_remainder())
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+        let mut acc = _mm256_setzero_si256();
+        let mut a = a.chunks_exact(32);
+        let mut b = b.chunks_exact(32);
+        for (x, y) in (&mut a).zip(&mut b) {
+            let x = _mm256_loadu_si256(x.as_ptr().cast());
+            let y = _mm256_loadu_si256(y.as_ptr().cast());
+            acc = _mm256_or_si256(acc, _mm256_xor_si256(x, y));
+        }
+        let vector_eq = u8::from(_mm256_testz_si256(acc, acc) == 1);
+        let tail_eq = u8::from(scalar::ct_eq(a.remainder(), b.remainder()));
+        vector_eq & tail_eq == 1
+    }
+
+    /// Broadcast a 16-byte table to both 128-bit lanes, for `_mm256_shuffle_epi8`.
+    #[target_feature(enable = "avx2")]
+    unsafe fn lanes(table: &[u8; 16]) -> __m256i {
+        _mm256_broadcastsi128_si256(_mm_loadu_si128(table.as_ptr().cast()))
+    }
+
+    /// Return a mask of the bytes that are at most `limit`, as unsigned values.
+    #[target_feature(enable = "avx2")]
+    unsafe fn at_most(v: __m256i, limit: u8) -> __m256i {
+        let limit = _mm256_set1_epi8(limit as i8);
+        _mm256_cmpeq_epi8(_mm256_max_epu8(v, limit), limit)
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn base64_encode(bytes: &[u8], out: &mut [u8], alphabet: &[u8; 64]) -> usize {
+        // Each lane encodes 12 bytes, with each group of 3 bytes spread over 4 as `b1 b0 b2 b1`
+        let spread = lanes(&[1, 0, 2, 1, 4, 3, 5, 4, 7, 6, 8, 7, 10, 9, 11, 10]);
+        // The difference between a symbol and its sextet for each range of the alphabet, indexed as computed below
+        let offsets = lanes(&[
+            b'a'.wrapping_sub(26),
+            b'0'.wrapping_sub(52),
+            b'0'.wrapping_sub(52),
+            b'0'.wrapping_sub(52),
+            b'0'.wrapping_sub(52),
+            b'0'.wrapping_sub(52),
+            b'0'.wrapping_sub(52),
+            b'0'.wrapping_sub(52),
+            b'0'.wrapping_sub(52),
+            b'0'.wrapping_sub(52),
+            b'0'.wrapping_sub(52),
+            alphabet[62].wrapping_sub(62),
+            alphabet[63].wrapping_sub(63),
+            b'A',
+            0,
+            0,
+        ]);
+        // Each block encodes 24 bytes but reads 28, so the last 4 bytes are always left for the caller
+        let blocks = (bytes.len().saturating_sub(4) / 24).min(out.len() / 32);
+        for (block, dst) in (0..blocks).zip(out.chunks_exact_mut(32)) {
+            let src = bytes.as_ptr().add(block.saturating_mul(24));
+            let v = _mm256_set_m128i(_mm_loadu_si128(src.add(12).cast()), _mm_loadu_si128(src.cast()));
+            let v = _mm256_shuffle_epi8(v, spread);
+            // Move the four sextets of each group into their own bytes
+            let hi = _mm256_mulhi_epu16(
+                _mm256_and_si256(v, _mm256_set1_epi32(0x0fc0_fc00)),
+                _mm256_set1_epi32(0x0400_0040),
+            );
+            let lo = _mm256_mullo_epi16(
+                _mm256_and_si256(v, _mm256_set1_epi32(0x003f_03f0)),
+                _mm256_set1_epi32(0x0100_0010),
+            );
+            let sextets = _mm256_or_si256(hi, lo);
+            // Index the offsets with 13 for 0-25, 0 for 26-51, 1-10 for 52-61, 11 for 62 and 12 for 63
+            let index = _mm256_subs_epu8(sextets, _mm256_set1_epi8(51));
+            let upper = _mm256_cmpgt_epi8(_mm256_set1_epi8(26), sextets);
+            let index = _mm256_or_si256(index, _mm256_and_si256(upper, _mm256_set1_epi8(13)));
+            let symbols = _mm256_add_epi8(sextets, _mm256_shuffle_epi8(offsets, index));
+            _mm256_storeu_si256(dst.as_mut_ptr().cast(), symbols);
+        }
+        blocks.saturating_mul(24)
+    }
+
+    /// Convert 32 base64 symbols to sextets, returning `None` if any symbol is invalid.
+    #[target_feature(enable = "avx2")]
+    unsafe fn sextets(c: __m256i, alphabet: &[u8; 64]) -> Option<__m256i> {
+        let upper = _mm256_sub_epi8(c, _mm256_set1_epi8(b'A' as i8));
+        let is_upper = at_most(upper, 25);
+        let lower = _mm256_sub_epi8(c, _mm256_set1_epi8(b'a' as i8));
+        let is_lower = at_most(lower, 25);
+        let digit = _mm256_sub_epi8(c, _mm256_set1_epi8(b'0' as i8));
+        let is_digit = at_most(digit, 9);
+        let is_62 = _mm256_cmpeq_epi8(c, _mm256_set1_epi8(alphabet[62] as i8));
+        let is_63 = _mm256_cmpeq_epi8(c, _mm256_set1_epi8(alphabet[63] as i8));
+        let valid = _mm256_or_si256(
+            _mm256_or_si256(is_upper, is_lower),
+            _mm256_or_si256(is_digit, _mm256_or_si256(is_62, is_63)),
+        );
+        if _mm256_movemask_epi8(valid) != -1 {
+            return None;
+        }
+        let letters = _mm256_or_si256(
+            _mm256_and_si256(is_upper, upper),
+            _mm256_and_si256(is_lower, _mm256_add_epi8(lower, _mm256_set1_epi8(26))),
+        );
+        let others = _mm256_or_si256(
+            _mm256_and_si256(is_digit, _mm256_add_epi8(digit, _mm256_set1_epi8(52))),
+            _mm256_or_si256(
+                _mm256_and_si256(is_62, _mm256_set1_epi8(62)),
+                _mm256_and_si256(is_63, _mm256_set1_epi8(63)),
+            ),
+        );
+        Some(_mm256_or_si256(letters, others))
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn base64_decode(b64: &[u8], out: &mut [u8], variant: Base64Variant) -> bool {
+        let alphabet = variant.alphabet();
+        // Each lane packs 16 sextets into 12 bytes at its start
+        let gather = lanes(&[2, 1, 0, 6, 5, 4, 10, 9, 8, 14, 13, 12, 0x80, 0x80, 0x80, 0x80]);
+        let mut src = b64.chunks_exact(32);
+        let mut dst = out.chunks_exact_mut(24);
+        for (src, dst) in (&mut src).zip(&mut dst) {
+            let Some(v) = sextets(_mm256_loadu_si256(src.as_ptr().cast()), alphabet) else {
+                return false;
+            };
+            // Pairs of sextets become 12-bit values, then pairs of those become 24-bit values
+            let pairs = _mm256_maddubs_epi16(v, _mm256_set1_epi32(0x0140_0140));
+            let groups = _mm256_madd_epi16(pairs, _mm256_set1_epi32(0x0001_1000));
+            let packed = _mm256_shuffle_epi8(groups, gather);
+            let packed = _mm256_permutevar8x32_epi32(packed, _mm256_setr_epi32(0, 1, 2, 4, 5, 6, 3, 7));
+            let mut buffer = [0u8; 32];
+            _mm256_storeu_si256(buffer.as_mut_ptr().cast(), packed);
+            dst.copy_from_slice(&buffer[..24]);
+        }
+        scalar::base64_decode(src.remainder(), dst.into_remainder(), variant)
+    }
+
+    // The constants and reduction follow Intel's "Fast CRC Computation for Generic Polynomials Using PCLMULQDQ
+    // Instruction", for the bit-reflected IEEE polynomial.
+    const K1: i64 = 0x1_5444_2bd4;
+    const K2: i64 = 0x1_c6e4_1596;
+    const K3: i64 = 0x1_7519_97d0;
+    const K4: i64 = 0x0_ccaa_009e;
+    const K5: i64 = 0x1_63cd_6124;
+    const P_X: i64 = 0x1_db71_0641;
+    const U_PRIME: i64 = 0x1_f701_1641;
+
+    /// Fold `a` forward by the distance encoded in `keys` and add `b`.
+    #[target_feature(enable = "pclmulqdq")]
+    unsafe fn fold(a: __m128i, b: __m128i, keys: __m128i) -> __m128i {
+        let lo = _mm_clmulepi64_si128(a, keys, 0x00);
+        let hi = _mm_clmulepi64_si128(a, keys, 0x11);
+        _mm_xor_si128(_mm_xor_si128(b, lo), hi)
+    }
+
+    #[target_feature(enable = "pclmulqdq", enable = "sse4.1")]
+    pub unsafe fn crc32_update(state: u32, data: &[u8]) -> u32 {
+        // Folding only pays off for longer inputs
+        if data.len() < 128 {
+            return scalar::crc32_update(state, data);
+        }
+        let mut blocks = data.chunks_exact(64);
+        let Some(first) = blocks.next() else {
+            return scalar::crc32_update(state, data);
+        };
+        let load = |block: &[u8], offset: usize| _mm_loadu_si128(block.as_ptr().add(offset).cast());
+        let mut x = [load(first, 0), load(first, 16), load(first, 32), load(first, 48)];
+        x[0] = _mm_xor_si128(x[0], _mm_cvtsi32_si128(state as i32));
+
+        let k1k2 = _mm_set_epi64x(K2, K1);
+        for block in &mut blocks {
+            for (i, x) in x.iter_mut().enumerate() {
+                *x = fold(*x, load(block, i.saturating_mul(16)), k1k2);
+            }
+        }
+        let k3k4 = _mm_set_epi64x(K4, K3);
+        let mut acc = fold(fold(fold(x[0], x[1], k3k4), x[2], k3k4), x[3], k3k4);
+        let mut rest = blocks.remainder().chunks_exact(16);
+        for block in &mut rest {
+            acc = fold(acc, load(block, 0), k3k4);
+        }
+
+        // Reduce from 128 to 64 bits
+        let low32 = _mm_set_epi32(0, 0, 0, -1);
+        let acc = _mm_xor_si128(_mm_clmulepi64_si128(acc, k3k4, 0x10), _mm_srli_si128(acc, 8));
+        let acc = _mm_xor_si128(
+            _mm_clmulepi64_si128(_mm_and_si128(acc, low32), _mm_set_epi64x(0, K5), 0x00),
+            _mm_srli_si128(acc, 4),
+        );
+        // Barrett reduction to 32 bits
+        let pu = _mm_set_epi64x(U_PRIME, P_X);
+        let t1 = _mm_clmulepi64_si128(_mm_and_si128(acc, low32), pu, 0x10);
+        let t2 = _mm_clmulepi64_si128(_mm_and_si128(t1, low32), pu, 0x00);
+        let crc = _mm_extract_epi32(_mm_xor_si128(acc, t2), 1) as u32;
+        scalar::crc32_update(crc, rest.remainder())
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use core::arch::aarch64::*;
+
+    use super::{scalar, Base64Variant};
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn hex_encode(bytes: &[u8], out: &mut [u8]) {
+        let lut = vld1q_u8(b"0123456789abcdef".as_ptr());
+        let mask = vdupq_n_u8(0x0f);
+        let mut src = bytes.chunks_exact(16);
+        let mut dst = out.chunks_exact_mut(32);
+        for (src, dst) in (&mut src).zip(&mut dst) {
+            let v = vld1q_u8(src.as_ptr());
+            let hi = vqtbl1q_u8(lut, vshrq_n_u8::<4>(v));
+            let lo = vqtbl1q_u8(lut, vandq_u8(v, mask));
+            vst1q_u8(dst.as_mut_ptr(), vzip1q_u8(hi, lo));
+            vst1q_u8(dst.as_mut_ptr().add(16), vzip2q_u8(hi, lo));
+        }
+        scalar::hex_encode(src.remainder(), dst.into_remainder());
+    }
+
+    /// Convert 16 hex characters to nibbles, returning `None` if any character is invalid.
+    #[target_feature(enable = "neon")]
+    unsafe fn nibbles(c: uint8x16_t) -> Option<uint8x16_t> {
+        let digit = vsubq_u8(c, vdupq_n_u8(b'0'));
+        let is_digit = vcleq_u8(digit, vdupq_n_u8(9));
+        let alpha = vsubq_u8(vorrq_u8(c, vdupq_n_u8(0x20)), vdupq_n_u8(b'a'));
+        let is_alpha = vcleq_u8(alpha, vdupq_n_u8(5));
+        if vminvq_u8(vorrq_u8(is_digit, is_alpha)) != 0xff {
+            return None;
+        }
+        Some(vbslq_u8(is_digit, digit, vaddq_u8(alpha, vdupq_n_u8(10))))
+    }
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn hex_decode(hex: &[u8], out: &mut [u8]) -> bool {
+        let mut src = hex.chunks_exact(32);
+        let mut dst = out.chunks_exact_mut(16);
+        for (src, dst) in (&mut src).zip(&mut dst) {
+            let (n0, n1) = match (nibbles(vld1q_u8(src.as_ptr())), nibbles(vld1q_u8(src.as_ptr().add(16)))) {
+                (Some(n0), Some(n1)) => (n0, n1),
+                _ => return false,
+            };
+            // Even positions hold the high nibbles, odd positions the low nibbles
+            let bytes = vorrq_u8(vshlq_n_u8::<4>(vuzp1q_u8(n0, n1)), vuzp2q_u8(n0, n1));
+            vst1q_u8(dst.as_mut_ptr(), bytes);
+        }
+        scalar::hex_decode(src.remainder(), dst.into_remainder())
+    }
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+        let mut acc = vdupq_n_u8(0);
+        let mut a = a.chunks_exact(16);
+        let mut b = b.chunks_exact(16);
+        for (x, y) in (&mut a).zip(&mut b) {
+            acc = vorrq_u8(acc, veorq_u8(vld1q_u8(x.as_ptr()), vld1q_u8(y.as_ptr())));
+        }
+        let vector_eq = u8::from(vmaxvq_u8(acc) == 0);
+        let tail_eq = u8::from(scalar::ct_eq(a.remainder(), b.remainder()));
+        vector_eq & tail_eq == 1
+    }
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn base64_encode(bytes: &[u8], out: &mut [u8], alphabet: &[u8; 64]) -> usize {
+        let table = vld1q_u8_x4(alphabet.as_ptr());
+        let mut blocks = 0usize;
+        for (src, dst) in bytes.chunks_exact(48).zip(out.chunks_exact_mut(64)) {
+            // The bytes are loaded deinterleaved, so `a`, `b` and `c` hold the first, second and third byte of 16
+            // groups
+            let uint8x16x3_t(a, b, c) = vld3q_u8(src.as_ptr());
+            let s0 = vshrq_n_u8::<2>(a);
+            let s1 = vorrq_u8(vandq_u8(vshlq_n_u8::<4>(a), vdupq_n_u8(0x30)), vshrq_n_u8::<4>(b));
+            let s2 = vorrq_u8(vandq_u8(vshlq_n_u8::<2>(b), vdupq_n_u8(0x3c)), vshrq_n_u8::<6>(c));
+            let s3 = vandq_u8(c, vdupq_n_u8(0x3f));
+            let symbols = uint8x16x4_t(
+                vqtbl4q_u8(table, s0),
+                vqtbl4q_u8(table, s1),
+                vqtbl4q_u8(table, s2),
+                vqtbl4q_u8(table, s3),
+            );
+            vst4q_u8(dst.as_mut_ptr(), symbols);
+            blocks = blocks.saturating_add(1);
+        }
+        blocks.saturating_mul(48)
+    }
+
+    /// Convert 16 base64 symbols to sextets, returning `None` if any symbol is invalid.
+    #[target_feature(enable = "neon")]
+    unsafe fn sextets(c: uint8x16_t, alphabet: &[u8; 64]) -> Option<uint8x16_t> {
+        let upper = vsubq_u8(c, vdupq_n_u8(b'A'));
+        let is_upper = vcleq_u8(upper, vdupq_n_u8(25));
+        let lower = vsubq_u8(c, vdupq_n_u8(b'a'));
+        let is_lower = vcleq_u8(lower, vdupq_n_u8(25));
+        let digit = vsubq_u8(c, vdupq_n_u8(b'0'));
+        let is_digit = vcleq_u8(digit, vdupq_n_u8(9));
+        let is_62 = vceqq_u8(c, vdupq_n_u8(alphabet[62]));
+        let is_63 = vceqq_u8(c, vdupq_n_u8(alphabet[63]));
+        let valid = vorrq_u8(vorrq_u8(is_upper, is_lower), vorrq_u8(is_digit, vorrq_u8(is_62, is_63)));
+        if vminvq_u8(valid) != 0xff {
+            return None;
+        }
+        let letters = vorrq_u8(
+            vandq_u8(is_upper, upper),
+            vandq_u8(is_lower, vaddq_u8(lower, vdupq_n_u8(26))),
+        );
+        let others = vorrq_u8(
+            vandq_u8(is_digit, vaddq_u8(digit, vdupq_n_u8(52))),
+            vorrq_u8(vandq_u8(is_62, vdupq_n_u8(62)), vandq_u8(is_63, vdupq_n_u8(63))),
+        );
+        Some(vorrq_u8(letters, others))
+    }
+
+    #[target_feature(enable = "neon")]
+    pub unsafe fn base64_decode(b64: &[u8], out: &mut [u8], variant: Base64Variant) -> bool {
+        let alphabet = variant.alphabet();
+        let mut src = b64.chunks_exact(64);
+        let mut dst = out.chunks_exact_mut(48);
+        for (src, dst) in (&mut src).zip(&mut dst) {
+            // The symbols are loaded deinterleaved, so each register holds the same position of 16 groups
+            let uint8x16x4_t(c0, c1, c2, c3) = vld4q_u8(src.as_ptr());
+            let (Some(s0), Some(s1), Some(s2), Some(s3)) = (
+                sextets(c0, alphabet),
+                sextets(c1, alphabet),
+                sextets(c2, alphabet),
+                sextets(c3, alphabet),
+            ) else {
+                return false;
+            };
+            let bytes = uint8x16x3_t(
+                vorrq_u8(vshlq_n_u8::<2>(s0), vshrq_n_u8::<4>(s1)),
+                vorrq_u8(vshlq_n_u8::<4>(s1), vshrq_n_u8::<2>(s2)),
+                vorrq_u8(vshlq_n_u8::<6>(s2), s3),
+            );
+            vst3q_u8(dst.as_mut_ptr(), bytes);
+        }
+        scalar::base64_decode(src.remainder(), dst.into_remainder(), variant)
+    }
+
+    #[target_feature(enable = "crc")]
+    pub unsafe fn crc32_update(state: u32, data: &[u8]) -> u32 {
+        let mut words = data.chunks_exact(8);
+        let mut crc = state;
+        for word in &mut words {
+            let mut buffer = [0u8; 8];
+            buffer.copy_from_slice(word);
+            crc = __crc32d(crc, u64::from_le_bytes(buffer));
+        }
+        words.remainder().iter().fold(crc, |crc, byte| __crc32b(crc, *byte))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::OsRng, RngCore};
+
+    use super::*;
+
+    fn random_bytes(len: usize) -> Vec<u8> {
+        let mut bytes = vec![0u8; len];
+        OsRng.fill_bytes(&mut bytes);
+        bytes
+    }
+
+    #[test]
+    fn encode_matches_scalar() {
+        for len in 0..200 {
+            let bytes = random_bytes(len);
+            let mut expected = vec![0u8; len * 2];
+            scalar::hex_encode(&bytes, &mut expected);
+            let mut actual = vec![0u8; len * 2];
+            encode(backend(), &bytes, &mut actual);
+            assert_eq!(actual, expected);
+            assert_eq!(to_hex(&bytes), crate::hex::to_hex(&bytes));
+        }
+        // Every byte value
+        let bytes = (0..=255u8).collect::<Vec<_>>();
+        assert_eq!(to_hex(&bytes), crate::hex::to_hex(&bytes));
+    }
+
+    #[test]
+    fn decode_matches_scalar() {
+        for len in 0..200 {
+            let bytes = random_bytes(len);
+            let hex = crate::hex::to_hex(&bytes);
+            assert_eq!(from_hex(&hex).unwrap(), bytes);
+            assert_eq!(from_hex(&hex.to_uppercase()).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn decode_every_character_pair() {
+        // Place every possible character pair at positions covering the vector body and the scalar tail
+        let mut hex = vec![b'0'; 130];
+        for position in [0, 30, 62, 64, 126, 128] {
+            for c0 in 0..=255u8 {
+                for c1 in 0..=255u8 {
+                    hex[position] = c0;
+                    hex[position + 1] = c1;
+                    let mut expected = vec![0u8; 65];
+                    let expected_ok = scalar::hex_decode(&hex, &mut expected);
+                    let mut actual = vec![0u8; 65];
+                    let actual_ok = decode(backend(), &hex, &mut actual);
+                    assert_eq!(actual_ok, expected_ok);
+                    if expected_ok {
+                        assert_eq!(actual, expected);
+                    }
+                }
+            }
+            hex[position] = b'0';
+            hex[position + 1] = b'0';
+        }
+    }
+
+    #[test]
+    fn from_hex_errors() {
+        assert!(matches!(from_hex("800"), Err(HexError::LengthError {})));
+        assert!(matches!(
+            from_hex(" 0x8080gf"),
+            Err(HexError::InvalidCharacter {
+                position: 7,
+                character: 'g'
+            })
+        ));
+        assert!(matches!(from_hex("🖖🥴"), Err(HexError::HexConversionError {})));
+        assert_eq!(from_hex(" 0x0a0b0c0d ").unwrap(), vec![10, 11, 12, 13]);
+    }
+
+    #[test]
+    fn ct_eq_matches_scalar() {
+        for len in 0..100 {
+            let a = random_bytes(len);
+            assert!(ct_eq(&a, &a.clone()));
+            for i in 0..len {
+                let mut b = a.clone();
+                b[i] ^= 1;
+                assert!(!ct_eq(&a, &b));
+                assert_eq!(ct_eq(&a, &b), scalar::ct_eq(&a, &b));
+            }
+        }
+        assert!(!ct_eq(&[1, 2, 3], &[1, 2]));
+    }
+    const BASE64_VARIANTS: [Base64Variant; 4] = [
+        Base64Variant::Standard,
+        Base64Variant::StandardNoPad,
+        Base64Variant::UrlSafe,
+        Base64Variant::UrlSafeNoPad,
+    ];
+
+    fn scalar_to_base64(bytes: &[u8], variant: Base64Variant) -> String {
+        let mut out = vec![0u8; crate::base64::encoded_len(bytes.len(), variant)];
+        crate::base64::encode_into(bytes, &mut out, variant).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn scalar_from_base64(b64: &[u8], variant: Base64Variant) -> Result<Vec<u8>, Base64CodecError> {
+        let mut out = vec![0u8; decoded_capacity(b64.len())];
+        let len = crate::base64::decode_into(b64, &mut out, variant)?;
+        out.truncate(len);
+        Ok(out)
+    }
+
+    #[test]
+    fn base64_matches_scalar() {
+        for variant in BASE64_VARIANTS {
+            for len in 0..300 {
+                let bytes = random_bytes(len);
+                let b64 = to_base64(&bytes, variant);
+                assert_eq!(b64, scalar_to_base64(&bytes, variant));
+                assert_eq!(from_base64(&b64, variant).unwrap(), bytes);
+            }
+        }
+    }
+
+    #[test]
+    fn base64_encode_every_byte() {
+        // Place every byte value at every position of the first two vector blocks and the scalar tail
+        let mut bytes = vec![0u8; 100];
+        for variant in BASE64_VARIANTS {
+            for position in 0..bytes.len() {
+                for byte in 0..=255u8 {
+                    bytes[position] = byte;
+                    assert_eq!(to_base64(&bytes, variant), scalar_to_base64(&bytes, variant));
+                }
+                bytes[position] = 0;
+            }
+        }
+    }
+
+    #[test]
+    fn base64_decode_every_symbol_pair() {
+        // Place every possible byte pair at positions covering the vector body, the scalar tail and the padding
+        let mut b64 = vec![b'A'; 132];
+        for variant in [Base64Variant::Standard, Base64Variant::UrlSafeNoPad] {
+            for position in [0, 30, 62, 64, 126, 128, 130] {
+                for c0 in 0..=255u8 {
+                    for c1 in 0..=255u8 {
+                        b64[position] = c0;
+                        b64[position + 1] = c1;
+                        let expected = scalar_from_base64(&b64, variant);
+                        if let Ok(b64) = core::str::from_utf8(&b64) {
+                            assert_eq!(from_base64(b64, variant), expected);
+                        }
+                        // The backends only see whole groups without padding
+                        if !b64.contains(&b'=') {
+                            let mut actual = vec![0u8; 99];
+                            let actual_ok = base64_decode(backend(), &b64, &mut actual, variant);
+                            assert_eq!(actual_ok, expected.is_ok());
+                            if let Ok(expected) = expected {
+                                assert_eq!(actual, expected);
+                            }
+                        }
+                    }
+                }
+                b64[position] = b'A';
+                b64[position + 1] = b'A';
+            }
+        }
+    }
+
+    #[test]
+    fn base64_errors() {
+        let b64 = to_base64(&random_bytes(90), Base64Variant::Standard);
+        for position in [0, 40, 100, 119] {
+            let mut invalid = b64.clone().into_bytes();
+            invalid[position] = b'-';
+            let invalid = String::from_utf8(invalid).unwrap();
+            assert_eq!(
+                from_base64(&invalid, Base64Variant::Standard),
+                Err(Base64CodecError::InvalidCharacter {
+                    position,
+                    character: '-'
+                })
+            );
+        }
+        assert_eq!(
+            from_base64(&b64[..119], Base64Variant::Standard),
+            Err(Base64CodecError::InvalidPadding {})
+        );
+        assert_eq!(
+            from_base64(&b64[..117], Base64Variant::StandardNoPad),
+            Err(Base64CodecError::InvalidLength { length: 117 })
+        );
+        let mut emoji = "A".repeat(40);
+        emoji.push('🖖');
+        assert!(matches!(
+            from_base64(&emoji, Base64Variant::Standard),
+            Err(Base64CodecError::InvalidCharacter { position: 40, .. })
+        ));
+    }
+
+    #[test]
+    fn crc32_matches_scalar() {
+        for len in 0..1100 {
+            let data = random_bytes(len);
+            let expected = !crate::checksum::crc32_update(u32::MAX, &data);
+            assert_eq!(crc32(&data), expected);
+            // Any register state, as left by earlier updates
+            let state = (len as u32).wrapping_mul(0x9e37_79b9);
+            assert_eq!(crc32_update(state, &data), crate::checksum::crc32_update(state, &data));
+        }
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn crc32_every_byte() {
+        // Place every byte value at every position of the folded blocks and the scalar tail
+        let mut data = vec![0u8; 200];
+        for position in 0..data.len() {
+            for byte in 0..=255u8 {
+                data[position] = byte;
+                assert_eq!(crc32(&data), !crate::checksum::crc32_update(u32::MAX, &data));
+            }
+            data[position] = 0;
+        }
+    }
+}
@@ -0,0 +1,351 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A fixed-capacity, lock-free single-producer single-consumer byte queue.
+//!
+//! [SpscBytes] is a ring buffer of `N` bytes that needs nothing but atomic loads and stores, so it works on `no_std`
+//! targets without compare-and-swap, such as Cortex-M0. It is [split](SpscBytes::split) into a [Producer] and a
+//! [Consumer], which can live in different contexts, for example an interrupt handler and a task. Every operation is
+//! wait-free: it copies what fits and returns immediately, without spinning.
+//!
+//! ```edition2018
+//! # use tari_utilities::spsc::SpscBytes;
+//! let mut queue = SpscBytes::<8>::new();
+//! let (mut producer, mut consumer) = queue.split();
+//! assert_eq!(producer.push(b"hello world"), 8);
+//! let mut buf = [0u8; 5];
+//! assert_eq!(consumer.pop(&mut buf), 5);
+//! assert_eq!(&buf, b"hello");
+//! ```
+
+use core::{
+    cell::UnsafeCell,
+    ptr,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// A single-producer single-consumer ring buffer holding up to `N` bytes.
+///
+/// The read and write positions count modulo `2 * N` rather than `N`, so a full queue can be told apart from an empty
+/// one and the queue can hold all `N` bytes without a wasted slot, for any `N`.
+pub struct SpscBytes<const N: usize> {
+    buffer: UnsafeCell<[u8; N]>,
+    /// The number of bytes ever popped modulo `2 * N`, written only by the consumer
+    head: AtomicUsize,
+    /// The number of bytes ever pushed modulo `2 * N`, written only by the producer
+    tail: AtomicUsize,
+}
+
+// SAFETY: the producer and consumer only ever access disjoint regions of the buffer, delimited by `head` and `tail`,
+// and `split` ensures there is at most one of each.
+unsafe impl<const N: usize> Sync for SpscBytes<N> {}
+
+impl<const N: usize> SpscBytes<N> {
+    /// The modulus of the read and write positions.
+    const WRAP: usize = 2 * N;
+
+    /// Create an empty queue.
+    pub const fn new() -> Self {
+        Self {
+            buffer: UnsafeCell::new([0; N]),
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// The capacity in bytes.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// The number of bytes waiting to be popped.
+    pub fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        Self::distance(head, self.tail.load(Ordering::Acquire))
+    }
+
+    /// Return `true` if there are no bytes waiting.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Split the queue into its producer and consumer halves. Both halves borrow the queue mutably, so there is at most
+    /// one of each while they are alive.
+    pub fn split(&mut self) -> (Producer<'_, N>, Consumer<'_, N>) {
+        (Producer { queue: self }, Consumer { queue: self })
+    }
+
+    /// The number of bytes from position `from` to position `to`.
+    fn distance(from: usize, to: usize) -> usize {
+        if to >= from {
+            to.wrapping_sub(from)
+        } else {
+            Self::WRAP.wrapping_sub(from).wrapping_add(to)
+        }
+    }
+
+    /// Move `position` forward by `count`, which is at most `N`.
+    fn advance(position: usize, count: usize) -> usize {
+        let remaining = Self::WRAP.wrapping_sub(position);
+        if count < remaining {
+            position.wrapping_add(count)
+        } else {
+            count.wrapping_sub(remaining)
+        }
+    }
+
+    /// The index in the buffer of `position`.
+    fn index(position: usize) -> usize {
+        if position >= N {
+            position.wrapping_sub(N)
+        } else {
+            position
+        }
+    }
+
+    /// Copy `data` into the ring starting at position `position`, wrapping at the end.
+    ///
+    /// # Safety
+    /// The caller must have exclusive access to the `data.len()` bytes starting at `position`.
+    unsafe fn write_at(&self, position: usize, data: &[u8]) {
+        let start = Self::index(position);
+        let first = data.len().min(N.saturating_sub(start));
+        let buffer = self.buffer.get().cast::<u8>();
+        ptr::copy_nonoverlapping(data.as_ptr(), buffer.add(start), first);
+        ptr::copy_nonoverlapping(data.as_ptr().add(first), buffer, data.len().saturating_sub(first));
+    }
+
+    /// Copy bytes out of the ring starting at position `position`, wrapping at the end.
+    ///
+    /// # Safety
+    /// The caller must have exclusive access to the `out.len()` bytes starting at `position`.
+    unsafe fn read_at(&self, position: usize, out: &mut [u8]) {
+        let start = Self::index(position);
+        let first = out.len().min(N.saturating_sub(start));
+        let buffer = self.buffer.get().cast::<u8>();
+        ptr::copy_nonoverlapping(buffer.add(start), out.as_mut_ptr(), first);
+        ptr::copy_nonoverlapping(buffer, out.as_mut_ptr().add(first), out.len().saturating_sub(first));
+    }
+}
+
+impl<const N: usize> Default for SpscBytes<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The writing half of an [SpscBytes] queue.
+pub struct Producer<'a, const N: usize> {
+    queue: &'a SpscBytes<N>,
+}
+
+impl<const N: usize> Producer<'_, N> {
+    /// The number of bytes that can be pushed right now.
+    pub fn free_space(&self) -> usize {
+        let head = self.queue.head.load(Ordering::Acquire);
+        N.saturating_sub(SpscBytes::<N>::distance(head, self.queue.tail.load(Ordering::Relaxed)))
+    }
+
+    /// Push as many bytes of `data` as fit and return how many were pushed.
+    pub fn push(&mut self, data: &[u8]) -> usize {
+        let count = data.len().min(self.free_space());
+        self.write(data.get(..count).unwrap_or_default());
+        count
+    }
+
+    /// Push all of `data` if it fits, or nothing if it does not. Returns whether the data was pushed. Use this to keep
+    /// frames whole.
+    pub fn push_all(&mut self, data: &[u8]) -> bool {
+        if data.len() > self.free_space() {
+            return false;
+        }
+        self.write(data);
+        true
+    }
+
+    fn write(&mut self, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+        let tail = self.queue.tail.load(Ordering::Relaxed);
+        // SAFETY: the bytes from `tail` up to the free space belong to the producer until `tail` is advanced, and
+        // there is only one producer
+        unsafe { self.queue.write_at(tail, data) };
+        self.queue
+            .tail
+            .store(SpscBytes::<N>::advance(tail, data.len()), Ordering::Release);
+    }
+}
+
+/// The reading half of an [SpscBytes] queue.
+pub struct Consumer<'a, const N: usize> {
+    queue: &'a SpscBytes<N>,
+}
+
+impl<const N: usize> Consumer<'_, N> {
+    /// The number of bytes that can be popped right now.
+    pub fn available(&self) -> usize {
+        let tail = self.queue.tail.load(Ordering::Acquire);
+        SpscBytes::<N>::distance(self.queue.head.load(Ordering::Relaxed), tail)
+    }
+
+    /// Pop up to `out.len()` bytes into `out` and return how many were popped.
+    pub fn pop(&mut self, out: &mut [u8]) -> usize {
+        let count = out.len().min(self.available());
+        self.read(out.get_mut(..count).unwrap_or_default());
+        count
+    }
+
+    /// Pop exactly `out.len()` bytes if that many are available, or nothing otherwise. Returns whether bytes were
+    /// popped.
+    pub fn pop_exact(&mut self, out: &mut [u8]) -> bool {
+        if out.len() > self.available() {
+            return false;
+        }
+        self.read(out);
+        true
+    }
+
+    fn read(&mut self, out: &mut [u8]) {
+        if out.is_empty() {
+            return;
+        }
+        let head = self.queue.head.load(Ordering::Relaxed);
+        // SAFETY: the bytes from `head` up to the available count were published by the producer and belong to the
+        // consumer until `head` is advanced, and there is only one consumer
+        unsafe { self.queue.read_at(head, out) };
+        self.queue
+            .head
+            .store(SpscBytes::<N>::advance(head, out.len()), Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    #[test]
+    fn wrap_around() {
+        let mut queue = SpscBytes::<4>::new();
+        assert_eq!(queue.capacity(), 4);
+        let (mut producer, mut consumer) = queue.split();
+        let mut buf = [0u8; 4];
+        assert_eq!(producer.push(&[1, 2, 3]), 3);
+        assert_eq!(consumer.pop(&mut buf[..2]), 2);
+        assert_eq!(buf[..2], [1, 2]);
+        assert_eq!(producer.free_space(), 3);
+        assert_eq!(producer.push(&[4, 5, 6, 7]), 3);
+        assert_eq!(producer.free_space(), 0);
+        assert!(!producer.push_all(&[8]));
+        assert_eq!(consumer.available(), 4);
+        assert_eq!(consumer.pop(&mut buf), 4);
+        assert_eq!(buf, [3, 4, 5, 6]);
+        assert_eq!(consumer.pop(&mut buf), 0);
+        assert!(producer.push_all(&[9, 10]));
+        assert!(!consumer.pop_exact(&mut buf[..3]));
+        assert!(consumer.pop_exact(&mut buf[..2]));
+        assert_eq!(buf[..2], [9, 10]);
+        assert!(queue.is_empty());
+    }
+
+    /// Push and pop runs of every size from 1 to `N` across many wraps of the positions, starting just before the
+    /// wrap point, and check every byte comes out in order.
+    fn check_wrapping<const N: usize>() {
+        let mut queue = SpscBytes::<N>::new();
+        let start = SpscBytes::<N>::WRAP - 1;
+        queue.head = AtomicUsize::new(start);
+        queue.tail = AtomicUsize::new(start);
+        let (mut producer, mut consumer) = queue.split();
+        let mut next_in = 0u8;
+        let mut next_out = 0u8;
+        let mut buf = [0u8; N];
+        for round in 0..1000usize {
+            let push_len = round % N + 1;
+            let data = (0..push_len).map(|i| next_in.wrapping_add(i as u8)).collect::<Vec<_>>();
+            let pushed = producer.push(&data);
+            next_in = next_in.wrapping_add(pushed as u8);
+            assert_eq!(consumer.available(), next_in.wrapping_sub(next_out) as usize);
+
+            let pop_len = (round * 7 + 3) % N + 1;
+            let popped = consumer.pop(&mut buf[..pop_len]);
+            for byte in &buf[..popped] {
+                assert_eq!(*byte, next_out);
+                next_out = next_out.wrapping_add(1);
+            }
+            assert_eq!(producer.free_space(), N - next_in.wrapping_sub(next_out) as usize);
+        }
+        let mut rest = [0u8; N];
+        let popped = consumer.pop(&mut rest);
+        assert!(rest[..popped].iter().all(|byte| {
+            let expected = next_out;
+            next_out = next_out.wrapping_add(1);
+            *byte == expected
+        }));
+        assert_eq!(next_in, next_out);
+    }
+
+    #[test]
+    fn counters_wrap() {
+        check_wrapping::<1>();
+        check_wrapping::<3>();
+        check_wrapping::<4>();
+        check_wrapping::<7>();
+        check_wrapping::<64>();
+
+        let mut queue = SpscBytes::<3>::new();
+        queue.head = AtomicUsize::new(5);
+        queue.tail = AtomicUsize::new(5);
+        let (mut producer, mut consumer) = queue.split();
+        let mut buf = [0u8; 3];
+        for round in 0..4u8 {
+            assert!(producer.push_all(&[round, round + 1, round + 2]));
+            assert!(!producer.push_all(&[0]));
+            assert!(consumer.pop_exact(&mut buf));
+            assert_eq!(buf, [round, round + 1, round + 2]);
+        }
+    }
+
+    #[test]
+    fn threads() {
+        const TOTAL: usize = 100_000;
+        let mut queue = SpscBytes::<64>::new();
+        let (mut producer, mut consumer) = queue.split();
+        std::thread::scope(|s| {
+            s.spawn(move || {
+                let data = (0..TOTAL).map(|i| i as u8).collect::<Vec<_>>();
+                let mut sent = 0;
+                while sent < TOTAL {
+                    sent += producer.push(&data[sent..(sent + 13).min(TOTAL)]);
+                }
+            });
+            let mut received = Vec::with_capacity(TOTAL);
+            let mut buf = [0u8; 17];
+            while received.len() < TOTAL {
+                let n = consumer.pop(&mut buf);
+                received.extend_from_slice(&buf[..n]);
+            }
+            assert!(received.iter().enumerate().all(|(i, b)| *b == i as u8));
+        });
+    }
+}
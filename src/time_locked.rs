@@ -0,0 +1,194 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Secrets that cannot be revealed before a given time.
+//!
+//! A [TimeLocked] value keeps its contents in a [Hidden] container together with a not-before [EpochTime]. Every
+//! access takes a [TimeProvider], and returns [TimeLockedError::Locked] while the clock is earlier than the unlock
+//! time. This suits delayed-reveal recovery material and keys that are scheduled to come into use later.
+//!
+//! ```edition2018
+//! # use std::time::Duration;
+//! # use tari_utilities::{epoch_time::{EpochTime, FixedTimeProvider}, time_locked::TimeLocked};
+//! let locked = TimeLocked::new([7u8; 32], EpochTime::from(1_000));
+//! assert!(locked.reveal(&FixedTimeProvider(Duration::from_secs(999))).is_err());
+//! assert_eq!(locked.reveal(&FixedTimeProvider(Duration::from_secs(1_000))).unwrap(), &[7u8; 32]);
+//! ```
+
+use core::{any::type_name, fmt};
+
+use snafu::prelude::*;
+use zeroize::Zeroize;
+
+use crate::{
+    epoch_time::{EpochTime, TimeProvider},
+    hidden::Hidden,
+};
+
+/// Errors for accessing time-locked values.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum TimeLockedError {
+    /// The value may not be accessed yet.
+    #[snafu(display("The value is locked until {not_before}, but the time is {now}"))]
+    Locked {
+        /// The unlock time
+        not_before: EpochTime,
+        /// The time of the access attempt
+        now: EpochTime,
+    },
+}
+
+/// A hidden value that is only revealed from its unlock time onwards.
+///
+/// Like [Hidden], the value is masked in `Debug` and `Display` output and zeroized when dropped.
+#[derive(Clone, Debug)]
+pub struct TimeLocked<T>
+where T: Zeroize
+{
+    value: Hidden<T>,
+    not_before: EpochTime,
+}
+
+impl<T> TimeLocked<T>
+where T: Zeroize
+{
+    /// Lock `value` until `not_before`.
+    pub fn new(value: T, not_before: EpochTime) -> Self {
+        Self::from_hidden(Hidden::hide(value), not_before)
+    }
+
+    /// Lock an already hidden value until `not_before`.
+    pub fn from_hidden(value: Hidden<T>, not_before: EpochTime) -> Self {
+        Self { value, not_before }
+    }
+
+    /// The time from which the value can be accessed.
+    pub fn not_before(&self) -> EpochTime {
+        self.not_before
+    }
+
+    /// Returns true if the value can be accessed at the time given by `clock`.
+    pub fn is_unlocked<C: TimeProvider>(&self, clock: &C) -> bool {
+        clock.now() >= self.not_before
+    }
+
+    /// Reveal the value as an immutable reference.
+    ///
+    /// # Errors
+    /// Returns [TimeLockedError::Locked] if the clock is before the unlock time.
+    pub fn reveal<C: TimeProvider>(&self, clock: &C) -> Result<&T, TimeLockedError> {
+        self.check(clock)?;
+        Ok(self.value.reveal())
+    }
+
+    /// Reveal the value as a mutable reference.
+    ///
+    /// # Errors
+    /// Returns [TimeLockedError::Locked] if the clock is before the unlock time.
+    pub fn reveal_mut<C: TimeProvider>(&mut self, clock: &C) -> Result<&mut T, TimeLockedError> {
+        self.check(clock)?;
+        Ok(self.value.reveal_mut())
+    }
+
+    /// Release the hidden value, removing the time lock. If the value is still locked, it is handed back unchanged
+    /// along with the error.
+    pub fn unlock<C: TimeProvider>(self, clock: &C) -> Result<Hidden<T>, (Self, TimeLockedError)> {
+        match self.check(clock) {
+            Ok(()) => Ok(self.value),
+            Err(err) => Err((self, err)),
+        }
+    }
+
+    fn check<C: TimeProvider>(&self, clock: &C) -> Result<(), TimeLockedError> {
+        let now = clock.now();
+        ensure!(now >= self.not_before, LockedSnafu {
+            not_before: self.not_before,
+            now
+        });
+        Ok(())
+    }
+}
+
+/// Only display masked data and the unlock time, keeping the hidden data hidden
+impl<T> fmt::Display for TimeLocked<T>
+where T: Zeroize
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TimeLocked<{}> until {}", type_name::<T>(), self.not_before)
+    }
+}
+
+impl<T> Zeroize for TimeLocked<T>
+where T: Zeroize
+{
+    fn zeroize(&mut self) {
+        self.value.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::epoch_time::FixedTimeProvider;
+
+    fn at(secs: u64) -> FixedTimeProvider {
+        FixedTimeProvider(Duration::from_secs(secs))
+    }
+
+    #[test]
+    fn access_is_gated_by_time() {
+        let mut locked = TimeLocked::new(vec![1u8, 2, 3], EpochTime::from(100));
+        assert_eq!(locked.not_before(), EpochTime::from(100));
+        assert!(!locked.is_unlocked(&at(99)));
+        assert_eq!(
+            locked.reveal(&at(99)),
+            Err(TimeLockedError::Locked {
+                not_before: EpochTime::from(100),
+                now: EpochTime::from(99)
+            })
+        );
+        assert!(locked.reveal_mut(&at(0)).is_err());
+
+        assert!(locked.is_unlocked(&at(100)));
+        locked.reveal_mut(&at(100)).unwrap().push(4);
+        assert_eq!(locked.reveal(&at(200)).unwrap(), &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn unlock() {
+        let locked = TimeLocked::new(5u64, EpochTime::from(10));
+        let (locked, err) = locked.unlock(&at(9)).unwrap_err();
+        assert!(matches!(err, TimeLockedError::Locked { .. }));
+        assert_eq!(locked.unlock(&at(10)).unwrap().reveal(), &5);
+    }
+
+    #[test]
+    fn masked_and_zeroized() {
+        let mut locked = TimeLocked::new([9u8; 4], EpochTime::from(0));
+        assert!(!format!("{:?}", locked).contains('9'));
+        assert_eq!(format!("{}", locked), "TimeLocked<[u8; 4]> until 0");
+        locked.zeroize();
+        assert_eq!(locked.reveal(&at(0)).unwrap(), &[0u8; 4]);
+    }
+}
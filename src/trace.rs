@@ -0,0 +1,231 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Recording of values in `tracing` fields without leaking secrets.
+//!
+//! `tracing::Value` is sealed, so this module provides the [TraceValue] trait instead. Its `trace_value` method
+//! returns a value that can be recorded directly in a `tracing` field. Secret types (`Hidden` and `SafePassword`, with
+//! the `zeroize` feature) are always recorded as `<redacted>`, and byte arrays and the [HexFmt] and [HexUpperFmt]
+//! wrappers are recorded as abbreviated hex so that large buffers do not flood the logs.
+//!
+//! ```edition2018
+//! # #[cfg(feature = "zeroize")] {
+//! # use tari_utilities::{trace::TraceValue, SafePassword};
+//! let password = SafePassword::from("my secret passphrase");
+//! let key = [1u8; 32];
+//! tracing::info!(
+//!     password = password.trace_value(),
+//!     key = key.trace_value(),
+//!     "Unlocking wallet"
+//! );
+//! assert_eq!(password.trace_value().to_string(), "<redacted>");
+//! assert_eq!(key.trace_value().to_string(), "01010101..01010101");
+//! # }
+//! ```
+
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+use tracing::field::{display, DisplayValue};
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use crate::hex::{to_hex, HexFmt, HexUpperFmt};
+#[cfg(feature = "zeroize")]
+use crate::{Hidden, SafePassword};
+
+/// The number of bytes shown at each end of abbreviated hex.
+const ABBREVIATED_BYTES: usize = 4;
+
+/// A value recorded in a `tracing` field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TracedValue {
+    /// A secret, which is recorded as `<redacted>`.
+    Redacted,
+    /// Bytes, recorded as hex. Long values show only the first and last few bytes.
+    Hex(String),
+}
+
+impl fmt::Display for TracedValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TracedValue::Redacted => f.write_str("<redacted>"),
+            TracedValue::Hex(hex) => f.write_str(hex),
+        }
+    }
+}
+
+/// Types that know how to record themselves in a `tracing` field.
+pub trait TraceValue {
+    /// Return the value to record in a `tracing` field.
+    fn trace_value(&self) -> DisplayValue<TracedValue>;
+}
+
+/// Return the hex representation of the bytes, showing only the first and last few bytes of long values.
+pub fn abbreviated_hex(bytes: &[u8]) -> TracedValue {
+    if bytes.len() <= 2 * ABBREVIATED_BYTES {
+        return TracedValue::Hex(to_hex(bytes));
+    }
+    let (head, _) = bytes.split_at(ABBREVIATED_BYTES);
+    let (_, tail) = bytes.split_at(bytes.len().saturating_sub(ABBREVIATED_BYTES));
+    TracedValue::Hex(format!("{}..{}", to_hex(head), to_hex(tail)))
+}
+
+impl TraceValue for [u8] {
+    fn trace_value(&self) -> DisplayValue<TracedValue> {
+        display(abbreviated_hex(self))
+    }
+}
+
+impl TraceValue for Vec<u8> {
+    fn trace_value(&self) -> DisplayValue<TracedValue> {
+        display(abbreviated_hex(self))
+    }
+}
+
+impl<const N: usize> TraceValue for [u8; N] {
+    fn trace_value(&self) -> DisplayValue<TracedValue> {
+        display(abbreviated_hex(self))
+    }
+}
+
+impl TraceValue for HexFmt<'_> {
+    fn trace_value(&self) -> DisplayValue<TracedValue> {
+        display(abbreviated_hex(self.0))
+    }
+}
+
+impl TraceValue for HexUpperFmt<'_> {
+    fn trace_value(&self) -> DisplayValue<TracedValue> {
+        let mut value = abbreviated_hex(self.0);
+        if let TracedValue::Hex(hex) = &mut value {
+            hex.make_ascii_uppercase();
+        }
+        display(value)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<T: Zeroize> TraceValue for Hidden<T> {
+    fn trace_value(&self) -> DisplayValue<TracedValue> {
+        display(TracedValue::Redacted)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl TraceValue for SafePassword {
+    fn trace_value(&self) -> DisplayValue<TracedValue> {
+        display(TracedValue::Redacted)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        string::ToString,
+        sync::{Arc, Mutex},
+    };
+
+    use tracing::{
+        field::{Field, Visit},
+        span,
+        Event,
+        Metadata,
+        Subscriber,
+    };
+
+    use super::*;
+
+    /// Records the fields of every event as `name=value` strings.
+    #[derive(Default)]
+    struct Recorder(Arc<Mutex<Vec<String>>>);
+
+    impl Visit for Recorder {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.lock().unwrap().push(format!("{}={:?}", field.name(), value));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            event.record(&mut Recorder(self.0.clone()));
+        }
+
+        fn enter(&self, _span: &span::Id) {}
+
+        fn exit(&self, _span: &span::Id) {}
+    }
+
+    #[test]
+    fn abbreviation() {
+        assert_eq!(abbreviated_hex(&[]), TracedValue::Hex(String::new()));
+        assert_eq!(
+            abbreviated_hex(&[1, 2, 3, 4, 5, 6, 7, 8]).to_string(),
+            "0102030405060708"
+        );
+        assert_eq!(
+            abbreviated_hex(&[1, 2, 3, 4, 5, 6, 7, 8, 9]).to_string(),
+            "01020304..06070809"
+        );
+    }
+
+    #[test]
+    fn hex_wrappers() {
+        let bytes = [0xabu8; 16];
+        assert_eq!(HexFmt(&bytes).trace_value().to_string(), "abababab..abababab");
+        assert_eq!(HexUpperFmt(&bytes).trace_value().to_string(), "ABABABAB..ABABABAB");
+        assert_eq!(HexUpperFmt(&[0xcd]).trace_value().to_string(), "CD");
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn secrets_are_redacted() {
+        let fields = Arc::new(Mutex::new(Vec::new()));
+        let password = SafePassword::from("hunter2");
+        let key = Hidden::hide([0xabu8; 32]);
+        let bytes = vec![0xcdu8; 100];
+        tracing::subscriber::with_default(Recorder(fields.clone()), || {
+            tracing::info!(
+                password = password.trace_value(),
+                key = key.trace_value(),
+                bytes = bytes.trace_value()
+            );
+        });
+        assert_eq!(*fields.lock().unwrap(), vec![
+            "password=<redacted>".to_string(),
+            "key=<redacted>".to_string(),
+            "bytes=cdcdcdcd..cdcdcdcd".to_string(),
+        ]);
+    }
+}
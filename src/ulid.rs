@@ -0,0 +1,450 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! ULIDs: lexicographically sortable 128-bit identifiers.
+//!
+//! A ULID is a 48-bit Unix timestamp in milliseconds followed by 80 random bits. Its text form is 26 characters of
+//! Crockford base32, so sorting ULIDs as strings or as bytes sorts them by creation time.
+//!
+//! With the `rand` feature, ULIDs can be generated with `Ulid::new` or, when identifiers created in the same
+//! millisecond must also stay ordered, with a `UlidGenerator`. Timestamps come from a
+//! [TimeProvider](crate::epoch_time::TimeProvider).
+//!
+//! ```edition2018
+//! # use tari_utilities::ulid::Ulid;
+//! let ulid: Ulid = "01ARZ3NDEKTSV4RRFFQ69G5FAV".parse().unwrap();
+//! assert_eq!(ulid.timestamp_millis(), 1_469_922_850_259);
+//! assert_eq!(ulid.to_string(), "01ARZ3NDEKTSV4RRFFQ69G5FAV");
+//! ```
+
+use core::{convert::TryInto, fmt, str::FromStr};
+
+use snafu::prelude::*;
+
+use crate::{base32::Base32Variant, uuid::Uuid};
+
+/// Errors for parsing and generating ULIDs.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum UlidError {
+    /// The input is not 26 characters or 16 bytes long.
+    #[snafu(display("Invalid ULID length {actual}"))]
+    InvalidLength {
+        /// The length of the input
+        actual: usize,
+    },
+    /// The string contains a character that is not Crockford base32.
+    #[snafu(display("Invalid ULID character at position {position}"))]
+    InvalidCharacter {
+        /// The index of the offending character
+        position: usize,
+    },
+    /// The string encodes a value larger than 128 bits.
+    #[snafu(display("ULID string overflows 128 bits"))]
+    Overflow {},
+    /// More ULIDs were generated in a single millisecond than the random bits can order.
+    #[snafu(display("Monotonic ULID random component overflowed"))]
+    MonotonicOverflow {},
+}
+
+/// The length of the text form.
+const ENCODED_LEN: usize = 26;
+
+/// The number of random bits.
+const RANDOM_BITS: u32 = 80;
+
+/// The mask of the random bits.
+const RANDOM_MASK: u128 = (1 << RANDOM_BITS) - 1;
+
+/// The largest timestamp a ULID can hold.
+const MAX_TIMESTAMP: u64 = (1 << 48) - 1;
+
+/// A ULID.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ulid(u128);
+
+impl Ulid {
+    /// The nil ULID, with every bit zero.
+    pub const NIL: Ulid = Ulid(0);
+
+    /// Create a ULID from a timestamp and random bits. Timestamps beyond 48 bits and random values beyond 80 bits are
+    /// truncated.
+    pub const fn from_parts(timestamp_millis: u64, random: u128) -> Self {
+        Self((((timestamp_millis & MAX_TIMESTAMP) as u128) << RANDOM_BITS) | (random & RANDOM_MASK))
+    }
+
+    /// Create a ULID from its big-endian bytes.
+    pub const fn from_bytes(bytes: [u8; 16]) -> Self {
+        Self(u128::from_be_bytes(bytes))
+    }
+
+    /// Create a ULID from exactly 16 big-endian bytes.
+    ///
+    /// # Errors
+    /// Returns [UlidError::InvalidLength] if the slice is not 16 bytes long.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, UlidError> {
+        bytes
+            .try_into()
+            .map(Self::from_bytes)
+            .map_err(|_| UlidError::InvalidLength { actual: bytes.len() })
+    }
+
+    /// Return the big-endian bytes of the ULID.
+    pub const fn to_bytes(&self) -> [u8; 16] {
+        self.0.to_be_bytes()
+    }
+
+    /// Return the Unix time in milliseconds at which the ULID was created.
+    pub const fn timestamp_millis(&self) -> u64 {
+        (self.0 >> RANDOM_BITS) as u64
+    }
+
+    /// Return the 80 random bits.
+    pub const fn random(&self) -> u128 {
+        self.0 & RANDOM_MASK
+    }
+
+    /// Return `true` if this is the nil ULID.
+    pub const fn is_nil(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Generate a ULID from the system clock and the operating system's random number generator.
+    #[cfg(feature = "rand")]
+    pub fn new() -> Self {
+        Self::new_from(&crate::epoch_time::SystemTimeProvider, &mut rand::rngs::OsRng)
+    }
+
+    /// Generate a ULID from the given clock and random number generator. ULIDs created in the same millisecond are
+    /// ordered randomly; use a [UlidGenerator] if they must be ordered by creation.
+    #[cfg(feature = "rand")]
+    pub fn new_from<T, R>(time: &T, rng: &mut R) -> Self
+    where
+        T: crate::epoch_time::TimeProvider + ?Sized,
+        R: rand::RngCore + ?Sized,
+    {
+        Self::from_parts(millis_since_epoch(time), random_bits(rng))
+    }
+}
+
+impl FromStr for Ulid {
+    type Err = UlidError;
+
+    /// Parse the 26-character Crockford base32 form.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars = s.as_bytes();
+        ensure!(chars.len() == ENCODED_LEN, InvalidLengthSnafu { actual: chars.len() });
+        let mut value = 0u128;
+        for (position, &c) in chars.iter().enumerate() {
+            let digit = Base32Variant::Crockford
+                .decode_symbol(c)
+                .context(InvalidCharacterSnafu { position })?;
+            // 26 characters hold 130 bits, so the first character may only carry 3
+            ensure!(position > 0 || digit < 8, OverflowSnafu);
+            value = (value << 5) | u128::from(digit);
+        }
+        Ok(Self(value))
+    }
+}
+
+impl fmt::Display for Ulid {
+    /// Write the 26-character uppercase Crockford base32 form.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut chars = [0u8; ENCODED_LEN];
+        let mut value = self.0;
+        for c in chars.iter_mut().rev() {
+            *c = Base32Variant::Crockford
+                .alphabet()
+                .get((value & 0x1f) as usize)
+                .copied()
+                .unwrap_or(b'0');
+            value >>= 5;
+        }
+        f.write_str(core::str::from_utf8(&chars).map_err(|_| fmt::Error)?)
+    }
+}
+
+impl fmt::Debug for Ulid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Ulid({})", self)
+    }
+}
+
+impl From<[u8; 16]> for Ulid {
+    fn from(bytes: [u8; 16]) -> Self {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl From<Ulid> for [u8; 16] {
+    fn from(ulid: Ulid) -> Self {
+        ulid.to_bytes()
+    }
+}
+
+impl From<Ulid> for u128 {
+    fn from(ulid: Ulid) -> Self {
+        ulid.0
+    }
+}
+
+impl From<u128> for Ulid {
+    fn from(value: u128) -> Self {
+        Self(value)
+    }
+}
+
+/// The bytes are carried over unchanged, so the UUID has no meaningful version.
+impl From<Ulid> for Uuid {
+    fn from(ulid: Ulid) -> Self {
+        Uuid::from_bytes(ulid.to_bytes())
+    }
+}
+
+impl From<Uuid> for Ulid {
+    fn from(uuid: Uuid) -> Self {
+        Self::from_bytes(*uuid.as_bytes())
+    }
+}
+
+#[cfg(feature = "rand")]
+fn millis_since_epoch<T: crate::epoch_time::TimeProvider + ?Sized>(time: &T) -> u64 {
+    use core::convert::TryFrom;
+
+    u64::try_from(time.since_epoch().as_millis()).unwrap_or(u64::MAX)
+}
+
+#[cfg(feature = "rand")]
+fn random_bits<R: rand::RngCore + ?Sized>(rng: &mut R) -> u128 {
+    let mut bytes = [0u8; 16];
+    rng.fill_bytes(&mut bytes);
+    u128::from_be_bytes(bytes)
+}
+
+/// Generates ULIDs that strictly increase, even within a millisecond or when the clock steps backwards.
+///
+/// The first ULID in a millisecond has random bits; later ones in the same millisecond increment them by one. If the
+/// clock goes backwards, the last timestamp is reused.
+#[cfg(feature = "rand")]
+#[derive(Debug, Clone, Default)]
+pub struct UlidGenerator {
+    last: Option<Ulid>,
+}
+
+#[cfg(feature = "rand")]
+impl UlidGenerator {
+    /// Create a generator.
+    pub const fn new() -> Self {
+        Self { last: None }
+    }
+
+    /// Generate the next ULID from the system clock and the operating system's random number generator.
+    ///
+    /// # Errors
+    /// Returns [UlidError::MonotonicOverflow] if the random bits of the millisecond are exhausted.
+    pub fn generate(&mut self) -> Result<Ulid, UlidError> {
+        self.generate_from(&crate::epoch_time::SystemTimeProvider, &mut rand::rngs::OsRng)
+    }
+
+    /// Generate the next ULID from the given clock and random number generator.
+    ///
+    /// # Errors
+    /// Returns [UlidError::MonotonicOverflow] if the random bits of the millisecond are exhausted.
+    pub fn generate_from<T, R>(&mut self, time: &T, rng: &mut R) -> Result<Ulid, UlidError>
+    where
+        T: crate::epoch_time::TimeProvider + ?Sized,
+        R: rand::RngCore + ?Sized,
+    {
+        let millis = millis_since_epoch(time).min(MAX_TIMESTAMP);
+        let ulid = match self.last {
+            Some(last) if millis <= last.timestamp_millis() => {
+                let random = last.random().checked_add(1).filter(|r| *r <= RANDOM_MASK);
+                Ulid::from_parts(last.timestamp_millis(), random.context(MonotonicOverflowSnafu)?)
+            },
+            _ => Ulid::from_parts(millis, random_bits(rng)),
+        };
+        self.last = Some(ulid);
+        Ok(ulid)
+    }
+}
+
+/// Serializes as the base32 string in human-readable formats and as 16 bytes otherwise.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Ulid {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_bytes(&self.to_bytes())
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Ulid {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct UlidVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for UlidVisitor {
+            type Value = Ulid;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a 26-character ULID string or 16 bytes")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse()
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                Ulid::from_slice(v).map_err(|_| E::invalid_length(v.len(), &self))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(UlidVisitor)
+        } else {
+            deserializer.deserialize_bytes(UlidVisitor)
+        }
+    }
+}
+
+/// Encodes as the 16 big-endian bytes of [Ulid::to_bytes], so encodings sort in time order.
+#[cfg(feature = "borsh")]
+impl borsh::BorshSerialize for Ulid {
+    fn serialize<W: borsh::maybestd::io::Write>(&self, writer: &mut W) -> borsh::maybestd::io::Result<()> {
+        writer.write_all(&self.to_bytes())
+    }
+}
+
+#[cfg(feature = "borsh")]
+impl borsh::BorshDeserialize for Ulid {
+    fn deserialize_reader<R: borsh::maybestd::io::Read>(reader: &mut R) -> borsh::maybestd::io::Result<Self> {
+        let mut bytes = [0u8; 16];
+        reader.read_exact(&mut bytes)?;
+        Ok(Ulid::from_bytes(bytes))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn borsh() {
+        use borsh::BorshDeserialize;
+
+        let ulid = Ulid::from_parts(0x0102_0304_0506, 7);
+        let bytes = borsh::to_vec(&ulid).unwrap();
+        assert_eq!(bytes, ulid.to_bytes());
+        assert_eq!(bytes[0], 0x01);
+        assert_eq!(Ulid::try_from_slice(&bytes).unwrap(), ulid);
+    }
+
+    #[test]
+    fn parsing() {
+        let ulid = "01ARZ3NDEKTSV4RRFFQ69G5FAV".parse::<Ulid>().unwrap();
+        assert_eq!(ulid.timestamp_millis(), 1_469_922_850_259);
+        assert_eq!("01arz3ndektsv4rrffq69g5fav".parse::<Ulid>(), Ok(ulid));
+        assert_eq!(Ulid::from_parts(ulid.timestamp_millis(), ulid.random()), ulid);
+        assert_eq!(Ulid::from_bytes(ulid.to_bytes()), ulid);
+        assert_eq!(Ulid::from(Uuid::from(ulid)), ulid);
+        assert_eq!(Ulid::NIL.to_string(), "00000000000000000000000000");
+        assert_eq!(Ulid::from(u128::MAX).to_string(), "7ZZZZZZZZZZZZZZZZZZZZZZZZZ");
+
+        assert_eq!(
+            "01ARZ3NDEKTSV4RRFFQ69G5FA".parse::<Ulid>(),
+            Err(UlidError::InvalidLength { actual: 25 })
+        );
+        assert_eq!(
+            "01ARZ3NDEKTSV4RRFFQ69G5FAU".parse::<Ulid>(),
+            Err(UlidError::InvalidCharacter { position: 25 })
+        );
+        assert_eq!(
+            "80000000000000000000000000".parse::<Ulid>(),
+            Err(UlidError::Overflow {})
+        );
+        assert_eq!(Ulid::from_slice(&[0; 15]), Err(UlidError::InvalidLength { actual: 15 }));
+    }
+
+    #[test]
+    fn ordering() {
+        let a = Ulid::from_parts(1, u128::MAX);
+        let b = Ulid::from_parts(2, 0);
+        assert!(a < b);
+        assert!(a.to_string() < b.to_string());
+        assert!(a.to_bytes() < b.to_bytes());
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn monotonic_generation() {
+        use std::time::Duration;
+
+        use crate::epoch_time::FixedTimeProvider;
+
+        let rng = &mut rand::rngs::OsRng;
+        let now = FixedTimeProvider(Duration::from_millis(1_000));
+        let mut generator = UlidGenerator::new();
+        let first = generator.generate_from(&now, rng).unwrap();
+        let second = generator.generate_from(&now, rng).unwrap();
+        assert_eq!(first.timestamp_millis(), 1_000);
+        assert_eq!(second.random(), first.random() + 1);
+
+        let earlier = generator
+            .generate_from(&FixedTimeProvider(Duration::from_millis(999)), rng)
+            .unwrap();
+        assert!(earlier > second);
+        assert_eq!(earlier.timestamp_millis(), 1_000);
+
+        let later = generator
+            .generate_from(&FixedTimeProvider(Duration::from_millis(1_001)), rng)
+            .unwrap();
+        assert_eq!(later.timestamp_millis(), 1_001);
+
+        let mut generator = UlidGenerator {
+            last: Some(Ulid::from_parts(1_001, RANDOM_MASK)),
+        };
+        let full = FixedTimeProvider(Duration::from_millis(1_001));
+        assert_eq!(
+            generator.generate_from(&full, rng),
+            Err(UlidError::MonotonicOverflow {})
+        );
+        assert_eq!(Ulid::new_from(&full, rng).timestamp_millis(), 1_001);
+        assert!(!Ulid::new().is_nil());
+        assert!(generator.generate().is_ok());
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn serialization() {
+        let ulid = "01ARZ3NDEKTSV4RRFFQ69G5FAV".parse::<Ulid>().unwrap();
+        let json = serde_json::to_string(&ulid).unwrap();
+        assert_eq!(json, "\"01ARZ3NDEKTSV4RRFFQ69G5FAV\"");
+        assert_eq!(serde_json::from_str::<Ulid>(&json).unwrap(), ulid);
+        let binary = bincode::serialize(&ulid).unwrap();
+        assert_eq!(bincode::deserialize::<Ulid>(&binary).unwrap(), ulid);
+    }
+}
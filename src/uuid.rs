@@ -0,0 +1,352 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! UUIDs as defined by RFC 9562, for request identifiers and trace correlation.
+//!
+//! Any UUID can be parsed and formatted in its canonical hyphenated form (`xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx`) or
+//! converted to and from its 16-byte form. With the `rand` feature, random version 4 UUIDs and time-ordered version 7
+//! UUIDs can be generated. Version 7 UUIDs start with the Unix time in milliseconds, taken from a
+//! [TimeProvider](crate::epoch_time::TimeProvider), so they sort by creation time.
+//!
+//! ```edition2018
+//! # use tari_utilities::uuid::Uuid;
+//! let uuid: Uuid = "0190a5d6-3b7e-7cc3-9b1a-2f6c0e4d5a10".parse().unwrap();
+//! assert_eq!(uuid.version(), 7);
+//! assert_eq!(uuid.timestamp_millis(), Some(0x0190a5d63b7e));
+//! assert_eq!(uuid.to_string(), "0190a5d6-3b7e-7cc3-9b1a-2f6c0e4d5a10");
+//! ```
+
+use core::{convert::TryInto, fmt, str::FromStr};
+
+use snafu::prelude::*;
+
+use crate::hex::{fmt_hex, hex_nibble};
+
+/// Errors for parsing UUIDs.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum UuidError {
+    /// The string is not a hyphenated UUID.
+    #[snafu(display("Invalid UUID string"))]
+    InvalidString {},
+    /// The byte slice is not 16 bytes long.
+    #[snafu(display("Expected 16 bytes for a UUID but got {actual}"))]
+    InvalidLength {
+        /// The length of the slice
+        actual: usize,
+    },
+}
+
+/// The positions of the hyphens in the canonical string form.
+const HYPHENS: [usize; 4] = [8, 13, 18, 23];
+
+/// A UUID. With the `borsh` feature, it is encoded as its 16 bytes.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "borsh", derive(borsh::BorshSerialize, borsh::BorshDeserialize))]
+pub struct Uuid([u8; 16]);
+
+impl Uuid {
+    /// The nil UUID, with every bit zero.
+    pub const NIL: Uuid = Uuid([0; 16]);
+
+    /// Create a UUID from its bytes.
+    pub const fn from_bytes(bytes: [u8; 16]) -> Self {
+        Self(bytes)
+    }
+
+    /// Create a UUID from exactly 16 bytes.
+    ///
+    /// # Errors
+    /// Returns [UuidError::InvalidLength] if the slice is not 16 bytes long.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, UuidError> {
+        bytes
+            .try_into()
+            .map(Self)
+            .map_err(|_| UuidError::InvalidLength { actual: bytes.len() })
+    }
+
+    /// Return the bytes of the UUID.
+    pub const fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    /// Return the version number in the UUID, such as 4 or 7.
+    pub fn version(&self) -> u8 {
+        self.0.get(6).copied().unwrap_or_default() >> 4
+    }
+
+    /// Return `true` if this is the nil UUID.
+    pub fn is_nil(&self) -> bool {
+        *self == Self::NIL
+    }
+
+    /// Return the Unix time in milliseconds at which a version 7 UUID was created, or `None` for other versions.
+    pub fn timestamp_millis(&self) -> Option<u64> {
+        if self.version() != 7 {
+            return None;
+        }
+        let mut millis = [0u8; 8];
+        millis.get_mut(2..)?.copy_from_slice(self.0.get(..6)?);
+        Some(u64::from_be_bytes(millis))
+    }
+
+    /// Set the version and the RFC 9562 variant bits.
+    #[cfg(feature = "rand")]
+    fn with_version(mut self, version: u8) -> Self {
+        if let Some(byte) = self.0.get_mut(6) {
+            *byte = (*byte & 0x0f) | (version << 4);
+        }
+        if let Some(byte) = self.0.get_mut(8) {
+            *byte = (*byte & 0x3f) | 0x80;
+        }
+        self
+    }
+
+    /// Generate a random version 4 UUID with the operating system's random number generator.
+    #[cfg(feature = "rand")]
+    pub fn new_v4() -> Self {
+        Self::new_v4_from_rng(&mut rand::rngs::OsRng)
+    }
+
+    /// Generate a random version 4 UUID.
+    #[cfg(feature = "rand")]
+    pub fn new_v4_from_rng<R: rand::RngCore + ?Sized>(rng: &mut R) -> Self {
+        let mut bytes = [0u8; 16];
+        rng.fill_bytes(&mut bytes);
+        Self(bytes).with_version(4)
+    }
+
+    /// Generate a version 7 UUID from the system clock and the operating system's random number generator.
+    #[cfg(feature = "rand")]
+    pub fn new_v7() -> Self {
+        Self::new_v7_from(&crate::epoch_time::SystemTimeProvider, &mut rand::rngs::OsRng)
+    }
+
+    /// Generate a version 7 UUID from the given clock and random number generator. UUIDs created in the same
+    /// millisecond are ordered randomly.
+    #[cfg(feature = "rand")]
+    pub fn new_v7_from<T, R>(time: &T, rng: &mut R) -> Self
+    where
+        T: crate::epoch_time::TimeProvider + ?Sized,
+        R: rand::RngCore + ?Sized,
+    {
+        use core::convert::TryFrom;
+
+        let millis = u64::try_from(time.since_epoch().as_millis()).unwrap_or(u64::MAX);
+        let mut bytes = [0u8; 16];
+        let (timestamp, random) = bytes.split_at_mut(6);
+        // The timestamp field is 48 bits, which lasts until the year 10889
+        timestamp.copy_from_slice(millis.to_be_bytes().get(2..).unwrap_or_default());
+        rng.fill_bytes(random);
+        Self(bytes).with_version(7)
+    }
+}
+
+impl FromStr for Uuid {
+    type Err = UuidError;
+
+    /// Parse the canonical hyphenated form. Upper and lower case hex digits are accepted.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars = s.as_bytes();
+        ensure!(chars.len() == 36, InvalidStringSnafu);
+        let mut digits = chars
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !HYPHENS.contains(i))
+            .map(|(_, &c)| hex_nibble(c));
+        ensure!(HYPHENS.iter().all(|&i| chars.get(i) == Some(&b'-')), InvalidStringSnafu);
+        let mut bytes = [0u8; 16];
+        for byte in &mut bytes {
+            match (digits.next().flatten(), digits.next().flatten()) {
+                (Some(hi), Some(lo)) => *byte = (hi << 4) | lo,
+                _ => return InvalidStringSnafu.fail(),
+            }
+        }
+        Ok(Self(bytes))
+    }
+}
+
+impl fmt::Display for Uuid {
+    /// Write the canonical lowercase hyphenated form.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let groups = [0..4, 4..6, 6..8, 8..10, 10..16];
+        for (i, group) in groups.iter().cloned().enumerate() {
+            if i > 0 {
+                f.write_str("-")?;
+            }
+            fmt_hex(f, self.0.get(group).unwrap_or_default())?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Uuid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Uuid({})", self)
+    }
+}
+
+impl From<[u8; 16]> for Uuid {
+    fn from(bytes: [u8; 16]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<Uuid> for [u8; 16] {
+    fn from(uuid: Uuid) -> Self {
+        uuid.0
+    }
+}
+
+/// Serializes as the hyphenated string in human-readable formats and as 16 bytes otherwise.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Uuid {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Uuid {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct UuidVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for UuidVisitor {
+            type Value = Uuid;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                formatter.write_str("a hyphenated UUID string or 16 bytes")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                v.parse()
+                    .map_err(|_| E::invalid_value(serde::de::Unexpected::Str(v), &self))
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                Uuid::from_slice(v).map_err(|_| E::invalid_length(v.len(), &self))
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(UuidVisitor)
+        } else {
+            deserializer.deserialize_bytes(UuidVisitor)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[cfg(feature = "borsh")]
+    #[test]
+    fn borsh() {
+        use borsh::BorshDeserialize;
+
+        let uuid = "01234567-89ab-cdef-0123-456789abcdef".parse::<Uuid>().unwrap();
+        let bytes = borsh::to_vec(&uuid).unwrap();
+        assert_eq!(&bytes, uuid.as_bytes());
+        assert_eq!(Uuid::try_from_slice(&bytes).unwrap(), uuid);
+        assert!(Uuid::try_from_slice(&bytes[..15]).is_err());
+    }
+
+    #[test]
+    fn parsing() {
+        let s = "f81d4fae-7dec-11d0-a765-00a0c91e6bf6";
+        let uuid = s.parse::<Uuid>().unwrap();
+        assert_eq!(uuid.as_bytes()[..4], [0xf8, 0x1d, 0x4f, 0xae]);
+        assert_eq!(uuid.version(), 1);
+        assert_eq!(uuid.timestamp_millis(), None);
+        assert_eq!(uuid.to_string(), s);
+        assert_eq!(s.to_uppercase().parse::<Uuid>(), Ok(uuid));
+        assert_eq!(Uuid::NIL.to_string(), "00000000-0000-0000-0000-000000000000");
+        assert!(Uuid::NIL.is_nil());
+        for bad in [
+            "",
+            "f81d4fae7dec11d0a76500a0c91e6bf6",
+            "{f81d4fae-7dec-11d0-a765-00a0c91e6bf6}",
+            "f81d4fae-7dec-11d0-a765-00a0c91e6bf",
+            "f81d4fae-7dec-11d0-a765_00a0c91e6bf6",
+            "f81d4fae-7dec-11d0-a765-00a0c91e6bfg",
+            "f81d4fa-e7dec-11d0-a765-00a0c91e6bf6",
+            "f81d4fae-7dec-11d0-a765-00a0c91e6bé",
+        ] {
+            assert_eq!(bad.parse::<Uuid>(), Err(UuidError::InvalidString {}), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn bytes() {
+        let bytes = [7u8; 16];
+        assert_eq!(Uuid::from_slice(&bytes), Ok(Uuid::from(bytes)));
+        assert_eq!(<[u8; 16]>::from(Uuid::from_bytes(bytes)), bytes);
+        assert_eq!(
+            Uuid::from_slice(&bytes[1..]),
+            Err(UuidError::InvalidLength { actual: 15 })
+        );
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn generation() {
+        use std::time::Duration;
+
+        use crate::epoch_time::FixedTimeProvider;
+
+        let v4 = Uuid::new_v4();
+        assert_eq!(v4.version(), 4);
+        assert_eq!(v4.as_bytes()[8] >> 6, 0b10);
+        assert_ne!(v4, Uuid::new_v4());
+
+        let clock = FixedTimeProvider(Duration::from_millis(0x0190_a5d6_3b7e));
+        let v7 = Uuid::new_v7_from(&clock, &mut rand::rngs::OsRng);
+        assert_eq!(v7.version(), 7);
+        assert_eq!(v7.as_bytes()[8] >> 6, 0b10);
+        assert_eq!(v7.timestamp_millis(), Some(0x0190_a5d6_3b7e));
+        assert!(v7.to_string().starts_with("0190a5d6-3b7e-7"));
+        let later = Uuid::new_v7_from(
+            &FixedTimeProvider(Duration::from_millis(0x0190_a5d6_3b7f)),
+            &mut rand::rngs::OsRng,
+        );
+        assert!(v7 < later);
+        assert_eq!(Uuid::new_v7().version(), 7);
+    }
+
+    #[cfg(feature = "serialize")]
+    #[test]
+    fn serialization() {
+        let uuid = "f81d4fae-7dec-11d0-a765-00a0c91e6bf6".parse::<Uuid>().unwrap();
+        let json = serde_json::to_string(&uuid).unwrap();
+        assert_eq!(json, "\"f81d4fae-7dec-11d0-a765-00a0c91e6bf6\"");
+        assert_eq!(serde_json::from_str::<Uuid>(&json).unwrap(), uuid);
+        let binary = bincode::serialize(&uuid).unwrap();
+        assert_eq!(&binary[8..], uuid.as_bytes());
+        assert_eq!(bincode::deserialize::<Uuid>(&binary).unwrap(), uuid);
+    }
+}
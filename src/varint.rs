@@ -0,0 +1,487 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! LEB128 variable-length integers, as used for length prefixes and integer fields in most binary and P2P protocols.
+//!
+//! Unsigned integers use unsigned LEB128 and signed integers use signed LEB128, for every integer type from `u16` to
+//! `u128` and `i16` to `i128`. Decoding only accepts minimal encodings that fit in the requested type, so every value
+//! has exactly one encoding, and never reads more than [Varint::MAX_LEN] bytes, so a malicious peer cannot make a
+//! decoder consume an unbounded run of continuation bytes.
+//!
+//! [encode_into] and [decode] work on caller buffers without allocating, [decode_from] reads a single varint from a
+//! byte iterator, and [decode_iter] decodes a stream of consecutive varints.
+//!
+//! ```edition2018
+//! # use tari_utilities::varint::{decode, decode_iter, encode, encode_into};
+//! assert_eq!(encode(300u32), [0xac, 0x02]);
+//! assert_eq!(encode(-2i64), [0x7e]);
+//! assert_eq!(decode::<u16>(&[0xac, 0x02, 0xff]), Ok((300, 2)));
+//!
+//! let mut buf = [0u8; 3];
+//! assert_eq!(encode_into(-129i16, &mut buf), Ok(2));
+//! assert_eq!(&buf[..2], [0xff, 0x7e]);
+//!
+//! let values = decode_iter::<u64, _>(vec![0x01, 0x80, 0x01, 0x7f])
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(values, [1, 128, 127]);
+//! ```
+
+use alloc::vec::Vec;
+use core::{convert::TryFrom, marker::PhantomData};
+
+use snafu::prelude::*;
+
+/// The length of the longest varint of any supported type, that of a 128-bit integer.
+pub const MAX_LEN: usize = 19;
+
+/// The bit that marks every byte of a varint except the last.
+const CONTINUATION: u8 = 0x80;
+/// The value bits of each byte.
+const PAYLOAD: u8 = 0x7f;
+/// The sign bit of the last byte of a signed varint.
+const SIGN: u8 = 0x40;
+
+/// Errors for varint encoding and decoding.
+#[derive(Debug, Clone, Snafu, PartialEq, Eq)]
+pub enum VarintError {
+    /// The input ends before the last byte of the varint.
+    #[snafu(display("The input ends within a varint"))]
+    Truncated {},
+    /// No byte within the longest encoding of the type ends the varint.
+    #[snafu(display("The varint is longer than the {max_len} bytes allowed for its type"))]
+    TooLong {
+        /// The length of the longest encoding of the type
+        max_len: usize,
+    },
+    /// The varint encodes a value outside the range of the type.
+    #[snafu(display("The varint does not fit in its type"))]
+    Overflow {},
+    /// The varint has redundant trailing bytes.
+    #[snafu(display("The varint is not minimally encoded"))]
+    NonMinimal {},
+    /// The output buffer is shorter than the encoding.
+    #[snafu(display("The output buffer is too small; {needed} bytes are needed"))]
+    BufferTooSmall {
+        /// The length of the encoding
+        needed: usize,
+    },
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// An integer type with a LEB128 encoding. This is implemented for `u16` to `u128` and `i16` to `i128`.
+pub trait Varint: Copy + sealed::Sealed {
+    /// The length of the longest encoding of the type.
+    const MAX_LEN: usize;
+    /// Whether the type uses signed LEB128.
+    const SIGNED: bool;
+
+    /// Return the value as 128 bits, sign-extended for signed types.
+    fn into_bits(self) -> u128;
+
+    /// Return the value with the given 128 bits, or `None` if it does not fit in the type.
+    fn from_bits(bits: u128) -> Option<Self>;
+
+    /// Return a buffer holding the encoding of the value, and the length of the encoding.
+    fn encode(self) -> ([u8; MAX_LEN], usize) {
+        if Self::SIGNED {
+            encode_signed(self.into_bits() as i128)
+        } else {
+            encode_unsigned(self.into_bits())
+        }
+    }
+}
+
+macro_rules! impl_varint {
+    ($signed:expr, $wide:ty, $($ty:ty),+) => {$(
+        impl sealed::Sealed for $ty {}
+
+        impl Varint for $ty {
+            const MAX_LEN: usize = (<$ty>::BITS as usize).div_ceil(7);
+            const SIGNED: bool = $signed;
+
+            fn into_bits(self) -> u128 {
+                <$wide>::from(self) as u128
+            }
+
+            fn from_bits(bits: u128) -> Option<Self> {
+                <$ty>::try_from(bits as $wide).ok()
+            }
+        }
+    )+};
+}
+
+impl_varint!(false, u128, u16, u32, u64, u128);
+impl_varint!(true, i128, i16, i32, i64, i128);
+
+/// Encode a value as unsigned LEB128, returning a buffer holding the encoding and the length of the encoding.
+// The index stays below `MAX_LEN`, and a `const fn` cannot use the checked slice methods
+#[allow(clippy::indexing_slicing)]
+pub const fn encode_unsigned(mut value: u128) -> ([u8; MAX_LEN], usize) {
+    let mut out = [0u8; MAX_LEN];
+    let mut len = 0;
+    // A `u128` has at most 18 bytes before the last
+    while value > PAYLOAD as u128 {
+        out[len] = value as u8 | CONTINUATION;
+        value >>= 7;
+        len = len.saturating_add(1);
+    }
+    out[len] = value as u8;
+    (out, len.saturating_add(1))
+}
+
+/// Encode a value as signed LEB128, returning a buffer holding the encoding and the length of the encoding.
+// The index stays below `MAX_LEN`, and a `const fn` cannot use the checked slice methods
+#[allow(clippy::indexing_slicing)]
+pub const fn encode_signed(mut value: i128) -> ([u8; MAX_LEN], usize) {
+    let mut out = [0u8; MAX_LEN];
+    let mut len = 0;
+    // An `i128` has at most 18 bytes before the last
+    loop {
+        let byte = value as u8 & PAYLOAD;
+        value >>= 7;
+        if (value == 0 && byte & SIGN == 0) || (value == -1 && byte & SIGN != 0) {
+            out[len] = byte;
+            return (out, len.saturating_add(1));
+        }
+        out[len] = byte | CONTINUATION;
+        len = len.saturating_add(1);
+    }
+}
+
+/// The number of bytes the value encodes to.
+pub fn encoded_len<T: Varint>(value: T) -> usize {
+    value.encode().1
+}
+
+/// Encode the value as a varint.
+pub fn encode<T: Varint>(value: T) -> Vec<u8> {
+    let (buf, len) = value.encode();
+    buf.get(..len).unwrap_or_default().to_vec()
+}
+
+/// Encode the value as a varint into the start of `out`, returning the number of bytes written.
+///
+/// # Errors
+/// Returns [VarintError::BufferTooSmall] if the encoding does not fit in `out`, in which case `out` is unchanged.
+pub fn encode_into<T: Varint>(value: T, out: &mut [u8]) -> Result<usize, VarintError> {
+    let (buf, len) = value.encode();
+    let encoded = buf.get(..len).unwrap_or_default();
+    let dest = out.get_mut(..len).context(BufferTooSmallSnafu { needed: len })?;
+    dest.copy_from_slice(encoded);
+    Ok(len)
+}
+
+/// Decode a varint from the start of `bytes`, returning the value and the number of bytes read. Any bytes after the
+/// varint are ignored.
+///
+/// # Errors
+/// Returns [VarintError::Truncated] if `bytes` ends within the varint, [VarintError::TooLong] if there is no final
+/// byte within [Varint::MAX_LEN] bytes, and [VarintError::NonMinimal] or [VarintError::Overflow] if the varint is not
+/// the minimal encoding of a value of the type.
+pub fn decode<T: Varint>(bytes: &[u8]) -> Result<(T, usize), VarintError> {
+    match bytes.iter().take(T::MAX_LEN).position(|byte| byte & CONTINUATION == 0) {
+        Some(last) => {
+            let len = last.saturating_add(1);
+            let value = decode_complete(bytes.get(..len).unwrap_or_default())?;
+            Ok((value, len))
+        },
+        None if bytes.len() >= T::MAX_LEN => TooLongSnafu { max_len: T::MAX_LEN }.fail(),
+        None => TruncatedSnafu.fail(),
+    }
+}
+
+/// Decode a varint from a byte iterator, consuming exactly the bytes of the varint. Errors are the same as for
+/// [decode], and at most [Varint::MAX_LEN] bytes are consumed.
+pub fn decode_from<T: Varint, I: Iterator<Item = u8>>(bytes: &mut I) -> Result<T, VarintError> {
+    let mut buf = [0u8; MAX_LEN];
+    let mut len = 0usize;
+    for slot in buf.iter_mut().take(T::MAX_LEN) {
+        let byte = bytes.next().context(TruncatedSnafu)?;
+        *slot = byte;
+        len = len.saturating_add(1);
+        if byte & CONTINUATION == 0 {
+            return decode_complete(buf.get(..len).unwrap_or_default());
+        }
+    }
+    TooLongSnafu { max_len: T::MAX_LEN }.fail()
+}
+
+/// Decode consecutive varints from a stream of bytes. The iterator ends when the bytes end between varints, or after
+/// the first error.
+///
+/// ```edition2018
+/// # use tari_utilities::varint::{decode_iter, VarintError};
+/// let mut values = decode_iter::<i32, _>([0x7f, 0x80, 0x7f, 0x80]);
+/// assert_eq!(values.next(), Some(Ok(-1)));
+/// assert_eq!(values.next(), Some(Ok(-128)));
+/// assert_eq!(values.next(), Some(Err(VarintError::Truncated {})));
+/// assert_eq!(values.next(), None);
+/// ```
+pub fn decode_iter<T: Varint, I: IntoIterator<Item = u8>>(bytes: I) -> DecodeIter<I::IntoIter, T> {
+    DecodeIter {
+        bytes: bytes.into_iter(),
+        failed: false,
+        value: PhantomData,
+    }
+}
+
+/// An iterator over the varints in a stream of bytes, created by [decode_iter].
+#[derive(Debug, Clone)]
+pub struct DecodeIter<I, T> {
+    bytes: I,
+    failed: bool,
+    value: PhantomData<fn() -> T>,
+}
+
+impl<I: Iterator<Item = u8>, T: Varint> Iterator for DecodeIter<I, T> {
+    type Item = Result<T, VarintError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let first = self.bytes.next()?;
+        let result = decode_from(&mut core::iter::once(first).chain(&mut self.bytes));
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
+
+/// Decode a varint whose last byte is the last byte of `bytes`.
+fn decode_complete<T: Varint>(bytes: &[u8]) -> Result<T, VarintError> {
+    let mut bits = 0u128;
+    for (shift, byte) in (0..u32::MAX).step_by(7).zip(bytes) {
+        // Bits past the 128th are lost here, and caught by the comparison with the canonical encoding below
+        bits |= u128::from(byte & PAYLOAD).checked_shl(shift).unwrap_or_default();
+    }
+    let Some((&last, rest)) = bytes.split_last() else {
+        return TruncatedSnafu.fail();
+    };
+    // A final byte that only repeats what the previous byte implies could have been left off
+    let redundant = match rest.last() {
+        Some(previous) if T::SIGNED => (last == 0 && previous & SIGN == 0) || (last == PAYLOAD && previous & SIGN != 0),
+        Some(_) => last == 0,
+        None => false,
+    };
+    ensure!(!redundant, NonMinimalSnafu);
+    if T::SIGNED && last & SIGN != 0 {
+        let used = u32::try_from(bytes.len()).unwrap_or(u32::MAX).saturating_mul(7);
+        bits |= u128::MAX.checked_shl(used).unwrap_or_default();
+    }
+    let value = T::from_bits(bits).context(OverflowSnafu)?;
+    let (canonical, len) = value.encode();
+    ensure!(canonical.get(..len) == Some(bytes), OverflowSnafu);
+    Ok(value)
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::vec;
+
+    use super::*;
+
+    #[test]
+    fn max_lengths() {
+        assert_eq!(<u16 as Varint>::MAX_LEN, 3);
+        assert_eq!(<u32 as Varint>::MAX_LEN, 5);
+        assert_eq!(<u64 as Varint>::MAX_LEN, 10);
+        assert_eq!(<u128 as Varint>::MAX_LEN, MAX_LEN);
+        assert_eq!(<i16 as Varint>::MAX_LEN, 3);
+        assert_eq!(<i128 as Varint>::MAX_LEN, MAX_LEN);
+        assert_eq!(encoded_len(u128::MAX), MAX_LEN);
+        assert_eq!(encoded_len(i128::MIN), MAX_LEN);
+        assert_eq!(encoded_len(i128::MAX), MAX_LEN);
+        assert_eq!(encoded_len(u64::MAX), 10);
+        assert_eq!(encoded_len(i64::MIN), 10);
+    }
+
+    #[test]
+    fn unsigned() {
+        let cases: [(u64, &[u8]); 5] = [
+            (0, &[0]),
+            (127, &[0x7f]),
+            (128, &[0x80, 0x01]),
+            (300, &[0xac, 0x02]),
+            (u64::MAX, &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]),
+        ];
+        for (value, encoded) in cases {
+            assert_eq!(encode(value), encoded);
+            assert_eq!(decode(encoded), Ok((value, encoded.len())));
+            assert_eq!(
+                decode::<u64>(&encoded[..encoded.len() - 1]),
+                Err(VarintError::Truncated {})
+            );
+            assert_eq!(decode_from::<u64, _>(&mut encoded.iter().copied()), Ok(value));
+            assert_eq!(decode(encoded), Ok((u128::from(value), encoded.len())));
+        }
+        assert_eq!(encode(u16::MAX), [0xff, 0xff, 0x03]);
+        assert_eq!(decode(&[0xff, 0xff, 0x03]), Ok((u16::MAX, 3)));
+        assert_eq!(decode::<u16>(&[0xff, 0xff, 0x04]), Err(VarintError::Overflow {}));
+        assert_eq!(
+            decode::<u32>(&[0x80, 0x80, 0x80, 0x80, 0x10]),
+            Err(VarintError::Overflow {})
+        );
+
+        let mut max = vec![0xff; 18];
+        max.push(0x03);
+        assert_eq!(encode(u128::MAX), max);
+        assert_eq!(decode(&max), Ok((u128::MAX, MAX_LEN)));
+        // The bits past the 128th must be zero
+        *max.last_mut().unwrap() = 0x07;
+        assert_eq!(decode::<u128>(&max), Err(VarintError::Overflow {}));
+        *max.last_mut().unwrap() = 0x04;
+        assert_eq!(decode::<u128>(&max), Err(VarintError::Overflow {}));
+    }
+
+    #[test]
+    fn signed() {
+        let cases: [(i64, &[u8]); 9] = [
+            (0, &[0]),
+            (1, &[0x01]),
+            (-1, &[0x7f]),
+            (63, &[0x3f]),
+            (64, &[0xc0, 0x00]),
+            (-64, &[0x40]),
+            (-65, &[0xbf, 0x7f]),
+            (-123_456, &[0xc0, 0xbb, 0x78]),
+            (i64::MIN, &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7f]),
+        ];
+        for (value, encoded) in cases {
+            assert_eq!(encode(value), encoded);
+            assert_eq!(decode(encoded), Ok((value, encoded.len())));
+            assert_eq!(decode(encoded), Ok((i128::from(value), encoded.len())));
+            assert_eq!(decode_from::<i64, _>(&mut encoded.iter().copied()), Ok(value));
+        }
+        assert_eq!(encode(i64::MAX), [
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00
+        ]);
+        assert_eq!(decode(&[0xff, 0xff, 0x01]), Ok((i16::MAX, 3)));
+        assert_eq!(decode(&[0x80, 0x80, 0x7e]), Ok((i16::MIN, 3)));
+        assert_eq!(decode::<i16>(&[0x80, 0x80, 0x02]), Err(VarintError::Overflow {}));
+        assert_eq!(decode::<i16>(&[0xff, 0xff, 0x7d]), Err(VarintError::Overflow {}));
+
+        for value in [i128::MIN, i128::MAX, i128::MIN + 1, i128::MAX - 1] {
+            let encoded = encode(value);
+            assert_eq!(decode(&encoded), Ok((value, MAX_LEN)));
+            // The bits past the 128th must repeat the sign bit
+            let mut flipped = encoded.clone();
+            *flipped.last_mut().unwrap() ^= 0x20;
+            assert_eq!(decode::<i128>(&flipped), Err(VarintError::Overflow {}));
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        for shift in 0..128 {
+            let value = 1u128 << shift;
+            for value in [value, value - 1, value | 1] {
+                assert_eq!(decode(&encode(value)), Ok((value, encoded_len(value))));
+                let signed = value as i128;
+                assert_eq!(decode(&encode(signed)), Ok((signed, encoded_len(signed))));
+                let negative = signed.wrapping_neg();
+                assert_eq!(decode(&encode(negative)), Ok((negative, encoded_len(negative))));
+            }
+        }
+        for value in [u16::MIN, 1, 127, 128, 16_383, 16_384, u16::MAX] {
+            assert_eq!(decode(&encode(value)), Ok((value, encoded_len(value))));
+        }
+        for value in [i32::MIN, -8_193, -8_192, 0, 8_191, 8_192, i32::MAX] {
+            assert_eq!(decode(&encode(value)), Ok((value, encoded_len(value))));
+        }
+    }
+
+    #[test]
+    fn non_minimal() {
+        assert_eq!(decode::<u64>(&[0x80, 0x00]), Err(VarintError::NonMinimal {}));
+        assert_eq!(decode::<u32>(&[0x81, 0x80, 0x00]), Err(VarintError::NonMinimal {}));
+        assert_eq!(decode::<i64>(&[0x80, 0x00]), Err(VarintError::NonMinimal {}));
+        assert_eq!(decode::<i64>(&[0xff, 0x7f]), Err(VarintError::NonMinimal {}));
+        assert_eq!(decode::<i64>(&[0x81, 0x00]), Err(VarintError::NonMinimal {}));
+        // The same bytes are minimal when the previous sign bit differs
+        assert_eq!(decode::<i64>(&[0xc0, 0x00]), Ok((64, 2)));
+        assert_eq!(decode::<i64>(&[0xbf, 0x7f]), Ok((-65, 2)));
+    }
+
+    #[test]
+    fn length_guard() {
+        let endless = [0xffu8; 64];
+        assert_eq!(decode::<u16>(&endless), Err(VarintError::TooLong { max_len: 3 }));
+        assert_eq!(decode::<u64>(&endless), Err(VarintError::TooLong { max_len: 10 }));
+        assert_eq!(decode::<i128>(&endless), Err(VarintError::TooLong { max_len: MAX_LEN }));
+
+        let mut stream = core::iter::repeat_n(0x80u8, 100);
+        assert_eq!(
+            decode_from::<u32, _>(&mut stream),
+            Err(VarintError::TooLong { max_len: 5 })
+        );
+        // Nothing past the maximum length is consumed
+        assert_eq!(stream.count(), 95);
+    }
+
+    #[test]
+    fn encode_into_buffers() {
+        let mut buf = [0xaau8; 4];
+        assert_eq!(encode_into(300u32, &mut buf), Ok(2));
+        assert_eq!(buf, [0xac, 0x02, 0xaa, 0xaa]);
+        assert_eq!(
+            encode_into(u32::MAX, &mut buf),
+            Err(VarintError::BufferTooSmall { needed: 5 })
+        );
+        assert_eq!(buf, [0xac, 0x02, 0xaa, 0xaa]);
+        assert_eq!(encode_into(-1i16, &mut buf[..1]), Ok(1));
+        assert_eq!(
+            encode_into(0u16, &mut []),
+            Err(VarintError::BufferTooSmall { needed: 1 })
+        );
+    }
+
+    #[test]
+    fn const_encoding() {
+        const PREFIX: ([u8; MAX_LEN], usize) = encode_unsigned(300);
+        assert_eq!(&PREFIX.0[..PREFIX.1], [0xac, 0x02]);
+        const NEGATIVE: ([u8; MAX_LEN], usize) = encode_signed(-129);
+        assert_eq!(&NEGATIVE.0[..NEGATIVE.1], [0xff, 0x7e]);
+    }
+
+    #[test]
+    fn streams() {
+        let bytes = [encode(1u64), encode(u64::MAX), encode(0u64), encode(300u64)].concat();
+        let mut iter = bytes.iter().copied();
+        assert_eq!(decode_from::<u64, _>(&mut iter), Ok(1));
+        assert_eq!(iter.len(), bytes.len() - 1);
+
+        let values = decode_iter::<u64, _>(bytes.iter().copied())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(values, [1, u64::MAX, 0, 300]);
+        assert_eq!(decode_iter::<u64, _>(Vec::new()).next(), None);
+
+        let mut values = decode_iter::<u16, _>([0x01, 0x80, 0x00, 0x02]);
+        assert_eq!(values.next(), Some(Ok(1)));
+        assert_eq!(values.next(), Some(Err(VarintError::NonMinimal {})));
+        assert_eq!(values.next(), None);
+    }
+}
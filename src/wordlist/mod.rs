@@ -0,0 +1,354 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Mapping between bytes and words of a wordlist, the plumbing under seed phrases.
+//!
+//! A [Wordlist] of `2^k` words turns a bit string into words of `k` bits each, most significant bit first. The bits
+//! are the data followed by the leading `checksum_bits` bits of a checksum of the data. The checksum function is
+//! chosen by the caller, so any scheme can be built on top: BIP39, for example, uses 2048 words and the first
+//! `entropy_bits / 32` bits of SHA-256.
+//!
+//! The BIP39 English wordlist is available as `english::ENGLISH` with the `wordlist-english` feature.
+//!
+//! ```edition2018
+//! # use tari_utilities::wordlist::Wordlist;
+//! let words = ["zero", "one", "two", "three"];
+//! let wordlist = Wordlist::new(&words).unwrap();
+//! let parity = |data: &[u8]| [data.iter().fold(0u8, |acc, b| acc ^ b)];
+//!
+//! let phrase = wordlist.to_words(&[0b0001_1011], 2, parity).unwrap();
+//! assert_eq!(phrase, ["zero", "one", "two", "three", "zero"]);
+//! assert_eq!(wordlist.from_words(&phrase, 2, parity).unwrap(), [
+//!     0b0001_1011
+//! ]);
+//! ```
+
+#[cfg(feature = "wordlist-english")]
+pub mod english;
+
+use alloc::{vec, vec::Vec};
+
+use snafu::prelude::*;
+
+/// Errors for wordlists and for converting between bytes and words.
+#[derive(Debug, Clone, PartialEq, Eq, Snafu)]
+pub enum WordlistError {
+    /// The wordlist length is not a power of two between 2 and 65536.
+    #[snafu(display("A wordlist of {len} words is not a power of two between 2 and 65536"))]
+    InvalidWordlistLength {
+        /// The number of words in the list
+        len: usize,
+    },
+    /// The wordlist contains a word more than once.
+    #[snafu(display("The word at index {index} appears earlier in the wordlist"))]
+    DuplicateWord {
+        /// The index of the repeated word
+        index: usize,
+    },
+    /// The data and checksum bits do not divide into whole words.
+    #[snafu(display("{bits} bits cannot be split into whole words"))]
+    InvalidBitLength {
+        /// The number of data and checksum bits
+        bits: usize,
+    },
+    /// The checksum function returned fewer bits than requested.
+    #[snafu(display("The checksum is shorter than the requested checksum bits"))]
+    ChecksumTooShort {},
+    /// A word is not in the wordlist.
+    #[snafu(display("Word {position} is not in the wordlist"))]
+    UnknownWord {
+        /// The index of the word in the phrase
+        position: usize,
+    },
+    /// The number of words does not hold a whole number of bytes and the checksum.
+    #[snafu(display("{count} words do not hold whole bytes and the checksum"))]
+    InvalidWordCount {
+        /// The number of words in the phrase
+        count: usize,
+    },
+    /// The checksum bits in the words do not match the data.
+    #[snafu(display("Checksum mismatch"))]
+    ChecksumMismatch {},
+}
+
+/// Return bit `i` of `bytes`, counting from the most significant bit of the first byte.
+fn bit(bytes: &[u8], i: usize) -> bool {
+    bytes
+        .get(i / 8)
+        .is_some_and(|b| b.wrapping_shl((i % 8) as u32) & 0x80 != 0)
+}
+
+/// A list of `2^k` distinct words, each encoding `k` bits.
+#[derive(Debug, Clone, Copy)]
+pub struct Wordlist<'a> {
+    words: &'a [&'a str],
+    bits_per_word: usize,
+    sorted: bool,
+}
+
+impl<'a> Wordlist<'a> {
+    /// Create a wordlist.
+    ///
+    /// # Errors
+    /// Returns an error if the number of words is not a power of two between 2 and 65536, or a word is repeated.
+    pub fn new(words: &'a [&'a str]) -> Result<Self, WordlistError> {
+        let len = words.len();
+        ensure!(
+            len.is_power_of_two() && (2..=1 << 16).contains(&len),
+            InvalidWordlistLengthSnafu { len }
+        );
+        let sorted = words.windows(2).all(|pair| pair.first() < pair.get(1));
+        if !sorted {
+            for (index, word) in words.iter().enumerate() {
+                if words.iter().take(index).any(|other| other == word) {
+                    return DuplicateWordSnafu { index }.fail();
+                }
+            }
+        }
+        Ok(Self {
+            words,
+            bits_per_word: len.trailing_zeros() as usize,
+            sorted,
+        })
+    }
+
+    /// The words of the list.
+    pub fn words(&self) -> &'a [&'a str] {
+        self.words
+    }
+
+    /// The number of bits each word encodes.
+    pub fn bits_per_word(&self) -> usize {
+        self.bits_per_word
+    }
+
+    /// Return the word at `index`, if the index is in range.
+    pub fn word(&self, index: usize) -> Option<&'a str> {
+        self.words.get(index).copied()
+    }
+
+    /// Return the index of `word`, which must match exactly.
+    pub fn index_of(&self, word: &str) -> Option<usize> {
+        if self.sorted {
+            self.words.binary_search(&word).ok()
+        } else {
+            self.words.iter().position(|w| *w == word)
+        }
+    }
+
+    /// Encode `data` and the first `checksum_bits` bits of `checksum(data)` as words. Pass 0 checksum bits to encode
+    /// the data alone.
+    ///
+    /// # Errors
+    /// Returns an error if the bits do not divide into whole words, or the checksum has fewer bits than requested.
+    pub fn to_words<F, C>(&self, data: &[u8], checksum_bits: usize, checksum: F) -> Result<Vec<&'a str>, WordlistError>
+    where
+        F: FnOnce(&[u8]) -> C,
+        C: AsRef<[u8]>,
+    {
+        let data_bits = data.len().saturating_mul(8);
+        let total_bits = data_bits.saturating_add(checksum_bits);
+        ensure!(
+            total_bits.checked_rem(self.bits_per_word) == Some(0),
+            InvalidBitLengthSnafu { bits: total_bits }
+        );
+        let checksum = (checksum_bits > 0).then(|| checksum(data));
+        let checksum = checksum.as_ref().map_or(&[][..], AsRef::as_ref);
+        ensure!(checksum.len().saturating_mul(8) >= checksum_bits, ChecksumTooShortSnafu);
+
+        let bits = (0..data_bits)
+            .map(|i| bit(data, i))
+            .chain((0..checksum_bits).map(|i| bit(checksum, i)));
+        let mut words = Vec::with_capacity(total_bits.checked_div(self.bits_per_word).unwrap_or_default());
+        let (mut index, mut filled) = (0usize, 0usize);
+        for b in bits {
+            index = (index << 1) | usize::from(b);
+            filled = filled.saturating_add(1);
+            if filled == self.bits_per_word {
+                // The index has exactly `bits_per_word` bits, so it is always in range
+                words.extend(self.word(index));
+                index = 0;
+                filled = 0;
+            }
+        }
+        Ok(words)
+    }
+
+    /// Decode words produced by [to_words](Self::to_words) with the same checksum bits and function, verifying the
+    /// checksum.
+    ///
+    /// # Errors
+    /// Returns an error if a word is unknown, the number of words is wrong, or the checksum does not match.
+    pub fn from_words<S, F, C>(&self, words: &[S], checksum_bits: usize, checksum: F) -> Result<Vec<u8>, WordlistError>
+    where
+        S: AsRef<str>,
+        F: FnOnce(&[u8]) -> C,
+        C: AsRef<[u8]>,
+    {
+        let count = words.len();
+        let total_bits = count.saturating_mul(self.bits_per_word);
+        let data_bits = total_bits
+            .checked_sub(checksum_bits)
+            .filter(|bits| bits % 8 == 0)
+            .context(InvalidWordCountSnafu { count })?;
+        let mut data = vec![0u8; data_bits / 8];
+        let mut stored_checksum = vec![0u8; (checksum_bits.saturating_add(7)) / 8];
+        for (position, word) in words.iter().enumerate() {
+            let index = self.index_of(word.as_ref()).context(UnknownWordSnafu { position })?;
+            for j in 0..self.bits_per_word {
+                let i = position.saturating_mul(self.bits_per_word).saturating_add(j);
+                if (index >> (self.bits_per_word.saturating_sub(j).saturating_sub(1))) & 1 == 0 {
+                    continue;
+                }
+                let (buf, i) = match i.checked_sub(data_bits) {
+                    None => (&mut data, i),
+                    Some(i) => (&mut stored_checksum, i),
+                };
+                if let Some(byte) = buf.get_mut(i / 8) {
+                    *byte |= 0x80 >> (i % 8);
+                }
+            }
+        }
+        if checksum_bits > 0 {
+            let expected = checksum(&data);
+            let expected = expected.as_ref();
+            ensure!(
+                (0..checksum_bits).all(|i| bit(expected, i) == bit(&stored_checksum, i)) &&
+                    expected.len().saturating_mul(8) >= checksum_bits,
+                ChecksumMismatchSnafu
+            );
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NUMBERS: [&str; 4] = ["zero", "one", "two", "three"];
+
+    fn no_checksum(_: &[u8]) -> [u8; 0] {
+        []
+    }
+
+    #[test]
+    fn construction() {
+        assert_eq!(Wordlist::new(&NUMBERS).unwrap().bits_per_word(), 2);
+        assert_eq!(
+            Wordlist::new(&NUMBERS[..3]).unwrap_err(),
+            WordlistError::InvalidWordlistLength { len: 3 }
+        );
+        assert_eq!(
+            Wordlist::new(&NUMBERS[..1]).unwrap_err(),
+            WordlistError::InvalidWordlistLength { len: 1 }
+        );
+        assert_eq!(
+            Wordlist::new(&["a", "b", "c", "a"]).unwrap_err(),
+            WordlistError::DuplicateWord { index: 3 }
+        );
+        let wordlist = Wordlist::new(&NUMBERS).unwrap();
+        assert_eq!(wordlist.index_of("two"), Some(2));
+        assert_eq!(wordlist.index_of("four"), None);
+        assert_eq!(wordlist.word(3), Some("three"));
+        assert_eq!(wordlist.word(4), None);
+    }
+
+    #[test]
+    fn round_trip() {
+        let wordlist = Wordlist::new(&NUMBERS).unwrap();
+        let words = wordlist.to_words(&[0xe4], 0, no_checksum).unwrap();
+        assert_eq!(words, ["three", "two", "one", "zero"]);
+        assert_eq!(wordlist.from_words(&words, 0, no_checksum).unwrap(), [0xe4]);
+        assert_eq!(wordlist.to_words(&[], 0, no_checksum).unwrap(), Vec::<&str>::new());
+
+        // 8 data bits and 4 checksum bits in 3-bit words
+        let eight = ["a", "b", "c", "d", "e", "f", "g", "h"];
+        let wordlist = Wordlist::new(&eight).unwrap();
+        let words = wordlist.to_words(&[0xff], 4, |_| [0x50]).unwrap();
+        assert_eq!(words, ["h", "h", "g", "f"]);
+        assert_eq!(wordlist.from_words(&words, 4, |_| [0x5f]).unwrap(), [0xff]);
+        assert_eq!(
+            wordlist.from_words(&words, 4, |_| [0x60]).unwrap_err(),
+            WordlistError::ChecksumMismatch {}
+        );
+        assert_eq!(
+            wordlist.from_words(&words, 4, |_| []).unwrap_err(),
+            WordlistError::ChecksumMismatch {}
+        );
+    }
+
+    #[test]
+    fn invalid_input() {
+        let wordlist = Wordlist::new(&["a", "b", "c", "d", "e", "f", "g", "h"]).unwrap();
+        assert_eq!(
+            wordlist.to_words(&[1], 0, no_checksum).unwrap_err(),
+            WordlistError::InvalidBitLength { bits: 8 }
+        );
+        assert_eq!(
+            wordlist.to_words(&[1], 4, |_| []).unwrap_err(),
+            WordlistError::ChecksumTooShort {}
+        );
+        assert_eq!(
+            wordlist.from_words(&["a", "b", "c", "x"], 4, no_checksum).unwrap_err(),
+            WordlistError::UnknownWord { position: 3 }
+        );
+        assert_eq!(
+            wordlist.from_words(&["a", "b"], 4, no_checksum).unwrap_err(),
+            WordlistError::InvalidWordCount { count: 2 }
+        );
+        assert_eq!(
+            wordlist.from_words(&["a"], 4, no_checksum).unwrap_err(),
+            WordlistError::InvalidWordCount { count: 1 }
+        );
+    }
+
+    #[cfg(feature = "wordlist-english")]
+    #[test]
+    fn bip39_vectors() {
+        use sha2::{Digest, Sha256};
+
+        let wordlist = Wordlist::new(&english::ENGLISH).unwrap();
+        assert_eq!(wordlist.bits_per_word(), 11);
+        let sha256 = |data: &[u8]| Sha256::digest(data);
+        let vectors: [(&[u8], &str); 3] = [
+            (
+                &[0x00; 16],
+                "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+            ),
+            (
+                &[0x7f; 16],
+                "legal winner thank year wave sausage worth useful legal winner thank yellow",
+            ),
+            (
+                &[0xff; 32],
+                "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote",
+            ),
+        ];
+        for (entropy, phrase) in vectors {
+            let checksum_bits = entropy.len() * 8 / 32;
+            let words = wordlist.to_words(entropy, checksum_bits, sha256).unwrap();
+            assert_eq!(words.join(" "), phrase);
+            assert_eq!(wordlist.from_words(&words, checksum_bits, sha256).unwrap(), entropy);
+        }
+    }
+}